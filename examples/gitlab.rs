@@ -98,7 +98,8 @@ fn main() {
         // This example is requesting access to the the user's profile including email.
         .add_scope(Scope::new("email".to_string()))
         .add_scope(Scope::new("profile".to_string()))
-        .url();
+        .url()
+        .expect("authorization request should not violate PKCE policy");
 
     println!("Open this URL in your browser:\n{}\n", authorize_url);
 
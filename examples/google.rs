@@ -19,20 +19,16 @@ use std::io::{BufRead, BufReader, Write};
 use std::net::TcpListener;
 use std::process::exit;
 
-use serde::{Deserialize, Serialize};
 use url::Url;
 
 use openidconnect::core::{
-    CoreAuthDisplay, CoreClaimName, CoreClaimType, CoreClient, CoreClientAuthMethod, CoreGrantType,
-    CoreIdTokenClaims, CoreIdTokenVerifier, CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse,
-    CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreJwsSigningAlgorithm,
-    CoreResponseMode, CoreResponseType, CoreRevocableToken, CoreSubjectIdentifierType,
+    CoreClient, CoreIdTokenClaims, CoreIdTokenVerifier, CoreProviderMetadata, CoreResponseType,
+    CoreRevocableToken,
 };
 use openidconnect::reqwest::http_client;
 use openidconnect::{
-    AdditionalProviderMetadata, AuthenticationFlow, AuthorizationCode, ClientId, ClientSecret,
-    CsrfToken, IssuerUrl, Nonce, OAuth2TokenResponse, ProviderMetadata, RedirectUrl, RevocationUrl,
-    Scope,
+    AuthenticationFlow, AuthorizationCode, ClientId, ClientSecret, CsrfToken, IssuerUrl, Nonce,
+    OAuth2TokenResponse, RedirectUrl, Scope,
 };
 
 fn handle_error<T: std::error::Error>(fail: &T, msg: &'static str) {
@@ -46,32 +42,6 @@ fn handle_error<T: std::error::Error>(fail: &T, msg: &'static str) {
     exit(1);
 }
 
-// Teach openidconnect-rs about a Google custom extension to the OpenID Discovery response that we can use as the RFC
-// 7009 OAuth 2.0 Token Revocation endpoint. For more information about the Google specific Discovery response see the
-// Google OpenID Connect service documentation at: https://developers.google.com/identity/protocols/oauth2/openid-connect#discovery
-#[derive(Clone, Debug, Deserialize, Serialize)]
-struct RevocationEndpointProviderMetadata {
-    revocation_endpoint: String,
-}
-impl AdditionalProviderMetadata for RevocationEndpointProviderMetadata {}
-type GoogleProviderMetadata = ProviderMetadata<
-    RevocationEndpointProviderMetadata,
-    CoreAuthDisplay,
-    CoreClientAuthMethod,
-    CoreClaimName,
-    CoreClaimType,
-    CoreGrantType,
-    CoreJweContentEncryptionAlgorithm,
-    CoreJweKeyManagementAlgorithm,
-    CoreJwsSigningAlgorithm,
-    CoreJsonWebKeyType,
-    CoreJsonWebKeyUse,
-    CoreJsonWebKey,
-    CoreResponseMode,
-    CoreResponseType,
-    CoreSubjectIdentifierType,
->;
-
 fn main() {
     env_logger::init();
 
@@ -86,29 +56,23 @@ fn main() {
         IssuerUrl::new("https://accounts.google.com".to_string()).expect("Invalid issuer URL");
 
     // Fetch Google's OpenID Connect discovery document.
-    //
-    // Note: If we don't care about token revocation we can simply use CoreProviderMetadata here
-    // instead of GoogleProviderMetadata. If instead we wanted to optionally use the token
-    // revocation endpoint if it seems to be supported we could do something like this:
-    //   #[derive(Clone, Debug, Deserialize, Serialize)]
-    //   struct AllOtherProviderMetadata(HashMap<String, serde_json::Value>);
-    //   impl AdditionalClaims for AllOtherProviderMetadata {}
-    // And then test for the presence of "revocation_endpoint" in the map returned by a call to
-    // .additional_metadata().
-
-    let provider_metadata = GoogleProviderMetadata::discover(&issuer_url, http_client)
+    let provider_metadata = CoreProviderMetadata::discover(&issuer_url, http_client)
         .unwrap_or_else(|err| {
             handle_error(&err, "Failed to discover OpenID Provider");
             unreachable!();
         });
 
+    // Google publishes its RFC 7009 OAuth 2.0 Token Revocation endpoint in its discovery
+    // document. For more information about the Google specific Discovery response see the Google
+    // OpenID Connect service documentation at:
+    // https://developers.google.com/identity/protocols/oauth2/openid-connect#discovery
     let revocation_endpoint = provider_metadata
-        .additional_metadata()
-        .revocation_endpoint
-        .clone();
+        .revocation_endpoint()
+        .cloned()
+        .expect("Google should publish a revocation endpoint");
     println!(
         "Discovered Google revocation endpoint: {}",
-        revocation_endpoint
+        revocation_endpoint.as_str()
     );
 
     // Set up the config for the Google OAuth2 process.
@@ -123,9 +87,7 @@ fn main() {
         RedirectUrl::new("http://localhost:8080".to_string()).expect("Invalid redirect URL"),
     )
     // Google supports OAuth 2.0 Token Revocation (RFC-7009)
-    .set_revocation_uri(
-        RevocationUrl::new(revocation_endpoint).expect("Invalid revocation endpoint URL"),
-    );
+    .set_revocation_uri(revocation_endpoint);
 
     // Generate the authorization URL to which we'll redirect the user.
     let (authorize_url, csrf_state, nonce) = client
@@ -137,7 +99,8 @@ fn main() {
         // This example is requesting access to the "calendar" features and the user's profile.
         .add_scope(Scope::new("email".to_string()))
         .add_scope(Scope::new("profile".to_string()))
-        .url();
+        .url()
+        .expect("authorization request should not violate PKCE policy");
 
     println!("Open this URL in your browser:\n{}\n", authorize_url);
 
@@ -15,48 +15,14 @@
 //! ...and follow the instructions.
 //!
 
-use openidconnect::core::{
-    CoreAuthDisplay, CoreClaimName, CoreClaimType, CoreClient, CoreClientAuthMethod,
-    CoreDeviceAuthorizationResponse, CoreGrantType, CoreJsonWebKey, CoreJsonWebKeyType,
-    CoreJsonWebKeyUse, CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm,
-    CoreJwsSigningAlgorithm, CoreResponseMode, CoreResponseType, CoreSubjectIdentifierType,
-};
-use openidconnect::{
-    AdditionalProviderMetadata, AuthType, ClientId, ClientSecret, DeviceAuthorizationUrl,
-    IssuerUrl, ProviderMetadata, Scope,
-};
+use openidconnect::core::{CoreClient, CoreDeviceAuthorizationResponse, CoreProviderMetadata};
+use openidconnect::{AuthType, ClientId, ClientSecret, IssuerUrl, Scope};
 use std::env;
 
-use serde::{Deserialize, Serialize};
-
 use openidconnect::reqwest::http_client;
 
 use std::process::exit;
 
-// Obtain the device_authorization_url from the OIDC metadata provider.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-struct DeviceEndpointProviderMetadata {
-    device_authorization_endpoint: DeviceAuthorizationUrl,
-}
-impl AdditionalProviderMetadata for DeviceEndpointProviderMetadata {}
-type DeviceProviderMetadata = ProviderMetadata<
-    DeviceEndpointProviderMetadata,
-    CoreAuthDisplay,
-    CoreClientAuthMethod,
-    CoreClaimName,
-    CoreClaimType,
-    CoreGrantType,
-    CoreJweContentEncryptionAlgorithm,
-    CoreJweKeyManagementAlgorithm,
-    CoreJwsSigningAlgorithm,
-    CoreJsonWebKeyType,
-    CoreJsonWebKeyUse,
-    CoreJsonWebKey,
-    CoreResponseMode,
-    CoreResponseType,
-    CoreSubjectIdentifierType,
->;
-
 fn handle_error<T: std::error::Error>(fail: &T, msg: &'static str) {
     let mut err_msg = format!("ERROR: {}", msg);
     let mut cur_fail: Option<&dyn std::error::Error> = Some(fail);
@@ -84,17 +50,16 @@ fn main() -> Result<(), anyhow::Error> {
     .expect("Invalid issuer URL");
 
     // Fetch Okta's OpenID Connect discovery document.
-    let provider_metadata = DeviceProviderMetadata::discover(&issuer_url, http_client)
+    let provider_metadata = CoreProviderMetadata::discover(&issuer_url, http_client)
         .unwrap_or_else(|err| {
             handle_error(&err, "Failed to discover OpenID Provider");
             unreachable!();
         });
 
-    // Use the custom metadata to get the device_authorization_endpoint
     let device_authorization_endpoint = provider_metadata
-        .additional_metadata()
-        .device_authorization_endpoint
-        .clone();
+        .device_authorization_endpoint()
+        .cloned()
+        .expect("Provider should publish a device authorization endpoint");
 
     // Set up the config for the Okta device authorization process.
     let client =
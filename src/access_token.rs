@@ -0,0 +1,382 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use oauth2::helpers::deserialize_space_delimited_vec;
+use oauth2::{ClientId, Scope};
+
+use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
+use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt};
+use crate::verification::{AudiencesClaim, IssuerClaim};
+use crate::{
+    AdditionalClaims, Audience, AuthenticationContextClass, AuthenticationMethodReference,
+    ClaimsVerificationError, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken,
+    JsonWebTokenId, JweContentEncryptionAlgorithm, JwsSigningAlgorithm, JwtAccessTokenVerifier,
+    PrivateSigningKey, SubjectIdentifier,
+};
+
+// Explicit JWT type of access tokens (see https://tools.ietf.org/html/rfc9068#section-2.1).
+pub(crate) const ACCESS_TOKEN_TYPE: &str = "at+jwt";
+
+///
+/// Access token claims, as described in
+/// [RFC 9068](https://tools.ietf.org/html/rfc9068#section-2.2).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(bound = "AC: AdditionalClaims")]
+pub struct JwtAccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    #[serde(rename = "iss")]
+    issuer: IssuerUrl,
+    #[serde(rename = "exp", with = "serde_utc_seconds")]
+    expiration: DateTime<Utc>,
+    #[serde(rename = "aud", deserialize_with = "deserialize_string_or_vec")]
+    audiences: Vec<Audience>,
+    #[serde(rename = "sub")]
+    subject: SubjectIdentifier,
+    client_id: ClientId,
+    #[serde(rename = "iat", with = "serde_utc_seconds")]
+    issue_time: DateTime<Utc>,
+    #[serde(rename = "jti")]
+    jwt_id: JsonWebTokenId,
+    #[serde(
+        rename = "scope",
+        default,
+        deserialize_with = "deserialize_space_delimited_vec",
+        serialize_with = "oauth2::helpers::serialize_space_delimited_vec",
+        skip_serializing_if = "Option::is_none"
+    )]
+    scopes: Option<Vec<Scope>>,
+    #[serde(
+        default,
+        with = "serde_utc_seconds_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    auth_time: Option<DateTime<Utc>>,
+    #[serde(rename = "acr", default, skip_serializing_if = "Option::is_none")]
+    auth_context_ref: Option<AuthenticationContextClass>,
+    #[serde(rename = "amr", default, skip_serializing_if = "Option::is_none")]
+    auth_method_refs: Option<Vec<AuthenticationMethodReference>>,
+    #[serde(flatten)]
+    additional_claims: AC,
+}
+impl<AC> JwtAccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    ///
+    /// Initializes new access token claims containing the claims required by RFC 9068.
+    ///
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        issuer: IssuerUrl,
+        audiences: Vec<Audience>,
+        expiration: DateTime<Utc>,
+        issue_time: DateTime<Utc>,
+        subject: SubjectIdentifier,
+        client_id: ClientId,
+        jwt_id: JsonWebTokenId,
+        additional_claims: AC,
+    ) -> Self {
+        Self {
+            issuer,
+            expiration,
+            audiences,
+            subject,
+            client_id,
+            issue_time,
+            jwt_id,
+            scopes: None,
+            auth_time: None,
+            auth_context_ref: None,
+            auth_method_refs: None,
+            additional_claims,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_issuer -> issuer[IssuerUrl] ["iss"],
+            set_audiences -> audiences[Vec<Audience>] ["aud"],
+            set_expiration -> expiration[DateTime<Utc>] ["exp"],
+            set_issue_time -> issue_time[DateTime<Utc>] ["iat"],
+            set_subject -> subject[SubjectIdentifier] ["sub"],
+            set_client_id -> client_id[ClientId],
+            set_jwt_id -> jwt_id[JsonWebTokenId] ["jti"],
+            set_scopes -> scopes[Option<Vec<Scope>>] ["scope"],
+            set_auth_time -> auth_time[Option<DateTime<Utc>>],
+            set_auth_context_ref -> auth_context_ref[Option<AuthenticationContextClass>] ["acr"],
+            set_auth_method_refs -> auth_method_refs[Option<Vec<AuthenticationMethodReference>>] ["amr"],
+        }
+    ];
+
+    ///
+    /// Returns additional access token claims.
+    ///
+    pub fn additional_claims(&self) -> &AC {
+        &self.additional_claims
+    }
+    ///
+    /// Returns mutable additional access token claims.
+    ///
+    pub fn additional_claims_mut(&mut self) -> &mut AC {
+        &mut self.additional_claims
+    }
+}
+impl<AC> AudiencesClaim for JwtAccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(JwtAccessTokenClaims::audiences(self))
+    }
+}
+impl<AC> AudiencesClaim for &JwtAccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(JwtAccessTokenClaims::audiences(self))
+    }
+}
+impl<AC> IssuerClaim for JwtAccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(JwtAccessTokenClaims::issuer(self))
+    }
+}
+impl<AC> IssuerClaim for &JwtAccessTokenClaims<AC>
+where
+    AC: AdditionalClaims,
+{
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(JwtAccessTokenClaims::issuer(self))
+    }
+}
+
+///
+/// Access token in the JWT profile described in [RFC 9068](https://tools.ietf.org/html/rfc9068).
+///
+/// Resource servers can use this type to validate access tokens locally using the authorization
+/// server's JSON Web Key Set rather than via token introspection.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct JwtAccessToken<
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+>(
+    #[serde(bound = "AC: AdditionalClaims")]
+    JsonWebToken<JE, JS, JT, JwtAccessTokenClaims<AC>, JsonWebTokenJsonPayloadSerde>,
+);
+impl<AC, JE, JS, JT> FromStr for JwtAccessToken<AC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+    }
+}
+impl<AC, JE, JS, JT> JwtAccessToken<AC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Initializes an access token with the specified claims, signed using the given signing key
+    /// and algorithm.
+    ///
+    /// The token is explicitly typed using the `at+jwt` JOSE header `typ` value, as required by
+    /// RFC 9068.
+    ///
+    pub fn new<JU, K, S>(
+        claims: JwtAccessTokenClaims<AC>,
+        signing_key: &S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let mut header_params = serde_json::Map::new();
+        header_params.insert(
+            "typ".to_string(),
+            serde_json::Value::String(ACCESS_TOKEN_TYPE.to_string()),
+        );
+        JsonWebToken::new_with_header_params(claims, signing_key, &alg, header_params).map(Self)
+    }
+
+    ///
+    /// Verifies and returns a reference to the access token claims.
+    ///
+    /// The token must be explicitly typed as `at+jwt`, and its signature, issuer, audience, and
+    /// expiration are verified using the given verifier (see
+    /// [Section 4](https://tools.ietf.org/html/rfc9068#section-4) of RFC 9068). Authorization
+    /// decisions based on the `scope` claim and other claims are left to the resource server.
+    ///
+    pub fn claims<'a, JU, K>(
+        &'a self,
+        verifier: &JwtAccessTokenVerifier<JS, JT, JU, K>,
+    ) -> Result<&'a JwtAccessTokenClaims<AC>, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_claims(&self.0)
+    }
+}
+impl<AC, JE, JS, JT> std::fmt::Display for JwtAccessToken<AC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let serialized = serde_json::to_value(self)
+            // This should never arise, since we're just asking serde_json to serialize the
+            // signing input concatenated with the signature, both of which are precomputed.
+            .expect("access token serialization failed");
+        f.write_str(
+            serialized
+                .as_str()
+                // This should also never arise, since the JWT serializer always calls
+                // serialize_str.
+                .expect("access token serializer did not produce a str"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{TimeZone, Utc};
+    use oauth2::{ClientId, Scope};
+
+    use super::JwtAccessToken;
+    use crate::core::{
+        CoreJsonWebKey, CoreJsonWebKeySet, CoreJwsSigningAlgorithm, CoreJwtAccessToken,
+        CoreJwtAccessTokenClaims, CoreJwtAccessTokenVerifier, CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+    use crate::{
+        Audience, ClaimsVerificationError, EmptyAdditionalClaims, IssuerUrl, JsonWebToken,
+        JsonWebTokenId, SubjectIdentifier,
+    };
+
+    #[test]
+    fn test_jwt_access_token() {
+        let issuer = IssuerUrl::new("https://as.example.com".to_string()).unwrap();
+        let claims = CoreJwtAccessTokenClaims::new(
+            issuer.clone(),
+            vec![Audience::new("https://rs.example.com".to_string())],
+            Utc.timestamp_opt(1544932149, 0).single().unwrap(),
+            Utc.timestamp_opt(1544928549, 0).single().unwrap(),
+            SubjectIdentifier::new("5ba552d67".to_string()),
+            ClientId::new("s6BhdRkqt3".to_string()),
+            JsonWebTokenId::new("dbe39bf3a3ba4238a513f51d6e1691c4".to_string()),
+            EmptyAdditionalClaims {},
+        )
+        .set_scopes(Some(vec![
+            Scope::new("openid".to_string()),
+            Scope::new("profile".to_string()),
+        ]));
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let access_token = CoreJwtAccessToken::new(
+            claims.clone(),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+
+        let serialized = access_token.to_string();
+        let decode = |part: usize| {
+            serde_json::from_slice::<serde_json::Value>(
+                &base64::decode_config(
+                    serialized.split('.').nth(part).unwrap(),
+                    base64::URL_SAFE_NO_PAD,
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            decode(0),
+            serde_json::json!({"alg": "RS256", "typ": "at+jwt"})
+        );
+        assert_eq!(
+            decode(1),
+            serde_json::json!({
+                "iss": "https://as.example.com",
+                "exp": 1544932149,
+                "aud": ["https://rs.example.com"],
+                "sub": "5ba552d67",
+                "client_id": "s6BhdRkqt3",
+                "iat": 1544928549,
+                "jti": "dbe39bf3a3ba4238a513f51d6e1691c4",
+                "scope": "openid profile",
+            })
+        );
+
+        let access_token = CoreJwtAccessToken::from_str(&serialized).unwrap();
+        let verifier = CoreJwtAccessTokenVerifier::new(
+            Audience::new("https://rs.example.com".to_string()),
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        )
+        .set_time_fn(|| Utc.timestamp_opt(1544928550, 0).single().unwrap());
+        assert_eq!(access_token.claims(&verifier).unwrap(), &claims);
+
+        match access_token.claims(
+            &verifier
+                .clone()
+                .set_time_fn(|| Utc.timestamp_opt(1544932149, 0).single().unwrap()),
+        ) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let other_verifier = CoreJwtAccessTokenVerifier::new(
+            Audience::new("https://other-rs.example.com".to_string()),
+            issuer,
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        );
+        match access_token.claims(&other_verifier) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Tokens that aren't explicitly typed (e.g., ID tokens) are rejected.
+        let untyped: CoreJwtAccessToken = JwtAccessToken(
+            JsonWebToken::new(
+                claims,
+                &signing_key,
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            )
+            .unwrap(),
+        );
+        match untyped.claims(&verifier) {
+            Err(ClaimsVerificationError::Unsupported(msg)) => {
+                assert_eq!(msg, "missing JWT type (expected `at+jwt`)")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
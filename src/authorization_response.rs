@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use oauth2::{AccessToken, AuthorizationCode, CsrfToken, StandardErrorResponse};
+use thiserror::Error;
+use url::{form_urlencoded, Url};
+
+use crate::core::{CoreAuthErrorResponse, CoreAuthErrorResponseType};
+use crate::{
+    AdditionalClaims, ClaimsVerificationError, GenderClaim, IdToken, IdTokenClaims,
+    IdTokenVerifier, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, NonceVerifier,
+};
+
+///
+/// Authorization response parameters passed by the OpenID Connect Provider to the redirect URI.
+///
+/// This type supports responses to all three authentication flows. In the
+/// [Authorization Code Flow](https://openid.net/specs/openid-connect-core-1_0.html#CodeFlowAuth),
+/// the response parameters are passed in the redirect URI's query string, while the
+/// [Implicit](https://openid.net/specs/openid-connect-core-1_0.html#ImplicitFlowAuth) and
+/// [Hybrid](https://openid.net/specs/openid-connect-core-1_0.html#HybridFlowAuth) flows pass them
+/// in the URI fragment, which is never sent to the server and must be forwarded by client-side
+/// code (e.g., as a POST request body).
+///
+/// After parsing a response, use [`verify`](Self::verify) to check the `state` parameter, verify
+/// the ID token (if any), and validate its `c_hash` and `at_hash` claims against the
+/// authorization code and access token returned alongside it.
+///
+#[derive(Clone, Debug)]
+pub struct ParsedAuthorizationResponse<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    access_token: Option<AccessToken>,
+    code: Option<AuthorizationCode>,
+    expires_in: Option<Duration>,
+    id_token: Option<IdToken<AC, GC, JE, JS, JT>>,
+    state: Option<CsrfToken>,
+    token_type: Option<String>,
+}
+impl<AC, GC, JE, JS, JT> ParsedAuthorizationResponse<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Parses the authorization response parameters from the full redirect URL.
+    ///
+    /// The parameters are read from the URL fragment if it is present and non-empty, and from
+    /// the query string otherwise.
+    ///
+    pub fn from_url(url: &Url) -> Result<Self, AuthorizationResponseError> {
+        match url.fragment() {
+            Some(fragment) if !fragment.is_empty() => Self::from_params(fragment),
+            _ => Self::from_params(url.query().unwrap_or_default()),
+        }
+    }
+
+    ///
+    /// Parses the authorization response parameters from an `application/x-www-form-urlencoded`
+    /// string, such as a redirect URI's query string or fragment, or a `form_post` request body.
+    ///
+    /// If the response contains an `error` parameter, the error response is returned as
+    /// [`AuthorizationResponseError::ErrorResponse`].
+    ///
+    pub fn from_params(params: &str) -> Result<Self, AuthorizationResponseError> {
+        Self::from_form_post(params.as_bytes())
+    }
+
+    ///
+    /// Parses the authorization response parameters from the body of a request made to the
+    /// redirect URI using the
+    /// [`form_post`](https://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html)
+    /// response mode.
+    ///
+    /// The body must be encoded as `application/x-www-form-urlencoded`. If the response contains
+    /// an `error` parameter, the error response is returned as
+    /// [`AuthorizationResponseError::ErrorResponse`].
+    ///
+    pub fn from_form_post(body: &[u8]) -> Result<Self, AuthorizationResponseError> {
+        let mut param_map = HashMap::new();
+        for (name, value) in form_urlencoded::parse(body) {
+            // Authorization response parameters must not be included more than once (see
+            // Section 3.1 of RFC 6749).
+            if param_map
+                .insert(name.to_string(), value.into_owned())
+                .is_some()
+            {
+                return Err(AuthorizationResponseError::DuplicateParameter(
+                    name.into_owned(),
+                ));
+            }
+        }
+
+        if let Some(error) = param_map.remove("error") {
+            return Err(AuthorizationResponseError::ErrorResponse(
+                StandardErrorResponse::new(
+                    serde_plain::from_str(&error)
+                        .unwrap_or(CoreAuthErrorResponseType::Extension(error)),
+                    param_map.remove("error_description"),
+                    param_map.remove("error_uri"),
+                ),
+            ));
+        }
+
+        Ok(Self {
+            access_token: param_map.remove("access_token").map(AccessToken::new),
+            code: param_map.remove("code").map(AuthorizationCode::new),
+            expires_in: param_map
+                .remove("expires_in")
+                .map(|expires_in| {
+                    expires_in
+                        .parse::<u64>()
+                        .map(Duration::from_secs)
+                        .map_err(|_| {
+                            AuthorizationResponseError::InvalidParameter(
+                                "expires_in".to_string(),
+                                expires_in,
+                            )
+                        })
+                })
+                .transpose()?,
+            id_token: param_map
+                .remove("id_token")
+                .map(|id_token| {
+                    id_token
+                        .parse()
+                        .map_err(AuthorizationResponseError::IdToken)
+                })
+                .transpose()?,
+            state: param_map.remove("state").map(CsrfToken::new),
+            token_type: param_map.remove("token_type"),
+        })
+    }
+
+    ///
+    /// Returns the access token (`access_token`) parameter, if any.
+    ///
+    pub fn access_token(&self) -> Option<&AccessToken> {
+        self.access_token.as_ref()
+    }
+
+    ///
+    /// Returns the authorization code (`code`) parameter, if any.
+    ///
+    pub fn code(&self) -> Option<&AuthorizationCode> {
+        self.code.as_ref()
+    }
+
+    ///
+    /// Returns the access token lifetime (`expires_in`) parameter, if any.
+    ///
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.expires_in
+    }
+
+    ///
+    /// Returns the (unverified) ID token (`id_token`) parameter, if any.
+    ///
+    pub fn id_token(&self) -> Option<&IdToken<AC, GC, JE, JS, JT>> {
+        self.id_token.as_ref()
+    }
+
+    ///
+    /// Returns the CSRF state (`state`) parameter, if any.
+    ///
+    pub fn state(&self) -> Option<&CsrfToken> {
+        self.state.as_ref()
+    }
+
+    ///
+    /// Returns the access token type (`token_type`) parameter, if any.
+    ///
+    pub fn token_type(&self) -> Option<&str> {
+        self.token_type.as_deref()
+    }
+
+    ///
+    /// Verifies that the `state` parameter matches the CSRF state sent in the authorization
+    /// request.
+    ///
+    /// The comparison is performed in constant time.
+    ///
+    pub fn verify_state(
+        &self,
+        expected_state: &CsrfToken,
+    ) -> Result<(), AuthorizationResponseError> {
+        use subtle::ConstantTimeEq;
+
+        let state = self
+            .state
+            .as_ref()
+            .ok_or(AuthorizationResponseError::MissingState)?;
+        if bool::from(
+            state
+                .secret()
+                .as_bytes()
+                .ct_eq(expected_state.secret().as_bytes()),
+        ) {
+            Ok(())
+        } else {
+            Err(AuthorizationResponseError::InvalidState)
+        }
+    }
+
+    ///
+    /// Verifies the authorization response and returns the verified ID token claims, if the
+    /// response includes an ID token.
+    ///
+    /// This method verifies that the `state` parameter matches `expected_state` and verifies the
+    /// ID token using `verifier` and `nonce_verifier`. If the response includes both an ID token
+    /// and an authorization code, the ID token must contain a matching `c_hash` claim. Likewise,
+    /// if the response includes both an ID token and an access token, the ID token must contain a
+    /// matching `at_hash` claim (see
+    /// [Section 3.3.2.11](https://openid.net/specs/openid-connect-core-1_0.html#HybridIDToken)).
+    ///
+    pub fn verify<JU, K, N>(
+        &self,
+        expected_state: &CsrfToken,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+    ) -> Result<Option<&IdTokenClaims<AC, GC>>, AuthorizationResponseError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        self.verify_state(expected_state)?;
+
+        let id_token = if let Some(ref id_token) = self.id_token {
+            id_token
+        } else {
+            return Ok(None);
+        };
+        let claims = id_token
+            .claims(verifier, nonce_verifier)
+            .map_err(AuthorizationResponseError::ClaimsVerification)?;
+
+        if self.code.is_some() || self.access_token.is_some() {
+            let alg = id_token.signing_alg().map_err(|err| {
+                AuthorizationResponseError::ClaimsVerification(
+                    ClaimsVerificationError::Unsupported(err.to_string()),
+                )
+            })?;
+
+            if let Some(ref code) = self.code {
+                claims
+                    .code_hash()
+                    .ok_or_else(|| {
+                        ClaimsVerificationError::InvalidAuthorizationCodeHash(
+                            "missing `c_hash` claim".to_string(),
+                        )
+                    })
+                    .and_then(|code_hash| code_hash.verify(code, &alg))
+                    .map_err(AuthorizationResponseError::ClaimsVerification)?;
+            }
+            if let Some(ref access_token) = self.access_token {
+                claims
+                    .access_token_hash()
+                    .ok_or_else(|| {
+                        ClaimsVerificationError::InvalidAccessTokenHash(
+                            "missing `at_hash` claim".to_string(),
+                        )
+                    })
+                    .and_then(|access_token_hash| access_token_hash.verify(access_token, &alg))
+                    .map_err(AuthorizationResponseError::ClaimsVerification)?;
+            }
+        }
+
+        Ok(Some(claims))
+    }
+}
+
+///
+/// Error parsing or verifying an authorization response.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum AuthorizationResponseError {
+    ///
+    /// Failed to verify the ID token claims or the `c_hash` or `at_hash` claims.
+    ///
+    #[error("Failed to verify claims")]
+    ClaimsVerification(#[source] ClaimsVerificationError),
+    ///
+    /// A response parameter was included more than once.
+    ///
+    #[error("Duplicate `{0}` parameter")]
+    DuplicateParameter(String),
+    ///
+    /// The OpenID Connect Provider returned an error response.
+    ///
+    #[error("Authorization server returned error: {0}")]
+    ErrorResponse(CoreAuthErrorResponse),
+    ///
+    /// Failed to parse the ID token.
+    ///
+    #[error("Failed to parse ID token")]
+    IdToken(#[source] serde_json::Error),
+    ///
+    /// A response parameter has an invalid value.
+    ///
+    #[error("Invalid `{0}` parameter: {1}")]
+    InvalidParameter(String, String),
+    ///
+    /// The `state` parameter does not match the CSRF state sent in the authorization request.
+    ///
+    #[error("CSRF state mismatch")]
+    InvalidState,
+    ///
+    /// The response does not include a `code` parameter.
+    ///
+    #[error("Missing `code` parameter")]
+    MissingCode,
+    ///
+    /// The response does not include a `state` parameter.
+    ///
+    #[error("Missing `state` parameter")]
+    MissingState,
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::{AccessToken, AuthorizationCode, ClientId, CsrfToken};
+    use url::Url;
+
+    use crate::core::{
+        CoreAuthErrorResponseType, CoreIdTokenVerifier, CoreJsonWebKeySet,
+        CoreParsedAuthorizationResponse,
+    };
+    use crate::jwt::tests::TEST_RSA_PUB_KEY;
+    use crate::login::tests::new_id_token;
+    use crate::{ClaimsVerificationError, IssuerUrl, Nonce};
+
+    use super::AuthorizationResponseError;
+
+    fn new_verifier() -> CoreIdTokenVerifier<'static> {
+        CoreIdTokenVerifier::new_public_client(
+            ClientId::new("my_client".to_string()),
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str(TEST_RSA_PUB_KEY).unwrap()]),
+        )
+    }
+
+    fn redirect_url(fragment: &[(&str, &str)]) -> Url {
+        let mut url = Url::parse("https://app.example.com/cb").unwrap();
+        url.set_fragment(Some(
+            &url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(fragment)
+                .finish(),
+        ));
+        url
+    }
+
+    #[test]
+    fn test_hybrid_response() {
+        let nonce = Nonce::new("the_nonce".to_string());
+        let state = CsrfToken::new("the_state".to_string());
+        let code = AuthorizationCode::new("the_code".to_string());
+        let access_token = AccessToken::new("the_access_token".to_string());
+        let id_token = new_id_token(Some(&nonce), Some(&access_token), Some(&code)).to_string();
+
+        let url = redirect_url(&[
+            ("code", code.secret()),
+            ("id_token", &id_token),
+            ("access_token", access_token.secret()),
+            ("token_type", "Bearer"),
+            ("expires_in", "3600"),
+            ("state", state.secret()),
+        ]);
+        let response = CoreParsedAuthorizationResponse::from_url(&url).unwrap();
+        assert_eq!(response.code().unwrap().secret(), code.secret());
+        assert_eq!(
+            response.access_token().unwrap().secret(),
+            access_token.secret()
+        );
+        assert_eq!(response.token_type(), Some("Bearer"));
+        assert_eq!(
+            response.expires_in(),
+            Some(std::time::Duration::from_secs(3600))
+        );
+
+        let claims = response
+            .verify(&state, &new_verifier(), &nonce)
+            .unwrap()
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "the_subject");
+
+        match response.verify(
+            &CsrfToken::new("other_state".to_string()),
+            &new_verifier(),
+            &nonce,
+        ) {
+            Err(AuthorizationResponseError::InvalidState) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Substituted authorization code (c_hash mismatch)
+        let url = redirect_url(&[
+            ("code", "other_code"),
+            ("id_token", &id_token),
+            ("state", state.secret()),
+        ]);
+        match CoreParsedAuthorizationResponse::from_url(&url)
+            .unwrap()
+            .verify(&state, &new_verifier(), &nonce)
+        {
+            Err(AuthorizationResponseError::ClaimsVerification(
+                ClaimsVerificationError::InvalidAuthorizationCodeHash(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Missing at_hash claim
+        let id_token = new_id_token(Some(&nonce), None, Some(&code)).to_string();
+        let url = redirect_url(&[
+            ("id_token", &id_token),
+            ("access_token", access_token.secret()),
+            ("state", state.secret()),
+        ]);
+        match CoreParsedAuthorizationResponse::from_url(&url)
+            .unwrap()
+            .verify(&state, &new_verifier(), &nonce)
+        {
+            Err(AuthorizationResponseError::ClaimsVerification(
+                ClaimsVerificationError::InvalidAccessTokenHash(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_code_response() {
+        let state = CsrfToken::new("the_state".to_string());
+        let url = Url::parse("https://app.example.com/cb?code=the_code&state=the_state").unwrap();
+        let response = CoreParsedAuthorizationResponse::from_url(&url).unwrap();
+        assert_eq!(response.code().unwrap().secret(), "the_code");
+        assert!(response.id_token().is_none());
+        assert!(response
+            .verify(
+                &state,
+                &new_verifier(),
+                &Nonce::new("the_nonce".to_string())
+            )
+            .unwrap()
+            .is_none());
+
+        let response =
+            CoreParsedAuthorizationResponse::from_form_post(b"code=the%20code&state=the_state")
+                .unwrap();
+        assert_eq!(response.code().unwrap().secret(), "the code");
+        response.verify_state(&state).unwrap();
+
+        match CoreParsedAuthorizationResponse::from_params("code=the_code")
+            .unwrap()
+            .verify_state(&state)
+        {
+            Err(AuthorizationResponseError::MissingState) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_error_response() {
+        match CoreParsedAuthorizationResponse::from_params(
+            "error=login_required&error_description=Login+required&state=the_state",
+        ) {
+            Err(AuthorizationResponseError::ErrorResponse(error)) => {
+                assert_eq!(*error.error(), CoreAuthErrorResponseType::LoginRequired);
+                assert_eq!(
+                    error.error_description().map(String::as_str),
+                    Some("Login required")
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match CoreParsedAuthorizationResponse::from_form_post(b"error=access_denied") {
+            Err(AuthorizationResponseError::ErrorResponse(error)) => {
+                assert_eq!(*error.error(), CoreAuthErrorResponseType::AccessDenied);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match CoreParsedAuthorizationResponse::from_params("code=a&code=b") {
+            Err(AuthorizationResponseError::DuplicateParameter(name)) => assert_eq!(name, "code"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match CoreParsedAuthorizationResponse::from_params("id_token=invalid") {
+            Err(AuthorizationResponseError::IdToken(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
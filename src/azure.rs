@@ -0,0 +1,527 @@
+use std::borrow::Cow;
+use std::ops::Deref;
+
+use oauth2::{
+    AuthUrl, CodeTokenRequest, ErrorResponse, RefreshTokenRequest,
+    TokenResponse as OAuth2TokenResponse, TokenType, TokenUrl,
+};
+
+use crate::core::{
+    CoreAuthDisplay, CoreAuthPrompt, CoreErrorResponseType, CoreGenderClaim, CoreJsonWebKey,
+    CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm, CoreRevocableToken, CoreRevocationErrorResponse,
+    CoreTokenIntrospectionResponse, CoreTokenType,
+};
+use crate::{
+    AdditionalClaims, AuthDisplay, AuthPrompt, AuthorizationRequest, ClaimsVerificationError,
+    Client, ExtendedTokenFields, IdToken, IdTokenClaims, IdTokenFields, IssuerUrl,
+    JsonWebKeySetUrl, ResponseType, StandardErrorResponse, StandardTokenResponse, UserInfoUrl,
+};
+
+const AZURE_LOGIN_HOST: &str = "https://login.microsoftonline.com";
+const AZURE_V1_ISSUER_HOST: &str = "https://sts.windows.net";
+const AZURE_RESOURCE_PARAM: &str = "resource";
+const AZURE_TENANT_ID_PLACEHOLDER: &str = "{tenantid}";
+
+///
+/// Azure AD ID token claims, including the Azure-specific additional claims.
+///
+pub type AzureIdTokenClaims = IdTokenClaims<AzureAdditionalClaims, CoreGenderClaim>;
+
+///
+/// Azure AD ID token.
+///
+pub type AzureIdToken = IdToken<
+    AzureAdditionalClaims,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// Azure AD ID token fields, including the `ext_expires_in` token response extension.
+///
+pub type AzureIdTokenFields = IdTokenFields<
+    AzureAdditionalClaims,
+    ExtendedTokenFields,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// Azure AD token response.
+///
+pub type AzureTokenResponse = StandardTokenResponse<AzureIdTokenFields, CoreTokenType>;
+
+///
+/// Azure AD client.
+///
+pub type AzureClient = Client<
+    AzureAdditionalClaims,
+    CoreAuthDisplay,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreAuthPrompt,
+    StandardErrorResponse<CoreErrorResponseType>,
+    AzureTokenResponse,
+    CoreTokenType,
+    CoreTokenIntrospectionResponse,
+    CoreRevocableToken,
+    CoreRevocationErrorResponse,
+>;
+
+new_type![
+    ///
+    /// Azure AD tenant ID (`tid` claim).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    AzureTenantId(String)
+];
+
+new_type![
+    ///
+    /// Immutable identifier of the user within the Azure AD tenant (`oid` claim).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    AzureObjectId(String)
+];
+
+///
+/// Version of the Azure AD (Microsoft identity platform) endpoints.
+///
+/// The v1.0 and v2.0 endpoints differ in their URLs, their issuer identifiers, and the claims
+/// included in ID tokens.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum AzureEndpointVersion {
+    ///
+    /// Azure AD v1.0 endpoints, which issue tokens with the `https://sts.windows.net/{tenantid}/`
+    /// issuer.
+    ///
+    V1,
+    ///
+    /// Microsoft identity platform (v2.0) endpoints, which issue tokens with the
+    /// `https://login.microsoftonline.com/{tenantid}/v2.0` issuer.
+    ///
+    V2,
+}
+
+///
+/// Azure AD tenant used to authenticate users.
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum AzureTenant {
+    ///
+    /// Users with either a work or school account or a personal Microsoft account (multi-tenant).
+    ///
+    Common,
+    ///
+    /// Users with a work or school account (multi-tenant).
+    ///
+    Organizations,
+    ///
+    /// Users with a personal Microsoft account.
+    ///
+    Consumers,
+    ///
+    /// Users of a single tenant, identified by its tenant ID or domain name.
+    ///
+    Tenant(String),
+}
+impl AzureTenant {
+    ///
+    /// Returns the tenant path segment used in Azure AD endpoint URLs.
+    ///
+    pub fn as_str(&self) -> &str {
+        match self {
+            AzureTenant::Common => "common",
+            AzureTenant::Organizations => "organizations",
+            AzureTenant::Consumers => "consumers",
+            AzureTenant::Tenant(tenant) => tenant,
+        }
+    }
+
+    ///
+    /// Returns whether tokens for this tenant may be issued by more than one tenant, in which case
+    /// the issuer returned by [`issuer_url`](Self::issuer_url) contains a `{tenantid}`
+    /// placeholder.
+    ///
+    pub fn is_multi_tenant(&self) -> bool {
+        !matches!(self, AzureTenant::Tenant(_))
+    }
+
+    ///
+    /// Returns the authority URL for this tenant, which is the base of the other endpoint URLs.
+    ///
+    pub fn authority_url(&self, version: AzureEndpointVersion) -> String {
+        match version {
+            AzureEndpointVersion::V1 => format!("{}/{}", AZURE_LOGIN_HOST, self.as_str()),
+            AzureEndpointVersion::V2 => format!("{}/{}/v2.0", AZURE_LOGIN_HOST, self.as_str()),
+        }
+    }
+
+    ///
+    /// Returns the expected issuer (`iss` claim) of tokens issued for this tenant.
+    ///
+    /// For multi-tenant configurations, the returned issuer URL contains a `{tenantid}`
+    /// placeholder, which [`Client`] treats as a template (see
+    /// [`IssuerUrl::matches_template`]). Use [`verify_tenant_issuer`] to check that the tenant
+    /// in the issuer matches the `tid` claim.
+    ///
+    pub fn issuer_url(&self, version: AzureEndpointVersion) -> IssuerUrl {
+        let tenant = if self.is_multi_tenant() {
+            AZURE_TENANT_ID_PLACEHOLDER
+        } else {
+            self.as_str()
+        };
+        IssuerUrl::new(match version {
+            AzureEndpointVersion::V1 => format!("{}/{}/", AZURE_V1_ISSUER_HOST, tenant),
+            AzureEndpointVersion::V2 => format!("{}/{}/v2.0", AZURE_LOGIN_HOST, tenant),
+        })
+        .expect("Azure AD issuer URL should be valid")
+    }
+
+    ///
+    /// Returns the authorization endpoint URL for this tenant.
+    ///
+    pub fn auth_url(&self, version: AzureEndpointVersion) -> AuthUrl {
+        AuthUrl::new(self.oauth2_endpoint(version, "authorize"))
+            .expect("Azure AD authorization URL should be valid")
+    }
+
+    ///
+    /// Returns the token endpoint URL for this tenant.
+    ///
+    pub fn token_url(&self, version: AzureEndpointVersion) -> TokenUrl {
+        TokenUrl::new(self.oauth2_endpoint(version, "token"))
+            .expect("Azure AD token URL should be valid")
+    }
+
+    ///
+    /// Returns the JSON Web Key Set URL for this tenant.
+    ///
+    pub fn jwks_url(&self, version: AzureEndpointVersion) -> JsonWebKeySetUrl {
+        JsonWebKeySetUrl::new(match version {
+            AzureEndpointVersion::V1 => {
+                format!("{}/{}/discovery/keys", AZURE_LOGIN_HOST, self.as_str())
+            }
+            AzureEndpointVersion::V2 => {
+                format!("{}/{}/discovery/v2.0/keys", AZURE_LOGIN_HOST, self.as_str())
+            }
+        })
+        .expect("Azure AD JWKS URL should be valid")
+    }
+
+    ///
+    /// Returns the user info endpoint URL for this tenant.
+    ///
+    /// The v2.0 user info endpoint is hosted by Microsoft Graph and requires an access token
+    /// issued for Microsoft Graph.
+    ///
+    pub fn userinfo_url(&self, version: AzureEndpointVersion) -> UserInfoUrl {
+        UserInfoUrl::new(match version {
+            AzureEndpointVersion::V1 => {
+                format!("{}/{}/openid/userinfo", AZURE_LOGIN_HOST, self.as_str())
+            }
+            AzureEndpointVersion::V2 => "https://graph.microsoft.com/oidc/userinfo".to_string(),
+        })
+        .expect("Azure AD user info URL should be valid")
+    }
+
+    fn oauth2_endpoint(&self, version: AzureEndpointVersion, endpoint: &str) -> String {
+        match version {
+            AzureEndpointVersion::V1 => {
+                format!("{}/{}/oauth2/{}", AZURE_LOGIN_HOST, self.as_str(), endpoint)
+            }
+            AzureEndpointVersion::V2 => {
+                format!(
+                    "{}/{}/oauth2/v2.0/{}",
+                    AZURE_LOGIN_HOST,
+                    self.as_str(),
+                    endpoint
+                )
+            }
+        }
+    }
+}
+
+///
+/// Azure AD-specific ID token claims.
+///
+/// The `preferred_username` claim is a standard claim, and is available via
+/// [`StandardClaims::preferred_username`](crate::StandardClaims::preferred_username). Azure AD
+/// v1.0 tokens instead identify the user via the `upn` claim.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct AzureAdditionalClaims {
+    #[serde(rename = "tid", skip_serializing_if = "Option::is_none")]
+    tenant_id: Option<AzureTenantId>,
+    #[serde(rename = "oid", skip_serializing_if = "Option::is_none")]
+    object_id: Option<AzureObjectId>,
+    #[serde(rename = "upn", skip_serializing_if = "Option::is_none")]
+    user_principal_name: Option<String>,
+    #[serde(rename = "ver", skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+}
+impl AzureAdditionalClaims {
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_tenant_id -> tenant_id[Option<AzureTenantId>] ["tid"],
+            set_object_id -> object_id[Option<AzureObjectId>] ["oid"],
+            set_user_principal_name -> user_principal_name[Option<String>] ["upn"],
+            set_version -> version[Option<String>] ["ver"],
+        }
+    ];
+}
+impl AdditionalClaims for AzureAdditionalClaims {}
+
+///
+/// Verifies that the tenant in the issuer (`iss`) claim of Azure AD ID token claims matches the
+/// tenant ID (`tid`) claim.
+///
+/// Multi-tenant applications accept any issuer matching the templated issuer returned by
+/// [`AzureTenant::issuer_url`], so this check should be performed after verifying the ID token
+/// to ensure that a token issued by one tenant does not claim to represent another.
+///
+pub fn verify_tenant_issuer(claims: &AzureIdTokenClaims) -> Result<(), ClaimsVerificationError> {
+    let tenant_id = claims.additional_claims().tenant_id().ok_or_else(|| {
+        ClaimsVerificationError::InvalidIssuer("missing tenant ID (`tid`) claim".to_string())
+    })?;
+
+    let issuer = claims.issuer();
+    if [AzureEndpointVersion::V1, AzureEndpointVersion::V2]
+        .iter()
+        .any(|version| AzureTenant::Tenant(tenant_id.to_string()).issuer_url(*version) == *issuer)
+    {
+        Ok(())
+    } else {
+        Err(ClaimsVerificationError::InvalidIssuer(format!(
+            "issuer `{}` does not match tenant ID `{}`",
+            issuer.as_str(),
+            tenant_id.as_str()
+        )))
+    }
+}
+
+///
+/// Extension trait for adding the non-standard `resource` parameter used by the Azure AD v1.0
+/// endpoints to identify the API for which an access token is requested.
+///
+/// The v2.0 endpoints use scopes instead (e.g., `https://graph.microsoft.com/User.Read`).
+///
+pub trait AzureResourceExt<'a>: Sized {
+    ///
+    /// Sets the `resource` parameter.
+    ///
+    fn set_resource<R>(self, resource: R) -> Self
+    where
+        R: Into<Cow<'a, str>>;
+}
+impl<'a, AD, P, RT> AzureResourceExt<'a> for AuthorizationRequest<'a, AD, P, RT>
+where
+    AD: AuthDisplay,
+    P: AuthPrompt,
+    RT: ResponseType,
+{
+    fn set_resource<R>(self, resource: R) -> Self
+    where
+        R: Into<Cow<'a, str>>,
+    {
+        self.add_extra_param(AZURE_RESOURCE_PARAM, resource)
+    }
+}
+impl<'a, TE, TR, TT> AzureResourceExt<'a> for CodeTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: OAuth2TokenResponse<TT>,
+    TT: TokenType,
+{
+    fn set_resource<R>(self, resource: R) -> Self
+    where
+        R: Into<Cow<'a, str>>,
+    {
+        self.add_extra_param(AZURE_RESOURCE_PARAM, resource)
+    }
+}
+impl<'a, TE, TR, TT> AzureResourceExt<'a> for RefreshTokenRequest<'a, TE, TR, TT>
+where
+    TE: ErrorResponse + 'static,
+    TR: OAuth2TokenResponse<TT>,
+    TT: TokenType,
+{
+    fn set_resource<R>(self, resource: R) -> Self
+    where
+        R: Into<Cow<'a, str>>,
+    {
+        self.add_extra_param(AZURE_RESOURCE_PARAM, resource)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::{AuthUrl, ClientId, CsrfToken, TokenUrl};
+
+    use crate::core::{CoreAuthenticationFlow, CoreJsonWebKeySet};
+    use crate::{
+        Audience, ClaimsVerificationError, IssuerUrl, Nonce, StandardClaims, SubjectIdentifier,
+    };
+
+    use super::{
+        verify_tenant_issuer, AzureAdditionalClaims, AzureClient, AzureEndpointVersion,
+        AzureIdTokenClaims, AzureResourceExt, AzureTenant, AzureTenantId, AzureTokenResponse,
+    };
+
+    #[test]
+    fn test_tenant_urls() {
+        let tenant = AzureTenant::Tenant("contoso.onmicrosoft.com".to_string());
+        assert!(!tenant.is_multi_tenant());
+        assert_eq!(
+            tenant.auth_url(AzureEndpointVersion::V2),
+            AuthUrl::new(
+                "https://login.microsoftonline.com/contoso.onmicrosoft.com/oauth2/v2.0/authorize"
+                    .to_string()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            tenant.token_url(AzureEndpointVersion::V1),
+            TokenUrl::new(
+                "https://login.microsoftonline.com/contoso.onmicrosoft.com/oauth2/token"
+                    .to_string()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            tenant.jwks_url(AzureEndpointVersion::V2).as_str(),
+            "https://login.microsoftonline.com/contoso.onmicrosoft.com/discovery/v2.0/keys"
+        );
+        assert_eq!(
+            tenant.issuer_url(AzureEndpointVersion::V1).as_str(),
+            "https://sts.windows.net/contoso.onmicrosoft.com/"
+        );
+        assert_eq!(
+            AzureTenant::Common.authority_url(AzureEndpointVersion::V2),
+            "https://login.microsoftonline.com/common/v2.0"
+        );
+        assert!(AzureTenant::Organizations.is_multi_tenant());
+        assert_eq!(
+            AzureTenant::Organizations
+                .issuer_url(AzureEndpointVersion::V2)
+                .as_str(),
+            "https://login.microsoftonline.com/{tenantid}/v2.0"
+        );
+    }
+
+    #[test]
+    fn test_additional_claims() {
+        let claims = serde_json::from_str::<AzureIdTokenClaims>(
+            "{
+                \"iss\": \"https://login.microsoftonline.com/9122040d-6c67-4c5b-b112-36a304b66dad/v2.0\",
+                \"aud\": \"6cb04018-a3f5-46a7-b995-940c78f5aef3\",
+                \"exp\": 1536361411,
+                \"iat\": 1536274711,
+                \"sub\": \"AAAAAAAAAAAAAAAAAAAAAIkzqFVrSaSaFHy782bbtaQ\",
+                \"preferred_username\": \"abeli@microsoft.com\",
+                \"oid\": \"00000000-0000-0000-66f3-3332eca7ea81\",
+                \"tid\": \"9122040d-6c67-4c5b-b112-36a304b66dad\",
+                \"ver\": \"2.0\"
+            }",
+        )
+        .expect("failed to deserialize");
+
+        assert_eq!(
+            claims.additional_claims().tenant_id().unwrap().as_str(),
+            "9122040d-6c67-4c5b-b112-36a304b66dad"
+        );
+        assert_eq!(
+            claims.additional_claims().object_id().unwrap().as_str(),
+            "00000000-0000-0000-66f3-3332eca7ea81"
+        );
+        assert_eq!(claims.additional_claims().user_principal_name(), None);
+        assert_eq!(
+            claims.preferred_username().unwrap().as_str(),
+            "abeli@microsoft.com"
+        );
+
+        verify_tenant_issuer(&claims).expect("tenant should match issuer");
+        assert!(AzureTenant::Common
+            .issuer_url(AzureEndpointVersion::V2)
+            .matches_template(claims.issuer()));
+
+        let mut other_tenant_claims = claims.clone();
+        *other_tenant_claims.additional_claims_mut() = AzureAdditionalClaims::default()
+            .set_tenant_id(Some(AzureTenantId::new("other_tenant".to_string())));
+        match verify_tenant_issuer(&other_tenant_claims) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let v1_claims = AzureIdTokenClaims::new(
+            IssuerUrl::new("https://sts.windows.net/the_tenant/".to_string()).unwrap(),
+            vec![Audience::new("the_client".to_string())],
+            claims.expiration(),
+            claims.issue_time(),
+            StandardClaims::new(SubjectIdentifier::new("the_subject".to_string())),
+            AzureAdditionalClaims::default()
+                .set_tenant_id(Some(AzureTenantId::new("the_tenant".to_string()))),
+        );
+        verify_tenant_issuer(&v1_claims).expect("tenant should match issuer");
+    }
+
+    #[test]
+    fn test_token_response() {
+        let response = serde_json::from_str::<AzureTokenResponse>(
+            "{
+                \"access_token\": \"foobar\",
+                \"token_type\": \"Bearer\",
+                \"expires_in\": 3599,
+                \"ext_expires_in\": 3599
+            }",
+        )
+        .expect("failed to deserialize");
+        assert_eq!(
+            response.extra_fields().extra_fields().ext_expires_in(),
+            Some(std::time::Duration::from_secs(3599))
+        );
+    }
+
+    #[test]
+    fn test_resource_param() {
+        let tenant = AzureTenant::Tenant("the_tenant".to_string());
+        let client = AzureClient::new(
+            ClientId::new("the_client".to_string()),
+            None,
+            tenant.issuer_url(AzureEndpointVersion::V1),
+            tenant.auth_url(AzureEndpointVersion::V1),
+            Some(tenant.token_url(AzureEndpointVersion::V1)),
+            None,
+            CoreJsonWebKeySet::default(),
+        );
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .set_resource("https://graph.windows.net")
+            .url()
+            .unwrap();
+
+        assert_eq!(
+            "https://login.microsoftonline.com/the_tenant/oauth2/authorize?response_type=code&\
+             client_id=the_client&state=CSRF123&scope=openid&\
+             resource=https%3A%2F%2Fgraph.windows.net&nonce=NONCE456",
+            authorize_url.to_string()
+        );
+    }
+}
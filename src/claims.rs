@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::fmt::{Debug, Formatter, Result as FormatterResult};
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 use std::str;
 
 use chrono::{DateTime, Utc};
@@ -11,11 +13,11 @@ use crate::helpers::FlattenFilter;
 use crate::types::helpers::{split_language_tag_key, timestamp_to_utc, utc_to_seconds};
 use crate::types::{Boolean, LocalizedClaim, Timestamp};
 use crate::{
-    AddressCountry, AddressLocality, AddressPostalCode, AddressRegion, EndUserBirthday,
-    EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName,
-    EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone,
-    EndUserUsername, EndUserWebsiteUrl, FormattedAddress, LanguageTag, StreetAddress,
-    SubjectIdentifier,
+    AddressCountry, AddressLocality, AddressPostalCode, AddressRegion, ClaimsVerificationError,
+    EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
+    EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
+    EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress, IdTokenClaims,
+    LanguageTag, StreetAddress, SubjectIdentifier, UserInfoClaims,
 };
 
 ///
@@ -32,6 +34,49 @@ pub trait AdditionalClaims: Debug + DeserializeOwned + Serialize + 'static {}
 pub struct EmptyAdditionalClaims {}
 impl AdditionalClaims for EmptyAdditionalClaims {}
 
+///
+/// Catch-all additional claims.
+///
+/// Captures every claim not defined by OpenID Connect Core into a map from claim name to JSON
+/// value, which is useful for handling claims dynamically without defining a dedicated
+/// [`AdditionalClaims`] implementation.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct AllOtherClaims(HashMap<String, serde_json::Value>);
+impl AllOtherClaims {
+    ///
+    /// Creates a new `AllOtherClaims` wrapping the given map of claims.
+    ///
+    pub fn new(claims: HashMap<String, serde_json::Value>) -> Self {
+        Self(claims)
+    }
+
+    ///
+    /// Returns the underlying map of claims.
+    ///
+    pub fn into_inner(self) -> HashMap<String, serde_json::Value> {
+        self.0
+    }
+}
+impl AdditionalClaims for AllOtherClaims {}
+impl Deref for AllOtherClaims {
+    type Target = HashMap<String, serde_json::Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for AllOtherClaims {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl From<HashMap<String, serde_json::Value>> for AllOtherClaims {
+    fn from(claims: HashMap<String, serde_json::Value>) -> Self {
+        Self(claims)
+    }
+}
+
 ///
 /// Address claims.
 ///
@@ -186,6 +231,34 @@ where
             set_updated_at -> updated_at[Option<DateTime<Utc>>],
         }
     ];
+
+    ///
+    /// Returns the End-User's phone number (`phone_number`) claim only if the OpenID Connect
+    /// Provider has verified it (i.e., `phone_number_verified` is `true`).
+    ///
+    pub fn verified_phone_number(&self) -> Option<&EndUserPhoneNumber> {
+        if self.phone_number_verified == Some(true) {
+            self.phone_number.as_ref()
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Sets the End-User's phone number (`phone_number`) claim along with the corresponding
+    /// `phone_number_verified` claim.
+    ///
+    /// If `phone_number` is `None`, both claims are cleared.
+    ///
+    pub fn set_phone_number_with_verification(
+        mut self,
+        phone_number: Option<EndUserPhoneNumber>,
+        verified: bool,
+    ) -> Self {
+        self.phone_number_verified = phone_number.as_ref().map(|_| verified);
+        self.phone_number = phone_number;
+        self
+    }
 }
 impl<GC> FlattenFilter for StandardClaims<GC>
 where
@@ -309,3 +382,216 @@ where
         }
     }
 }
+
+///
+/// Consolidated view of the Standard Claims returned in an ID token and by the UserInfo endpoint
+/// for the same End-User.
+///
+/// Claims returned by the UserInfo endpoint take precedence, since they typically reflect the
+/// End-User's current profile, while the ID token reflects the profile at the time of
+/// authentication. Claims absent from the UserInfo response fall back to those in the ID token.
+/// The `email` and `email_verified` claims are taken together from the same source, as are the
+/// `phone_number` and `phone_number_verified` claims, so that a verification status never
+/// applies to a value it wasn't issued for.
+///
+/// Additional claims are not merged, and remain available via
+/// [`IdTokenClaims::additional_claims`] and [`UserInfoClaims::additional_claims`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergedClaims<GC>
+where
+    GC: GenderClaim,
+{
+    standard_claims: StandardClaims<GC>,
+}
+impl<GC> MergedClaims<GC>
+where
+    GC: GenderClaim,
+{
+    ///
+    /// Merges the claims from a verified ID token with those returned by the UserInfo endpoint.
+    ///
+    /// Returns [`ClaimsVerificationError::InvalidSubject`] if the `sub` claims differ, since
+    /// [Section 5.3.2](https://openid.net/specs/openid-connect-core-1_0.html#UserInfoResponse)
+    /// of the OpenID Connect Core spec prohibits using the UserInfo response in that case.
+    ///
+    pub fn new<IAC, UAC>(
+        id_token_claims: &IdTokenClaims<IAC, GC>,
+        user_info_claims: &UserInfoClaims<UAC, GC>,
+    ) -> Result<Self, ClaimsVerificationError>
+    where
+        IAC: AdditionalClaims,
+        UAC: AdditionalClaims,
+    {
+        let id_token = id_token_claims.standard_claims();
+        let user_info = user_info_claims.standard_claims();
+        if user_info.sub != id_token.sub {
+            return Err(ClaimsVerificationError::InvalidSubject(format!(
+                "expected `{}` (found `{}`)",
+                id_token.sub.as_str(),
+                user_info.sub.as_str()
+            )));
+        }
+
+        let (email, email_verified) = if user_info.email.is_some() {
+            (user_info.email.clone(), user_info.email_verified)
+        } else {
+            (id_token.email.clone(), id_token.email_verified)
+        };
+        let (phone_number, phone_number_verified) = if user_info.phone_number.is_some() {
+            (
+                user_info.phone_number.clone(),
+                user_info.phone_number_verified,
+            )
+        } else {
+            (
+                id_token.phone_number.clone(),
+                id_token.phone_number_verified,
+            )
+        };
+        Ok(Self {
+            standard_claims: StandardClaims {
+                sub: user_info.sub.clone(),
+                name: user_info.name.clone().or_else(|| id_token.name.clone()),
+                given_name: user_info
+                    .given_name
+                    .clone()
+                    .or_else(|| id_token.given_name.clone()),
+                family_name: user_info
+                    .family_name
+                    .clone()
+                    .or_else(|| id_token.family_name.clone()),
+                middle_name: user_info
+                    .middle_name
+                    .clone()
+                    .or_else(|| id_token.middle_name.clone()),
+                nickname: user_info
+                    .nickname
+                    .clone()
+                    .or_else(|| id_token.nickname.clone()),
+                preferred_username: user_info
+                    .preferred_username
+                    .clone()
+                    .or_else(|| id_token.preferred_username.clone()),
+                profile: user_info
+                    .profile
+                    .clone()
+                    .or_else(|| id_token.profile.clone()),
+                picture: user_info
+                    .picture
+                    .clone()
+                    .or_else(|| id_token.picture.clone()),
+                website: user_info
+                    .website
+                    .clone()
+                    .or_else(|| id_token.website.clone()),
+                email,
+                email_verified,
+                gender: user_info.gender.clone().or_else(|| id_token.gender.clone()),
+                birthday: user_info
+                    .birthday
+                    .clone()
+                    .or_else(|| id_token.birthday.clone()),
+                zoneinfo: user_info
+                    .zoneinfo
+                    .clone()
+                    .or_else(|| id_token.zoneinfo.clone()),
+                locale: user_info.locale.clone().or_else(|| id_token.locale.clone()),
+                phone_number,
+                phone_number_verified,
+                address: user_info
+                    .address
+                    .clone()
+                    .or_else(|| id_token.address.clone()),
+                updated_at: user_info.updated_at.or(id_token.updated_at),
+            },
+        })
+    }
+
+    ///
+    /// Returns the merged standard claims.
+    ///
+    pub fn standard_claims(&self) -> &StandardClaims<GC> {
+        &self.standard_claims
+    }
+
+    ///
+    /// Consumes this object and returns the merged standard claims.
+    ///
+    pub fn into_standard_claims(self) -> StandardClaims<GC> {
+        self.standard_claims
+    }
+}
+impl<GC> Deref for MergedClaims<GC>
+where
+    GC: GenderClaim,
+{
+    type Target = StandardClaims<GC>;
+    fn deref(&self) -> &StandardClaims<GC> {
+        &self.standard_claims
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::MergedClaims;
+    use crate::core::{CoreGenderClaim, CoreIdTokenClaims, CoreUserInfoClaims};
+    use crate::{
+        Audience, ClaimsVerificationError, EmptyAdditionalClaims, EndUserEmail, EndUserName,
+        EndUserPhoneNumber, EndUserUsername, IssuerUrl, StandardClaims, SubjectIdentifier,
+    };
+
+    #[test]
+    fn test_merged_claims() {
+        let id_token_claims = CoreIdTokenClaims::new(
+            IssuerUrl::new("https://server.example.com".to_string()).unwrap(),
+            vec![Audience::new("s6BhdRkqt3".to_string())],
+            Utc.timestamp_opt(1311281970, 0).unwrap(),
+            Utc.timestamp_opt(1311280970, 0).unwrap(),
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string()))
+                .set_name(Some(EndUserName::new("Jane Doe".to_string()).into()))
+                .set_preferred_username(Some(EndUserUsername::new("j.doe".to_string())))
+                .set_email(Some(EndUserEmail::new("jane@old.example.com".to_string())))
+                .set_email_verified(Some(true))
+                .set_phone_number_with_verification(
+                    Some(EndUserPhoneNumber::new("+1 (310) 123-4567".to_string())),
+                    true,
+                ),
+            EmptyAdditionalClaims {},
+        );
+        let user_info_claims = CoreUserInfoClaims::new(
+            StandardClaims::new(SubjectIdentifier::new("24400320".to_string()))
+                .set_name(Some(EndUserName::new("Jane Smith".to_string()).into()))
+                .set_email(Some(EndUserEmail::new("jane@new.example.com".to_string()))),
+            EmptyAdditionalClaims {},
+        );
+
+        let merged = MergedClaims::new(&id_token_claims, &user_info_claims).unwrap();
+        assert_eq!(merged.subject().as_str(), "24400320");
+        assert_eq!(
+            merged.name().unwrap().get(None).unwrap().as_str(),
+            "Jane Smith"
+        );
+        assert_eq!(merged.preferred_username().unwrap().as_str(), "j.doe");
+        // The email verification status is never taken from the other source.
+        assert_eq!(merged.email().unwrap().as_str(), "jane@new.example.com");
+        assert_eq!(merged.email_verified(), None);
+        assert_eq!(
+            merged.verified_phone_number().unwrap().as_str(),
+            "+1 (310) 123-4567"
+        );
+
+        let other_user_info_claims = CoreUserInfoClaims::new(
+            StandardClaims::<CoreGenderClaim>::new(SubjectIdentifier::new("other".to_string())),
+            EmptyAdditionalClaims {},
+        );
+        assert_eq!(
+            MergedClaims::new(&id_token_claims, &other_user_info_claims).unwrap_err(),
+            ClaimsVerificationError::InvalidSubject(
+                "expected `24400320` (found `other`)".to_string()
+            )
+        );
+    }
+}
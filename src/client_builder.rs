@@ -0,0 +1,519 @@
+use oauth2::{
+    AuthType, ClientId, ClientSecret, ErrorResponse, RedirectUrl, RevocableToken,
+    TokenIntrospectionResponse, TokenType,
+};
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    AdditionalClaims, AdditionalProviderMetadata, AuthDisplay, AuthPrompt, ClaimName, ClaimType,
+    Client, ClientAuthMethod, GenderClaim, GrantType, IssuerUrl, JsonWebKey, JsonWebKeyType,
+    JsonWebKeyUse, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    ProviderMetadata, ResponseMode, ResponseType, SubjectIdentifierType, TokenResponse,
+};
+
+///
+/// Builder for a [`Client`] that validates the client configuration against the provider's
+/// metadata before any requests are made.
+///
+/// [`build`](Self::build) checks that:
+///  * all of the provider's endpoints use the `https` scheme (unless
+///    [`allow_insecure_endpoints`](Self::allow_insecure_endpoints) is set),
+///  * each redirect URI is registered with the provider (if the registered redirect URIs are
+///    known; see [`set_registered_redirect_uris`](Self::set_registered_redirect_uris)),
+///  * the allowed ID token signing algorithms are supported by the provider, and
+///  * the client authentication method is supported by both this crate and the provider, and a
+///    client secret is configured if the method requires one.
+///
+#[allow(clippy::type_complexity)]
+pub struct ClientBuilder<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RS, S>
+where
+    A: AdditionalProviderMetadata,
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RS: ResponseType,
+    S: SubjectIdentifierType,
+{
+    provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RS, S>,
+    client_id: ClientId,
+    client_secret: Option<ClientSecret>,
+    auth_method: Option<CA>,
+    redirect_uris: Vec<RedirectUrl>,
+    registered_redirect_uris: Option<Vec<RedirectUrl>>,
+    id_token_signing_algs: Option<Vec<JS>>,
+    additional_issuers: Vec<IssuerUrl>,
+    allow_insecure_endpoints: bool,
+}
+impl<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RS, S>
+    ClientBuilder<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RS, S>
+where
+    A: AdditionalProviderMetadata,
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RS: ResponseType,
+    S: SubjectIdentifierType,
+{
+    ///
+    /// Initializes a client builder from the provider's OpenID Connect Discovery metadata.
+    ///
+    pub fn new(
+        provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RS, S>,
+        client_id: ClientId,
+    ) -> Self {
+        Self {
+            provider_metadata,
+            client_id,
+            client_secret: None,
+            auth_method: None,
+            redirect_uris: Vec::new(),
+            registered_redirect_uris: None,
+            id_token_signing_algs: None,
+            additional_issuers: Vec::new(),
+            allow_insecure_endpoints: false,
+        }
+    }
+
+    ///
+    /// Sets the client secret.
+    ///
+    pub fn set_client_secret(mut self, client_secret: Option<ClientSecret>) -> Self {
+        self.client_secret = client_secret;
+        self
+    }
+
+    ///
+    /// Sets the client authentication method used at the token endpoint.
+    ///
+    /// Only `client_secret_basic`, `client_secret_post`, and `none` are supported. If unset,
+    /// `client_secret_basic` is used if a client secret is configured, and `none` otherwise.
+    ///
+    pub fn set_auth_method(mut self, auth_method: Option<CA>) -> Self {
+        self.auth_method = auth_method;
+        self
+    }
+
+    ///
+    /// Sets the redirect URIs used by this client, the first of which is the default (see
+    /// [`Client::set_redirect_uris`]).
+    ///
+    pub fn set_redirect_uris(mut self, redirect_uris: Vec<RedirectUrl>) -> Self {
+        self.redirect_uris = redirect_uris;
+        self
+    }
+
+    ///
+    /// Sets the redirect URIs registered with the provider (e.g., as returned by
+    /// [dynamic client registration](crate::registration)), against which the client's redirect
+    /// URIs are validated.
+    ///
+    pub fn set_registered_redirect_uris(
+        mut self,
+        registered_redirect_uris: Option<Vec<RedirectUrl>>,
+    ) -> Self {
+        self.registered_redirect_uris = registered_redirect_uris;
+        self
+    }
+
+    ///
+    /// Restricts the ID token signing algorithms accepted by this client.
+    ///
+    /// If unset, all algorithms advertised by the provider are accepted.
+    ///
+    pub fn set_id_token_signing_algs(mut self, id_token_signing_algs: Option<Vec<JS>>) -> Self {
+        self.id_token_signing_algs = id_token_signing_algs;
+        self
+    }
+
+    ///
+    /// Sets additional issuers whose tokens this client trusts (see
+    /// [`Client::set_additional_issuers`]).
+    ///
+    pub fn set_additional_issuers(mut self, additional_issuers: Vec<IssuerUrl>) -> Self {
+        self.additional_issuers = additional_issuers;
+        self
+    }
+
+    ///
+    /// Allows provider endpoints that don't use the `https` scheme.
+    ///
+    /// # Security Warning
+    ///
+    /// This should only be used for local development and testing.
+    ///
+    pub fn allow_insecure_endpoints(mut self, allow_insecure_endpoints: bool) -> Self {
+        self.allow_insecure_endpoints = allow_insecure_endpoints;
+        self
+    }
+
+    ///
+    /// Validates the configuration and builds the client.
+    ///
+    pub fn build<AC, GC, P, TE, TR, TT, TIR, RT, TRE>(
+        self,
+    ) -> Result<
+        Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>,
+        ClientBuilderError,
+    >
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        P: AuthPrompt,
+        TE: ErrorResponse + 'static,
+        TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+        TT: TokenType + 'static,
+        TIR: TokenIntrospectionResponse<TT>,
+        RT: RevocableToken,
+        TRE: ErrorResponse + 'static,
+    {
+        if !self.allow_insecure_endpoints {
+            self.validate_endpoints()?;
+        }
+        self.validate_redirect_uris()?;
+        self.validate_signing_algs()?;
+        let auth_type = self.validate_auth_method()?;
+
+        let mut client = Client::from_provider_metadata(
+            self.provider_metadata,
+            self.client_id,
+            self.client_secret,
+        )
+        .set_auth_type(auth_type)
+        .set_additional_issuers(self.additional_issuers);
+        if !self.redirect_uris.is_empty() {
+            client = client
+                .set_redirect_uris(self.redirect_uris)
+                .expect("non-empty redirect URIs should never be rejected");
+        }
+        if let Some(id_token_signing_algs) = self.id_token_signing_algs {
+            client.id_token_signing_algs = Some(id_token_signing_algs);
+        }
+        Ok(client)
+    }
+
+    fn validate_endpoints(&self) -> Result<(), ClientBuilderError> {
+        let provider_metadata = &self.provider_metadata;
+        let endpoints: [(&'static str, Option<&Url>); 9] = [
+            ("issuer", Some(provider_metadata.issuer().url())),
+            (
+                "authorization",
+                Some(provider_metadata.authorization_endpoint().url()),
+            ),
+            (
+                "token",
+                provider_metadata.token_endpoint().map(|url| url.url()),
+            ),
+            (
+                "userinfo",
+                provider_metadata.userinfo_endpoint().map(|url| url.url()),
+            ),
+            ("jwks", Some(provider_metadata.jwks_uri().url())),
+            (
+                "registration",
+                provider_metadata
+                    .registration_endpoint()
+                    .map(|url| url.url()),
+            ),
+            (
+                "introspection",
+                provider_metadata
+                    .introspection_endpoint()
+                    .map(|url| url.url()),
+            ),
+            (
+                "revocation",
+                provider_metadata.revocation_endpoint().map(|url| url.url()),
+            ),
+            (
+                "device authorization",
+                provider_metadata
+                    .device_authorization_endpoint()
+                    .map(|url| url.url()),
+            ),
+        ];
+        for (name, url) in endpoints {
+            if let Some(url) = url {
+                if url.scheme() != "https" {
+                    return Err(ClientBuilderError::InsecureEndpoint(name, url.to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_redirect_uris(&self) -> Result<(), ClientBuilderError> {
+        if let Some(ref registered_redirect_uris) = self.registered_redirect_uris {
+            if let Some(unregistered) = self
+                .redirect_uris
+                .iter()
+                .find(|redirect_uri| !registered_redirect_uris.contains(redirect_uri))
+            {
+                return Err(ClientBuilderError::UnregisteredRedirectUri(
+                    unregistered.to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_signing_algs(&self) -> Result<(), ClientBuilderError> {
+        if let Some(ref id_token_signing_algs) = self.id_token_signing_algs {
+            let supported_algs = self
+                .provider_metadata
+                .id_token_signing_alg_values_supported();
+            if let Some(unsupported) = id_token_signing_algs
+                .iter()
+                .find(|alg| !supported_algs.contains(alg))
+            {
+                return Err(ClientBuilderError::UnsupportedSigningAlg(
+                    serde_plain::to_string(unsupported)
+                        // This should never arise, since signing algorithms always serialize
+                        // to plain strings.
+                        .expect("signing algorithm serialization failed"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_auth_method(&self) -> Result<AuthType, ClientBuilderError> {
+        let auth_method = match self.auth_method {
+            Some(ref auth_method) => serde_plain::to_string(auth_method)
+                // This should never arise, since client authentication methods always serialize
+                // to plain strings.
+                .expect("client authentication method serialization failed"),
+            None if self.client_secret.is_some() => "client_secret_basic".to_string(),
+            None => "none".to_string(),
+        };
+        let (auth_type, requires_secret) = match auth_method.as_str() {
+            "client_secret_basic" => (AuthType::BasicAuth, true),
+            "client_secret_post" => (AuthType::RequestBody, true),
+            "none" => (AuthType::RequestBody, false),
+            _ => return Err(ClientBuilderError::UnsupportedAuthMethod(auth_method)),
+        };
+        if requires_secret && self.client_secret.is_none() {
+            return Err(ClientBuilderError::MissingClientSecret(auth_method));
+        }
+
+        // Per OpenID Connect Discovery and RFC 8414, the default is `client_secret_basic` if the
+        // provider omits `token_endpoint_auth_methods_supported`, so any other method (including
+        // `none`) must be advertised explicitly.
+        let supported = match self
+            .provider_metadata
+            .token_endpoint_auth_methods_supported()
+        {
+            Some(supported_methods) => supported_methods.iter().any(|method| {
+                serde_plain::to_string(method)
+                    // This should never arise, since client authentication methods always
+                    // serialize to plain strings.
+                    .expect("client authentication method serialization failed")
+                    == auth_method
+            }),
+            None => auth_method == "client_secret_basic",
+        };
+        if !supported {
+            return Err(ClientBuilderError::AuthMethodNotAllowed(auth_method));
+        }
+        Ok(auth_type)
+    }
+}
+
+///
+/// Error validating the configuration of a [`ClientBuilder`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClientBuilderError {
+    ///
+    /// The provider does not allow the client authentication method at its token endpoint.
+    ///
+    #[error("Provider does not allow client authentication method `{0}`")]
+    AuthMethodNotAllowed(String),
+    ///
+    /// A provider endpoint does not use the `https` scheme.
+    ///
+    #[error("Provider {0} endpoint `{1}` must use https")]
+    InsecureEndpoint(&'static str, String),
+    ///
+    /// The client authentication method requires a client secret, but none was configured.
+    ///
+    #[error("Client authentication method `{0}` requires a client secret")]
+    MissingClientSecret(String),
+    ///
+    /// The client authentication method is not supported by this crate.
+    ///
+    #[error("Unsupported client authentication method `{0}`")]
+    UnsupportedAuthMethod(String),
+    ///
+    /// The provider does not support an allowed ID token signing algorithm.
+    ///
+    #[error("Provider does not support ID token signing algorithm `{0}`")]
+    UnsupportedSigningAlg(String),
+    ///
+    /// A redirect URI is not registered with the provider.
+    ///
+    #[error("Redirect URI `{0}` is not registered with the provider")]
+    UnregisteredRedirectUri(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::{ClientId, ClientSecret, RedirectUrl, TokenUrl};
+
+    use crate::core::{
+        CoreClient, CoreClientAuthMethod, CoreJwsSigningAlgorithm, CoreProviderMetadata,
+        CoreResponseType, CoreSubjectIdentifierType,
+    };
+
+    use super::{ClientBuilder, ClientBuilderError};
+
+    fn provider_metadata() -> CoreProviderMetadata {
+        crate::client_config::tests::provider_metadata()
+            .set_token_endpoint(Some(
+                TokenUrl::new("https://example.com/token".to_string()).unwrap(),
+            ))
+            .set_token_endpoint_auth_methods_supported(Some(vec![
+                CoreClientAuthMethod::ClientSecretBasic,
+                CoreClientAuthMethod::ClientSecretPost,
+            ]))
+    }
+
+    fn builder() -> ClientBuilder<
+        crate::EmptyAdditionalProviderMetadata,
+        crate::core::CoreAuthDisplay,
+        CoreClientAuthMethod,
+        crate::core::CoreClaimName,
+        crate::core::CoreClaimType,
+        crate::core::CoreGrantType,
+        crate::core::CoreJweContentEncryptionAlgorithm,
+        crate::core::CoreJweKeyManagementAlgorithm,
+        CoreJwsSigningAlgorithm,
+        crate::core::CoreJsonWebKeyType,
+        crate::core::CoreJsonWebKeyUse,
+        crate::core::CoreJsonWebKey,
+        crate::core::CoreResponseMode,
+        CoreResponseType,
+        CoreSubjectIdentifierType,
+    > {
+        ClientBuilder::new(provider_metadata(), ClientId::new("my_client".to_string()))
+            .set_client_secret(Some(ClientSecret::new("my_secret".to_string())))
+    }
+
+    #[test]
+    fn test_client_builder() {
+        let redirect_uri = RedirectUrl::new("https://app.example.com/cb".to_string()).unwrap();
+        let client: CoreClient = builder()
+            .set_auth_method(Some(CoreClientAuthMethod::ClientSecretPost))
+            .set_redirect_uris(vec![redirect_uri.clone()])
+            .set_registered_redirect_uris(Some(vec![redirect_uri.clone()]))
+            .set_id_token_signing_algs(Some(vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256]))
+            .build()
+            .unwrap();
+        assert_eq!(client.redirect_uris(), std::slice::from_ref(&redirect_uri));
+        assert!(matches!(client.auth_type(), oauth2::AuthType::RequestBody));
+
+        assert_eq!(
+            builder()
+                .set_redirect_uris(vec![redirect_uri.clone()])
+                .set_registered_redirect_uris(Some(vec![]))
+                .build()
+                .map(|_: CoreClient| ())
+                .unwrap_err(),
+            ClientBuilderError::UnregisteredRedirectUri("https://app.example.com/cb".to_string())
+        );
+        assert_eq!(
+            builder()
+                .set_id_token_signing_algs(Some(vec![CoreJwsSigningAlgorithm::EdDsa]))
+                .build()
+                .map(|_: CoreClient| ())
+                .unwrap_err(),
+            ClientBuilderError::UnsupportedSigningAlg("EdDSA".to_string())
+        );
+        assert_eq!(
+            builder()
+                .set_auth_method(Some(CoreClientAuthMethod::PrivateKeyJwt))
+                .build()
+                .map(|_: CoreClient| ())
+                .unwrap_err(),
+            ClientBuilderError::UnsupportedAuthMethod("private_key_jwt".to_string())
+        );
+        assert_eq!(
+            builder()
+                .set_auth_method(Some(CoreClientAuthMethod::None))
+                .build()
+                .map(|_: CoreClient| ())
+                .unwrap_err(),
+            ClientBuilderError::AuthMethodNotAllowed("none".to_string())
+        );
+        assert_eq!(
+            builder()
+                .set_client_secret(None)
+                .set_auth_method(Some(CoreClientAuthMethod::ClientSecretBasic))
+                .build()
+                .map(|_: CoreClient| ())
+                .unwrap_err(),
+            ClientBuilderError::MissingClientSecret("client_secret_basic".to_string())
+        );
+
+        let unadvertised_metadata =
+            provider_metadata().set_token_endpoint_auth_methods_supported(None);
+        assert!(ClientBuilder::new(
+            unadvertised_metadata.clone(),
+            ClientId::new("my_client".to_string())
+        )
+        .set_client_secret(Some(ClientSecret::new("my_secret".to_string())))
+        .build()
+        .map(|_: CoreClient| ())
+        .is_ok());
+        assert_eq!(
+            ClientBuilder::new(
+                unadvertised_metadata,
+                ClientId::new("my_client".to_string())
+            )
+            .build()
+            .map(|_: CoreClient| ())
+            .unwrap_err(),
+            ClientBuilderError::AuthMethodNotAllowed("none".to_string())
+        );
+
+        let insecure_metadata = provider_metadata().set_token_endpoint(Some(
+            TokenUrl::new("http://example.com/token".to_string()).unwrap(),
+        ));
+        assert_eq!(
+            ClientBuilder::new(
+                insecure_metadata.clone(),
+                ClientId::new("my_client".to_string())
+            )
+            .set_client_secret(Some(ClientSecret::new("my_secret".to_string())))
+            .build()
+            .map(|_: CoreClient| ())
+            .unwrap_err(),
+            ClientBuilderError::InsecureEndpoint("token", "http://example.com/token".to_string())
+        );
+        let _: CoreClient =
+            ClientBuilder::new(insecure_metadata, ClientId::new("my_client".to_string()))
+                .set_client_secret(Some(ClientSecret::new("my_secret".to_string())))
+                .allow_insecure_endpoints(true)
+                .build()
+                .unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+use oauth2::{
+    AuthType, AuthUrl, ClientId, ClientSecret, IntrospectionUrl, RedirectUrl, RevocationUrl, Scope,
+    TokenUrl,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::core::CoreClientAuthMethod;
+use crate::{IssuerUrl, UserInfoUrl};
+
+///
+/// Declarative client configuration, suitable for loading from configuration files (e.g., TOML
+/// or JSON) or environment variables via [`serde`].
+///
+/// Use [`Client::from_config`](crate::Client::from_config) to construct a client from this
+/// configuration and the provider's discovered metadata. Any endpoints specified here override
+/// those advertised by the provider.
+///
+/// Example (JSON):
+/// ```json
+/// {
+///   "issuer_url": "https://accounts.example.com",
+///   "client_id": "my_client",
+///   "client_secret": "my_secret",
+///   "redirect_uri": "https://app.example.com/callback",
+///   "scopes": ["email", "profile"],
+///   "auth_method": "client_secret_post"
+/// }
+/// ```
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClientConfig {
+    issuer_url: IssuerUrl,
+    client_id: ClientId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    client_secret: Option<ClientSecret>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    redirect_uri: Option<RedirectUrl>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scopes: Vec<Scope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auth_method: Option<CoreClientAuthMethod>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    additional_issuers: Vec<IssuerUrl>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    authorization_endpoint: Option<AuthUrl>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token_endpoint: Option<TokenUrl>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    userinfo_endpoint: Option<UserInfoUrl>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    introspection_endpoint: Option<IntrospectionUrl>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revocation_endpoint: Option<RevocationUrl>,
+}
+impl ClientConfig {
+    ///
+    /// Initializes a client configuration with the required fields.
+    ///
+    pub fn new(issuer_url: IssuerUrl, client_id: ClientId) -> Self {
+        Self {
+            issuer_url,
+            client_id,
+            client_secret: None,
+            redirect_uri: None,
+            scopes: Vec::new(),
+            auth_method: None,
+            additional_issuers: Vec::new(),
+            authorization_endpoint: None,
+            token_endpoint: None,
+            userinfo_endpoint: None,
+            introspection_endpoint: None,
+            revocation_endpoint: None,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["client configuration value"] {
+            set_issuer_url -> issuer_url[IssuerUrl],
+            set_client_id -> client_id[ClientId],
+            set_client_secret -> client_secret[Option<ClientSecret>],
+            set_redirect_uri -> redirect_uri[Option<RedirectUrl>],
+            set_scopes -> scopes[Vec<Scope>],
+            set_auth_method -> auth_method[Option<CoreClientAuthMethod>],
+            set_additional_issuers -> additional_issuers[Vec<IssuerUrl>],
+            set_authorization_endpoint -> authorization_endpoint[Option<AuthUrl>],
+            set_token_endpoint -> token_endpoint[Option<TokenUrl>],
+            set_userinfo_endpoint -> userinfo_endpoint[Option<UserInfoUrl>],
+            set_introspection_endpoint -> introspection_endpoint[Option<IntrospectionUrl>],
+            set_revocation_endpoint -> revocation_endpoint[Option<RevocationUrl>],
+        }
+    ];
+
+    pub(crate) fn auth_type(&self) -> Result<Option<AuthType>, ClientConfigError> {
+        match &self.auth_method {
+            None => Ok(None),
+            Some(CoreClientAuthMethod::ClientSecretBasic) => Ok(Some(AuthType::BasicAuth)),
+            Some(CoreClientAuthMethod::ClientSecretPost) | Some(CoreClientAuthMethod::None) => {
+                Ok(Some(AuthType::RequestBody))
+            }
+            Some(other) => Err(ClientConfigError::UnsupportedAuthMethod(
+                other.as_ref().to_string(),
+            )),
+        }
+    }
+}
+
+///
+/// Error constructing a client from a [`ClientConfig`].
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ClientConfigError {
+    ///
+    /// The provider metadata's `issuer` does not match the configured issuer URL.
+    ///
+    #[error("Provider metadata issuer `{0}` does not match configured issuer `{1}`")]
+    IssuerMismatch(String, String),
+    ///
+    /// The configured client authentication method is not supported.
+    ///
+    #[error("Unsupported client authentication method: {0}")]
+    UnsupportedAuthMethod(String),
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use oauth2::{AuthUrl, ClientId, RedirectUrl, Scope};
+
+    use crate::core::{
+        CoreClient, CoreClientAuthMethod, CoreJwsSigningAlgorithm, CoreProviderMetadata,
+        CoreResponseType, CoreSubjectIdentifierType,
+    };
+    use crate::{IssuerUrl, JsonWebKeySetUrl, ResponseTypes};
+
+    use super::{ClientConfig, ClientConfigError};
+
+    pub(crate) fn provider_metadata() -> CoreProviderMetadata {
+        CoreProviderMetadata::new(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            AuthUrl::new("https://example.com/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example.com/jwks.json".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn test_client_from_config() {
+        let config: ClientConfig = serde_json::from_str(
+            "{
+                \"issuer_url\": \"https://example.com\",
+                \"client_id\": \"my_client\",
+                \"client_secret\": \"my_secret\",
+                \"redirect_uri\": \"https://app.example.com/cb\",
+                \"scopes\": [\"email\", \"profile\"],
+                \"auth_method\": \"client_secret_post\",
+                \"authorization_endpoint\": \"https://proxy.example.com/authorize\"
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            config.scopes(),
+            &vec![
+                Scope::new("email".to_string()),
+                Scope::new("profile".to_string())
+            ]
+        );
+        assert_eq!(
+            config.auth_method(),
+            Some(&CoreClientAuthMethod::ClientSecretPost)
+        );
+
+        let client = CoreClient::from_config(&config, provider_metadata()).unwrap();
+        let (url, _, _) = client
+            .authorize_url(
+                crate::AuthenticationFlow::<CoreResponseType>::AuthorizationCode,
+                crate::CsrfToken::new_random,
+                crate::Nonce::new_random,
+            )
+            .url()
+            .unwrap();
+        assert_eq!(url.host_str(), Some("proxy.example.com"));
+        assert!(url
+            .query_pairs()
+            .any(|(key, value)| key == "redirect_uri" && value == "https://app.example.com/cb"));
+        assert!(url
+            .query_pairs()
+            .any(|(key, value)| key == "scope" && value == "openid email profile"));
+
+        match CoreClient::from_config(
+            &config
+                .clone()
+                .set_issuer_url(IssuerUrl::new("https://other.example.com".to_string()).unwrap()),
+            provider_metadata(),
+        ) {
+            Err(ClientConfigError::IssuerMismatch(_, _)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match CoreClient::from_config(
+            &config
+                .clone()
+                .set_auth_method(Some(CoreClientAuthMethod::PrivateKeyJwt)),
+            provider_metadata(),
+        ) {
+            Err(ClientConfigError::UnsupportedAuthMethod(method)) => {
+                assert_eq!(method, "private_key_jwt")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let config = ClientConfig::new(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            ClientId::new("my_client".to_string()),
+        )
+        .set_redirect_uri(Some(
+            RedirectUrl::new("https://app.example.com/cb".to_string()).unwrap(),
+        ));
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serialized,
+            "{\"issuer_url\":\"https://example.com\",\"client_id\":\"my_client\",\
+             \"redirect_uri\":\"https://app.example.com/cb\"}"
+        );
+        CoreClient::from_config(&config, provider_metadata()).unwrap();
+    }
+}
@@ -0,0 +1,737 @@
+use http::header::{HeaderValue, ACCEPT, LOCATION};
+use http::method::Method;
+use oauth2::{AuthType, ClientId, ClientSecret, CsrfToken, RedirectUrl, RequestTokenError, Scope};
+use thiserror::Error;
+use url::Url;
+
+use crate::core::{
+    CoreAuthenticationFlow, CoreClient, CoreClientAuthMethod, CoreClientRegistrationRequest,
+    CoreJwsSigningAlgorithm, CoreProviderMetadata, CoreUserInfoClaims,
+};
+use crate::{
+    ClaimsVerificationError, DiscoveryError, HttpRequest, HttpResponse, IssuerUrl, Nonce,
+    OAuth2TokenResponse, SignatureVerificationError, TokenResponse, UserInfoError,
+};
+
+///
+/// Base URL of the OpenID Foundation's Relying Party certification test suite.
+///
+pub const CERTIFICATION_BASE_URL: &str = "https://rp.certification.openid.net:8080";
+
+///
+/// OpenID Foundation Relying Party certification profile.
+///
+/// See the [certification overview](https://openid.net/certification/rp_testing/) for the tests
+/// required by each profile.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConformanceProfile {
+    ///
+    /// Basic RP profile, which uses the Authorization Code Flow.
+    ///
+    Basic,
+    ///
+    /// Config RP profile, which uses OpenID Connect Discovery.
+    ///
+    Config,
+    ///
+    /// Dynamic RP profile, which uses OpenID Connect Dynamic Client Registration.
+    ///
+    Dynamic,
+}
+
+///
+/// Outcome of running a [`ConformanceScenario`].
+///
+/// Many conformance tests deliberately return invalid responses, which the Relying Party is
+/// expected to reject. Each scenario specifies the outcome expected of a conforming Relying
+/// Party.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ConformanceOutcome {
+    ///
+    /// The flow completed successfully.
+    ///
+    Success,
+    ///
+    /// The provider metadata failed validation (e.g., due to an issuer mismatch).
+    ///
+    InvalidProviderMetadata,
+    ///
+    /// The token response or its ID token is malformed (e.g., the ID token is missing required
+    /// claims).
+    ///
+    InvalidTokenResponse,
+    ///
+    /// The ID token contains an invalid `aud` claim.
+    ///
+    InvalidAudience,
+    ///
+    /// The ID token contains an invalid `iss` claim.
+    ///
+    InvalidIssuer,
+    ///
+    /// The ID token contains an invalid `nonce` claim.
+    ///
+    InvalidNonce,
+    ///
+    /// The ID token has an invalid signature.
+    ///
+    InvalidSignature,
+    ///
+    /// The ID token doesn't identify its signing key, and more than one key matches.
+    ///
+    AmbiguousKeyId,
+    ///
+    /// The UserInfo response contains a `sub` claim that doesn't match the ID token's.
+    ///
+    InvalidUserInfoSubject,
+}
+
+///
+/// Conformance test scenario, which maps an OpenID Foundation certification test to the steps
+/// the Relying Party performs and the outcome expected of a conforming Relying Party.
+///
+/// Each scenario runs the Authorization Code Flow: discovery, (optionally) dynamic client
+/// registration, authorization, code exchange, ID token verification, and (optionally) a
+/// UserInfo request.
+///
+#[derive(Clone, Debug)]
+pub struct ConformanceScenario {
+    test_id: &'static str,
+    profiles: &'static [ConformanceProfile],
+    expected_outcome: ConformanceOutcome,
+    scopes: &'static [&'static str],
+    auth_method: Option<CoreClientAuthMethod>,
+    id_token_signing_alg: Option<CoreJwsSigningAlgorithm>,
+    userinfo_signing_alg: Option<CoreJwsSigningAlgorithm>,
+    request_user_info: bool,
+}
+impl ConformanceScenario {
+    fn new(
+        test_id: &'static str,
+        profiles: &'static [ConformanceProfile],
+        expected_outcome: ConformanceOutcome,
+    ) -> Self {
+        Self {
+            test_id,
+            profiles,
+            expected_outcome,
+            scopes: &[],
+            auth_method: None,
+            id_token_signing_alg: None,
+            userinfo_signing_alg: None,
+            request_user_info: false,
+        }
+    }
+
+    fn with_user_info(mut self, scopes: &'static [&'static str]) -> Self {
+        self.scopes = scopes;
+        self.request_user_info = true;
+        self
+    }
+
+    fn with_auth_method(mut self, auth_method: CoreClientAuthMethod) -> Self {
+        self.auth_method = Some(auth_method);
+        self
+    }
+
+    fn with_id_token_signing_alg(mut self, alg: CoreJwsSigningAlgorithm) -> Self {
+        self.id_token_signing_alg = Some(alg);
+        self
+    }
+
+    ///
+    /// Returns all supported scenarios.
+    ///
+    pub fn all() -> Vec<Self> {
+        use ConformanceOutcome::*;
+        use ConformanceProfile::*;
+
+        const BASIC: &[ConformanceProfile] = &[Basic];
+        const CONFIG: &[ConformanceProfile] = &[Config];
+        const DYNAMIC: &[ConformanceProfile] = &[Dynamic];
+        const USER_INFO_SCOPES: &[&str] = &["profile", "email", "address", "phone"];
+
+        vec![
+            Self::new("rp-response_type-code", BASIC, Success),
+            Self::new("rp-scope-userinfo-claims", BASIC, Success).with_user_info(USER_INFO_SCOPES),
+            Self::new("rp-nonce-invalid", BASIC, InvalidNonce),
+            Self::new("rp-token_endpoint-client_secret_basic", BASIC, Success)
+                .with_auth_method(CoreClientAuthMethod::ClientSecretBasic),
+            Self::new("rp-id_token-kid-absent-single-jwks", BASIC, Success),
+            Self::new("rp-id_token-iat", BASIC, InvalidTokenResponse),
+            Self::new("rp-id_token-aud", BASIC, InvalidAudience),
+            Self::new(
+                "rp-id_token-kid-absent-multiple-jwks",
+                BASIC,
+                AmbiguousKeyId,
+            ),
+            Self::new("rp-id_token-sig-rs256", BASIC, Success),
+            Self::new("rp-id_token-sub", BASIC, InvalidTokenResponse),
+            Self::new("rp-id_token-bad-sig-rs256", BASIC, InvalidSignature),
+            Self::new("rp-id_token-issuer-mismatch", BASIC, InvalidIssuer),
+            Self::new("rp-userinfo-bad-sub-claim", BASIC, InvalidUserInfoSubject)
+                .with_user_info(&["profile"]),
+            Self::new("rp-userinfo-bearer-header", BASIC, Success).with_user_info(&["profile"]),
+            Self::new("rp-token_endpoint-client_secret_post", BASIC, Success)
+                .with_auth_method(CoreClientAuthMethod::ClientSecretPost),
+            Self::new("rp-id_token-sig-hs256", BASIC, Success)
+                .with_id_token_signing_alg(CoreJwsSigningAlgorithm::HmacSha256),
+            Self::new("rp-id_token-bad-sig-hs256", BASIC, InvalidSignature)
+                .with_id_token_signing_alg(CoreJwsSigningAlgorithm::HmacSha256),
+            Self {
+                userinfo_signing_alg: Some(CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256),
+                ..Self::new("rp-userinfo-sig", BASIC, Success).with_user_info(&["profile"])
+            },
+            Self::new("rp-discovery-openid-configuration", CONFIG, Success),
+            Self::new("rp-discovery-jwks_uri-keys", CONFIG, Success),
+            Self::new(
+                "rp-discovery-issuer-not-matching-config",
+                CONFIG,
+                InvalidProviderMetadata,
+            ),
+            Self::new("rp-id_token-sig-none", CONFIG, Success)
+                .with_id_token_signing_alg(CoreJwsSigningAlgorithm::None),
+            Self::new("rp-registration-dynamic", DYNAMIC, Success),
+        ]
+    }
+
+    ///
+    /// Returns the scenarios required by the specified certification profile.
+    ///
+    pub fn for_profile(profile: ConformanceProfile) -> Vec<Self> {
+        Self::all()
+            .into_iter()
+            .filter(|scenario| scenario.profiles.contains(&profile))
+            .collect()
+    }
+
+    ///
+    /// Returns the scenario for the specified certification test ID, if supported.
+    ///
+    pub fn find(test_id: &str) -> Option<Self> {
+        Self::all()
+            .into_iter()
+            .find(|scenario| scenario.test_id == test_id)
+    }
+
+    ///
+    /// Returns the certification test ID (e.g., `rp-id_token-aud`).
+    ///
+    pub fn test_id(&self) -> &'static str {
+        self.test_id
+    }
+
+    ///
+    /// Returns the certification profiles that require this scenario.
+    ///
+    pub fn profiles(&self) -> &'static [ConformanceProfile] {
+        self.profiles
+    }
+
+    ///
+    /// Returns the outcome expected of a conforming Relying Party.
+    ///
+    pub fn expected_outcome(&self) -> ConformanceOutcome {
+        self.expected_outcome
+    }
+}
+
+///
+/// Harness that drives the OpenID Foundation Relying Party conformance test endpoints using this
+/// crate's [`CoreClient`].
+///
+/// Each certification test is served by a dedicated issuer of the form
+/// `{base_url}/{rp_id}/{test_id}`. By default, the harness registers a new client with each test
+/// issuer using dynamic client registration; use
+/// [`ConformanceHarness::set_client_credentials`] to use a pre-registered client instead.
+///
+/// Running the full suite requires network access to the certification servers:
+///
+/// ```rust,no_run
+/// use openidconnect::conformance::{ConformanceHarness, ConformanceProfile, ConformanceScenario};
+/// use openidconnect::reqwest::http_client;
+/// use openidconnect::RedirectUrl;
+///
+/// # fn main() -> Result<(), anyhow::Error> {
+/// let harness = ConformanceHarness::new(
+///     "my-rp".to_string(),
+///     RedirectUrl::new("http://localhost:8080".to_string())?,
+/// );
+/// for scenario in ConformanceScenario::for_profile(ConformanceProfile::Basic) {
+///     harness.run(&scenario, http_client)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct ConformanceHarness {
+    base_url: Url,
+    rp_id: String,
+    redirect_uri: RedirectUrl,
+    client_credentials: Option<(ClientId, Option<ClientSecret>)>,
+}
+impl ConformanceHarness {
+    ///
+    /// Creates a harness for the specified Relying Party identifier, which namespaces the test
+    /// issuers on the certification server.
+    ///
+    pub fn new(rp_id: String, redirect_uri: RedirectUrl) -> Self {
+        Self {
+            base_url: Url::parse(CERTIFICATION_BASE_URL).expect("invalid certification URL"),
+            rp_id,
+            redirect_uri,
+            client_credentials: None,
+        }
+    }
+
+    ///
+    /// Sets the base URL of the certification server (default: [`CERTIFICATION_BASE_URL`]).
+    ///
+    pub fn set_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    ///
+    /// Uses a pre-registered client instead of dynamic client registration.
+    ///
+    pub fn set_client_credentials(
+        mut self,
+        client_id: ClientId,
+        client_secret: Option<ClientSecret>,
+    ) -> Self {
+        self.client_credentials = Some((client_id, client_secret));
+        self
+    }
+
+    ///
+    /// Returns the issuer URL that serves the specified certification test.
+    ///
+    pub fn issuer_url(&self, test_id: &str) -> IssuerUrl {
+        IssuerUrl::new(format!(
+            "{}/{}/{}",
+            self.base_url.as_str().trim_end_matches('/'),
+            self.rp_id,
+            test_id
+        ))
+        .expect("invalid issuer URL")
+    }
+
+    ///
+    /// Runs the specified scenario using the specified synchronous HTTP client, returning an
+    /// error if the observed outcome differs from the scenario's expected outcome.
+    ///
+    /// The HTTP client must not follow redirects, since the authorization step reads the
+    /// authorization code from the authorization endpoint's redirect.
+    ///
+    pub fn run<HC, RE>(
+        &self,
+        scenario: &ConformanceScenario,
+        http_client: HC,
+    ) -> Result<(), ConformanceError>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let observed = self.observe(scenario, &http_client)?;
+        if observed == scenario.expected_outcome {
+            Ok(())
+        } else {
+            Err(ConformanceError::UnexpectedOutcome {
+                test_id: scenario.test_id.to_string(),
+                expected: scenario.expected_outcome,
+                observed,
+            })
+        }
+    }
+
+    ///
+    /// Runs each of the specified scenarios, returning the result of each.
+    ///
+    pub fn run_all<HC, RE>(
+        &self,
+        scenarios: &[ConformanceScenario],
+        http_client: HC,
+    ) -> Vec<(&'static str, Result<(), ConformanceError>)>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        scenarios
+            .iter()
+            .map(|scenario| (scenario.test_id, self.run(scenario, &http_client)))
+            .collect()
+    }
+
+    fn observe<HC, RE>(
+        &self,
+        scenario: &ConformanceScenario,
+        http_client: &HC,
+    ) -> Result<ConformanceOutcome, ConformanceError>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let provider_metadata =
+            match CoreProviderMetadata::discover(&self.issuer_url(scenario.test_id), http_client) {
+                Ok(provider_metadata) => provider_metadata,
+                Err(DiscoveryError::Validation(_)) => {
+                    return Ok(ConformanceOutcome::InvalidProviderMetadata)
+                }
+                Err(err) => return Err(ConformanceError::Discovery(error_chain(&err))),
+            };
+
+        let (client_id, client_secret) = match &self.client_credentials {
+            Some(client_credentials) => client_credentials.clone(),
+            None => self.register(scenario, &provider_metadata, http_client)?,
+        };
+        let mut client =
+            CoreClient::from_provider_metadata(provider_metadata, client_id, client_secret)
+                .set_redirect_uri(self.redirect_uri.clone());
+        if scenario.auth_method == Some(CoreClientAuthMethod::ClientSecretPost) {
+            client = client.set_auth_type(AuthType::RequestBody);
+        }
+
+        let (authorization_url, csrf_state, nonce) = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            )
+            .add_scopes(
+                scenario
+                    .scopes
+                    .iter()
+                    .map(|scope| Scope::new((*scope).to_string())),
+            )
+            .url()
+            // This should never arise, since the conformance client doesn't require PKCE.
+            .expect("authorization request violates PKCE policy");
+        let code = Self::authorize(authorization_url, &csrf_state, http_client)?;
+
+        let token_response = match client.exchange_code(code).request(http_client) {
+            Ok(token_response) => token_response,
+            Err(RequestTokenError::Parse(_, _)) => {
+                return Ok(ConformanceOutcome::InvalidTokenResponse)
+            }
+            Err(err) => return Err(ConformanceError::TokenExchange(error_chain(&err))),
+        };
+        let id_token = token_response.id_token().ok_or_else(|| {
+            ConformanceError::TokenExchange("token response is missing ID token".to_string())
+        })?;
+
+        let mut verifier = client.id_token_verifier();
+        match scenario.id_token_signing_alg {
+            Some(CoreJwsSigningAlgorithm::None) => {
+                verifier = verifier.insecure_disable_signature_check();
+            }
+            Some(ref alg) => verifier = verifier.set_allowed_algs(vec![alg.clone()]),
+            None => {}
+        }
+        let subject = match id_token.claims(&verifier, &nonce) {
+            Ok(claims) => claims.subject().clone(),
+            Err(err) => return Self::id_token_outcome(err),
+        };
+
+        if scenario.request_user_info {
+            let user_info_request = client
+                .user_info(token_response.access_token().to_owned(), Some(subject))
+                .map_err(|err| ConformanceError::UserInfo(error_chain(&err)))?;
+            let user_info_request = if scenario.userinfo_signing_alg.is_some() {
+                // The test suite omits the `iss` and `aud` claims from signed UserInfo
+                // responses, even though the Core spec says the RP SHOULD verify them.
+                user_info_request
+                    .require_issuer_match(false)
+                    .require_audience_match(false)
+            } else {
+                user_info_request.require_signed_response(false)
+            };
+            let user_info_result: Result<CoreUserInfoClaims, _> =
+                user_info_request.request(http_client);
+            match user_info_result {
+                Ok(_) => {}
+                Err(UserInfoError::ClaimsVerification(
+                    ClaimsVerificationError::InvalidSubject(_),
+                )) => return Ok(ConformanceOutcome::InvalidUserInfoSubject),
+                Err(err) => return Err(ConformanceError::UserInfo(error_chain(&err))),
+            }
+        }
+
+        Ok(ConformanceOutcome::Success)
+    }
+
+    fn register<HC, RE>(
+        &self,
+        scenario: &ConformanceScenario,
+        provider_metadata: &CoreProviderMetadata,
+        http_client: &HC,
+    ) -> Result<(ClientId, Option<ClientSecret>), ConformanceError>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let registration_endpoint = provider_metadata.registration_endpoint().ok_or_else(|| {
+            ConformanceError::Registration(
+                "provider does not support dynamic client registration".to_string(),
+            )
+        })?;
+        let registration_response =
+            CoreClientRegistrationRequest::new(vec![self.redirect_uri.clone()], Default::default())
+                .set_token_endpoint_auth_method(scenario.auth_method.clone())
+                .set_id_token_signed_response_alg(scenario.id_token_signing_alg.clone())
+                .set_userinfo_signed_response_alg(scenario.userinfo_signing_alg.clone())
+                .register(registration_endpoint, http_client)
+                .map_err(|err| ConformanceError::Registration(error_chain(&err)))?;
+        Ok((
+            registration_response.client_id().clone(),
+            registration_response.client_secret().cloned(),
+        ))
+    }
+
+    fn authorize<HC, RE>(
+        authorization_url: Url,
+        csrf_state: &CsrfToken,
+        http_client: &HC,
+    ) -> Result<oauth2::AuthorizationCode, ConformanceError>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let response = http_client(HttpRequest {
+            url: authorization_url,
+            method: Method::GET,
+            headers: vec![(ACCEPT, HeaderValue::from_static("text/html"))]
+                .into_iter()
+                .collect(),
+            body: Vec::new(),
+        })
+        .map_err(|err| ConformanceError::Authorization(error_chain(&err)))?;
+        if !response.status_code.is_redirection() {
+            return Err(ConformanceError::Authorization(format!(
+                "expected redirect, but authorization endpoint returned HTTP status {}",
+                response.status_code
+            )));
+        }
+        let redirect_url = response
+            .headers
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| Url::parse(location).ok())
+            .ok_or_else(|| {
+                ConformanceError::Authorization("missing or invalid Location header".to_string())
+            })?;
+
+        let param = |name: &str| {
+            redirect_url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        if let Some(error) = param("error") {
+            return Err(ConformanceError::Authorization(format!(
+                "authorization endpoint returned error `{}`",
+                error
+            )));
+        }
+        if param("state").as_ref() != Some(csrf_state.secret()) {
+            return Err(ConformanceError::Authorization(
+                "authorization response state does not match request".to_string(),
+            ));
+        }
+        param("code")
+            .map(oauth2::AuthorizationCode::new)
+            .ok_or_else(|| {
+                ConformanceError::Authorization("authorization response is missing code".into())
+            })
+    }
+
+    fn id_token_outcome(
+        err: ClaimsVerificationError,
+    ) -> Result<ConformanceOutcome, ConformanceError> {
+        match err {
+            ClaimsVerificationError::InvalidAudience(_) => Ok(ConformanceOutcome::InvalidAudience),
+            ClaimsVerificationError::InvalidIssuer(_) => Ok(ConformanceOutcome::InvalidIssuer),
+            ClaimsVerificationError::InvalidNonce(_) => Ok(ConformanceOutcome::InvalidNonce),
+            ClaimsVerificationError::MalformedClaims(_) => {
+                Ok(ConformanceOutcome::InvalidTokenResponse)
+            }
+            ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::CryptoError(_),
+            ) => Ok(ConformanceOutcome::InvalidSignature),
+            ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::AmbiguousKeyId(_),
+            ) => Ok(ConformanceOutcome::AmbiguousKeyId),
+            err => Err(ConformanceError::IdToken(error_chain(&err))),
+        }
+    }
+}
+
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message += &format!(": {}", cause);
+        source = cause.source();
+    }
+    message
+}
+
+///
+/// Error running a [`ConformanceScenario`].
+///
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum ConformanceError {
+    ///
+    /// The authorization request failed.
+    ///
+    #[error("Authorization failed: {0}")]
+    Authorization(String),
+    ///
+    /// Discovery failed for a reason other than invalid provider metadata.
+    ///
+    #[error("Discovery failed: {0}")]
+    Discovery(String),
+    ///
+    /// ID token verification failed for a reason not covered by [`ConformanceOutcome`].
+    ///
+    #[error("ID token verification failed: {0}")]
+    IdToken(String),
+    ///
+    /// Dynamic client registration failed.
+    ///
+    #[error("Client registration failed: {0}")]
+    Registration(String),
+    ///
+    /// The authorization code exchange failed.
+    ///
+    #[error("Token exchange failed: {0}")]
+    TokenExchange(String),
+    ///
+    /// The observed outcome differs from the scenario's expected outcome.
+    ///
+    #[error("Test `{test_id}` expected outcome {expected:?}, but observed {observed:?}")]
+    UnexpectedOutcome {
+        ///
+        /// Certification test ID.
+        ///
+        test_id: String,
+        ///
+        /// Outcome expected of a conforming Relying Party.
+        ///
+        expected: ConformanceOutcome,
+        ///
+        /// Observed outcome.
+        ///
+        observed: ConformanceOutcome,
+    },
+    ///
+    /// The UserInfo request failed.
+    ///
+    #[error("UserInfo request failed: {0}")]
+    UserInfo(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{HeaderValue, LOCATION};
+    use http::status::StatusCode;
+    use oauth2::{ClientId, ClientSecret, RedirectUrl};
+
+    use crate::core::CoreGenderClaim;
+    use crate::test_util::MockProvider;
+    use crate::{HttpRequest, HttpResponse, StandardClaims, SubjectIdentifier};
+
+    use super::{
+        ConformanceError, ConformanceHarness, ConformanceOutcome, ConformanceProfile,
+        ConformanceScenario,
+    };
+
+    #[test]
+    fn test_conformance_scenarios() {
+        let basic = ConformanceScenario::for_profile(ConformanceProfile::Basic);
+        assert!(basic
+            .iter()
+            .any(|scenario| scenario.test_id() == "rp-response_type-code"));
+        assert!(basic
+            .iter()
+            .all(|scenario| scenario.profiles().contains(&ConformanceProfile::Basic)));
+        assert_eq!(
+            ConformanceScenario::find("rp-id_token-aud")
+                .unwrap()
+                .expected_outcome(),
+            ConformanceOutcome::InvalidAudience
+        );
+        assert!(ConformanceScenario::find("rp-unknown").is_none());
+
+        let mut test_ids = ConformanceScenario::all()
+            .iter()
+            .map(ConformanceScenario::test_id)
+            .collect::<Vec<_>>();
+        test_ids.sort_unstable();
+        test_ids.dedup();
+        assert_eq!(test_ids.len(), ConformanceScenario::all().len());
+    }
+
+    #[test]
+    fn test_conformance_harness() {
+        let harness = ConformanceHarness::new(
+            "openidconnect-rs".to_string(),
+            RedirectUrl::new("http://localhost:8080".to_string()).unwrap(),
+        )
+        .set_base_url("https://op.example.com".parse().unwrap())
+        .set_client_credentials(
+            ClientId::new("my_client".to_string()),
+            Some(ClientSecret::new("my_secret".to_string())),
+        );
+
+        let run = |test_id: &str| {
+            let provider = MockProvider::new(harness.issuer_url(test_id));
+            assert_eq!(
+                provider.issuer().as_str(),
+                format!("https://op.example.com/openidconnect-rs/{}", test_id)
+            );
+            // Simulate the User Agent by answering authorization requests with a redirect.
+            let http_client = |request: HttpRequest| {
+                if request.url.path().ends_with("/authorize") {
+                    let claims = StandardClaims::<CoreGenderClaim>::new(SubjectIdentifier::new(
+                        "alice".to_string(),
+                    ));
+                    let redirect_url = provider.authorize(&request.url, claims)?;
+                    Ok(HttpResponse {
+                        status_code: StatusCode::FOUND,
+                        headers: vec![(
+                            LOCATION,
+                            HeaderValue::from_str(redirect_url.as_str()).unwrap(),
+                        )]
+                        .into_iter()
+                        .collect(),
+                        body: Vec::new(),
+                    })
+                } else {
+                    provider.http_client()(request)
+                }
+            };
+            harness.run(&ConformanceScenario::find(test_id).unwrap(), http_client)
+        };
+
+        run("rp-response_type-code").unwrap();
+        run("rp-userinfo-bearer-header").unwrap();
+
+        // The mock provider returns a valid nonce, which the scenario expects to be rejected.
+        match run("rp-nonce-invalid") {
+            Err(ConformanceError::UnexpectedOutcome {
+                expected, observed, ..
+            }) => {
+                assert_eq!(expected, ConformanceOutcome::InvalidNonce);
+                assert_eq!(observed, ConformanceOutcome::Success);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
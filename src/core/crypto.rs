@@ -5,7 +5,7 @@ use super::{jwk::CoreJsonCurveType, CoreJsonWebKey, CoreJsonWebKeyType};
 
 use std::ops::Deref;
 
-fn rsa_public_key(
+pub fn rsa_public_key(
     key: &CoreJsonWebKey,
 ) -> Result<(&Base64UrlEncodedBytes, &Base64UrlEncodedBytes), String> {
     if *key.key_type() != CoreJsonWebKeyType::RSA {
@@ -23,7 +23,7 @@ fn rsa_public_key(
     }
 }
 
-fn ec_public_key(
+pub fn ec_public_key(
     key: &CoreJsonWebKey,
 ) -> Result<
     (
@@ -119,9 +119,20 @@ pub fn verify_ec_signature(
                     SignatureVerificationError::CryptoError("EC Signature was wrong".to_string())
                 })
         }
-        CoreJsonCurveType::P521 => Err(SignatureVerificationError::UnsupportedAlg(
-            "P521".to_string(),
-        )),
+        CoreJsonCurveType::P521 => {
+            let public_key = p521::ecdsa::VerifyingKey::from_sec1_bytes(&pk)
+                .map_err(|e| SignatureVerificationError::InvalidKey(e.to_string()))?;
+            public_key
+                .verify(
+                    msg,
+                    &p521::ecdsa::Signature::from_slice(signature).map_err(|_| {
+                        SignatureVerificationError::CryptoError("Invalid signature".to_string())
+                    })?,
+                )
+                .map_err(|_| {
+                    SignatureVerificationError::CryptoError("EC Signature was wrong".to_string())
+                })
+        }
     }
 }
 
@@ -0,0 +1,1144 @@
+use aes::cipher::{BlockDecrypt, BlockEncrypt, BlockSizeUser};
+use aes_gcm::aead::consts::{U12, U16};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::AesGcm;
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use p256::elliptic_curve::group::Curve;
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint};
+use p256::elliptic_curve::{AffinePoint, CurveArithmetic, FieldBytesSize, PublicKey, SecretKey};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use subtle::ConstantTimeEq;
+
+use crate::types::Base64UrlEncodedBytes;
+use crate::{
+    DecryptionError, DecryptionKey, EncryptionError, EncryptionKey, JsonWebKey, JsonWebKeyId,
+    JsonWebKeyUse,
+};
+
+use super::jwk::{CoreJsonCurveType, EcSigningKey};
+use super::{
+    crypto, CoreEcPrivateSigningKey, CoreJsonWebKey, CoreJsonWebKeyType,
+    CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm, CoreRsaPrivateSigningKey,
+};
+
+// Initial value used by the AES Key Wrap algorithm (see Section 2.2.3.1 of RFC 3394).
+const AES_KEY_WRAP_IV: [u8; 8] = [0xA6; 8];
+
+#[derive(Serialize)]
+struct CoreJweHeader<'a> {
+    alg: &'a CoreJweKeyManagementAlgorithm,
+    enc: &'a CoreJweContentEncryptionAlgorithm,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cty: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kid: Option<&'a JsonWebKeyId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    epk: Option<CoreJsonWebKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    zip: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct CoreJweDecodedHeader {
+    alg: CoreJweKeyManagementAlgorithm,
+    enc: CoreJweContentEncryptionAlgorithm,
+    epk: Option<CoreJsonWebKey>,
+    apu: Option<Base64UrlEncodedBytes>,
+    apv: Option<Base64UrlEncodedBytes>,
+    zip: Option<String>,
+    crit: Option<Vec<String>>,
+}
+
+impl
+    EncryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJsonWebKeyType,
+    > for CoreJsonWebKey
+{
+    ///
+    /// Encrypts the given `plaintext` using this key.
+    ///
+    /// The `RSA-OAEP` and `RSA-OAEP-256` key management algorithms are supported for RSA keys,
+    /// the `ECDH-ES`, `ECDH-ES+A128KW`, `ECDH-ES+A192KW`, and `ECDH-ES+A256KW` algorithms are
+    /// supported for EC keys, and the `dir` (direct encryption) algorithm is supported for
+    /// symmetric keys. All content encryption algorithms defined by
+    /// [Section 5.1 of RFC 7518](https://tools.ietf.org/html/rfc7518#section-5.1) are supported.
+    ///
+    fn encrypt(
+        &self,
+        key_management_alg: &CoreJweKeyManagementAlgorithm,
+        content_encryption_alg: &CoreJweContentEncryptionAlgorithm,
+        content_type: Option<&str>,
+        plaintext: &[u8],
+    ) -> Result<String, EncryptionError> {
+        encrypt_jwe(
+            self,
+            key_management_alg,
+            content_encryption_alg,
+            content_type,
+            plaintext,
+            false,
+        )
+    }
+
+    ///
+    /// Compresses the given `plaintext` using DEFLATE and encrypts it using this key.
+    ///
+    /// The same algorithms are supported as for [`encrypt`](Self::encrypt).
+    ///
+    fn encrypt_compressed(
+        &self,
+        key_management_alg: &CoreJweKeyManagementAlgorithm,
+        content_encryption_alg: &CoreJweContentEncryptionAlgorithm,
+        content_type: Option<&str>,
+        plaintext: &[u8],
+    ) -> Result<String, EncryptionError> {
+        encrypt_jwe(
+            self,
+            key_management_alg,
+            content_encryption_alg,
+            content_type,
+            plaintext,
+            true,
+        )
+    }
+}
+
+fn encrypt_jwe(
+    key: &CoreJsonWebKey,
+    key_management_alg: &CoreJweKeyManagementAlgorithm,
+    content_encryption_alg: &CoreJweContentEncryptionAlgorithm,
+    content_type: Option<&str>,
+    plaintext: &[u8],
+    compress: bool,
+) -> Result<String, EncryptionError> {
+    if let Some(key_use) = key.key_use() {
+        if !key_use.allows_encryption() {
+            return Err(EncryptionError::InvalidKey(
+                "key usage not permitted for encryption".to_string(),
+            ));
+        }
+    }
+
+    let cek_len = content_encryption_key_len(content_encryption_alg);
+    let mut epk = None;
+    let (cek, encrypted_key) = match *key_management_alg {
+        CoreJweKeyManagementAlgorithm::RsaOaep | CoreJweKeyManagementAlgorithm::RsaOaepSha256 => {
+            let (n, e) = crypto::rsa_public_key(key).map_err(EncryptionError::InvalidKey)?;
+            let public_key = rsa::RsaPublicKey::new(
+                rsa::BigUint::from_bytes_be(n),
+                rsa::BigUint::from_bytes_be(e),
+            )
+            .map_err(|err| EncryptionError::InvalidKey(err.to_string()))?;
+
+            let cek = random_bytes(cek_len);
+            let encrypted_key = public_key
+                .encrypt(
+                    &mut rand::rngs::OsRng,
+                    rsa_oaep_padding(key_management_alg),
+                    &cek,
+                )
+                .map_err(|_| EncryptionError::CryptoError)?;
+            (cek, encrypted_key)
+        }
+        CoreJweKeyManagementAlgorithm::EcdhEs
+        | CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap128
+        | CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap192
+        | CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap256 => {
+            let (x, y, crv) = crypto::ec_public_key(key).map_err(EncryptionError::InvalidKey)?;
+            let (shared_secret, ephemeral_x, ephemeral_y) = match *crv {
+                CoreJsonCurveType::P256 => ecdh_ephemeral::<p256::NistP256>(x, y),
+                CoreJsonCurveType::P384 => ecdh_ephemeral::<p384::NistP384>(x, y),
+                CoreJsonCurveType::P521 => ecdh_ephemeral::<p521::NistP521>(x, y),
+            }
+            .map_err(EncryptionError::InvalidKey)?;
+            epk = Some(CoreJsonWebKey {
+                use_: None,
+                ..CoreJsonWebKey::new_ec(ephemeral_x, ephemeral_y, crv.clone(), None)
+            });
+
+            let (derived_key, key_wrap) = ecdh_es_derive_key(
+                key_management_alg,
+                content_encryption_alg,
+                &shared_secret,
+                &[],
+                &[],
+            );
+            if key_wrap {
+                let cek = random_bytes(cek_len);
+                let encrypted_key = aes_key_wrap(&derived_key, &cek)?;
+                (cek, encrypted_key)
+            } else {
+                (derived_key, Vec::new())
+            }
+        }
+        CoreJweKeyManagementAlgorithm::Direct => {
+            let cek = symmetric_key(key, cek_len).map_err(EncryptionError::InvalidKey)?;
+            (cek, Vec::new())
+        }
+        ref other => return Err(EncryptionError::UnsupportedAlg(alg_name(other))),
+    };
+
+    let header = serde_json::to_vec(&CoreJweHeader {
+        alg: key_management_alg,
+        enc: content_encryption_alg,
+        cty: content_type,
+        kid: key.key_id(),
+        epk,
+        zip: if compress { Some("DEF") } else { None },
+    })
+    .map_err(|err| EncryptionError::Other(err.to_string()))?;
+    let aad = base64::encode_config(header, base64::URL_SAFE_NO_PAD);
+
+    let compressed;
+    let plaintext = if compress {
+        compressed = miniz_oxide::deflate::compress_to_vec(plaintext, 6);
+        &compressed
+    } else {
+        plaintext
+    };
+    let (iv, ciphertext, tag) =
+        encrypt_content(content_encryption_alg, &cek, aad.as_bytes(), plaintext)?;
+
+    Ok([
+        aad,
+        base64::encode_config(encrypted_key, base64::URL_SAFE_NO_PAD),
+        base64::encode_config(iv, base64::URL_SAFE_NO_PAD),
+        base64::encode_config(ciphertext, base64::URL_SAFE_NO_PAD),
+        base64::encode_config(tag, base64::URL_SAFE_NO_PAD),
+    ]
+    .join("."))
+}
+
+impl
+    DecryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJsonWebKeyType,
+    > for CoreJsonWebKey
+{
+    ///
+    /// Decrypts the given JWE using this symmetric key and the `dir` (direct encryption) key
+    /// management algorithm.
+    ///
+    fn decrypt_with_limit(
+        &self,
+        jwe: &str,
+        max_decompressed_len: usize,
+    ) -> Result<Vec<u8>, DecryptionError> {
+        if let Some(key_use) = self.key_use() {
+            if !key_use.allows_encryption() {
+                return Err(DecryptionError::InvalidKey(
+                    "key usage not permitted for encryption".to_string(),
+                ));
+            }
+        }
+
+        decrypt_jwe(
+            jwe,
+            max_decompressed_len,
+            |header, _, cek_len| match header.alg {
+                CoreJweKeyManagementAlgorithm::Direct => {
+                    symmetric_key(self, cek_len).map_err(DecryptionError::InvalidKey)
+                }
+                ref other => Err(DecryptionError::UnsupportedAlg(alg_name(other))),
+            },
+        )
+    }
+}
+
+impl
+    DecryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJsonWebKeyType,
+    > for CoreRsaPrivateSigningKey
+{
+    ///
+    /// Decrypts the given JWE using this RSA private key and the `RSA-OAEP` or `RSA-OAEP-256` key
+    /// management algorithm.
+    ///
+    fn decrypt_with_limit(
+        &self,
+        jwe: &str,
+        max_decompressed_len: usize,
+    ) -> Result<Vec<u8>, DecryptionError> {
+        decrypt_jwe(
+            jwe,
+            max_decompressed_len,
+            |header, encrypted_key, _| match header.alg {
+                CoreJweKeyManagementAlgorithm::RsaOaep
+                | CoreJweKeyManagementAlgorithm::RsaOaepSha256 => self
+                    .key_pair
+                    .decrypt(rsa_oaep_padding(&header.alg), encrypted_key)
+                    .map_err(|_| DecryptionError::CryptoError),
+                ref other => Err(DecryptionError::UnsupportedAlg(alg_name(other))),
+            },
+        )
+    }
+}
+
+impl
+    DecryptionKey<
+        CoreJweContentEncryptionAlgorithm,
+        CoreJweKeyManagementAlgorithm,
+        CoreJsonWebKeyType,
+    > for CoreEcPrivateSigningKey
+{
+    ///
+    /// Decrypts the given JWE using this EC private key and the `ECDH-ES`, `ECDH-ES+A128KW`,
+    /// `ECDH-ES+A192KW`, or `ECDH-ES+A256KW` key management algorithm.
+    ///
+    fn decrypt_with_limit(
+        &self,
+        jwe: &str,
+        max_decompressed_len: usize,
+    ) -> Result<Vec<u8>, DecryptionError> {
+        decrypt_jwe(jwe, max_decompressed_len, |header, encrypted_key, _| {
+            match header.alg {
+                CoreJweKeyManagementAlgorithm::EcdhEs
+                | CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap128
+                | CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap192
+                | CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap256 => {}
+                ref other => return Err(DecryptionError::UnsupportedAlg(alg_name(other))),
+            }
+
+            let epk = header.epk.as_ref().ok_or_else(|| {
+                DecryptionError::Malformed("ephemeral public key `epk` is missing".to_string())
+            })?;
+            let (x, y, crv) = crypto::ec_public_key(epk).map_err(DecryptionError::Malformed)?;
+            let shared_secret = match (&self.key, crv) {
+                (EcSigningKey::P256(key), CoreJsonCurveType::P256) => {
+                    ecdh_static::<p256::NistP256>(&key.to_bytes(), x, y)
+                }
+                (EcSigningKey::P384(key), CoreJsonCurveType::P384) => {
+                    ecdh_static::<p384::NistP384>(&key.to_bytes(), x, y)
+                }
+                (EcSigningKey::P521(key), CoreJsonCurveType::P521) => {
+                    ecdh_static::<p521::NistP521>(&key.to_bytes(), x, y)
+                }
+                _ => {
+                    return Err(DecryptionError::InvalidKey(
+                        "ephemeral public key uses a different curve".to_string(),
+                    ))
+                }
+            }
+            .map_err(DecryptionError::Malformed)?;
+
+            let (derived_key, key_wrap) = ecdh_es_derive_key(
+                &header.alg,
+                &header.enc,
+                &shared_secret,
+                header.apu.as_deref().map(Vec::as_slice).unwrap_or_default(),
+                header.apv.as_deref().map(Vec::as_slice).unwrap_or_default(),
+            );
+            if key_wrap {
+                aes_key_unwrap(&derived_key, encrypted_key)
+            } else {
+                Ok(derived_key)
+            }
+        })
+    }
+}
+
+fn alg_name<A>(alg: &A) -> String
+where
+    A: std::fmt::Debug + Serialize,
+{
+    serde_plain::to_string(alg).unwrap_or_else(|err| {
+        panic!(
+            "encryption alg {:?} failed to serialize to a string: {}",
+            alg, err
+        )
+    })
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn rsa_oaep_padding(alg: &CoreJweKeyManagementAlgorithm) -> rsa::Oaep {
+    if *alg == CoreJweKeyManagementAlgorithm::RsaOaep {
+        rsa::Oaep::new::<sha1::Sha1>()
+    } else {
+        rsa::Oaep::new::<Sha256>()
+    }
+}
+
+fn symmetric_key(key: &CoreJsonWebKey, cek_len: usize) -> Result<Vec<u8>, String> {
+    if *key.key_type() != CoreJsonWebKeyType::Symmetric {
+        return Err("symmetric key required".to_string());
+    }
+    let cek = key
+        .k
+        .as_ref()
+        .ok_or_else(|| "symmetric key `k` is missing".to_string())?
+        .to_vec();
+    if cek.len() != cek_len {
+        return Err(format!(
+            "expected {}-byte key (found {} bytes)",
+            cek_len,
+            cek.len()
+        ));
+    }
+    Ok(cek)
+}
+
+fn content_encryption_key_len(alg: &CoreJweContentEncryptionAlgorithm) -> usize {
+    match *alg {
+        CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256 => 32,
+        CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384 => 48,
+        CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512 => 64,
+        CoreJweContentEncryptionAlgorithm::Aes128Gcm => 16,
+        CoreJweContentEncryptionAlgorithm::Aes192Gcm => 24,
+        CoreJweContentEncryptionAlgorithm::Aes256Gcm => 32,
+    }
+}
+
+// Parses a JWE in Compact Serialization, determines the content encryption key using the given
+// function (which is passed the header, the encrypted key, and the expected key length), and
+// returns the decrypted plaintext.
+fn decrypt_jwe<F>(
+    jwe: &str,
+    max_decompressed_len: usize,
+    content_encryption_key: F,
+) -> Result<Vec<u8>, DecryptionError>
+where
+    F: FnOnce(&CoreJweDecodedHeader, &[u8], usize) -> Result<Vec<u8>, DecryptionError>,
+{
+    let parts = jwe.split('.').collect::<Vec<_>>();
+    if parts.len() != 5 {
+        return Err(DecryptionError::Malformed(format!(
+            "expected 5 parts (found {})",
+            parts.len()
+        )));
+    }
+    let decode = |name: &str, part: &str| {
+        base64::decode_config(part, super::base64_url_safe_no_pad())
+            .map_err(|err| DecryptionError::Malformed(format!("invalid {}: {}", name, err)))
+    };
+    let header = serde_json::from_slice::<CoreJweDecodedHeader>(&decode("header", parts[0])?)
+        .map_err(|err| DecryptionError::Malformed(format!("invalid header: {}", err)))?;
+    match header.zip.as_deref() {
+        None | Some("DEF") => {}
+        Some(zip) => return Err(DecryptionError::UnsupportedAlg(zip.to_string())),
+    }
+    if header.crit.is_some() {
+        return Err(DecryptionError::Other(
+            "critical JWE header fields are unsupported".to_string(),
+        ));
+    }
+
+    let cek_len = content_encryption_key_len(&header.enc);
+    let cek = content_encryption_key(&header, &decode("encrypted key", parts[1])?, cek_len)?;
+    if cek.len() != cek_len {
+        return Err(DecryptionError::CryptoError);
+    }
+
+    let plaintext = decrypt_content(
+        &header.enc,
+        &cek,
+        parts[0].as_bytes(),
+        &decode("initialization vector", parts[2])?,
+        &decode("ciphertext", parts[3])?,
+        &decode("authentication tag", parts[4])?,
+    )?;
+    if header.zip.is_none() {
+        return Ok(plaintext);
+    }
+    miniz_oxide::inflate::decompress_to_vec_with_limit(&plaintext, max_decompressed_len).map_err(
+        |err| match err.status {
+            miniz_oxide::inflate::TINFLStatus::HasMoreOutput => {
+                DecryptionError::TooLarge(max_decompressed_len)
+            }
+            _ => DecryptionError::Malformed("invalid compressed plaintext".to_string()),
+        },
+    )
+}
+
+// Generates an ephemeral key pair on the recipient's curve, returning the shared secret (`Z`)
+// along with the ephemeral public key's `x` and `y` coordinates.
+fn ecdh_ephemeral<C>(x: &[u8], y: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), String>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let ephemeral_key = SecretKey::<C>::random(&mut rand::rngs::OsRng);
+    let shared_secret = ecdh_shared_secret(&ephemeral_key, x, y)?;
+    let ephemeral_point = ephemeral_key.public_key().to_encoded_point(false);
+    Ok((
+        shared_secret,
+        ephemeral_point.x().expect("uncompressed point").to_vec(),
+        ephemeral_point.y().expect("uncompressed point").to_vec(),
+    ))
+}
+
+fn ecdh_static<C>(private_key: &[u8], x: &[u8], y: &[u8]) -> Result<Vec<u8>, String>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let private_key = SecretKey::<C>::from_slice(private_key)
+        .map_err(|_| "invalid EC private key".to_string())?;
+    ecdh_shared_secret(&private_key, x, y)
+}
+
+fn ecdh_shared_secret<C>(private_key: &SecretKey<C>, x: &[u8], y: &[u8]) -> Result<Vec<u8>, String>
+where
+    C: CurveArithmetic,
+    AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C>,
+    FieldBytesSize<C>: ModulusSize,
+{
+    let public_key = PublicKey::<C>::from_sec1_bytes(&[&[0x04], x, y].concat())
+        .map_err(|_| "invalid EC public key".to_string())?;
+    let shared_point = (public_key.to_projective() * *private_key.to_nonzero_scalar()).to_affine();
+    Ok(shared_point
+        .to_encoded_point(false)
+        .x()
+        .expect("uncompressed point")
+        .to_vec())
+}
+
+// Derives the key agreed upon using ECDH-ES (see Section 4.6.2 of RFC 7518). Returns the derived
+// key and whether it's used to wrap the content encryption key (as opposed to being used as the
+// content encryption key directly).
+fn ecdh_es_derive_key(
+    key_management_alg: &CoreJweKeyManagementAlgorithm,
+    content_encryption_alg: &CoreJweContentEncryptionAlgorithm,
+    shared_secret: &[u8],
+    apu: &[u8],
+    apv: &[u8],
+) -> (Vec<u8>, bool) {
+    let (algorithm_id, key_len, key_wrap) = match *key_management_alg {
+        CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap128 => {
+            (alg_name(key_management_alg), 16, true)
+        }
+        CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap192 => {
+            (alg_name(key_management_alg), 24, true)
+        }
+        CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap256 => {
+            (alg_name(key_management_alg), 32, true)
+        }
+        _ => (
+            alg_name(content_encryption_alg),
+            content_encryption_key_len(content_encryption_alg),
+            false,
+        ),
+    };
+
+    // Concat KDF (see Section 5.8.1 of NIST SP 800-56A).
+    let mut other_info = Vec::new();
+    for field in [algorithm_id.as_bytes(), apu, apv] {
+        other_info.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        other_info.extend_from_slice(field);
+    }
+    other_info.extend_from_slice(&((key_len * 8) as u32).to_be_bytes());
+
+    let mut derived_key = Vec::with_capacity(key_len);
+    let mut counter = 1u32;
+    while derived_key.len() < key_len {
+        let mut hasher = Sha256::new();
+        hasher.update(counter.to_be_bytes());
+        hasher.update(shared_secret);
+        hasher.update(&other_info);
+        derived_key.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+    derived_key.truncate(key_len);
+    (derived_key, key_wrap)
+}
+
+// See https://tools.ietf.org/html/rfc3394#section-2.2.1.
+fn aes_key_wrap(kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    match kek.len() {
+        16 => aes_key_wrap_with::<aes::Aes128>(kek, cek),
+        24 => aes_key_wrap_with::<aes::Aes192>(kek, cek),
+        32 => aes_key_wrap_with::<aes::Aes256>(kek, cek),
+        _ => Err(EncryptionError::CryptoError),
+    }
+}
+
+fn aes_key_wrap_with<C>(kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, EncryptionError>
+where
+    C: BlockEncrypt + BlockSizeUser<BlockSize = U16> + KeyInit,
+{
+    let cipher = C::new_from_slice(kek).map_err(|_| EncryptionError::CryptoError)?;
+    let mut a = AES_KEY_WRAP_IV;
+    let mut r = cek
+        .chunks(8)
+        .map(|chunk| chunk.to_vec())
+        .collect::<Vec<_>>();
+    let n = r.len();
+    for j in 0..6 {
+        for (i, r_i) in r.iter_mut().enumerate() {
+            let mut block = aes::Block::default();
+            block[..8].copy_from_slice(&a);
+            block[8..].copy_from_slice(r_i);
+            cipher.encrypt_block(&mut block);
+            let t = ((n * j) + i + 1) as u64;
+            for (a_byte, (b_byte, t_byte)) in
+                a.iter_mut().zip(block[..8].iter().zip(t.to_be_bytes()))
+            {
+                *a_byte = b_byte ^ t_byte;
+            }
+            r_i.copy_from_slice(&block[8..]);
+        }
+    }
+    Ok([a.to_vec(), r.concat()].concat())
+}
+
+// See https://tools.ietf.org/html/rfc3394#section-2.2.2.
+fn aes_key_unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, DecryptionError> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return Err(DecryptionError::CryptoError);
+    }
+    match kek.len() {
+        16 => aes_key_unwrap_with::<aes::Aes128>(kek, wrapped),
+        24 => aes_key_unwrap_with::<aes::Aes192>(kek, wrapped),
+        32 => aes_key_unwrap_with::<aes::Aes256>(kek, wrapped),
+        _ => Err(DecryptionError::CryptoError),
+    }
+}
+
+fn aes_key_unwrap_with<C>(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, DecryptionError>
+where
+    C: BlockDecrypt + BlockSizeUser<BlockSize = U16> + KeyInit,
+{
+    let cipher = C::new_from_slice(kek).map_err(|_| DecryptionError::CryptoError)?;
+    let mut a = [0; 8];
+    a.copy_from_slice(&wrapped[..8]);
+    let mut r = wrapped[8..]
+        .chunks(8)
+        .map(|chunk| chunk.to_vec())
+        .collect::<Vec<_>>();
+    let n = r.len();
+    for j in (0..6).rev() {
+        for (i, r_i) in r.iter_mut().enumerate().rev() {
+            let t = ((n * j) + i + 1) as u64;
+            let mut block = aes::Block::default();
+            for (b_byte, (a_byte, t_byte)) in
+                block[..8].iter_mut().zip(a.iter().zip(t.to_be_bytes()))
+            {
+                *b_byte = a_byte ^ t_byte;
+            }
+            block[8..].copy_from_slice(r_i);
+            cipher.decrypt_block(&mut block);
+            a.copy_from_slice(&block[..8]);
+            r_i.copy_from_slice(&block[8..]);
+        }
+    }
+    if !bool::from(a.ct_eq(&AES_KEY_WRAP_IV)) {
+        return Err(DecryptionError::CryptoError);
+    }
+    Ok(r.concat())
+}
+
+// Returns the initialization vector, ciphertext, and authentication tag.
+fn encrypt_content(
+    alg: &CoreJweContentEncryptionAlgorithm,
+    cek: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), EncryptionError> {
+    match *alg {
+        CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256 => {
+            encrypt_aes_cbc_hmac::<aes::Aes128, Hmac<Sha256>>(cek, aad, plaintext)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384 => {
+            encrypt_aes_cbc_hmac::<aes::Aes192, Hmac<Sha384>>(cek, aad, plaintext)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512 => {
+            encrypt_aes_cbc_hmac::<aes::Aes256, Hmac<Sha512>>(cek, aad, plaintext)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes128Gcm => {
+            encrypt_aes_gcm::<aes_gcm::Aes128Gcm>(cek, aad, plaintext)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes192Gcm => {
+            encrypt_aes_gcm::<AesGcm<aes::Aes192, U12>>(cek, aad, plaintext)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes256Gcm => {
+            encrypt_aes_gcm::<aes_gcm::Aes256Gcm>(cek, aad, plaintext)
+        }
+    }
+}
+
+fn decrypt_content(
+    alg: &CoreJweContentEncryptionAlgorithm,
+    cek: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptionError> {
+    match *alg {
+        CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256 => {
+            decrypt_aes_cbc_hmac::<aes::Aes128, Hmac<Sha256>>(cek, aad, iv, ciphertext, tag)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384 => {
+            decrypt_aes_cbc_hmac::<aes::Aes192, Hmac<Sha384>>(cek, aad, iv, ciphertext, tag)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512 => {
+            decrypt_aes_cbc_hmac::<aes::Aes256, Hmac<Sha512>>(cek, aad, iv, ciphertext, tag)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes128Gcm => {
+            decrypt_aes_gcm::<aes_gcm::Aes128Gcm>(cek, aad, iv, ciphertext, tag)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes192Gcm => {
+            decrypt_aes_gcm::<AesGcm<aes::Aes192, U12>>(cek, aad, iv, ciphertext, tag)
+        }
+        CoreJweContentEncryptionAlgorithm::Aes256Gcm => {
+            decrypt_aes_gcm::<aes_gcm::Aes256Gcm>(cek, aad, iv, ciphertext, tag)
+        }
+    }
+}
+
+// See https://tools.ietf.org/html/rfc7518#section-5.2.2.1.
+fn encrypt_aes_cbc_hmac<C, M>(
+    cek: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), EncryptionError>
+where
+    C: cbc::cipher::BlockCipher + cbc::cipher::BlockEncryptMut + cbc::cipher::KeyInit,
+    M: Mac + KeyInit,
+{
+    let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+
+    let iv = random_bytes(16);
+
+    let ciphertext = cbc::Encryptor::<C>::new_from_slices(enc_key, &iv)
+        .map_err(|_| EncryptionError::CryptoError)?
+        .encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+
+    let tag = aes_cbc_hmac_tag::<M>(mac_key, aad, &iv, &ciphertext)
+        .map_err(|_| EncryptionError::CryptoError)?;
+
+    Ok((iv, ciphertext, tag))
+}
+
+// See https://tools.ietf.org/html/rfc7518#section-5.2.2.2.
+fn decrypt_aes_cbc_hmac<C, M>(
+    cek: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptionError>
+where
+    C: cbc::cipher::BlockCipher + cbc::cipher::BlockDecryptMut + cbc::cipher::KeyInit,
+    M: Mac + KeyInit,
+{
+    let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+
+    // The authentication tag must be verified before decrypting the ciphertext to avoid padding
+    // oracle attacks.
+    let expected_tag = aes_cbc_hmac_tag::<M>(mac_key, aad, iv, ciphertext)
+        .map_err(|_| DecryptionError::CryptoError)?;
+    if !bool::from(expected_tag.ct_eq(tag)) {
+        return Err(DecryptionError::CryptoError);
+    }
+
+    cbc::Decryptor::<C>::new_from_slices(enc_key, iv)
+        .map_err(|_| DecryptionError::CryptoError)?
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| DecryptionError::CryptoError)
+}
+
+fn aes_cbc_hmac_tag<M>(
+    mac_key: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, hmac::digest::InvalidLength>
+where
+    M: Mac + KeyInit,
+{
+    let mut mac = <M as Mac>::new_from_slice(mac_key)?;
+    mac.update(aad);
+    mac.update(iv);
+    mac.update(ciphertext);
+    mac.update(&((aad.len() as u64) * 8).to_be_bytes());
+    let mut tag = mac.finalize().into_bytes().to_vec();
+    tag.truncate(mac_key.len());
+    Ok(tag)
+}
+
+// See https://tools.ietf.org/html/rfc7518#section-5.3.
+fn encrypt_aes_gcm<A>(
+    cek: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), EncryptionError>
+where
+    A: Aead + KeyInit,
+{
+    let mut iv = aes_gcm::aead::Nonce::<A>::default();
+    rand::rngs::OsRng.fill_bytes(&mut iv);
+
+    let mut ciphertext = A::new_from_slice(cek)
+        .map_err(|_| EncryptionError::CryptoError)?
+        .encrypt(
+            &iv,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| EncryptionError::CryptoError)?;
+    // The 128-bit authentication tag is appended to the ciphertext.
+    let tag = ciphertext.split_off(ciphertext.len() - 16);
+
+    Ok((iv.to_vec(), ciphertext, tag))
+}
+
+fn decrypt_aes_gcm<A>(
+    cek: &[u8],
+    aad: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, DecryptionError>
+where
+    A: Aead + KeyInit,
+{
+    let mut nonce = aes_gcm::aead::Nonce::<A>::default();
+    if iv.len() != nonce.len() || tag.len() != 16 {
+        return Err(DecryptionError::CryptoError);
+    }
+    nonce.copy_from_slice(iv);
+    A::new_from_slice(cek)
+        .map_err(|_| DecryptionError::CryptoError)?
+        .decrypt(
+            &nonce,
+            Payload {
+                msg: &[ciphertext, tag].concat(),
+                aad,
+            },
+        )
+        .map_err(|_| DecryptionError::CryptoError)
+}
+
+#[cfg(test)]
+mod tests {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use cbc::cipher::block_padding::Pkcs7;
+    use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+    use chrono::{TimeZone, Utc};
+    use hmac::{Hmac, Mac};
+    use rsa::pkcs1::DecodeRsaPrivateKey;
+    use sha2::Sha256;
+
+    use super::{aes_key_unwrap, aes_key_wrap, ecdh_es_derive_key};
+    use crate::core::{
+        CoreEcPrivateSigningKey, CoreIdToken, CoreIdTokenClaims, CoreJsonCurveType, CoreJsonWebKey,
+        CoreJsonWebKeyUse, CoreJweContentEncryptionAlgorithm, CoreJweKeyManagementAlgorithm,
+        CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+    use crate::{
+        Audience, DecryptionError, DecryptionKey, EncryptionError, EncryptionKey, IssuerUrl,
+        JsonWebKey, PrivateSigningKey, StandardClaims, SubjectIdentifier,
+    };
+
+    fn decode(part: &str) -> Vec<u8> {
+        base64::decode_config(part, base64::URL_SAFE_NO_PAD).unwrap()
+    }
+
+    #[test]
+    fn test_id_token_rsa_oaep_encryption() {
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let id_token = CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                IssuerUrl::new("https://example.com".to_string()).unwrap(),
+                vec![Audience::new("my_client".to_string())],
+                Utc.timestamp_opt(1544932149, 0).single().unwrap(),
+                Utc.timestamp_opt(1544928549, 0).single().unwrap(),
+                StandardClaims::new(SubjectIdentifier::new("the_subject".to_string())),
+                Default::default(),
+            ),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut encryption_key =
+            serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY).expect("invalid JWK");
+        match id_token.encrypt(
+            &encryption_key,
+            &CoreJweKeyManagementAlgorithm::RsaOaep,
+            &CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256,
+        ) {
+            Err(crate::JsonWebTokenError::EncryptionError(EncryptionError::InvalidKey(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        encryption_key.use_ = Some(CoreJsonWebKeyUse::Encryption);
+        let jwe = id_token
+            .encrypt(
+                &encryption_key,
+                &CoreJweKeyManagementAlgorithm::RsaOaep,
+                &CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256,
+            )
+            .unwrap();
+
+        let parts = jwe.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 5);
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&decode(parts[0])).unwrap(),
+            serde_json::json!({
+                "alg": "RSA-OAEP",
+                "enc": "A128CBC-HS256",
+                "cty": "JWT",
+                "kid": "bilbo.baggins@hobbiton.example",
+            })
+        );
+
+        let private_key = rsa::RsaPrivateKey::from_pkcs1_pem(TEST_RSA_PRIV_KEY).unwrap();
+        let cek = private_key
+            .decrypt(rsa::Oaep::new::<sha1::Sha1>(), &decode(parts[1]))
+            .unwrap();
+        assert_eq!(cek.len(), 32);
+        let (mac_key, enc_key) = cek.split_at(16);
+        let (iv, ciphertext, tag) = (decode(parts[2]), decode(parts[3]), decode(parts[4]));
+
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(mac_key).unwrap();
+        mac.update(parts[0].as_bytes());
+        mac.update(&iv);
+        mac.update(&ciphertext);
+        mac.update(&((parts[0].len() as u64) * 8).to_be_bytes());
+        assert_eq!(&mac.finalize().into_bytes()[..16], &tag[..]);
+
+        let plaintext = cbc::Decryptor::<aes::Aes128>::new_from_slices(enc_key, &iv)
+            .unwrap()
+            .decrypt_padded_vec_mut::<Pkcs7>(&ciphertext)
+            .unwrap();
+        assert_eq!(String::from_utf8(plaintext).unwrap(), id_token.to_string());
+    }
+
+    #[test]
+    fn test_direct_encryption() {
+        let key = CoreJsonWebKey::new_symmetric(vec![7; 32]);
+        let jwe = key
+            .encrypt(
+                &CoreJweKeyManagementAlgorithm::Direct,
+                &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+                None,
+                b"hello world",
+            )
+            .unwrap();
+        let parts = jwe.split('.').collect::<Vec<_>>();
+        assert_eq!(parts[1], "");
+
+        let mut ciphertext = decode(parts[3]);
+        ciphertext.extend(decode(parts[4]));
+        let plaintext = aes_gcm::Aes256Gcm::new_from_slice(&[7; 32])
+            .unwrap()
+            .decrypt(
+                &aes_gcm::Nonce::from(<[u8; 12]>::try_from(decode(parts[2])).unwrap()),
+                Payload {
+                    msg: &ciphertext,
+                    aad: parts[0].as_bytes(),
+                },
+            )
+            .unwrap();
+        assert_eq!(plaintext, b"hello world");
+
+        assert_eq!(
+            key.encrypt(
+                &CoreJweKeyManagementAlgorithm::Direct,
+                &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                None,
+                b"hello world",
+            ),
+            Err(EncryptionError::InvalidKey(
+                "expected 16-byte key (found 32 bytes)".to_string()
+            ))
+        );
+        assert_eq!(
+            key.encrypt(
+                &CoreJweKeyManagementAlgorithm::AesKeyWrap128,
+                &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                None,
+                b"hello world",
+            ),
+            Err(EncryptionError::UnsupportedAlg("A128KW".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_jwe_round_trip() {
+        // See https://tools.ietf.org/html/rfc3394#section-4.1.
+        let kek = (0..16).collect::<Vec<u8>>();
+        let cek = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD,
+            0xEE, 0xFF,
+        ];
+        let wrapped = aes_key_wrap(&kek, &cek).unwrap();
+        assert_eq!(
+            wrapped,
+            [
+                0x1F, 0xA6, 0x8B, 0x0A, 0x81, 0x12, 0xB4, 0x47, 0xAE, 0xF3, 0x4B, 0xD8, 0xFB, 0x5A,
+                0x7B, 0x82, 0x9D, 0x3E, 0x86, 0x23, 0x71, 0xD2, 0xCF, 0xE5,
+            ]
+        );
+        assert_eq!(aes_key_unwrap(&kek, &wrapped).unwrap(), cek);
+        assert_eq!(
+            aes_key_unwrap(&[0; 16], &wrapped),
+            Err(DecryptionError::CryptoError)
+        );
+
+        // See https://tools.ietf.org/html/rfc7518#appendix-C.
+        let shared_secret = [
+            158, 86, 217, 29, 129, 113, 53, 211, 114, 131, 66, 131, 191, 132, 38, 156, 251, 49,
+            110, 163, 218, 128, 106, 72, 246, 218, 167, 121, 140, 254, 144, 196,
+        ];
+        let (derived_key, key_wrap) = ecdh_es_derive_key(
+            &CoreJweKeyManagementAlgorithm::EcdhEs,
+            &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+            &shared_secret,
+            b"Alice",
+            b"Bob",
+        );
+        assert!(!key_wrap);
+        assert_eq!(
+            base64::encode_config(derived_key, base64::URL_SAFE_NO_PAD),
+            "VqqN6vgjbSBcIijNcacQGg"
+        );
+
+        // RSA-OAEP
+        let mut rsa_public_key =
+            serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY).expect("invalid JWK");
+        rsa_public_key.use_ = Some(CoreJsonWebKeyUse::Encryption);
+        let rsa_private_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        for alg in [
+            CoreJweKeyManagementAlgorithm::RsaOaep,
+            CoreJweKeyManagementAlgorithm::RsaOaepSha256,
+        ] {
+            let jwe = rsa_public_key
+                .encrypt(
+                    &alg,
+                    &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+                    None,
+                    b"hello world",
+                )
+                .unwrap();
+            assert_eq!(rsa_private_key.decrypt(&jwe).unwrap(), b"hello world");
+        }
+
+        // ECDH-ES
+        for crv in [
+            CoreJsonCurveType::P256,
+            CoreJsonCurveType::P384,
+            CoreJsonCurveType::P521,
+        ] {
+            let ec_private_key = CoreEcPrivateSigningKey::generate(crv, None);
+            let ec_public_key = CoreJsonWebKey {
+                use_: Some(CoreJsonWebKeyUse::Encryption),
+                ..ec_private_key.as_verification_key()
+            };
+            for (alg, enc) in [
+                (
+                    CoreJweKeyManagementAlgorithm::EcdhEs,
+                    CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256,
+                ),
+                (
+                    CoreJweKeyManagementAlgorithm::EcdhEs,
+                    CoreJweContentEncryptionAlgorithm::Aes256CbcHmacSha512,
+                ),
+                (
+                    CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap128,
+                    CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                ),
+                (
+                    CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap192,
+                    CoreJweContentEncryptionAlgorithm::Aes192Gcm,
+                ),
+                (
+                    CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap256,
+                    CoreJweContentEncryptionAlgorithm::Aes192CbcHmacSha384,
+                ),
+            ] {
+                let jwe = ec_public_key
+                    .encrypt(&alg, &enc, Some("JWT"), b"hello world")
+                    .unwrap();
+                let header = serde_json::from_slice::<serde_json::Value>(&decode(
+                    jwe.split('.').next().unwrap(),
+                ))
+                .unwrap();
+                assert_eq!(header["epk"]["kty"], "EC");
+                assert_eq!(ec_private_key.decrypt(&jwe).unwrap(), b"hello world");
+            }
+        }
+
+        // Direct encryption
+        let symmetric_key = CoreJsonWebKey::new_symmetric(vec![7; 32]);
+        let jwe = symmetric_key
+            .encrypt(
+                &CoreJweKeyManagementAlgorithm::Direct,
+                &CoreJweContentEncryptionAlgorithm::Aes128CbcHmacSha256,
+                None,
+                b"hello world",
+            )
+            .unwrap();
+        assert_eq!(symmetric_key.decrypt(&jwe).unwrap(), b"hello world");
+
+        // Tampered ciphertexts, wrong keys, and mismatched algorithms are rejected.
+        let mut parts = jwe.split('.').map(str::to_string).collect::<Vec<_>>();
+        let mut ciphertext = decode(&parts[3]);
+        ciphertext[0] ^= 1;
+        parts[3] = base64::encode_config(ciphertext, base64::URL_SAFE_NO_PAD);
+        assert_eq!(
+            symmetric_key.decrypt(&parts.join(".")),
+            Err(DecryptionError::CryptoError)
+        );
+        assert_eq!(
+            CoreJsonWebKey::new_symmetric(vec![8; 32]).decrypt(&jwe),
+            Err(DecryptionError::CryptoError)
+        );
+        assert_eq!(
+            rsa_private_key.decrypt(&jwe),
+            Err(DecryptionError::UnsupportedAlg("dir".to_string()))
+        );
+        match symmetric_key.decrypt("not.a.jwe") {
+            Err(DecryptionError::Malformed(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_jwe_compression() {
+        let key = CoreJsonWebKey::new_symmetric(vec![7; 16]);
+        let plaintext = vec![b'a'; 10_000];
+        let jwe = key
+            .encrypt_compressed(
+                &CoreJweKeyManagementAlgorithm::Direct,
+                &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                None,
+                &plaintext,
+            )
+            .unwrap();
+        let parts = jwe.split('.').collect::<Vec<_>>();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&decode(parts[0])).unwrap()["zip"],
+            "DEF"
+        );
+        assert!(decode(parts[3]).len() < 1000);
+
+        assert_eq!(key.decrypt(&jwe).unwrap(), plaintext);
+        assert_eq!(
+            key.decrypt_with_limit(&jwe, 1000),
+            Err(DecryptionError::TooLarge(1000))
+        );
+    }
+}
@@ -17,28 +17,38 @@ use oauth2::{
 use serde::{Deserialize, Serialize};
 
 use crate::registration::{
-    ClientMetadata, ClientRegistrationRequest, ClientRegistrationResponse,
-    EmptyAdditionalClientMetadata, EmptyAdditionalClientRegistrationResponse,
-    RegisterErrorResponseType,
+    ClientConfigurationRequest, ClientMetadata, ClientRegistrationRequest,
+    ClientRegistrationResponse, EmptyAdditionalClientMetadata,
+    EmptyAdditionalClientRegistrationResponse, RegisterErrorResponseType,
 };
 use crate::{
-    ApplicationType, AuthDisplay, AuthPrompt, ClaimName, ClaimType, Client, ClientAuthMethod,
-    EmptyAdditionalClaims, EmptyAdditionalProviderMetadata, GenderClaim, GrantType, IdToken,
-    IdTokenClaims, IdTokenFields, IdTokenVerifier, JsonWebKeySet, JweContentEncryptionAlgorithm,
-    JweKeyManagementAlgorithm, JwsSigningAlgorithm, ProviderMetadata, ResponseMode, ResponseType,
-    SubjectIdentifierType, UserInfoClaims, UserInfoJsonWebToken, UserInfoVerifier,
+    ApplicationType, AuthDisplay, AuthPrompt, AuthenticationMethodReference, ClaimName, ClaimType,
+    Client, ClientAuthMethod, DiscoveryCache, EmptyAdditionalClaims,
+    EmptyAdditionalProviderMetadata, ExtendedTokenFields, GenderClaim, GrantType, IdToken,
+    IdTokenClaims, IdTokenFields, IdTokenVerifier, JsonWebKeySet, JsonWebSignature, JsonWebToken,
+    JsonWebTokenJsonPayloadSerde, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm,
+    JwsSigningAlgorithm, JwtAccessToken, JwtAccessTokenClaims, JwtAccessTokenVerifier, LogoutToken,
+    ParsedAuthorizationResponse, ProviderMetadata, ProviderMetadataBuilder,
+    ProviderMetadataSnapshot, ResponseMode, ResponseType, SubjectIdentifierType, UserInfoClaims,
+    UserInfoJsonWebToken, UserInfoVerifier,
 };
 
 use super::AuthenticationFlow;
 
 pub use self::jwk::{
-    CoreHmacKey, CoreJsonWebKey, CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreRsaPrivateSigningKey,
+    CoreEcPrivateSigningKey, CoreHmacKey, CoreJsonCurveType, CoreJsonWebKey, CoreJsonWebKeyType,
+    CoreJsonWebKeyUse, CoreRsaPrivateSigningKey,
 };
+pub use self::registry::{ClientRegistry, ClientRegistryError};
+pub use self::scope::{CoreScope, ScopeSet};
 
 mod crypto;
 
+mod jwe;
 // Private purely for organizational reasons; exported publicly above.
 mod jwk;
+mod registry;
+mod scope;
 
 ///
 /// Standard implementation of DeviceAuthorizationResponse which throws away extra received response fields.
@@ -116,6 +126,27 @@ pub type CoreClientRegistrationRequest = ClientRegistrationRequest<
     CoreSubjectIdentifierType,
 >;
 
+///
+/// OpenID Connect Core client configuration request
+/// ([RFC 7592](https://tools.ietf.org/html/rfc7592)).
+///
+pub type CoreClientConfigurationRequest = ClientConfigurationRequest<
+    EmptyAdditionalClientMetadata,
+    EmptyAdditionalClientRegistrationResponse,
+    CoreApplicationType,
+    CoreClientAuthMethod,
+    CoreRegisterErrorResponseType,
+    CoreGrantType,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJweKeyManagementAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreResponseType,
+    CoreSubjectIdentifierType,
+>;
+
 ///
 /// OpenID Connect Core client registration response.
 ///
@@ -146,6 +177,62 @@ pub type CoreIdToken = IdToken<
     CoreJsonWebKeyType,
 >;
 
+///
+/// JSON Web Token with a JSON payload of type `P`, using the OpenID Connect Core algorithms and key
+/// types.
+///
+pub type CoreJsonWebToken<P> = JsonWebToken<
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    P,
+    JsonWebTokenJsonPayloadSerde,
+>;
+
+///
+/// JSON Web Signature with a JSON payload of type `P` using the JWS JSON Serialization, using the
+/// OpenID Connect Core algorithms and key types.
+///
+pub type CoreJsonWebSignature<P> = JsonWebSignature<
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    P,
+    JsonWebTokenJsonPayloadSerde,
+>;
+
+///
+/// OpenID Connect Back-Channel Logout token.
+///
+pub type CoreLogoutToken =
+    LogoutToken<CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm, CoreJsonWebKeyType>;
+
+///
+/// RFC 9068 JWT access token.
+///
+pub type CoreJwtAccessToken = JwtAccessToken<
+    EmptyAdditionalClaims,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// RFC 9068 JWT access token claims.
+///
+pub type CoreJwtAccessTokenClaims = JwtAccessTokenClaims<EmptyAdditionalClaims>;
+
+///
+/// RFC 9068 JWT access token verifier.
+///
+pub type CoreJwtAccessTokenVerifier<'a> = JwtAccessTokenVerifier<
+    'a,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+>;
+
 ///
 /// OpenID Connect Core ID token claims.
 ///
@@ -179,12 +266,47 @@ pub type CoreIdTokenVerifier<'a> = IdTokenVerifier<
 ///
 pub type CoreTokenResponse = StandardTokenResponse<CoreIdTokenFields, CoreTokenType>;
 
+///
+/// OpenID Connect Core ID token fields with widely-used provider-specific extensions.
+///
+pub type CoreExtendedIdTokenFields = IdTokenFields<
+    EmptyAdditionalClaims,
+    ExtendedTokenFields,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+///
+/// OpenID Connect Core token response with widely-used provider-specific extensions (see
+/// [`ExtendedTokenFields`]).
+///
+pub type CoreExtendedTokenResponse =
+    StandardTokenResponse<CoreExtendedIdTokenFields, CoreTokenType>;
+
+///
+/// OpenID Connect Core authentication error response returned to the client's redirect URI.
+///
+pub type CoreAuthErrorResponse = StandardErrorResponse<CoreAuthErrorResponseType>;
+
 ///
 /// OpenID Connect Core JSON Web Key Set.
 ///
 pub type CoreJsonWebKeySet =
     JsonWebKeySet<CoreJwsSigningAlgorithm, CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJsonWebKey>;
 
+///
+/// OpenID Connect Core authorization response.
+///
+pub type CoreParsedAuthorizationResponse = ParsedAuthorizationResponse<
+    EmptyAdditionalClaims,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
 ///
 /// OpenID Connect Core provider metadata.
 ///
@@ -206,6 +328,53 @@ pub type CoreProviderMetadata = ProviderMetadata<
     CoreSubjectIdentifierType,
 >;
 
+///
+/// OpenID Connect Core provider metadata builder.
+///
+pub type CoreProviderMetadataBuilder = ProviderMetadataBuilder<
+    EmptyAdditionalProviderMetadata,
+    CoreAuthDisplay,
+    CoreClientAuthMethod,
+    CoreClaimName,
+    CoreClaimType,
+    CoreGrantType,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJweKeyManagementAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreResponseMode,
+    CoreResponseType,
+    CoreSubjectIdentifierType,
+>;
+
+///
+/// Snapshot of OpenID Connect Core provider metadata and its JSON Web Key Set.
+///
+pub type CoreProviderMetadataSnapshot = ProviderMetadataSnapshot<
+    EmptyAdditionalProviderMetadata,
+    CoreAuthDisplay,
+    CoreClientAuthMethod,
+    CoreClaimName,
+    CoreClaimType,
+    CoreGrantType,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJweKeyManagementAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+    CoreJsonWebKeyUse,
+    CoreJsonWebKey,
+    CoreResponseMode,
+    CoreResponseType,
+    CoreSubjectIdentifierType,
+>;
+
+///
+/// Cache of OpenID Connect Core provider metadata.
+///
+pub type CoreDiscoveryCache = DiscoveryCache<CoreProviderMetadata>;
+
 ///
 /// OpenID Connect Core user info claims.
 ///
@@ -427,6 +596,193 @@ impl Display for CoreAuthPrompt {
     }
 }
 
+///
+/// Authentication Method Reference (`amr`) value registered by
+/// [RFC 8176](https://www.rfc-editor.org/rfc/rfc8176#section-2).
+///
+/// Values may be converted to and from [`AuthenticationMethodReference`], which is the type
+/// used by the `amr` ID token claim, allowing policy code to match on specific methods:
+///
+/// ```rust
+/// # use openidconnect::AuthenticationMethodReference;
+/// # use openidconnect::core::CoreAuthenticationMethodReference;
+/// let amr = AuthenticationMethodReference::new("mfa".to_string());
+/// assert_eq!(
+///     CoreAuthenticationMethodReference::from(&amr),
+///     CoreAuthenticationMethodReference::Mfa
+/// );
+/// ```
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CoreAuthenticationMethodReference {
+    ///
+    /// Biometric authentication using facial recognition.
+    ///
+    Face,
+    ///
+    /// Biometric authentication using a fingerprint.
+    ///
+    Fpt,
+    ///
+    /// Use of geolocation information for authentication, such as that provided by
+    /// W3C Geolocation API.
+    ///
+    Geo,
+    ///
+    /// Proof-of-Possession (PoP) of a hardware-secured key.
+    ///
+    Hwk,
+    ///
+    /// Biometric authentication using an iris scan.
+    ///
+    Iris,
+    ///
+    /// Knowledge-based authentication.
+    ///
+    Kba,
+    ///
+    /// Multiple-channel authentication, which involves communication over more than one
+    /// distinct communication channel.
+    ///
+    Mca,
+    ///
+    /// Multiple-factor authentication, in which multiple authentication factors of
+    /// different kinds were used.
+    ///
+    Mfa,
+    ///
+    /// One-time password.
+    ///
+    Otp,
+    ///
+    /// Personal Identification Number (PIN) or pattern (not restricted to containing only
+    /// numbers) that a user enters to unlock a key on the device.
+    ///
+    Pin,
+    ///
+    /// Proof-of-possession (PoP) of a key, where the key is not known to be hardware- or
+    /// software-secured.
+    ///
+    Pop,
+    ///
+    /// Password-based authentication.
+    ///
+    Pwd,
+    ///
+    /// Risk-based authentication.
+    ///
+    Rba,
+    ///
+    /// Biometric authentication using a retina scan.
+    ///
+    Retina,
+    ///
+    /// Smart card.
+    ///
+    Sc,
+    ///
+    /// Confirmation using SMS text message to the user at a registered number.
+    ///
+    Sms,
+    ///
+    /// Proof-of-Possession (PoP) of a software-secured key.
+    ///
+    Swk,
+    ///
+    /// Confirmation by telephone call to the user at a registered number.
+    ///
+    Tel,
+    ///
+    /// User presence test.
+    ///
+    User,
+    ///
+    /// Biometric authentication using a voiceprint.
+    ///
+    Vbm,
+    ///
+    /// Windows integrated authentication.
+    ///
+    Wia,
+    ///
+    /// A value not registered by RFC 8176.
+    ///
+    Extension(String),
+}
+deserialize_from_str!(CoreAuthenticationMethodReference);
+serialize_as_str!(CoreAuthenticationMethodReference);
+impl CoreAuthenticationMethodReference {
+    fn from_str(s: &str) -> Self {
+        match s {
+            "face" => CoreAuthenticationMethodReference::Face,
+            "fpt" => CoreAuthenticationMethodReference::Fpt,
+            "geo" => CoreAuthenticationMethodReference::Geo,
+            "hwk" => CoreAuthenticationMethodReference::Hwk,
+            "iris" => CoreAuthenticationMethodReference::Iris,
+            "kba" => CoreAuthenticationMethodReference::Kba,
+            "mca" => CoreAuthenticationMethodReference::Mca,
+            "mfa" => CoreAuthenticationMethodReference::Mfa,
+            "otp" => CoreAuthenticationMethodReference::Otp,
+            "pin" => CoreAuthenticationMethodReference::Pin,
+            "pop" => CoreAuthenticationMethodReference::Pop,
+            "pwd" => CoreAuthenticationMethodReference::Pwd,
+            "rba" => CoreAuthenticationMethodReference::Rba,
+            "retina" => CoreAuthenticationMethodReference::Retina,
+            "sc" => CoreAuthenticationMethodReference::Sc,
+            "sms" => CoreAuthenticationMethodReference::Sms,
+            "swk" => CoreAuthenticationMethodReference::Swk,
+            "tel" => CoreAuthenticationMethodReference::Tel,
+            "user" => CoreAuthenticationMethodReference::User,
+            "vbm" => CoreAuthenticationMethodReference::Vbm,
+            "wia" => CoreAuthenticationMethodReference::Wia,
+            ext => CoreAuthenticationMethodReference::Extension(ext.to_string()),
+        }
+    }
+}
+impl AsRef<str> for CoreAuthenticationMethodReference {
+    fn as_ref(&self) -> &str {
+        match *self {
+            CoreAuthenticationMethodReference::Face => "face",
+            CoreAuthenticationMethodReference::Fpt => "fpt",
+            CoreAuthenticationMethodReference::Geo => "geo",
+            CoreAuthenticationMethodReference::Hwk => "hwk",
+            CoreAuthenticationMethodReference::Iris => "iris",
+            CoreAuthenticationMethodReference::Kba => "kba",
+            CoreAuthenticationMethodReference::Mca => "mca",
+            CoreAuthenticationMethodReference::Mfa => "mfa",
+            CoreAuthenticationMethodReference::Otp => "otp",
+            CoreAuthenticationMethodReference::Pin => "pin",
+            CoreAuthenticationMethodReference::Pop => "pop",
+            CoreAuthenticationMethodReference::Pwd => "pwd",
+            CoreAuthenticationMethodReference::Rba => "rba",
+            CoreAuthenticationMethodReference::Retina => "retina",
+            CoreAuthenticationMethodReference::Sc => "sc",
+            CoreAuthenticationMethodReference::Sms => "sms",
+            CoreAuthenticationMethodReference::Swk => "swk",
+            CoreAuthenticationMethodReference::Tel => "tel",
+            CoreAuthenticationMethodReference::User => "user",
+            CoreAuthenticationMethodReference::Vbm => "vbm",
+            CoreAuthenticationMethodReference::Wia => "wia",
+            CoreAuthenticationMethodReference::Extension(ref ext) => ext.as_str(),
+        }
+    }
+}
+impl Display for CoreAuthenticationMethodReference {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+impl From<&AuthenticationMethodReference> for CoreAuthenticationMethodReference {
+    fn from(amr: &AuthenticationMethodReference) -> Self {
+        Self::from_str(amr)
+    }
+}
+impl From<CoreAuthenticationMethodReference> for AuthenticationMethodReference {
+    fn from(amr: CoreAuthenticationMethodReference) -> Self {
+        AuthenticationMethodReference::new(amr.as_ref().to_string())
+    }
+}
+
 new_type![
     ///
     /// OpenID Connect Core claim name.
@@ -835,7 +1191,7 @@ pub enum CoreJwsSigningAlgorithm {
     #[serde(rename = "ES384")]
     EcdsaP384Sha384,
     ///
-    /// ECDSA using P-521 and SHA-512 (currently unsupported).
+    /// ECDSA using P-521 and SHA-512.
     ///
     #[serde(rename = "ES512")]
     EcdsaP521Sha512,
@@ -855,6 +1211,22 @@ pub enum CoreJwsSigningAlgorithm {
     #[serde(rename = "PS512")]
     RsaSsaPssSha512,
     ///
+    /// Edwards-curve Digital Signature Algorithm (currently unsupported).
+    ///
+    /// This variant allows provider metadata and JOSE headers that advertise `EdDSA` to be
+    /// parsed, but signatures using this algorithm can't be verified: [`CoreJsonWebKey`] rejects
+    /// them with [`SignatureVerificationError::UnsupportedAlg`](
+    /// crate::SignatureVerificationError::UnsupportedAlg), and [`ClientBuilder`](
+    /// crate::ClientBuilder) rejects it as an ID token signing algorithm.
+    ///
+    /// The hash function associated with `EdDSA` depends on the key's curve, which isn't known
+    /// from the algorithm alone. The access token and authorization code hashes for this
+    /// algorithm are computed using SHA-512, which is only correct for the Ed25519 curve. Hashes
+    /// for Ed448 keys (which use SHAKE256) are not supported.
+    ///
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+    ///
     /// No digital signature or MAC performed.
     ///
     /// # Security Warning
@@ -883,6 +1255,7 @@ impl JwsSigningAlgorithm<CoreJsonWebKeyType> for CoreJwsSigningAlgorithm {
             CoreJwsSigningAlgorithm::EcdsaP256Sha256
             | CoreJwsSigningAlgorithm::EcdsaP384Sha384
             | CoreJwsSigningAlgorithm::EcdsaP521Sha512 => Some(CoreJsonWebKeyType::EllipticCurve),
+            CoreJwsSigningAlgorithm::EdDsa => Some(CoreJsonWebKeyType::OctetKeyPair),
             CoreJwsSigningAlgorithm::None => None,
         }
     }
@@ -915,7 +1288,8 @@ impl JwsSigningAlgorithm<CoreJsonWebKeyType> for CoreJwsSigningAlgorithm {
             CoreJwsSigningAlgorithm::HmacSha512
             | CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha512
             | CoreJwsSigningAlgorithm::RsaSsaPssSha512
-            | CoreJwsSigningAlgorithm::EcdsaP521Sha512 => {
+            | CoreJwsSigningAlgorithm::EcdsaP521Sha512
+            | CoreJwsSigningAlgorithm::EdDsa => {
                 let mut hasher = Sha512::new();
                 hasher.update(bytes);
                 hasher.finalize().to_vec()
@@ -1034,6 +1408,24 @@ pub enum CoreAuthErrorResponseType {
 deserialize_from_str!(CoreAuthErrorResponseType);
 serialize_as_str!(CoreAuthErrorResponseType);
 impl CoreAuthErrorResponseType {
+    ///
+    /// Returns `true` if this error indicates that the request could not be completed without
+    /// End-User interaction (i.e., `interaction_required`, `login_required`,
+    /// `account_selection_required`, or `consent_required`).
+    ///
+    /// These errors are typically returned in response to requests with a `prompt` value of
+    /// `none`, in which case the client may retry the request without `prompt=none`.
+    ///
+    pub fn requires_interaction(&self) -> bool {
+        matches!(
+            *self,
+            CoreAuthErrorResponseType::AccountSelectionRequired
+                | CoreAuthErrorResponseType::ConsentRequired
+                | CoreAuthErrorResponseType::InteractionRequired
+                | CoreAuthErrorResponseType::LoginRequired
+        )
+    }
+
     fn from_str(s: &str) -> Self {
         match s {
             "access_denied" => CoreAuthErrorResponseType::AccessDenied,
@@ -1064,7 +1456,7 @@ impl AsRef<str> for CoreAuthErrorResponseType {
             CoreAuthErrorResponseType::ConsentRequired => "consent_required",
             CoreAuthErrorResponseType::InteractionRequired => "interaction_required",
             CoreAuthErrorResponseType::InvalidRequest => "invalid_request",
-            CoreAuthErrorResponseType::InvalidRequestObject => "invalid_request_obbject",
+            CoreAuthErrorResponseType::InvalidRequestObject => "invalid_request_object",
             CoreAuthErrorResponseType::InvalidRequestUri => "invalid_request_uri",
             CoreAuthErrorResponseType::InvalidScope => "invalid_scope",
             CoreAuthErrorResponseType::LoginRequired => "login_required",
@@ -1079,6 +1471,12 @@ impl AsRef<str> for CoreAuthErrorResponseType {
         }
     }
 }
+impl ErrorResponseType for CoreAuthErrorResponseType {}
+impl Display for CoreAuthErrorResponseType {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
 
 ///
 /// OpenID Connect Core registration error response type.
@@ -1175,6 +1573,26 @@ pub enum CoreResponseMode {
     ///
     FormPost,
     ///
+    /// In this mode, the Authorization Server returns a signed (and optionally encrypted) JWT
+    /// containing the Authorization Response parameters, using the default response mode for the
+    /// requested response type. See
+    /// [JWT Secured Authorization Response Mode for OAuth 2.0 (JARM)](
+    ///     https://openid.net/specs/oauth-v2-jarm.html#name-response-mode-jwt).
+    ///
+    Jwt,
+    ///
+    /// JARM mode encoding the response JWT in the query string of the `redirect_uri`.
+    ///
+    QueryJwt,
+    ///
+    /// JARM mode encoding the response JWT in the fragment of the `redirect_uri`.
+    ///
+    FragmentJwt,
+    ///
+    /// JARM mode submitting the response JWT to the `redirect_uri` as an HTML form value.
+    ///
+    FormPostJwt,
+    ///
     /// An extension not defined by any of the supported specifications.
     ///
     Extension(String),
@@ -1187,6 +1605,10 @@ impl CoreResponseMode {
             "query" => CoreResponseMode::Query,
             "fragment" => CoreResponseMode::Fragment,
             "form_post" => CoreResponseMode::FormPost,
+            "jwt" => CoreResponseMode::Jwt,
+            "query.jwt" => CoreResponseMode::QueryJwt,
+            "fragment.jwt" => CoreResponseMode::FragmentJwt,
+            "form_post.jwt" => CoreResponseMode::FormPostJwt,
             ext => CoreResponseMode::Extension(ext.to_string()),
         }
     }
@@ -1197,6 +1619,10 @@ impl AsRef<str> for CoreResponseMode {
             CoreResponseMode::Query => "query",
             CoreResponseMode::Fragment => "fragment",
             CoreResponseMode::FormPost => "form_post",
+            CoreResponseMode::Jwt => "jwt",
+            CoreResponseMode::QueryJwt => "query.jwt",
+            CoreResponseMode::FragmentJwt => "fragment.jwt",
+            CoreResponseMode::FormPostJwt => "form_post.jwt",
             CoreResponseMode::Extension(ref ext) => ext.as_str(),
         }
     }
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::core::{CoreClient, CoreDiscoveryCache, CoreProviderMetadata};
+use crate::{
+    ClientConfig, ClientConfigError, DiscoveryError, HttpRequest, HttpResponse, IssuerUrl,
+};
+
+///
+/// Registry of [`CoreClient`]s keyed by issuer, for multi-tenant applications in which each
+/// tenant brings its own OpenID Connect Provider.
+///
+/// Each issuer is registered with its own [`ClientConfig`] (including client credentials).
+/// Provider metadata and JSON Web Key Sets are discovered lazily on first use and cached for the
+/// registry's time-to-live, after which they are re-discovered. The registry is safe to share
+/// across threads (e.g., behind an [`Arc`]).
+///
+/// Concurrent requests for an issuer whose metadata is missing or expired share a single
+/// discovery (see [`DiscoveryCache`](crate::DiscoveryCache)).
+///
+pub struct ClientRegistry {
+    configs: RwLock<HashMap<IssuerUrl, Arc<ClientConfig>>>,
+    provider_metadata: CoreDiscoveryCache,
+}
+impl ClientRegistry {
+    ///
+    /// Creates an empty registry whose discovered provider metadata expires after `ttl`.
+    ///
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            configs: RwLock::new(HashMap::new()),
+            provider_metadata: CoreDiscoveryCache::new(ttl),
+        }
+    }
+
+    ///
+    /// Registers (or replaces) the client configuration for the configuration's issuer.
+    ///
+    /// Replacing a configuration does not evict the issuer's cached provider metadata.
+    ///
+    pub fn register(&self, config: ClientConfig) {
+        self.configs
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(config.issuer_url().clone(), Arc::new(config));
+    }
+
+    ///
+    /// Removes the client configuration and cached provider metadata for `issuer_url`.
+    ///
+    pub fn unregister(&self, issuer_url: &IssuerUrl) {
+        self.configs
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(issuer_url);
+        self.provider_metadata.invalidate(issuer_url);
+    }
+
+    ///
+    /// Evicts the cached provider metadata (including the JSON Web Key Set) for `issuer_url`,
+    /// forcing it to be re-discovered on next use.
+    ///
+    /// This should be called when an ID token fails verification because it was signed by an
+    /// unknown key, which usually indicates that the provider has rotated its signing keys.
+    ///
+    pub fn evict(&self, issuer_url: &IssuerUrl) {
+        self.provider_metadata.invalidate(issuer_url);
+    }
+
+    ///
+    /// Returns whether a client configuration is registered for `issuer_url`.
+    ///
+    pub fn contains(&self, issuer_url: &IssuerUrl) -> bool {
+        self.configs
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains_key(issuer_url)
+    }
+
+    ///
+    /// Returns a client for `issuer_url`, performing OpenID Connect Discovery using the specified
+    /// synchronous HTTP client if the provider metadata is missing or expired.
+    ///
+    pub fn client<HC, RE>(
+        &self,
+        issuer_url: &IssuerUrl,
+        http_client: HC,
+    ) -> Result<CoreClient, ClientRegistryError<RE>>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let config = self.config(issuer_url)?;
+        let provider_metadata = self
+            .provider_metadata
+            .get_or_discover(issuer_url, |issuer_url| {
+                CoreProviderMetadata::discover(issuer_url, http_client)
+            })
+            .map_err(ClientRegistryError::Discovery)?;
+        CoreClient::from_config(&config, (*provider_metadata).clone())
+            .map_err(ClientRegistryError::Config)
+    }
+
+    ///
+    /// Returns a client for `issuer_url`, performing OpenID Connect Discovery using the specified
+    /// asynchronous HTTP client if the provider metadata is missing or expired.
+    ///
+    pub async fn client_async<F, HC, RE>(
+        &self,
+        issuer_url: &IssuerUrl,
+        http_client: HC,
+    ) -> Result<CoreClient, ClientRegistryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: Fn(HttpRequest) -> F,
+        RE: std::error::Error + 'static,
+    {
+        let config = self.config(issuer_url)?;
+        let provider_metadata = self
+            .provider_metadata
+            .get_or_discover_async(issuer_url, |issuer_url| {
+                CoreProviderMetadata::discover_async(issuer_url, http_client)
+            })
+            .await
+            .map_err(ClientRegistryError::Discovery)?;
+        CoreClient::from_config(&config, (*provider_metadata).clone())
+            .map_err(ClientRegistryError::Config)
+    }
+
+    fn config<RE>(
+        &self,
+        issuer_url: &IssuerUrl,
+    ) -> Result<Arc<ClientConfig>, ClientRegistryError<RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        self.configs
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(issuer_url)
+            .cloned()
+            .ok_or_else(|| ClientRegistryError::UnknownIssuer(issuer_url.to_string()))
+    }
+}
+impl std::fmt::Debug for ClientRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientRegistry")
+            .field(
+                "issuers",
+                &self
+                    .configs
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .keys()
+                    .collect::<Vec<_>>(),
+            )
+            .field("provider_metadata", &self.provider_metadata)
+            .finish()
+    }
+}
+
+///
+/// Error returned by [`ClientRegistry`].
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ClientRegistryError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// Failed to construct a client from the registered configuration.
+    ///
+    #[error("Invalid client configuration")]
+    Config(#[source] ClientConfigError),
+    ///
+    /// Failed to discover the provider metadata.
+    ///
+    #[error("Provider discovery failed")]
+    Discovery(#[source] DiscoveryError<RE>),
+    ///
+    /// No client configuration is registered for the issuer.
+    ///
+    #[error("No client registered for issuer `{0}`")]
+    UnknownIssuer(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use http::header::{HeaderValue, CONTENT_TYPE};
+    use http::status::StatusCode;
+    use oauth2::ClientId;
+
+    use crate::core::{CoreClient, CoreResponseType};
+    use crate::{
+        AuthenticationFlow, ClientConfig, CsrfToken, HttpRequest, HttpResponse, IssuerUrl, Nonce,
+    };
+
+    use super::{ClientRegistry, ClientRegistryError};
+
+    fn client_id(client: &CoreClient) -> String {
+        let (url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::<CoreResponseType>::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            )
+            .url()
+            .unwrap();
+        url.query_pairs()
+            .find(|(key, _)| key == "client_id")
+            .map(|(_, value)| value.to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_client_registry() {
+        let requests = Cell::new(0);
+        let http_client = |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+            requests.set(requests.get() + 1);
+            let issuer = format!(
+                "{}://{}",
+                request.url.scheme(),
+                request.url.host_str().unwrap()
+            );
+            let body = if request.url.path().ends_with("/openid-configuration") {
+                format!(
+                    "{{\
+                        \"issuer\": \"{0}\",\
+                        \"authorization_endpoint\": \"{0}/authorize\",\
+                        \"jwks_uri\": \"{0}/jwks.json\",\
+                        \"response_types_supported\": [\"code\"],\
+                        \"subject_types_supported\": [\"public\"],\
+                        \"id_token_signing_alg_values_supported\": [\"RS256\"]\
+                    }}",
+                    issuer
+                )
+            } else {
+                "{\"keys\": []}".to_string()
+            };
+            Ok(HttpResponse {
+                status_code: StatusCode::OK,
+                headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                    .into_iter()
+                    .collect(),
+                body: body.into_bytes(),
+            })
+        };
+
+        let tenant_a = IssuerUrl::new("https://a.example.com".to_string()).unwrap();
+        let tenant_b = IssuerUrl::new("https://b.example.com".to_string()).unwrap();
+
+        let registry = ClientRegistry::new(Duration::from_secs(3600));
+        registry.register(ClientConfig::new(
+            tenant_a.clone(),
+            ClientId::new("client_a".to_string()),
+        ));
+        registry.register(ClientConfig::new(
+            tenant_b.clone(),
+            ClientId::new("client_b".to_string()),
+        ));
+        assert!(registry.contains(&tenant_a));
+
+        let client_a = registry.client(&tenant_a, http_client).unwrap();
+        assert_eq!(client_id(&client_a), "client_a");
+        // Discovery document + JWKS.
+        assert_eq!(requests.get(), 2);
+
+        // Cached metadata is reused.
+        registry.client(&tenant_a, http_client).unwrap();
+        assert_eq!(requests.get(), 2);
+
+        let client_b = registry.client(&tenant_b, http_client).unwrap();
+        assert_eq!(client_id(&client_b), "client_b");
+        assert_eq!(requests.get(), 4);
+
+        // Eviction forces re-discovery.
+        registry.evict(&tenant_a);
+        registry.client(&tenant_a, http_client).unwrap();
+        assert_eq!(requests.get(), 6);
+
+        registry.unregister(&tenant_a);
+        assert!(!registry.contains(&tenant_a));
+        match registry.client(&tenant_a, http_client) {
+            Err(ClientRegistryError::UnknownIssuer(issuer)) => {
+                assert_eq!(issuer, "https://a.example.com")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use std::convert::Infallible;
+use std::fmt::{Display, Error as FormatterError, Formatter};
+use std::iter::FromIterator;
+use std::str::FromStr;
+
+use crate::Scope;
+
+///
+/// Standard scope values used to request sets of Claims.
+///
+/// These values are defined in
+/// [Section 5.4](https://openid.net/specs/openid-connect-core-1_0.html#ScopeClaims) and
+/// [Section 11](https://openid.net/specs/openid-connect-core-1_0.html#OfflineAccess) of the
+/// OpenID Connect Core spec.
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum CoreScope {
+    ///
+    /// Requests access to the End-User's default profile Claims.
+    ///
+    Profile,
+    ///
+    /// Requests access to the `email` and `email_verified` Claims.
+    ///
+    Email,
+    ///
+    /// Requests access to the `address` Claim.
+    ///
+    Address,
+    ///
+    /// Requests a Refresh Token that can be used to obtain an Access Token granting access to
+    /// the End-User's UserInfo Endpoint even when the End-User is not present.
+    ///
+    OfflineAccess,
+    ///
+    /// Indicates that the request is an OpenID Connect request. This scope is required by all
+    /// OpenID Connect authentication requests.
+    ///
+    OpenId,
+    ///
+    /// Requests access to the `phone_number` and `phone_number_verified` Claims.
+    ///
+    Phone,
+    ///
+    /// An extension not defined by the OpenID Connect Core spec.
+    ///
+    Extension(String),
+}
+deserialize_from_str!(CoreScope);
+serialize_as_str!(CoreScope);
+impl CoreScope {
+    fn from_str(s: &str) -> Self {
+        match s {
+            "profile" => CoreScope::Profile,
+            "email" => CoreScope::Email,
+            "address" => CoreScope::Address,
+            "offline_access" => CoreScope::OfflineAccess,
+            "openid" => CoreScope::OpenId,
+            "phone" => CoreScope::Phone,
+            ext => CoreScope::Extension(ext.to_string()),
+        }
+    }
+
+    ///
+    /// Returns the names of the Claims that this scope requests access to, as defined in
+    /// [Section 5.4](https://openid.net/specs/openid-connect-core-1_0.html#ScopeClaims) of the
+    /// OpenID Connect Core spec.
+    ///
+    /// Scopes that do not request access to any Claims (e.g., `openid`, `offline_access`, and
+    /// extensions) return an empty slice.
+    ///
+    pub fn claims(&self) -> &'static [&'static str] {
+        match *self {
+            CoreScope::Profile => &[
+                "name",
+                "family_name",
+                "given_name",
+                "middle_name",
+                "nickname",
+                "preferred_username",
+                "profile",
+                "picture",
+                "website",
+                "gender",
+                "birthdate",
+                "zoneinfo",
+                "locale",
+                "updated_at",
+            ],
+            CoreScope::Email => &["email", "email_verified"],
+            CoreScope::Address => &["address"],
+            CoreScope::Phone => &["phone_number", "phone_number_verified"],
+            CoreScope::OfflineAccess | CoreScope::OpenId | CoreScope::Extension(_) => &[],
+        }
+    }
+}
+impl AsRef<str> for CoreScope {
+    fn as_ref(&self) -> &str {
+        match *self {
+            CoreScope::Profile => "profile",
+            CoreScope::Email => "email",
+            CoreScope::Address => "address",
+            CoreScope::OfflineAccess => "offline_access",
+            CoreScope::OpenId => "openid",
+            CoreScope::Phone => "phone",
+            CoreScope::Extension(ref ext) => ext.as_str(),
+        }
+    }
+}
+impl Display for CoreScope {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+impl From<CoreScope> for Scope {
+    fn from(scope: CoreScope) -> Self {
+        Scope::new(scope.as_ref().to_string())
+    }
+}
+
+///
+/// Ordered set of scopes, such as the `scope` parameter of an authorization request or token
+/// response.
+///
+/// A `ScopeSet` can be parsed from a space-delimited string (e.g., `"openid email profile"`)
+/// and is formatted the same way. Duplicate scopes are ignored.
+///
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ScopeSet(Vec<Scope>);
+impl ScopeSet {
+    ///
+    /// Initialize an empty set of scopes.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Adds the given scope to the set and returns the updated set.
+    ///
+    pub fn add_scope<S>(mut self, scope: S) -> Self
+    where
+        S: Into<Scope>,
+    {
+        self.insert(scope);
+        self
+    }
+
+    ///
+    /// Adds the given scope to the set.
+    ///
+    /// Returns `true` if the scope was not already present in the set.
+    ///
+    pub fn insert<S>(&mut self, scope: S) -> bool
+    where
+        S: Into<Scope>,
+    {
+        let scope = scope.into();
+        if self.contains(scope.as_str()) {
+            false
+        } else {
+            self.0.push(scope);
+            true
+        }
+    }
+
+    ///
+    /// Returns `true` if the set contains the given scope.
+    ///
+    pub fn contains<S>(&self, scope: S) -> bool
+    where
+        S: AsRef<str>,
+    {
+        self.0.iter().any(|s| s.as_str() == scope.as_ref())
+    }
+
+    ///
+    /// Returns `true` if the set contains the `openid` scope, which is required by all OpenID
+    /// Connect authentication requests.
+    ///
+    pub fn is_openid(&self) -> bool {
+        self.contains(CoreScope::OpenId)
+    }
+
+    ///
+    /// Returns an iterator over the scopes in the set, in insertion order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = &Scope> {
+        self.0.iter()
+    }
+
+    ///
+    /// Returns the number of scopes in the set.
+    ///
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    ///
+    /// Returns `true` if the set contains no scopes.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    ///
+    /// Returns the names of the standard Claims that the scopes in this set request access to,
+    /// without duplicates (see [`CoreScope::claims`]).
+    ///
+    pub fn claims(&self) -> Vec<&'static str> {
+        let mut claims: Vec<&'static str> = Vec::new();
+        for claim in self
+            .0
+            .iter()
+            .flat_map(|scope| CoreScope::from_str(scope).claims())
+        {
+            if !claims.contains(claim) {
+                claims.push(claim);
+            }
+        }
+        claims
+    }
+}
+impl FromStr for ScopeSet {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.split(' ')
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| Scope::new(scope.to_string()))
+            .collect())
+    }
+}
+impl Display for ScopeSet {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        for (i, scope) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", scope.as_str())?;
+        }
+        Ok(())
+    }
+}
+impl<S> FromIterator<S> for ScopeSet
+where
+    S: Into<Scope>,
+{
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut scopes = ScopeSet::new();
+        scopes.extend(iter);
+        scopes
+    }
+}
+impl<S> Extend<S> for ScopeSet
+where
+    S: Into<Scope>,
+{
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for scope in iter {
+            self.insert(scope);
+        }
+    }
+}
+impl IntoIterator for ScopeSet {
+    type Item = Scope;
+    type IntoIter = std::vec::IntoIter<Scope>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Scope;
+
+    use super::{CoreScope, ScopeSet};
+
+    #[test]
+    fn test_scope_set() {
+        let scopes = "openid  email profile email custom"
+            .parse::<ScopeSet>()
+            .unwrap();
+        assert_eq!(scopes.len(), 4);
+        assert_eq!(scopes.to_string(), "openid email profile custom");
+        assert!(scopes.is_openid());
+        assert!(scopes.contains(CoreScope::Email));
+        assert!(scopes.contains("custom"));
+        assert!(!scopes.contains(CoreScope::Phone));
+
+        let claims = scopes.claims();
+        assert_eq!(&claims[..2], &["email", "email_verified"]);
+        assert!(claims.contains(&"preferred_username"));
+        assert!(!claims.contains(&"phone_number"));
+
+        let scopes = ScopeSet::new()
+            .add_scope(CoreScope::OpenId)
+            .add_scope(CoreScope::OfflineAccess)
+            .add_scope(Scope::new("openid".to_string()));
+        assert_eq!(
+            scopes.into_iter().collect::<Vec<_>>(),
+            vec![
+                Scope::new("openid".to_string()),
+                Scope::new("offline_access".to_string())
+            ]
+        );
+        assert!("".parse::<ScopeSet>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_core_scope_serde() {
+        assert_eq!(
+            serde_json::from_str::<CoreScope>("\"offline_access\"").unwrap(),
+            CoreScope::OfflineAccess
+        );
+        assert_eq!(
+            serde_json::from_str::<CoreScope>("\"custom\"").unwrap(),
+            CoreScope::Extension("custom".to_string())
+        );
+        assert_eq!(
+            serde_json::to_string(&CoreScope::OpenId).unwrap(),
+            "\"openid\""
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use crate::core::CoreJwsSigningAlgorithm;
+use crate::AuthenticationMethodReference;
 
-use super::CoreGrantType;
+use super::{
+    CoreAuthErrorResponse, CoreAuthErrorResponseType, CoreAuthenticationMethodReference,
+    CoreGrantType,
+};
 
 #[test]
 fn test_grant_type_serialize() {
@@ -12,6 +16,53 @@ fn test_grant_type_serialize() {
     );
 }
 
+#[test]
+fn test_auth_error_response() {
+    let error_response = serde_json::from_str::<CoreAuthErrorResponse>(
+        "{\"error\":\"login_required\",\"error_description\":\"Login required\"}",
+    )
+    .unwrap();
+    assert_eq!(
+        *error_response.error(),
+        CoreAuthErrorResponseType::LoginRequired
+    );
+    assert!(error_response.error().requires_interaction());
+    assert!(CoreAuthErrorResponseType::ConsentRequired.requires_interaction());
+    assert!(!CoreAuthErrorResponseType::AccessDenied.requires_interaction());
+    assert!(
+        !CoreAuthErrorResponseType::Extension("interaction_needed".to_string())
+            .requires_interaction()
+    );
+}
+
+#[test]
+fn test_authentication_method_reference() {
+    assert_eq!(
+        CoreAuthenticationMethodReference::from(&AuthenticationMethodReference::new(
+            "hwk".to_string()
+        )),
+        CoreAuthenticationMethodReference::Hwk
+    );
+    assert_eq!(
+        CoreAuthenticationMethodReference::from(&AuthenticationMethodReference::new(
+            "password".to_string()
+        )),
+        CoreAuthenticationMethodReference::Extension("password".to_string())
+    );
+    assert_eq!(
+        AuthenticationMethodReference::from(CoreAuthenticationMethodReference::Mfa),
+        AuthenticationMethodReference::new("mfa".to_string())
+    );
+    assert_eq!(
+        serde_json::from_str::<Vec<CoreAuthenticationMethodReference>>("[\"pwd\",\"otp\"]")
+            .unwrap(),
+        vec![
+            CoreAuthenticationMethodReference::Pwd,
+            CoreAuthenticationMethodReference::Otp
+        ]
+    );
+}
+
 #[test]
 fn test_signature_alg_serde_plain() {
     assert_eq!(
@@ -1,30 +1,60 @@
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
-use http::header::{HeaderValue, ACCEPT};
+use chrono::{DateTime, Utc};
+use http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
 use http::method::Method;
 use http::status::StatusCode;
-use oauth2::{AuthUrl, Scope, TokenUrl};
+use oauth2::{
+    AuthUrl, DeviceAuthorizationUrl, IntrospectionUrl, PkceCodeChallengeMethod, RevocationUrl,
+    Scope, TokenUrl,
+};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_with::{serde_as, skip_serializing_none, VecSkipError};
 use thiserror::Error;
 
-use super::http_utils::{check_content_type, MIME_TYPE_JSON};
+use super::http_utils::{body_excerpt, check_content_type, MIME_TYPE_JSON};
 use super::types::{
-    AuthDisplay, AuthenticationContextClass, ClaimName, ClaimType, ClientAuthMethod, GrantType,
-    IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse,
-    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag,
-    OpPolicyUrl, OpTosUrl, RegistrationUrl, ResponseMode, ResponseType, ResponseTypes,
+    AuthDisplay, AuthenticationContextClass, ClaimName, ClaimType, ClientAuthMethod, EndSessionUrl,
+    GrantType, IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType,
+    JsonWebKeyUse, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    LanguageTag, OpPolicyUrl, OpTosUrl, RegistrationUrl, ResponseMode, ResponseType, ResponseTypes,
     ServiceDocUrl, SubjectIdentifierType,
 };
 use super::{HttpRequest, HttpResponse, UserInfoUrl, CONFIG_URL_SUFFIX};
+use crate::helpers::block_on;
+use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
+use crate::{
+    Audience, AudiencesClaim, ClaimsVerificationError, IssuerClaim, JsonWebTokenError,
+    PrivateSigningKey, SignedMetadataVerifier,
+};
 
 ///
 /// Trait for adding extra fields to [`ProviderMetadata`].
 ///
-pub trait AdditionalProviderMetadata: Clone + Debug + DeserializeOwned + Serialize {}
+/// Fields that [`ProviderMetadata`] deserializes itself (including the RFC 8414
+/// `revocation_endpoint`, `introspection_endpoint`,
+/// `introspection_endpoint_auth_methods_supported`, `code_challenge_methods_supported`, and
+/// `device_authorization_endpoint` fields) are never passed to the additional metadata, so
+/// implementations must not declare them.
+///
+pub trait AdditionalProviderMetadata: Clone + Debug + DeserializeOwned + Serialize {
+    ///
+    /// Returns the end session endpoint, if this metadata includes one (see
+    /// [`LogoutProviderMetadata`](crate::LogoutProviderMetadata)).
+    ///
+    /// This is used by [`Client::from_provider_metadata`](crate::Client::from_provider_metadata)
+    /// to configure the client's end session endpoint.
+    ///
+    fn end_session_endpoint(&self) -> Option<&EndSessionUrl> {
+        None
+    }
+}
 
 // In order to support serde flatten, this must be an empty struct rather than an empty
 // tuple struct.
@@ -39,6 +69,12 @@ impl AdditionalProviderMetadata for EmptyAdditionalProviderMetadata {}
 /// Provider metadata returned by [OpenID Connect Discovery](
 /// https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata).
 ///
+/// Since version 4.0.0, the [RFC 8414](https://tools.ietf.org/html/rfc8414#section-2) and
+/// [RFC 8628](https://tools.ietf.org/html/rfc8628#section-4) endpoint and capability fields are
+/// available through typed getters (e.g., [`revocation_endpoint`](Self::revocation_endpoint)).
+/// These fields are no longer flattened into the [`AdditionalProviderMetadata`], so custom
+/// additional metadata types that declared them must drop them and use the getters instead.
+///
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -153,6 +189,13 @@ where
     require_request_uri_registration: Option<bool>,
     op_policy_uri: Option<OpPolicyUrl>,
     op_tos_uri: Option<OpTosUrl>,
+    revocation_endpoint: Option<RevocationUrl>,
+    introspection_endpoint: Option<IntrospectionUrl>,
+    #[serde(bound(deserialize = "CA: ClientAuthMethod"))]
+    introspection_endpoint_auth_methods_supported: Option<Vec<CA>>,
+    code_challenge_methods_supported: Option<Vec<PkceCodeChallengeMethod>>,
+    device_authorization_endpoint: Option<DeviceAuthorizationUrl>,
+    signed_metadata: Option<String>,
 
     #[serde(bound(deserialize = "A: AdditionalProviderMetadata"), flatten)]
     additional_metadata: A,
@@ -179,6 +222,20 @@ where
     RT: ResponseType,
     S: SubjectIdentifierType,
 {
+    ///
+    /// Returns a builder for new provider metadata with default additional metadata.
+    ///
+    /// See [`ProviderMetadataBuilder`] for details.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn builder(
+    ) -> ProviderMetadataBuilder<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+    where
+        A: Default,
+    {
+        ProviderMetadataBuilder::new(A::default())
+    }
+
     ///
     /// Instantiates new provider metadata.
     ///
@@ -228,6 +285,12 @@ where
             require_request_uri_registration: None,
             op_policy_uri: None,
             op_tos_uri: None,
+            revocation_endpoint: None,
+            introspection_endpoint: None,
+            introspection_endpoint_auth_methods_supported: None,
+            code_challenge_methods_supported: None,
+            device_authorization_endpoint: None,
+            signed_metadata: None,
             additional_metadata,
             _phantom_jt: PhantomData,
         }
@@ -283,6 +346,15 @@ where
             set_require_request_uri_registration -> require_request_uri_registration[Option<bool>],
             set_op_policy_uri -> op_policy_uri[Option<OpPolicyUrl>],
             set_op_tos_uri -> op_tos_uri[Option<OpTosUrl>],
+            set_revocation_endpoint -> revocation_endpoint[Option<RevocationUrl>],
+            set_introspection_endpoint -> introspection_endpoint[Option<IntrospectionUrl>],
+            set_introspection_endpoint_auth_methods_supported
+                -> introspection_endpoint_auth_methods_supported[Option<Vec<CA>>],
+            set_code_challenge_methods_supported
+                -> code_challenge_methods_supported[Option<Vec<PkceCodeChallengeMethod>>],
+            set_device_authorization_endpoint
+                -> device_authorization_endpoint[Option<DeviceAuthorizationUrl>],
+            set_signed_metadata -> signed_metadata[Option<String>],
         }
     ];
 
@@ -294,6 +366,61 @@ where
         issuer_url: &IssuerUrl,
         http_client: HC,
     ) -> Result<Self, DiscoveryError<RE>>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        Self::discover_with_issuer_policy(issuer_url, &IssuerMismatchPolicy::Reject, http_client)
+    }
+
+    ///
+    /// Fetches the OpenID Connect Discovery document and associated JSON Web Key Set from the
+    /// OpenID Connect Provider, validating the document's `issuer` according to the specified
+    /// [`IssuerMismatchPolicy`].
+    ///
+    /// This should only be used to accommodate providers whose documented `issuer` differs from
+    /// the URL used for discovery (e.g., providers behind a proxy or vanity domain). See
+    /// [`IssuerMismatchPolicy`] for the security implications.
+    ///
+    pub fn discover_with_issuer_policy<HC, RE>(
+        issuer_url: &IssuerUrl,
+        issuer_policy: &IssuerMismatchPolicy,
+        http_client: HC,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        Self::discover_impl(issuer_url, issuer_policy, false, http_client)
+            .map(|(provider_metadata, _)| provider_metadata)
+    }
+
+    ///
+    /// Fetches the OpenID Connect Discovery document and associated JSON Web Key Set from the
+    /// OpenID Connect Provider, tolerating invalid or missing non-essential fields.
+    ///
+    /// See [`from_json_lenient`](Self::from_json_lenient) for details. Any fields that were
+    /// ignored are returned as warnings, which should be logged.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn discover_lenient<HC, RE>(
+        issuer_url: &IssuerUrl,
+        http_client: HC,
+    ) -> Result<(Self, Vec<ProviderMetadataWarning>), DiscoveryError<RE>>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        Self::discover_impl(issuer_url, &IssuerMismatchPolicy::Reject, true, http_client)
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn discover_impl<HC, RE>(
+        issuer_url: &IssuerUrl,
+        issuer_policy: &IssuerMismatchPolicy,
+        lenient: bool,
+        http_client: HC,
+    ) -> Result<(Self, Vec<ProviderMetadataWarning>), DiscoveryError<RE>>
     where
         HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
         RE: std::error::Error + 'static,
@@ -304,11 +431,18 @@ where
 
         http_client(Self::discovery_request(discovery_url))
             .map_err(DiscoveryError::Request)
-            .and_then(|http_response| Self::discovery_response(issuer_url, http_response))
-            .and_then(|provider_metadata| {
-                JsonWebKeySet::fetch(provider_metadata.jwks_uri(), http_client).map(|jwks| Self {
-                    jwks,
-                    ..provider_metadata
+            .and_then(|http_response| {
+                Self::discovery_response(issuer_url, issuer_policy, lenient, http_response)
+            })
+            .and_then(|(provider_metadata, warnings)| {
+                JsonWebKeySet::fetch(provider_metadata.jwks_uri(), http_client).map(|jwks| {
+                    (
+                        Self {
+                            jwks,
+                            ..provider_metadata
+                        },
+                        warnings,
+                    )
                 })
             })
     }
@@ -321,6 +455,68 @@ where
         issuer_url: IssuerUrl,
         http_client: HC,
     ) -> Result<Self, DiscoveryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: Fn(HttpRequest) -> F,
+        RE: std::error::Error + 'static,
+    {
+        Self::discover_async_with_issuer_policy(
+            issuer_url,
+            &IssuerMismatchPolicy::Reject,
+            http_client,
+        )
+        .await
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, validating the document's `issuer` according to the
+    /// specified [`IssuerMismatchPolicy`].
+    ///
+    /// See [`discover_with_issuer_policy`](Self::discover_with_issuer_policy) for details.
+    ///
+    pub async fn discover_async_with_issuer_policy<F, HC, RE>(
+        issuer_url: IssuerUrl,
+        issuer_policy: &IssuerMismatchPolicy,
+        http_client: HC,
+    ) -> Result<Self, DiscoveryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: Fn(HttpRequest) -> F,
+        RE: std::error::Error + 'static,
+    {
+        Self::discover_async_impl(issuer_url, issuer_policy, false, http_client)
+            .await
+            .map(|(provider_metadata, _)| provider_metadata)
+    }
+
+    ///
+    /// Asynchronously fetches the OpenID Connect Discovery document and associated JSON Web Key Set
+    /// from the OpenID Connect Provider, tolerating invalid or missing non-essential fields.
+    ///
+    /// See [`discover_lenient`](Self::discover_lenient) for details.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub async fn discover_async_lenient<F, HC, RE>(
+        issuer_url: IssuerUrl,
+        http_client: HC,
+    ) -> Result<(Self, Vec<ProviderMetadataWarning>), DiscoveryError<RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: Fn(HttpRequest) -> F,
+        RE: std::error::Error + 'static,
+    {
+        Self::discover_async_impl(issuer_url, &IssuerMismatchPolicy::Reject, true, http_client)
+            .await
+    }
+
+    #[allow(clippy::type_complexity)]
+    async fn discover_async_impl<F, HC, RE>(
+        issuer_url: IssuerUrl,
+        issuer_policy: &IssuerMismatchPolicy,
+        lenient: bool,
+        http_client: HC,
+    ) -> Result<(Self, Vec<ProviderMetadataWarning>), DiscoveryError<RE>>
     where
         F: Future<Output = Result<HttpResponse, RE>>,
         HC: Fn(HttpRequest) -> F,
@@ -330,16 +526,23 @@ where
             .join(CONFIG_URL_SUFFIX)
             .map_err(DiscoveryError::UrlParse)?;
 
-        let provider_metadata = http_client(Self::discovery_request(discovery_url))
+        let (provider_metadata, warnings) = http_client(Self::discovery_request(discovery_url))
             .await
             .map_err(DiscoveryError::Request)
-            .and_then(|http_response| Self::discovery_response(&issuer_url, http_response))?;
+            .and_then(|http_response| {
+                Self::discovery_response(&issuer_url, issuer_policy, lenient, http_response)
+            })?;
 
         JsonWebKeySet::fetch_async(provider_metadata.jwks_uri(), http_client)
             .await
-            .map(|jwks| Self {
-                jwks,
-                ..provider_metadata
+            .map(|jwks| {
+                (
+                    Self {
+                        jwks,
+                        ..provider_metadata
+                    },
+                    warnings,
+                )
             })
     }
 
@@ -354,10 +557,13 @@ where
         }
     }
 
+    #[allow(clippy::type_complexity)]
     fn discovery_response<RE>(
         issuer_url: &IssuerUrl,
+        issuer_policy: &IssuerMismatchPolicy,
+        lenient: bool,
         discovery_response: HttpResponse,
-    ) -> Result<Self, DiscoveryError<RE>>
+    ) -> Result<(Self, Vec<ProviderMetadataWarning>), DiscoveryError<RE>>
     where
         RE: std::error::Error + 'static,
     {
@@ -377,34 +583,645 @@ where
             )
         })?;
 
-        let provider_metadata = serde_path_to_error::deserialize::<_, Self>(
-            &mut serde_json::Deserializer::from_slice(&discovery_response.body),
-        )
-        .map_err(DiscoveryError::Parse)?;
+        let parsed = if lenient {
+            Self::from_json_lenient(&discovery_response.body)
+        } else {
+            serde_path_to_error::deserialize::<_, Self>(&mut serde_json::Deserializer::from_slice(
+                &discovery_response.body,
+            ))
+            .map(|provider_metadata| (provider_metadata, Vec::new()))
+        };
+        let (provider_metadata, warnings) = parsed.map_err(|err| {
+            DiscoveryError::Parse(
+                discovery_response.status_code,
+                discovery_response.headers.get(CONTENT_TYPE).cloned(),
+                discovery_response.body.clone(),
+                err,
+            )
+        })?;
 
-        if provider_metadata.issuer() != issuer_url {
+        if provider_metadata.issuer() != issuer_url
+            && !issuer_policy.allows(provider_metadata.issuer())
+        {
             Err(DiscoveryError::Validation(format!(
                 "unexpected issuer URI `{}` (expected `{}`)",
                 provider_metadata.issuer().as_str(),
                 issuer_url.as_str()
             )))
         } else {
-            Ok(provider_metadata)
+            Ok((provider_metadata, warnings))
+        }
+    }
+
+    ///
+    /// Parses a discovery document, tolerating invalid or missing non-essential fields.
+    ///
+    /// Some providers omit fields required by the
+    /// [OpenID Connect Discovery](https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata)
+    /// spec (e.g., `subject_types_supported`) or publish values this crate can't parse. Rather
+    /// than failing, this method:
+    ///  * treats missing or invalid `response_types_supported`, `subject_types_supported`, and
+    ///    `id_token_signing_alg_values_supported` fields as empty lists,
+    ///  * ignores invalid elements of other lists, and
+    ///  * ignores other invalid optional fields.
+    ///
+    /// Each of these adjustments is returned as a [`ProviderMetadataWarning`]. An error is still
+    /// returned if the document is not a JSON object or if the `issuer`, `authorization_endpoint`,
+    /// or `jwks_uri` field is missing or invalid, since these are needed to use the provider.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn from_json_lenient(
+        json: &[u8],
+    ) -> Result<(Self, Vec<ProviderMetadataWarning>), serde_path_to_error::Error<serde_json::Error>>
+    {
+        let mut value = serde_path_to_error::deserialize::<_, serde_json::Value>(
+            &mut serde_json::Deserializer::from_slice(json),
+        )?;
+        let mut warnings = Vec::new();
+        if let Some(fields) = value.as_object_mut() {
+            for field in LENIENT_LIST_FIELDS {
+                if !fields.contains_key(*field) {
+                    fields.insert(field.to_string(), serde_json::Value::Array(Vec::new()));
+                    warnings.push(ProviderMetadataWarning {
+                        field: field.to_string(),
+                        message: "missing required field".to_string(),
+                    });
+                }
+            }
+        }
+
+        // Each iteration removes an invalid value, so this loop terminates.
+        loop {
+            let err = match serde_path_to_error::deserialize::<_, Self>(&value) {
+                Ok(provider_metadata) => return Ok((provider_metadata, warnings)),
+                Err(err) => err,
+            };
+            let mut path = err.path().iter();
+            let field = match (value.as_object_mut(), path.next()) {
+                (Some(fields), Some(serde_path_to_error::Segment::Map { key }))
+                    if !ESSENTIAL_FIELDS.contains(&key.as_str()) =>
+                {
+                    match (fields.get_mut(key), path.next()) {
+                        (
+                            Some(serde_json::Value::Array(values)),
+                            Some(serde_path_to_error::Segment::Seq { index }),
+                        ) if *index < values.len() => {
+                            values.remove(*index);
+                            Some(format!("{}[{}]", key, index))
+                        }
+                        (Some(_), _) if LENIENT_LIST_FIELDS.contains(&key.as_str()) => {
+                            let empty = serde_json::Value::Array(Vec::new());
+                            (fields.insert(key.clone(), empty.clone()) != Some(empty))
+                                .then(|| key.clone())
+                        }
+                        (Some(_), _) => fields.remove(key).map(|_| key.clone()),
+                        (None, _) => None,
+                    }
+                }
+                _ => None,
+            };
+            match field {
+                Some(field) => warnings.push(ProviderMetadataWarning {
+                    field,
+                    message: format!("ignored invalid value: {}", err.inner()),
+                }),
+                None => return Err(err),
+            }
+        }
+    }
+
+    ///
+    /// Verifies the `signed_metadata` JWT (if any) and returns the provider metadata with the
+    /// signed values taking precedence over the corresponding plain JSON values (see
+    /// [Section 2.1 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-2.1)).
+    ///
+    /// If the metadata does not include a `signed_metadata` value, it is returned unchanged.
+    /// Applications that require signed metadata should check
+    /// [`signed_metadata`](Self::signed_metadata) before calling this method. The signed metadata
+    /// must not change the `issuer` value.
+    ///
+    pub fn verify_signed_metadata(
+        self,
+        verifier: &SignedMetadataVerifier<JS, JT, JU, K>,
+    ) -> Result<Self, SignedMetadataError> {
+        let signed_metadata = if let Some(ref signed_metadata) = self.signed_metadata {
+            serde_json::from_value::<
+                JsonWebToken<JE, JS, JT, SignedMetadataClaims, JsonWebTokenJsonPayloadSerde>,
+            >(serde_json::Value::String(signed_metadata.clone()))
+            .map_err(SignedMetadataError::Parse)?
+        } else {
+            return Ok(self);
+        };
+        let signed_claims = verifier
+            .verified_claims(signed_metadata)
+            .map_err(SignedMetadataError::ClaimsVerification)?;
+
+        let mut merged = serde_json::to_value(&self).map_err(SignedMetadataError::Parse)?;
+        let merged_object = merged
+            .as_object_mut()
+            .expect("provider metadata must serialize to a JSON object");
+        merged_object.extend(
+            signed_claims
+                .metadata
+                .into_iter()
+                .filter(|(name, _)| !JWT_REGISTERED_CLAIMS.contains(&name.as_str())),
+        );
+        let verified =
+            serde_json::from_value::<Self>(merged).map_err(SignedMetadataError::Parse)?;
+        if verified.issuer != self.issuer {
+            return Err(SignedMetadataError::ClaimsVerification(
+                ClaimsVerificationError::InvalidIssuer(format!(
+                    "signed metadata issuer `{}` does not match `{}`",
+                    verified.issuer.as_str(),
+                    self.issuer.as_str()
+                )),
+            ));
+        }
+        Ok(Self {
+            jwks: self.jwks,
+            ..verified
+        })
+    }
+
+    ///
+    /// Signs this provider metadata using the given key, returning a JWT suitable for the
+    /// `signed_metadata` value (see
+    /// [Section 2.1 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-2.1)).
+    ///
+    /// The JWT contains every metadata value (except `signed_metadata` itself) along with an
+    /// `iss` claim identifying this provider's `issuer` as the party attesting to the metadata.
+    ///
+    pub fn sign_metadata<SK>(&self, signing_key: &SK, alg: JS) -> Result<String, JsonWebTokenError>
+    where
+        SK: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let mut metadata =
+            match serde_json::to_value(self).map_err(JsonWebTokenError::SerializationError)? {
+                serde_json::Value::Object(metadata) => metadata,
+                _ => panic!("provider metadata must serialize to a JSON object"),
+            };
+        metadata.remove("signed_metadata");
+        let jwt = JsonWebToken::<JE, JS, JT, _, JsonWebTokenJsonPayloadSerde>::new(
+            SignedMetadataClaims {
+                iss: Some(self.issuer.clone()),
+                metadata,
+            },
+            signing_key,
+            &alg,
+        )?;
+        match serde_json::to_value(&jwt).map_err(JsonWebTokenError::SerializationError)? {
+            serde_json::Value::String(jwt) => Ok(jwt),
+            _ => panic!("JWT must serialize to a string"),
+        }
+    }
+
+    ///
+    /// Returns a serializable snapshot of this provider metadata and its JSON Web Key Set.
+    ///
+    /// See [`ProviderMetadataSnapshot`] for details.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn snapshot(
+        &self,
+    ) -> ProviderMetadataSnapshot<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+    where
+        Self: Clone,
+    {
+        ProviderMetadataSnapshot {
+            captured_at: Utc::now(),
+            jwks: self.jwks.clone(),
+            // The JWKS is stored separately since it isn't serialized with the provider metadata.
+            provider_metadata: ProviderMetadata {
+                jwks: JsonWebKeySet::default(),
+                ..self.clone()
+            },
+        }
+    }
+
+    ///
+    /// Returns additional provider metadata fields.
+    ///
+    pub fn additional_metadata(&self) -> &A {
+        &self.additional_metadata
+    }
+    ///
+    /// Returns mutable additional provider metadata fields.
+    ///
+    pub fn additional_metadata_mut(&mut self) -> &mut A {
+        &mut self.additional_metadata
+    }
+}
+
+///
+/// Builder for [`ProviderMetadata`].
+///
+/// Unlike [`ProviderMetadata::new`], the fields required by
+/// [OpenID Connect Discovery](https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderMetadata)
+/// are specified by name, and [`build`](Self::build) returns an error identifying any required
+/// field that is missing or empty. Optional fields may then be specified using the setters on
+/// the resulting [`ProviderMetadata`].
+///
+#[derive(Clone, Debug)]
+#[allow(clippy::type_complexity)]
+pub struct ProviderMetadataBuilder<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+where
+    A: AdditionalProviderMetadata,
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+{
+    issuer: Option<IssuerUrl>,
+    authorization_endpoint: Option<AuthUrl>,
+    jwks_uri: Option<JsonWebKeySetUrl>,
+    response_types_supported: Vec<ResponseTypes<RT>>,
+    subject_types_supported: Vec<S>,
+    id_token_signing_alg_values_supported: Vec<JS>,
+    additional_metadata: A,
+    _phantom: PhantomData<(AD, CA, CN, CT, G, JE, JK, JT, JU, K, RM)>,
+}
+impl<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+    ProviderMetadataBuilder<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+where
+    A: AdditionalProviderMetadata,
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+{
+    ///
+    /// Instantiates a builder with the given additional provider metadata.
+    ///
+    pub fn new(additional_metadata: A) -> Self {
+        Self {
+            issuer: None,
+            authorization_endpoint: None,
+            jwks_uri: None,
+            response_types_supported: Vec::new(),
+            subject_types_supported: Vec::new(),
+            id_token_signing_alg_values_supported: Vec::new(),
+            additional_metadata,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Specifies the provider's issuer identifier (required).
+    ///
+    pub fn issuer(mut self, issuer: IssuerUrl) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    ///
+    /// Specifies the provider's authorization endpoint (required).
+    ///
+    pub fn authorization_endpoint(mut self, authorization_endpoint: AuthUrl) -> Self {
+        self.authorization_endpoint = Some(authorization_endpoint);
+        self
+    }
+
+    ///
+    /// Specifies the URL of the provider's JSON Web Key Set (required).
+    ///
+    pub fn jwks_uri(mut self, jwks_uri: JsonWebKeySetUrl) -> Self {
+        self.jwks_uri = Some(jwks_uri);
+        self
+    }
+
+    ///
+    /// Specifies the supported response types (at least one is required).
+    ///
+    pub fn response_types_supported<I>(mut self, response_types_supported: I) -> Self
+    where
+        I: IntoIterator<Item = ResponseTypes<RT>>,
+    {
+        self.response_types_supported = response_types_supported.into_iter().collect();
+        self
+    }
+
+    ///
+    /// Specifies the supported subject identifier types (at least one is required).
+    ///
+    pub fn subject_types_supported<I>(mut self, subject_types_supported: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+    {
+        self.subject_types_supported = subject_types_supported.into_iter().collect();
+        self
+    }
+
+    ///
+    /// Specifies the supported ID token signing algorithms (at least one is required).
+    ///
+    pub fn id_token_signing_alg_values_supported<I>(
+        mut self,
+        id_token_signing_alg_values_supported: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.id_token_signing_alg_values_supported =
+            id_token_signing_alg_values_supported.into_iter().collect();
+        self
+    }
+
+    ///
+    /// Specifies the additional provider metadata.
+    ///
+    pub fn additional_metadata(mut self, additional_metadata: A) -> Self {
+        self.additional_metadata = additional_metadata;
+        self
+    }
+
+    ///
+    /// Builds the provider metadata, returning an error if any required field is missing or
+    /// empty.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn build(
+        self,
+    ) -> Result<
+        ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
+        ProviderMetadataBuilderError,
+    > {
+        let issuer = self
+            .issuer
+            .ok_or(ProviderMetadataBuilderError::MissingField("issuer"))?;
+        let authorization_endpoint =
+            self.authorization_endpoint
+                .ok_or(ProviderMetadataBuilderError::MissingField(
+                    "authorization_endpoint",
+                ))?;
+        let jwks_uri = self
+            .jwks_uri
+            .ok_or(ProviderMetadataBuilderError::MissingField("jwks_uri"))?;
+        if self.response_types_supported.is_empty() {
+            return Err(ProviderMetadataBuilderError::MissingField(
+                "response_types_supported",
+            ));
+        }
+        if self.subject_types_supported.is_empty() {
+            return Err(ProviderMetadataBuilderError::MissingField(
+                "subject_types_supported",
+            ));
+        }
+        if self.id_token_signing_alg_values_supported.is_empty() {
+            return Err(ProviderMetadataBuilderError::MissingField(
+                "id_token_signing_alg_values_supported",
+            ));
+        }
+
+        Ok(ProviderMetadata::new(
+            issuer,
+            authorization_endpoint,
+            jwks_uri,
+            self.response_types_supported,
+            self.subject_types_supported,
+            self.id_token_signing_alg_values_supported,
+            self.additional_metadata,
+        ))
+    }
+}
+
+///
+/// Error building [`ProviderMetadata`] using a [`ProviderMetadataBuilder`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProviderMetadataBuilderError {
+    ///
+    /// A required field was not specified (or, for a list, was empty).
+    ///
+    #[error("Missing required provider metadata field `{0}`")]
+    MissingField(&'static str),
+}
+
+///
+/// Serializable snapshot of [`ProviderMetadata`] together with its JSON Web Key Set.
+///
+/// The JSON Web Key Set fetched during discovery is not part of the provider metadata's
+/// serialized form. A snapshot captures both so that a fully functional
+/// [`Client`](crate::Client) can be rebuilt (via
+/// [`Client::from_provider_metadata`](crate::Client::from_provider_metadata)) without network
+/// access, such as in air-gapped deployments or to avoid discovery on cold starts.
+///
+/// Since providers periodically rotate their signing keys, snapshots should be refreshed
+/// regularly; [`captured_at`](Self::captured_at) can be used to determine a snapshot's age.
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(bound = "")]
+#[allow(clippy::type_complexity)]
+pub struct ProviderMetadataSnapshot<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+where
+    A: AdditionalProviderMetadata,
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+{
+    captured_at: DateTime<Utc>,
+    provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>,
+    jwks: JsonWebKeySet<JS, JT, JU, K>,
+}
+impl<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+    ProviderMetadataSnapshot<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S>
+where
+    A: AdditionalProviderMetadata,
+    AD: AuthDisplay,
+    CA: ClientAuthMethod,
+    CN: ClaimName,
+    CT: ClaimType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RM: ResponseMode,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+{
+    ///
+    /// Returns the time at which the snapshot was captured.
+    ///
+    pub fn captured_at(&self) -> DateTime<Utc> {
+        self.captured_at
+    }
+
+    ///
+    /// Returns the provider's JSON Web Key Set.
+    ///
+    pub fn jwks(&self) -> &JsonWebKeySet<JS, JT, JU, K> {
+        &self.jwks
+    }
+
+    ///
+    /// Returns the provider metadata, including its JSON Web Key Set.
+    ///
+    pub fn into_provider_metadata(
+        self,
+    ) -> ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RT, S> {
+        ProviderMetadata {
+            jwks: self.jwks,
+            ..self.provider_metadata
         }
     }
+}
+
+///
+/// Policy for handling a discovery document whose `issuer` does not match the issuer URL used
+/// for discovery.
+///
+/// [OpenID Connect Discovery](https://openid.net/specs/openid-connect-discovery-1_0.html#ProviderConfigurationValidation)
+/// requires the `issuer` returned by the provider to exactly match the issuer URL used to
+/// retrieve the document. Relaxing this check makes it possible for a provider (or an attacker
+/// able to tamper with the discovery document) to impersonate a different issuer, so any
+/// mismatch accepted here should be reviewed and documented.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IssuerMismatchPolicy {
+    ///
+    /// Reject any mismatch (the default and specification-compliant behavior).
+    ///
+    #[default]
+    Reject,
+    ///
+    /// Accept a document whose `issuer` exactly matches one of the specified issuer URLs.
+    ///
+    AllowIssuers(Vec<IssuerUrl>),
+    ///
+    /// Accept a document with any `issuer`.
+    ///
+    /// **WARNING**: this disables issuer validation of the discovery document entirely. ID tokens
+    /// are still validated against the `issuer` returned in the document.
+    ///
+    InsecureAllowAny,
+}
+impl IssuerMismatchPolicy {
+    fn allows(&self, issuer: &IssuerUrl) -> bool {
+        match self {
+            IssuerMismatchPolicy::Reject => false,
+            IssuerMismatchPolicy::AllowIssuers(allowed) => allowed.contains(issuer),
+            IssuerMismatchPolicy::InsecureAllowAny => true,
+        }
+    }
+}
+
+// Required list fields that are treated as empty when missing or invalid by
+// `ProviderMetadata::from_json_lenient`.
+const LENIENT_LIST_FIELDS: &[&str] = &[
+    "response_types_supported",
+    "subject_types_supported",
+    "id_token_signing_alg_values_supported",
+];
+
+// Fields that must be valid even when parsing provider metadata leniently.
+const ESSENTIAL_FIELDS: &[&str] = &["issuer", "authorization_endpoint", "jwks_uri"];
+
+///
+/// Warning about a provider metadata field that was missing or ignored while parsing a discovery
+/// document leniently (see [`ProviderMetadata::from_json_lenient`]).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProviderMetadataWarning {
+    field: String,
+    message: String,
+}
+impl ProviderMetadataWarning {
+    ///
+    /// Returns the name of the affected field (e.g., `subject_types_supported` or
+    /// `response_types_supported[1]`).
+    ///
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    ///
+    /// Returns a description of the problem.
+    ///
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl std::fmt::Display for ProviderMetadataWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "`{}`: {}", self.field, self.message)
+    }
+}
 
+// Registered JWT claims (see Section 4.1 of RFC 7519), which are excluded when merging the
+// claims of a `signed_metadata` JWT into the provider metadata.
+const JWT_REGISTERED_CLAIMS: &[&str] = &["iss", "sub", "aud", "exp", "nbf", "iat", "jti"];
+
+// Claims of a `signed_metadata` JWT.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SignedMetadataClaims {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iss: Option<IssuerUrl>,
+    #[serde(flatten)]
+    metadata: serde_json::Map<String, serde_json::Value>,
+}
+impl AudiencesClaim for SignedMetadataClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        None
+    }
+}
+impl IssuerClaim for SignedMetadataClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        self.iss.as_ref()
+    }
+}
+
+///
+/// Error verifying signed provider metadata.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SignedMetadataError {
     ///
-    /// Returns additional provider metadata fields.
+    /// Failed to verify the `signed_metadata` JWT.
     ///
-    pub fn additional_metadata(&self) -> &A {
-        &self.additional_metadata
-    }
+    #[error("Failed to verify signed metadata")]
+    ClaimsVerification(#[source] ClaimsVerificationError),
     ///
-    /// Returns mutable additional provider metadata fields.
+    /// Failed to parse the `signed_metadata` JWT or to merge its claims into the provider
+    /// metadata.
     ///
-    pub fn additional_metadata_mut(&mut self) -> &mut A {
-        &mut self.additional_metadata
-    }
+    #[error("Failed to parse signed metadata")]
+    Parse(#[source] serde_json::Error),
 }
 
 ///
@@ -424,8 +1241,19 @@ where
     ///
     /// Failed to parse server response.
     ///
-    #[error("Failed to parse server response")]
-    Parse(#[source] serde_path_to_error::Error<serde_json::Error>),
+    /// Contains the HTTP status code, `Content-Type` header (if any), and body of the response
+    /// that could not be parsed.
+    ///
+    #[error(
+        "Failed to parse server response (HTTP status code {0}, body: `{}`)",
+        body_excerpt(.2)
+    )]
+    Parse(
+        StatusCode,
+        Option<HeaderValue>,
+        Vec<u8>,
+        #[source] serde_path_to_error::Error<serde_json::Error>,
+    ),
     ///
     /// An error occurred while sending the request or receiving the response (e.g., network
     /// connectivity failed).
@@ -435,7 +1263,7 @@ where
     ///
     /// Server returned an invalid response.
     ///
-    #[error("Server returned invalid response: {2}")]
+    #[error("Server returned invalid response: {2} (body: `{}`)", body_excerpt(.1))]
     Response(StatusCode, Vec<u8>, String),
     ///
     /// Failed to parse discovery URL from issuer URL.
@@ -448,6 +1276,250 @@ where
     #[error("Validation error: {0}")]
     Validation(String),
 }
+impl<RE> DiscoveryError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// Returns the HTTP status code of the server response, if the error was caused by an
+    /// invalid response.
+    ///
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            DiscoveryError::Parse(status_code, ..) | DiscoveryError::Response(status_code, ..) => {
+                Some(*status_code)
+            }
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the raw body of the server response, if the error was caused by an invalid
+    /// response.
+    ///
+    pub fn response_body(&self) -> Option<&[u8]> {
+        match self {
+            DiscoveryError::Parse(_, _, body, _) | DiscoveryError::Response(_, body, _) => {
+                Some(body)
+            }
+            _ => None,
+        }
+    }
+}
+
+///
+/// Thread-safe cache of provider metadata (or any other per-issuer discovery result, such as a
+/// [`JsonWebKeySet`]) with a fixed time-to-live.
+///
+/// Entries are keyed by [`IssuerUrl`] and re-fetched on the first access after they expire. The
+/// cache is intended to be shared across requests (e.g., behind an [`Arc`]):
+///
+/// ```rust,ignore
+/// let cache = Arc::new(CoreDiscoveryCache::new(Duration::from_secs(3600)));
+/// let provider_metadata = cache.get_or_discover(&issuer_url, |issuer_url| {
+///     CoreProviderMetadata::discover(issuer_url, http_client)
+/// })?;
+/// ```
+///
+/// Discovery is single-flight per issuer: when several threads (or tasks) miss the cache for the
+/// same issuer at once, only one of them invokes the discovery function, and the others wait for
+/// it and then use its result. Synchronous and asynchronous callers share the same per-issuer
+/// lock. Expired entries are evicted when they're next accessed.
+///
+pub struct DiscoveryCache<M> {
+    ttl: Duration,
+    slots: Mutex<HashMap<IssuerUrl, CacheSlot<M>>>,
+}
+
+// Cached entry for an issuer (if any), along with the lock held while discovering it.
+struct CacheSlot<M> {
+    discovery_lock: Arc<futures_util::lock::Mutex<()>>,
+    entry: Option<(Instant, Arc<M>)>,
+}
+impl<M> CacheSlot<M> {
+    fn new() -> Self {
+        Self {
+            discovery_lock: Arc::new(futures_util::lock::Mutex::new(())),
+            entry: None,
+        }
+    }
+
+    // Drops the entry if it has expired, and returns the remaining (unexpired) entry.
+    fn unexpired_entry(&mut self, ttl: Duration) -> Option<Arc<M>> {
+        if matches!(self.entry, Some((fetched_at, _)) if fetched_at.elapsed() >= ttl) {
+            self.entry = None;
+        }
+        self.entry.as_ref().map(|(_, entry)| entry.clone())
+    }
+
+    // Returns `true` if the slot holds no entry and no caller is discovering it, in which case
+    // it may be removed from the cache.
+    fn is_unused(&self) -> bool {
+        self.entry.is_none() && Arc::strong_count(&self.discovery_lock) == 1
+    }
+}
+impl<M> DiscoveryCache<M> {
+    ///
+    /// Creates an empty cache whose entries expire after `ttl`.
+    ///
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    ///
+    /// Returns the time-to-live of cache entries.
+    ///
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    ///
+    /// Returns the cached, unexpired entry for `issuer_url`, if any.
+    ///
+    /// An expired entry is evicted from the cache.
+    ///
+    pub fn get(&self, issuer_url: &IssuerUrl) -> Option<Arc<M>> {
+        let mut slots = self.lock();
+        let slot = slots.get_mut(issuer_url)?;
+        let entry = slot.unexpired_entry(self.ttl);
+        if slot.is_unused() {
+            slots.remove(issuer_url);
+        }
+        entry
+    }
+
+    ///
+    /// Inserts (or replaces) the entry for `issuer_url`, resetting its time-to-live.
+    ///
+    /// Expired entries for other issuers are evicted.
+    ///
+    pub fn insert(&self, issuer_url: IssuerUrl, entry: M) -> Arc<M> {
+        let entry = Arc::new(entry);
+        let mut slots = self.lock();
+        slots.retain(|_, slot| {
+            slot.unexpired_entry(self.ttl);
+            !slot.is_unused()
+        });
+        slots.entry(issuer_url).or_insert_with(CacheSlot::new).entry =
+            Some((Instant::now(), entry.clone()));
+        entry
+    }
+
+    ///
+    /// Removes the entry for `issuer_url`, forcing the next access to re-fetch it.
+    ///
+    /// This is useful when a token is signed by a key that's missing from the cached JSON Web
+    /// Key Set, which may indicate that the provider has rotated its keys.
+    ///
+    pub fn invalidate(&self, issuer_url: &IssuerUrl) {
+        let mut slots = self.lock();
+        if let Some(slot) = slots.get_mut(issuer_url) {
+            slot.entry = None;
+            if slot.is_unused() {
+                slots.remove(issuer_url);
+            }
+        }
+    }
+
+    ///
+    /// Removes all entries.
+    ///
+    pub fn clear(&self) {
+        self.lock().retain(|_, slot| {
+            slot.entry = None;
+            !slot.is_unused()
+        });
+    }
+
+    ///
+    /// Returns the cached entry for `issuer_url`, or calls `discover` to fetch it if the entry is
+    /// missing or expired.
+    ///
+    /// If another caller is already discovering the entry for `issuer_url`, this blocks until it
+    /// finishes and then returns its result (or calls `discover` if it failed). Errors returned by
+    /// `discover` are not cached.
+    ///
+    pub fn get_or_discover<F, E>(&self, issuer_url: &IssuerUrl, discover: F) -> Result<Arc<M>, E>
+    where
+        F: FnOnce(&IssuerUrl) -> Result<M, E>,
+    {
+        if let Some(entry) = self.get(issuer_url) {
+            return Ok(entry);
+        }
+
+        let discovery_lock = self.discovery_lock(issuer_url);
+        let _guard = block_on(discovery_lock.lock());
+        if let Some(entry) = self.get(issuer_url) {
+            return Ok(entry);
+        }
+        Ok(self.insert(issuer_url.clone(), discover(issuer_url)?))
+    }
+
+    ///
+    /// Asynchronously returns the cached entry for `issuer_url`, or calls `discover` to fetch it if
+    /// the entry is missing or expired.
+    ///
+    /// If another caller is already discovering the entry for `issuer_url`, this waits for it to
+    /// finish and then returns its result (or calls `discover` if it failed). The cache itself is
+    /// not locked while awaiting `discover`. Errors returned by `discover` are not cached.
+    ///
+    pub async fn get_or_discover_async<F, FU, E>(
+        &self,
+        issuer_url: &IssuerUrl,
+        discover: F,
+    ) -> Result<Arc<M>, E>
+    where
+        F: FnOnce(IssuerUrl) -> FU,
+        FU: Future<Output = Result<M, E>>,
+    {
+        if let Some(entry) = self.get(issuer_url) {
+            return Ok(entry);
+        }
+
+        let discovery_lock = self.discovery_lock(issuer_url);
+        let _guard = discovery_lock.lock().await;
+        if let Some(entry) = self.get(issuer_url) {
+            return Ok(entry);
+        }
+        Ok(self.insert(issuer_url.clone(), discover(issuer_url.clone()).await?))
+    }
+
+    // Returns the lock that serializes discovery for `issuer_url`.
+    fn discovery_lock(&self, issuer_url: &IssuerUrl) -> Arc<futures_util::lock::Mutex<()>> {
+        self.lock()
+            .entry(issuer_url.clone())
+            .or_insert_with(CacheSlot::new)
+            .discovery_lock
+            .clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<IssuerUrl, CacheSlot<M>>> {
+        // A panic while holding the lock can't leave the map in an inconsistent state, so it's
+        // safe to ignore poisoning.
+        self.slots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+impl<M> Debug for DiscoveryCache<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiscoveryCache")
+            .field("ttl", &self.ttl)
+            .field(
+                "issuers",
+                &self
+                    .lock()
+                    .iter()
+                    .filter(|(_, slot)| slot.entry.is_some())
+                    .map(|(issuer_url, _)| issuer_url)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -1426,6 +2498,63 @@ mod tests {
         assert_eq!(provider_metadata, redeserialized_metadata);
     }
 
+    #[test]
+    fn test_discovery_oauth_extension_fields() {
+        use oauth2::{
+            DeviceAuthorizationUrl, IntrospectionUrl, PkceCodeChallengeMethod, RevocationUrl,
+        };
+
+        let json_response = r#"{
+            "issuer": "https://example.com",
+            "authorization_endpoint": "https://example.com/authorize",
+            "jwks_uri": "https://example.com/jwks.json",
+            "response_types_supported": ["code"],
+            "subject_types_supported": ["public"],
+            "id_token_signing_alg_values_supported": ["RS256"],
+            "revocation_endpoint": "https://example.com/revoke",
+            "introspection_endpoint": "https://example.com/introspect",
+            "introspection_endpoint_auth_methods_supported": [
+                "client_secret_basic",
+                "private_key_jwt"
+            ],
+            "code_challenge_methods_supported": ["plain", "S256"],
+            "device_authorization_endpoint": "https://example.com/device"
+        }"#;
+
+        let provider_metadata: CoreProviderMetadata = serde_json::from_str(json_response).unwrap();
+        assert_eq!(
+            Some(&RevocationUrl::new("https://example.com/revoke".to_string()).unwrap()),
+            provider_metadata.revocation_endpoint()
+        );
+        assert_eq!(
+            Some(&IntrospectionUrl::new("https://example.com/introspect".to_string()).unwrap()),
+            provider_metadata.introspection_endpoint()
+        );
+        assert_eq!(
+            Some(&vec![
+                CoreClientAuthMethod::ClientSecretBasic,
+                CoreClientAuthMethod::PrivateKeyJwt,
+            ]),
+            provider_metadata.introspection_endpoint_auth_methods_supported()
+        );
+        assert_eq!(
+            Some(&vec![
+                PkceCodeChallengeMethod::new("plain".to_string()),
+                PkceCodeChallengeMethod::new("S256".to_string()),
+            ]),
+            provider_metadata.code_challenge_methods_supported()
+        );
+        assert_eq!(
+            Some(&DeviceAuthorizationUrl::new("https://example.com/device".to_string()).unwrap()),
+            provider_metadata.device_authorization_endpoint()
+        );
+
+        let serialized_json = serde_json::to_string(&provider_metadata).unwrap();
+        let redeserialized_metadata: CoreProviderMetadata =
+            serde_json::from_str(&serialized_json).unwrap();
+        assert_eq!(provider_metadata, redeserialized_metadata);
+    }
+
     // Tests that we ignore enum values that the OIDC provider supports but that the client does
     // not (which trigger serde deserialization errors while parsing the provider metadata).
     #[test]
@@ -1614,4 +2743,478 @@ mod tests {
             serde_json::from_str(&serialized_json).unwrap();
         assert_eq!(provider_metadata, redeserialized_metadata);
     }
+
+    #[test]
+    fn test_discover_issuer_mismatch() {
+        use http::header::{HeaderValue, CONTENT_TYPE};
+        use http::status::StatusCode;
+
+        use super::{DiscoveryError, IssuerMismatchPolicy};
+        use crate::{HttpRequest, HttpResponse, IssuerUrl};
+
+        let http_client = |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+            let body = if request.url.path().ends_with("/openid-configuration") {
+                "{\
+                    \"issuer\": \"https://vanity.example.com\",\
+                    \"authorization_endpoint\": \"https://vanity.example.com/authorize\",\
+                    \"jwks_uri\": \"https://vanity.example.com/jwks.json\",\
+                    \"response_types_supported\": [\"code\"],\
+                    \"subject_types_supported\": [\"public\"],\
+                    \"id_token_signing_alg_values_supported\": [\"RS256\"]\
+                }"
+            } else {
+                "{\"keys\": []}"
+            };
+            Ok(HttpResponse {
+                status_code: StatusCode::OK,
+                headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                    .into_iter()
+                    .collect(),
+                body: body.as_bytes().to_vec(),
+            })
+        };
+        let issuer_url = IssuerUrl::new("https://idp.example.com".to_string()).unwrap();
+
+        match CoreProviderMetadata::discover(&issuer_url, http_client) {
+            Err(DiscoveryError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match CoreProviderMetadata::discover_with_issuer_policy(
+            &issuer_url,
+            &IssuerMismatchPolicy::AllowIssuers(vec![IssuerUrl::new(
+                "https://other.example.com".to_string(),
+            )
+            .unwrap()]),
+            http_client,
+        ) {
+            Err(DiscoveryError::Validation(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let provider_metadata = CoreProviderMetadata::discover_with_issuer_policy(
+            &issuer_url,
+            &IssuerMismatchPolicy::AllowIssuers(vec![IssuerUrl::new(
+                "https://vanity.example.com".to_string(),
+            )
+            .unwrap()]),
+            http_client,
+        )
+        .unwrap();
+        assert_eq!(
+            provider_metadata.issuer().as_str(),
+            "https://vanity.example.com"
+        );
+
+        CoreProviderMetadata::discover_with_issuer_policy(
+            &issuer_url,
+            &IssuerMismatchPolicy::InsecureAllowAny,
+            http_client,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_discover_invalid_response() {
+        use http::header::{HeaderValue, CONTENT_TYPE};
+        use http::status::StatusCode;
+
+        use super::DiscoveryError;
+        use crate::{HttpRequest, HttpResponse, IssuerUrl};
+
+        let issuer_url = IssuerUrl::new("https://idp.example.com".to_string()).unwrap();
+        let http_client = |content_type: &'static str, body: String| {
+            move |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(HttpResponse {
+                    status_code: StatusCode::OK,
+                    headers: vec![(CONTENT_TYPE, HeaderValue::from_static(content_type))]
+                        .into_iter()
+                        .collect(),
+                    body: body.as_bytes().to_vec(),
+                })
+            }
+        };
+
+        match CoreProviderMetadata::discover(
+            &issuer_url,
+            http_client(
+                "application/json",
+                "{\"issuer\": \"https://idp.example.com\"}".to_string(),
+            ),
+        ) {
+            Err(err @ DiscoveryError::Parse(..)) => {
+                assert_eq!(err.status_code(), Some(StatusCode::OK));
+                assert_eq!(
+                    err.response_body(),
+                    Some("{\"issuer\": \"https://idp.example.com\"}".as_bytes())
+                );
+                assert_eq!(
+                    err.to_string(),
+                    "Failed to parse server response (HTTP status code 200 OK, body: \
+                     `{\"issuer\": \"https://idp.example.com\"}`)"
+                );
+                if let DiscoveryError::Parse(_, content_type, _, _) = err {
+                    assert_eq!(
+                        content_type,
+                        Some(HeaderValue::from_static("application/json"))
+                    );
+                }
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match CoreProviderMetadata::discover(&issuer_url, http_client("text/html", "x".repeat(300)))
+        {
+            Err(err @ DiscoveryError::Response(..)) => {
+                assert_eq!(err.status_code(), Some(StatusCode::OK));
+                assert_eq!(err.response_body().map(<[u8]>::len), Some(300));
+                assert!(err
+                    .to_string()
+                    .ends_with(&format!("(body: `{}... (300 bytes)`)", "x".repeat(256))));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_discover_lenient() {
+        use http::header::{HeaderValue, CONTENT_TYPE};
+        use http::status::StatusCode;
+
+        use super::DiscoveryError;
+        use crate::{HttpRequest, HttpResponse, IssuerUrl};
+
+        let http_client = |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+            let body = if request.url.path().ends_with("/openid-configuration") {
+                "{\
+                    \"issuer\": \"https://idp.example.com\",\
+                    \"authorization_endpoint\": \"https://idp.example.com/authorize\",\
+                    \"jwks_uri\": \"https://idp.example.com/jwks.json\",\
+                    \"response_types_supported\": [\"code\", 5, \"id_token\"],\
+                    \"id_token_signing_alg_values_supported\": [\"RS256\"],\
+                    \"response_modes_supported\": \"query\",\
+                    \"claims_parameter_supported\": \"yes\"\
+                }"
+            } else {
+                "{\"keys\": []}"
+            };
+            Ok(HttpResponse {
+                status_code: StatusCode::OK,
+                headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                    .into_iter()
+                    .collect(),
+                body: body.as_bytes().to_vec(),
+            })
+        };
+        let issuer_url = IssuerUrl::new("https://idp.example.com".to_string()).unwrap();
+
+        match CoreProviderMetadata::discover(&issuer_url, http_client) {
+            Err(DiscoveryError::Parse(..)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let (provider_metadata, warnings) =
+            CoreProviderMetadata::discover_lenient(&issuer_url, http_client).unwrap();
+        assert_eq!(
+            provider_metadata.response_types_supported(),
+            &vec![
+                ResponseTypes::new(vec![CoreResponseType::Code]),
+                ResponseTypes::new(vec![CoreResponseType::IdToken]),
+            ]
+        );
+        assert!(provider_metadata.subject_types_supported().is_empty());
+        assert_eq!(provider_metadata.response_modes_supported(), None);
+        assert_eq!(provider_metadata.claims_parameter_supported(), None);
+        let mut fields = warnings
+            .iter()
+            .map(|warning| warning.field())
+            .collect::<Vec<_>>();
+        fields.sort_unstable();
+        assert_eq!(
+            fields,
+            vec![
+                "claims_parameter_supported",
+                "response_modes_supported",
+                "response_types_supported[1]",
+                "subject_types_supported",
+            ]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "`subject_types_supported`: missing required field"
+        );
+
+        // Essential fields must still be present and valid.
+        assert!(CoreProviderMetadata::from_json_lenient(
+            b"{\"issuer\": \"https://idp.example.com\", \"jwks_uri\": 5}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_discovery_cache() {
+        use std::time::Duration;
+
+        use super::DiscoveryCache;
+        use crate::IssuerUrl;
+
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let other_issuer_url = IssuerUrl::new("https://other.example.com".to_string()).unwrap();
+
+        let cache = DiscoveryCache::new(Duration::from_secs(3600));
+        assert_eq!(None, cache.get(&issuer_url));
+        assert_eq!(
+            Err("failed"),
+            cache.get_or_discover(&issuer_url, |_| Err("failed"))
+        );
+        assert_eq!(None, cache.get(&issuer_url));
+
+        assert_eq!(
+            1,
+            *cache
+                .get_or_discover(&issuer_url, |_| Ok::<_, ()>(1))
+                .unwrap()
+        );
+        assert_eq!(
+            1,
+            *cache
+                .get_or_discover(&issuer_url, |_| -> Result<_, ()> {
+                    panic!("cached entry should be used")
+                })
+                .unwrap()
+        );
+        assert_eq!(
+            2,
+            *cache
+                .get_or_discover(&other_issuer_url, |_| Ok::<_, ()>(2))
+                .unwrap()
+        );
+
+        cache.invalidate(&issuer_url);
+        assert_eq!(None, cache.get(&issuer_url));
+        assert_eq!(2, *cache.get(&other_issuer_url).unwrap());
+
+        // Expired entries are evicted on access and re-fetched.
+        let cache = DiscoveryCache::new(Duration::ZERO);
+        cache.insert(issuer_url.clone(), 1);
+        cache.insert(other_issuer_url.clone(), 2);
+        assert_eq!(1, cache.lock().len());
+        assert_eq!(None, cache.get(&other_issuer_url));
+        assert!(cache.lock().is_empty());
+        assert_eq!(
+            3,
+            *cache
+                .get_or_discover(&issuer_url, |_| Ok::<_, ()>(3))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_discovery_cache_single_flight() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Barrier;
+        use std::time::Duration;
+
+        use super::DiscoveryCache;
+        use crate::helpers::block_on;
+        use crate::IssuerUrl;
+
+        let issuer_url = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let cache = DiscoveryCache::new(Duration::from_secs(3600));
+        let discoveries = AtomicUsize::new(0);
+        let discover = || {
+            discoveries.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            Ok::<_, ()>(discoveries.load(Ordering::SeqCst))
+        };
+
+        // Synchronous and asynchronous callers that miss the cache at the same time share a single
+        // discovery.
+        let barrier = Barrier::new(4);
+        std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|i| {
+                    let (barrier, cache, issuer_url) = (&barrier, &cache, &issuer_url);
+                    scope.spawn(move || {
+                        barrier.wait();
+                        if i % 2 == 0 {
+                            cache.get_or_discover(issuer_url, |_| discover())
+                        } else {
+                            block_on(
+                                cache.get_or_discover_async(issuer_url, |_| async { discover() }),
+                            )
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                assert_eq!(1, *handle.join().unwrap().unwrap());
+            }
+        });
+        assert_eq!(1, discoveries.load(Ordering::SeqCst));
+
+        // Discovery locks aren't retained once discovery completes.
+        cache.invalidate(&issuer_url);
+        assert!(cache.lock().is_empty());
+        assert_eq!(
+            Err(()),
+            cache.get_or_discover(&issuer_url, |_| Err::<usize, _>(()))
+        );
+        assert_eq!(None, cache.get(&issuer_url));
+        assert!(cache.lock().is_empty());
+    }
+
+    #[test]
+    fn test_provider_metadata_snapshot() {
+        use crate::core::{CoreJsonWebKeySet, CoreProviderMetadataSnapshot};
+        use crate::jwt::tests::TEST_RSA_PUB_KEY;
+
+        let provider_metadata = CoreProviderMetadata::new(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+            AuthUrl::new("https://example.com/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example.com/jwks.json".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            Default::default(),
+        )
+        .set_jwks(CoreJsonWebKeySet::new(vec![serde_json::from_str(
+            TEST_RSA_PUB_KEY,
+        )
+        .unwrap()]));
+
+        // The JWKS is not part of the serialized provider metadata.
+        let redeserialized_metadata: CoreProviderMetadata =
+            serde_json::from_str(&serde_json::to_string(&provider_metadata).unwrap()).unwrap();
+        assert!(redeserialized_metadata.jwks().keys().is_empty());
+
+        let snapshot = provider_metadata.snapshot();
+        let redeserialized_snapshot: CoreProviderMetadataSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        assert_eq!(snapshot, redeserialized_snapshot);
+        assert_eq!(1, redeserialized_snapshot.jwks().keys().len());
+        assert_eq!(
+            provider_metadata,
+            redeserialized_snapshot.into_provider_metadata()
+        );
+    }
+    #[test]
+    fn test_signed_metadata() {
+        use crate::core::{CoreJsonWebKeySet, CoreRsaPrivateSigningKey};
+        use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+        use crate::{ClaimsVerificationError, SignedMetadataError, SignedMetadataVerifier};
+
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let signed_token_endpoint = TokenUrl::new("https://example.com/token".to_string()).unwrap();
+        let provider_metadata = CoreProviderMetadata::new(
+            issuer.clone(),
+            AuthUrl::new("https://example.com/authorize".to_string()).unwrap(),
+            JsonWebKeySetUrl::new("https://example.com/jwks.json".to_string()).unwrap(),
+            vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+            vec![CoreSubjectIdentifierType::Public],
+            vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+            Default::default(),
+        )
+        .set_token_endpoint(Some(signed_token_endpoint.clone()));
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let signed_metadata = provider_metadata
+            .sign_metadata(&signing_key, CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+            .unwrap();
+
+        // The plain JSON values have been tampered with, but the signed values take precedence.
+        let tampered_metadata = provider_metadata
+            .clone()
+            .set_token_endpoint(Some(
+                TokenUrl::new("https://attacker.example.com/token".to_string()).unwrap(),
+            ))
+            .set_signed_metadata(Some(signed_metadata));
+        let tampered_metadata: CoreProviderMetadata =
+            serde_json::from_str(&serde_json::to_string(&tampered_metadata).unwrap()).unwrap();
+
+        let keys = CoreJsonWebKeySet::new(vec![serde_json::from_str(TEST_RSA_PUB_KEY).unwrap()]);
+        let verified = tampered_metadata
+            .clone()
+            .verify_signed_metadata(&SignedMetadataVerifier::new(issuer.clone(), keys.clone()))
+            .unwrap();
+        assert_eq!(verified.token_endpoint(), Some(&signed_token_endpoint));
+        assert_eq!(verified.issuer(), &issuer);
+
+        // Metadata without a signed_metadata value is returned unchanged.
+        assert_eq!(
+            provider_metadata
+                .clone()
+                .verify_signed_metadata(&SignedMetadataVerifier::new(issuer.clone(), keys.clone()))
+                .unwrap(),
+            provider_metadata
+        );
+
+        match tampered_metadata
+            .clone()
+            .verify_signed_metadata(&SignedMetadataVerifier::new(
+                IssuerUrl::new("https://attester.example.com".to_string()).unwrap(),
+                keys,
+            )) {
+            Err(SignedMetadataError::ClaimsVerification(
+                ClaimsVerificationError::InvalidIssuer(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match tampered_metadata.verify_signed_metadata(&SignedMetadataVerifier::new(
+            issuer,
+            CoreJsonWebKeySet::new(vec![]),
+        )) {
+            Err(SignedMetadataError::ClaimsVerification(
+                ClaimsVerificationError::SignatureVerification(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_provider_metadata_builder() {
+        let builder = CoreProviderMetadata::builder()
+            .issuer(IssuerUrl::new("https://example".to_string()).unwrap())
+            .authorization_endpoint(AuthUrl::new("https://example/authorize".to_string()).unwrap())
+            .jwks_uri(JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap())
+            .response_types_supported(vec![ResponseTypes::new(vec![CoreResponseType::Code])])
+            .subject_types_supported(vec![CoreSubjectIdentifierType::Public]);
+
+        assert_eq!(
+            builder.clone().build().unwrap_err(),
+            super::ProviderMetadataBuilderError::MissingField(
+                "id_token_signing_alg_values_supported"
+            )
+        );
+        assert_eq!(
+            CoreProviderMetadata::builder()
+                .build()
+                .unwrap_err()
+                .to_string(),
+            "Missing required provider metadata field `issuer`"
+        );
+
+        let provider_metadata = builder
+            .id_token_signing_alg_values_supported(vec![
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            ])
+            .build()
+            .unwrap()
+            .set_token_endpoint(Some(
+                TokenUrl::new("https://example/token".to_string()).unwrap(),
+            ));
+        assert_eq!(
+            provider_metadata,
+            CoreProviderMetadata::new(
+                IssuerUrl::new("https://example".to_string()).unwrap(),
+                AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+                JsonWebKeySetUrl::new("https://example/jwks".to_string()).unwrap(),
+                vec![ResponseTypes::new(vec![CoreResponseType::Code])],
+                vec![CoreSubjectIdentifierType::Public],
+                vec![CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256],
+                Default::default(),
+            )
+            .set_token_endpoint(Some(
+                TokenUrl::new("https://example/token".to_string()).unwrap()
+            ))
+        );
+    }
 }
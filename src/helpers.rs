@@ -5,7 +5,12 @@ use serde_value::{Value, ValueDeserializer};
 
 use std::cmp::PartialEq;
 use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::future::Future;
 use std::marker::PhantomData;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::Thread;
 
 pub(crate) trait FlattenFilter {
     fn should_include(field_name: &str) -> bool;
@@ -182,3 +187,32 @@ where
         Debug::fmt(&self.inner, f)
     }
 }
+
+// Wakes a thread parked in `block_on`.
+struct ThreadWaker(Thread);
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// Runs the given future to completion on the current thread, parking the thread whenever the
+// future is pending.
+pub(crate) fn block_on<F>(future: F) -> F::Output
+where
+    F: Future,
+{
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        std::thread::park();
+    }
+}
@@ -1,12 +1,29 @@
 use http::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
 use oauth2::AccessToken;
 
+pub const MIME_TYPE_FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
 pub const MIME_TYPE_JSON: &str = "application/json";
 pub const MIME_TYPE_JWKS: &str = "application/jwk-set+json";
 pub const MIME_TYPE_JWT: &str = "application/jwt";
 
 pub const BEARER: &str = "Bearer";
 
+// Maximum number of bytes of a response body to include in error messages.
+const MAX_BODY_EXCERPT_LEN: usize = 256;
+
+// Returns a lossy UTF-8 excerpt of the given response body suitable for error messages.
+pub fn body_excerpt(body: &[u8]) -> String {
+    if body.len() > MAX_BODY_EXCERPT_LEN {
+        format!(
+            "{}... ({} bytes)",
+            String::from_utf8_lossy(&body[..MAX_BODY_EXCERPT_LEN]),
+            body.len()
+        )
+    } else {
+        String::from_utf8_lossy(body).into_owned()
+    }
+}
+
 // The [essence](https://mimesniff.spec.whatwg.org/#mime-type-essence) is the <type>/<subtype>
 // representation.
 pub fn content_type_has_essence(content_type: &HeaderValue, expected_essence: &str) -> bool {
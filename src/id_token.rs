@@ -1,27 +1,31 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::str::FromStr;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use oauth2::ClientId;
 use serde::Serialize;
 use serde_json::Value;
+use serde_with::{serde_as, DisplayFromStr, PickFirst};
 
 use crate::helpers::FilteredFlatten;
 use crate::jwt::JsonWebTokenAccess;
 use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
 use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds, serde_utc_seconds_opt};
 use crate::types::LocalizedClaim;
+use crate::verification::verify_signature;
 use crate::{
     AccessToken, AccessTokenHash, AdditionalClaims, AddressClaim, Audience, AudiencesClaim,
     AuthenticationContextClass, AuthenticationMethodReference, AuthorizationCode,
-    AuthorizationCodeHash, ClaimsVerificationError, EndUserBirthday, EndUserEmail,
-    EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
-    EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
-    EndUserWebsiteUrl, ExtraTokenFields, GenderClaim, IdTokenVerifier, IssuerClaim, IssuerUrl,
-    JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken, JsonWebTokenAlgorithm,
-    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LanguageTag, Nonce, NonceVerifier,
-    PrivateSigningKey, SigningError, StandardClaims, SubjectIdentifier,
+    AuthorizationCodeHash, ClaimsVerificationError, DecryptionKey, EncryptionKey, EndUserBirthday,
+    EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName,
+    EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone,
+    EndUserUsername, EndUserWebsiteUrl, ExtraTokenFields, GenderClaim, IdTokenVerifier,
+    IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse, JsonWebToken,
+    JsonWebTokenAlgorithm, JsonWebTokenHeader, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag, Nonce, NonceVerifier,
+    PrivateSigningKey, SigningError, StandardClaims, SubjectIdentifier, VerifiedSignature,
 };
 
 // This wrapper layer exists instead of directly verifying the JWT and returning the claims so that
@@ -30,6 +34,11 @@ use crate::{
 ///
 /// OpenID Connect ID token.
 ///
+/// Parsing an ID token only decodes its JOSE header and signature; the claims are decoded lazily
+/// when first accessed (e.g., by [`IdToken::claims`]), which returns
+/// [`ClaimsVerificationError::MalformedClaims`] if they are invalid. This avoids the cost of
+/// decoding claims for ID tokens that are merely forwarded.
+///
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct IdToken<
     AC: AdditionalClaims,
@@ -84,29 +93,102 @@ where
         K: JsonWebKey<JS, JT, JU>,
         S: PrivateSigningKey<JS, JT, JU, K>,
     {
+        JsonWebToken::new(
+            Self::claims_with_hashes(claims, &alg, access_token, code)?,
+            signing_key,
+            &alg,
+        )
+        .map(Self)
+    }
+
+    ///
+    /// Initializes an ID token in the same manner as [`new`](Self::new), including the given
+    /// additional parameters in the JOSE header (see
+    /// [`JsonWebToken::new_with_header_params`]).
+    ///
+    pub fn new_with_header_params<JU, K, S>(
+        claims: IdTokenClaims<AC, GC>,
+        signing_key: &S,
+        alg: JS,
+        access_token: Option<&AccessToken>,
+        code: Option<&AuthorizationCode>,
+        header_params: serde_json::Map<String, Value>,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        JsonWebToken::new_with_header_params(
+            Self::claims_with_hashes(claims, &alg, access_token, code)?,
+            signing_key,
+            &alg,
+            header_params,
+        )
+        .map(Self)
+    }
+
+    fn claims_with_hashes(
+        claims: IdTokenClaims<AC, GC>,
+        alg: &JS,
+        access_token: Option<&AccessToken>,
+        code: Option<&AuthorizationCode>,
+    ) -> Result<IdTokenClaims<AC, GC>, JsonWebTokenError> {
         let at_hash = access_token
-            .map(|at| {
-                AccessTokenHash::from_token(at, &alg).map_err(JsonWebTokenError::SigningError)
-            })
+            .map(|at| AccessTokenHash::from_token(at, alg).map_err(JsonWebTokenError::SigningError))
             .transpose()?
             .or_else(|| claims.access_token_hash.clone());
         let c_hash = code
             .map(|c| {
-                AuthorizationCodeHash::from_code(c, &alg).map_err(JsonWebTokenError::SigningError)
+                AuthorizationCodeHash::from_code(c, alg).map_err(JsonWebTokenError::SigningError)
             })
             .transpose()?
             .or_else(|| claims.code_hash.clone());
+        Ok(IdTokenClaims {
+            access_token_hash: at_hash,
+            code_hash: c_hash,
+            ..claims
+        })
+    }
 
-        JsonWebToken::new(
-            IdTokenClaims {
-                access_token_hash: at_hash,
-                code_hash: c_hash,
-                ..claims
-            },
-            signing_key,
-            &alg,
-        )
-        .map(Self)
+    ///
+    /// Encrypts this signed ID token using the given key and algorithms, returning the resulting
+    /// nested JWT in JWE Compact Serialization.
+    ///
+    /// As described in
+    /// [Section 10.2](https://openid.net/specs/openid-connect-core-1_0.html#Encryption) of the
+    /// OpenID Connect Core spec, ID tokens are signed and then encrypted. Providers should encrypt
+    /// ID tokens using the client's registered public encryption key along with the
+    /// `id_token_encrypted_response_alg` and `id_token_encrypted_response_enc` values from the
+    /// client's registration (see [`ClientMetadata`](crate::registration::ClientMetadata)). If
+    /// only `id_token_encrypted_response_alg` is registered, the content encryption algorithm
+    /// defaults to `A128CBC-HS256`.
+    ///
+    pub fn encrypt<JK, EK>(
+        &self,
+        encryption_key: &EK,
+        alg: &JK,
+        enc: &JE,
+    ) -> Result<String, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        EK: EncryptionKey<JE, JK, JT>,
+    {
+        self.0.encrypt(encryption_key, alg, enc)
+    }
+
+    ///
+    /// Decrypts an encrypted ID token (i.e., a nested JWT) using the client's private decryption
+    /// key, returning the inner signed ID token.
+    ///
+    /// The returned ID token must still be verified using [`claims`](Self::claims).
+    ///
+    pub fn decrypt<JK, DK>(nested_jwt: &str, decryption_key: &DK) -> Result<Self, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        DK: DecryptionKey<JE, JK, JT>,
+    {
+        JsonWebToken::decrypt(nested_jwt, decryption_key).map(Self)
     }
 
     ///
@@ -125,6 +207,57 @@ where
         verifier.verified_claims(&self.0, nonce_verifier)
     }
 
+    ///
+    /// Verifies and returns a reference to the ID token claims, along with information about the
+    /// key that verified the token's signature.
+    ///
+    /// The signature information is `None` if the verifier does not check signatures (see
+    /// [`IdTokenVerifier::insecure_disable_signature_check`]).
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn claims_with_signature<'a, JU, K, N>(
+        &'a self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+    ) -> Result<
+        (&'a IdTokenClaims<AC, GC>, Option<VerifiedSignature<JS, JT>>),
+        ClaimsVerificationError,
+    >
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        verifier.verified_claims_with_signature(&self.0, nonce_verifier)
+    }
+
+    ///
+    /// Verifies the signature of this ID token using the given keys, and returns a reference to
+    /// the ID token claims along with information about the verified signature (i.e., the `alg`
+    /// and `kid` header fields and the type of the key that verified the signature).
+    ///
+    /// # Security Warning
+    ///
+    /// This function does **not** validate any claims, including the issuer, audiences,
+    /// expiration time, or nonce. The returned claims must be validated separately, such as by
+    /// using a [`ClaimsValidator`](crate::ClaimsValidator). To verify the signature and claims
+    /// together, use [`IdToken::claims`] instead.
+    ///
+    /// Any signature algorithm supported by one of the given keys is accepted. Signatures using a
+    /// shared secret (e.g., `HS256`) and unsigned ID tokens are rejected.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn verify_signature<JU, K>(
+        &self,
+        signature_keys: &JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Result<(&IdTokenClaims<AC, GC>, VerifiedSignature<JS, JT>), ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verify_signature(&self.0, signature_keys)
+    }
+
     ///
     /// Verifies and returns the ID token claims.
     ///
@@ -141,6 +274,58 @@ where
         verifier.verified_claims_owned(self.0, nonce_verifier)
     }
 
+    ///
+    /// Verifies the ID token claims and returns the full claims payload as a JSON value.
+    ///
+    /// This is useful for accessing custom claims without defining an [`AdditionalClaims`]
+    /// implementation. The claims are verified exactly as in [`IdToken::claims`] before the
+    /// payload is returned.
+    ///
+    pub fn claims_raw<JU, K, N>(
+        &self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+        nonce_verifier: N,
+    ) -> Result<serde_json::Value, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        N: NonceVerifier,
+    {
+        self.claims(verifier, nonce_verifier)?;
+        serde_json::from_slice(self.0.raw_payload())
+            .map_err(|err| ClaimsVerificationError::MalformedClaims(err.to_string()))
+    }
+
+    ///
+    /// Returns the JOSE header of this ID token **without verifying its signature**.
+    ///
+    /// # Security Warning
+    ///
+    /// The header may have been forged, so it must only be used to decide how to verify the
+    /// token (e.g., selecting a key or tenant configuration based on the `kid` field) or for
+    /// debugging. Use [`IdToken::claims`] to verify the token.
+    ///
+    pub fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        self.0.unverified_header()
+    }
+
+    ///
+    /// Returns the ID token claims **without verifying the token's signature or claims**.
+    ///
+    /// This is useful for routing a token to the appropriate verifier before verifying it, such
+    /// as choosing a tenant configuration in a multi-tenant application based on the `iss` claim.
+    /// This function returns [`ClaimsVerificationError::MalformedClaims`] if the claims cannot
+    /// be decoded.
+    ///
+    /// # Security Warning
+    ///
+    /// The claims may have been forged, so they must only be used to decide how to verify the
+    /// token or for debugging. Use [`IdToken::claims`] to verify the token.
+    ///
+    pub fn unverified_claims(&self) -> Result<&IdTokenClaims<AC, GC>, ClaimsVerificationError> {
+        self.0.unverified_payload_ref()
+    }
+
     ///
     /// Returns the [`JwsSigningAlgorithm`] used to sign this ID token.
     ///
@@ -322,6 +507,13 @@ where
         }
     ];
 
+    ///
+    /// Returns the standard claims as a `StandardClaims` object.
+    ///
+    pub fn standard_claims(&self) -> &StandardClaims<GC> {
+        &self.standard_claims
+    }
+
     ///
     /// Returns additional ID token claims.
     ///
@@ -436,29 +628,87 @@ where
 {
 }
 
+///
+/// Widely-used provider-specific extensions to the OAuth2 token response.
+///
+/// This type may be used as the `EF` type parameter of [`IdTokenFields`] (see
+/// [`CoreExtendedTokenResponse`][crate::core::CoreExtendedTokenResponse]) to expose the following
+/// fields, which are ignored by [`EmptyExtraTokenFields`][crate::EmptyExtraTokenFields]:
+///  * `refresh_expires_in`: lifetime of the refresh token, as returned by Keycloak.
+///  * `ext_expires_in`: extended lifetime of the access token, as returned by Azure AD.
+///
+/// Both fields are accepted as either JSON numbers or numeric strings.
+///
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ExtendedTokenFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
+    refresh_expires_in: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<PickFirst<(_, DisplayFromStr)>>")]
+    ext_expires_in: Option<u64>,
+}
+impl ExtendedTokenFields {
+    ///
+    /// Returns the lifetime of the refresh token (`refresh_expires_in`), if provided.
+    ///
+    pub fn refresh_expires_in(&self) -> Option<Duration> {
+        self.refresh_expires_in.map(Duration::from_secs)
+    }
+    ///
+    /// Sets the lifetime of the refresh token (`refresh_expires_in`).
+    ///
+    pub fn set_refresh_expires_in(&mut self, refresh_expires_in: Option<&Duration>) {
+        self.refresh_expires_in = refresh_expires_in.map(Duration::as_secs);
+    }
+
+    ///
+    /// Returns the extended lifetime of the access token (`ext_expires_in`), if provided.
+    ///
+    /// Azure AD uses this value to indicate how long the access token remains usable while the
+    /// token service is unavailable.
+    ///
+    pub fn ext_expires_in(&self) -> Option<Duration> {
+        self.ext_expires_in.map(Duration::from_secs)
+    }
+    ///
+    /// Sets the extended lifetime of the access token (`ext_expires_in`).
+    ///
+    pub fn set_ext_expires_in(&mut self, ext_expires_in: Option<&Duration>) {
+        self.ext_expires_in = ext_expires_in.map(Duration::as_secs);
+    }
+}
+impl ExtraTokenFields for ExtendedTokenFields {}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::marker::PhantomData;
     use std::str::FromStr;
+    use std::time::Duration;
 
     use chrono::{TimeZone, Utc};
     use oauth2::basic::BasicTokenType;
     use oauth2::{ClientId, TokenResponse};
     use url::Url;
 
-    use crate::claims::{AdditionalClaims, EmptyAdditionalClaims, StandardClaims};
-    use crate::core::{CoreGenderClaim, CoreIdToken, CoreIdTokenClaims, CoreTokenResponse};
+    use crate::claims::{AdditionalClaims, AllOtherClaims, EmptyAdditionalClaims, StandardClaims};
+    use crate::core::{
+        CoreExtendedTokenResponse, CoreGenderClaim, CoreIdToken, CoreIdTokenClaims,
+        CoreIdTokenVerifier, CoreJwsSigningAlgorithm, CoreTokenResponse,
+    };
     use crate::jwt::JsonWebTokenAccess;
     use crate::{
         AccessTokenHash, AddressClaim, AddressCountry, AddressLocality, AddressPostalCode,
         AddressRegion, Audience, AuthenticationContextClass, AuthenticationMethodReference,
-        AuthorizationCodeHash, EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName,
-        EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl,
-        EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, FormattedAddress,
-        IssuerUrl, LanguageTag, Nonce, StreetAddress, SubjectIdentifier,
+        AuthorizationCodeHash, ClaimsVerificationError, EndUserBirthday, EndUserEmail,
+        EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
+        EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
+        EndUserWebsiteUrl, FormattedAddress, IssuerUrl, JsonWebTokenAlgorithm, LanguageTag, Nonce,
+        StreetAddress, SubjectIdentifier,
     };
 
-    use super::{AudiencesClaim, IdTokenClaims, IssuerClaim};
+    use super::{AudiencesClaim, ExtendedTokenFields, IdTokenClaims, IssuerClaim};
 
     #[test]
     fn test_id_token() {
@@ -473,8 +723,16 @@ mod tests {
         // `serde::Deserialize` implementation is tested within the `FromStr` implementation
         let id_token = CoreIdToken::from_str(ID_TOKEN).expect("failed to parse id_token");
 
-        let claims = id_token.0.unverified_payload_ref();
+        let claims = id_token.unverified_claims().unwrap();
 
+        assert_eq!(
+            id_token.unverified_header().alg,
+            JsonWebTokenAlgorithm::Signature(
+                CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                PhantomData
+            )
+        );
+        assert_eq!(id_token.unverified_header().kid, None);
         assert_eq!(
             *claims.issuer().url(),
             Url::parse("https://server.example.com").unwrap()
@@ -508,6 +766,34 @@ mod tests {
         assert_eq!(de, format!("\"{}\"", ID_TOKEN));
     }
 
+    #[test]
+    fn test_id_token_claims_raw() {
+        static ID_TOKEN: &str = concat!(
+            "eyJhbGciOiJSUzI1NiJ9.",
+            "eyJpc3MiOiJodHRwczovL3NlcnZlci5leGFtcGxlLmNvbSIsImF1ZCI6WyJzNkJoZ",
+            "FJrcXQzIl0sImV4cCI6MTMxMTI4MTk3MCwiaWF0IjoxMzExMjgwOTcwLCJzdWIiOi",
+            "IyNDQwMDMyMCIsInRmYV9tZXRob2QiOiJ1MmYifQ.",
+            "aW52YWxpZF9zaWduYXR1cmU"
+        );
+
+        let id_token = CoreIdToken::from_str(ID_TOKEN).expect("failed to parse id_token");
+        let verifier = CoreIdTokenVerifier::new_insecure_without_verification()
+            .set_time_fn(|| Utc.timestamp_opt(1311281000, 0).unwrap());
+
+        let claims = id_token
+            .claims_raw(&verifier, |_: Option<&Nonce>| Ok(()))
+            .expect("failed to verify claims");
+        assert_eq!(claims["sub"], "24400320");
+        assert_eq!(claims["tfa_method"], "u2f");
+
+        // Claims are still verified before being returned.
+        let expired_verifier = CoreIdTokenVerifier::new_insecure_without_verification();
+        match id_token.claims_raw(&expired_verifier, |_: Option<&Nonce>| Ok(())) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_oauth2_response() {
         let response_str = "{\
@@ -524,7 +810,7 @@ mod tests {
         assert_eq!(*response.token_type(), BasicTokenType::Bearer);
 
         let id_token = response.extra_fields().id_token();
-        let claims = id_token.unwrap().0.unverified_payload_ref();
+        let claims = id_token.unwrap().0.unverified_payload_ref().unwrap();
 
         assert_eq!(
             *claims.issuer().url(),
@@ -557,6 +843,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extended_token_fields() {
+        let response_str = "{\
+            \"access_token\":\"foobar\",\
+            \"token_type\":\"bearer\",\
+            \"expires_in\":300,\
+            \"refresh_expires_in\":1800,\
+            \"ext_expires_in\":\"3600\"\
+        }";
+        let response = serde_json::from_str::<CoreExtendedTokenResponse>(response_str)
+            .expect("failed to deserialize");
+
+        assert_eq!(response.expires_in(), Some(Duration::from_secs(300)));
+        assert_eq!(response.extra_fields().id_token(), None);
+        let extra_fields = response.extra_fields().extra_fields();
+        assert_eq!(
+            extra_fields.refresh_expires_in(),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(
+            extra_fields.ext_expires_in(),
+            Some(Duration::from_secs(3600))
+        );
+
+        assert_eq!(
+            serde_json::to_string(&response).expect("failed to serialize"),
+            "{\
+                \"access_token\":\"foobar\",\
+                \"token_type\":\"bearer\",\
+                \"expires_in\":300,\
+                \"id_token\":null,\
+                \"refresh_expires_in\":1800,\
+                \"ext_expires_in\":3600\
+            }"
+        );
+
+        let mut extra_fields = ExtendedTokenFields::default();
+        assert_eq!(extra_fields.refresh_expires_in(), None);
+        assert_eq!(extra_fields.ext_expires_in(), None);
+        assert_eq!(serde_json::to_string(&extra_fields).unwrap(), "{}");
+        extra_fields.set_refresh_expires_in(Some(&Duration::from_secs(60)));
+        assert_eq!(
+            serde_json::to_string(&extra_fields).unwrap(),
+            "{\"refresh_expires_in\":60}"
+        );
+    }
+
     #[test]
     fn test_minimal_claims_serde() {
         let new_claims = CoreIdTokenClaims::new(
@@ -1087,10 +1420,6 @@ mod tests {
         .expect_err("missing claim should fail to deserialize");
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
-    struct AllOtherClaims(HashMap<String, serde_json::Value>);
-    impl AdditionalClaims for AllOtherClaims {}
-
     #[test]
     fn test_catch_all_additional_claims() {
         let claims = serde_json::from_str::<IdTokenClaims<AllOtherClaims, CoreGenderClaim>>(
@@ -1106,8 +1435,28 @@ mod tests {
         )
         .expect("failed to deserialize");
 
-        assert_eq!(claims.additional_claims().0.len(), 1);
-        assert_eq!(claims.additional_claims().0["tfa_method"], "u2f");
+        assert_eq!(claims.additional_claims().len(), 1);
+        assert_eq!(claims.additional_claims()["tfa_method"], "u2f");
+    }
+
+    #[test]
+    fn test_verified_phone_number() {
+        let phone_number = EndUserPhoneNumber::new("+16045551234".to_string());
+        let claims =
+            StandardClaims::<CoreGenderClaim>::new(SubjectIdentifier::new("24400320".to_string()));
+        assert_eq!(claims.verified_phone_number(), None);
+
+        let claims = claims.set_phone_number_with_verification(Some(phone_number.clone()), false);
+        assert_eq!(claims.phone_number(), Some(&phone_number));
+        assert_eq!(claims.phone_number_verified(), Some(false));
+        assert_eq!(claims.verified_phone_number(), None);
+
+        let claims = claims.set_phone_number_with_verification(Some(phone_number.clone()), true);
+        assert_eq!(claims.verified_phone_number(), Some(&phone_number));
+
+        let claims = claims.set_phone_number_with_verification(None, true);
+        assert_eq!(claims.phone_number(), None);
+        assert_eq!(claims.phone_number_verified(), None);
     }
 
     #[test]
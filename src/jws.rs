@@ -0,0 +1,441 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::jwt::{JsonWebTokenJsonPayloadSerde, JsonWebTokenPayloadSerde};
+use crate::{
+    ClaimsVerificationError, JsonWebKey, JsonWebKeyId, JsonWebKeySet, JsonWebKeyType,
+    JsonWebKeyUse, JsonWebTokenAlgorithm, JsonWebTokenError, JsonWebTokenHeader,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, PrivateSigningKey,
+    SignatureVerificationError,
+};
+
+// Raw JWS JSON Serialization (see Section 7.2 of RFC 7515). The flattened syntax places the members
+// of a single signature alongside the payload instead of in a `signatures` array.
+#[derive(Deserialize, Serialize)]
+struct RawJsonWebSignature {
+    payload: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signatures: Option<Vec<RawSignature>>,
+    #[serde(flatten)]
+    flattened: Option<RawSignature>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct RawSignature {
+    protected: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    header: Option<serde_json::Map<String, serde_json::Value>>,
+    signature: String,
+}
+
+///
+/// Signature of a [`JsonWebSignature`], along with its JOSE headers.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonWebSignatureEntry<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    protected_header: JsonWebTokenHeader<JE, JS, JT>,
+    protected_base64: String,
+    unprotected_header: Option<serde_json::Map<String, serde_json::Value>>,
+    signature: Vec<u8>,
+}
+impl<JE, JS, JT> JsonWebSignatureEntry<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Returns the integrity-protected JOSE header (`protected`).
+    ///
+    pub fn protected_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        &self.protected_header
+    }
+
+    ///
+    /// Returns the unprotected JOSE header (`header`), if any.
+    ///
+    pub fn unprotected_header(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.unprotected_header.as_ref()
+    }
+
+    ///
+    /// Returns the ID of the key used to create this signature, taken from the protected header
+    /// or, if absent there, the unprotected header.
+    ///
+    pub fn key_id(&self) -> Option<JsonWebKeyId> {
+        self.protected_header.kid.clone().or_else(|| {
+            self.unprotected_header
+                .as_ref()
+                .and_then(|header| header.get("kid"))
+                .and_then(|kid| kid.as_str())
+                .map(|kid| JsonWebKeyId::new(kid.to_string()))
+        })
+    }
+
+    fn to_raw(&self) -> RawSignature {
+        RawSignature {
+            protected: self.protected_base64.clone(),
+            header: self.unprotected_header.clone(),
+            signature: base64::encode_config(&self.signature, base64::URL_SAFE_NO_PAD),
+        }
+    }
+}
+
+///
+/// JSON Web Signature (JWS) using the JWS JSON Serialization defined in
+/// [Section 7.2 of RFC 7515](https://tools.ietf.org/html/rfc7515#section-7.2).
+///
+/// Unlike a [`JsonWebToken`](crate::JsonWebToken), which uses the compact serialization, the
+/// same payload may be signed by multiple keys (e.g., for federation entity statements or
+/// during key rotation). Tokens are serialized using the general syntax, and either the general
+/// or flattened syntax is accepted during deserialization. Use
+/// [`to_flattened_json`](Self::to_flattened_json) to produce the flattened syntax.
+///
+/// [`claims`](Self::claims) accepts the payload if any one of the signatures is valid.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonWebSignature<JE, JS, JT, P, S = JsonWebTokenJsonPayloadSerde>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    payload: P,
+    payload_base64: String,
+    signatures: Vec<JsonWebSignatureEntry<JE, JS, JT>>,
+    _phantom: PhantomData<S>,
+}
+impl<JE, JS, JT, P, S> JsonWebSignature<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    ///
+    /// Instantiates an unsigned JWS with the given payload.
+    ///
+    /// Use [`sign`](Self::sign) to add one or more signatures before serializing it.
+    ///
+    pub fn new(payload: P) -> Result<Self, JsonWebTokenError> {
+        let serialized_payload =
+            S::serialize(&payload).map_err(JsonWebTokenError::SerializationError)?;
+        Ok(Self {
+            payload,
+            payload_base64: base64::encode_config(serialized_payload, base64::URL_SAFE_NO_PAD),
+            signatures: Vec::new(),
+            _phantom: PhantomData,
+        })
+    }
+
+    ///
+    /// Adds a signature using the specified key and algorithm.
+    ///
+    /// The protected header includes the signing key's ID (if any).
+    ///
+    pub fn sign<JU, K, SK>(mut self, signing_key: &SK, alg: &JS) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        SK: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let protected_header = JsonWebTokenHeader::new(
+            JsonWebTokenAlgorithm::Signature(alg.clone(), PhantomData),
+            signing_key.as_verification_key().key_id().cloned(),
+        );
+        let protected_base64 = base64::encode_config(
+            serde_json::to_string(&protected_header)
+                .map_err(JsonWebTokenError::SerializationError)?,
+            base64::URL_SAFE_NO_PAD,
+        );
+        let signature = signing_key
+            .sign(
+                alg,
+                format!("{}.{}", protected_base64, self.payload_base64).as_bytes(),
+            )
+            .map_err(JsonWebTokenError::SigningError)?;
+        self.signatures.push(JsonWebSignatureEntry {
+            protected_header,
+            protected_base64,
+            unprotected_header: None,
+            signature,
+        });
+        Ok(self)
+    }
+
+    ///
+    /// Returns the signatures of this JWS.
+    ///
+    pub fn signatures(&self) -> &[JsonWebSignatureEntry<JE, JS, JT>] {
+        &self.signatures
+    }
+
+    ///
+    /// Returns the payload of this JWS **without verifying any of its signatures**.
+    ///
+    pub fn unverified_claims(&self) -> &P {
+        &self.payload
+    }
+
+    ///
+    /// Returns the payload of this JWS if at least one of its signatures was created by a key in
+    /// the given key set using one of the allowed algorithms.
+    ///
+    /// Signatures with unsupported critical header parameters (`crit`) are ignored.
+    ///
+    pub fn claims<JU, K>(
+        &self,
+        keys: &JsonWebKeySet<JS, JT, JU, K>,
+        allowed_algs: &[JS],
+    ) -> Result<&P, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        let mut last_err = SignatureVerificationError::NoMatchingKey;
+        for entry in &self.signatures {
+            let alg = match entry.protected_header.alg {
+                JsonWebTokenAlgorithm::Signature(ref alg, _) if allowed_algs.contains(alg) => alg,
+                ref other => {
+                    last_err = SignatureVerificationError::DisallowedAlg(
+                        serde_plain::to_string(other).unwrap_or_else(|_| format!("{:?}", other)),
+                    );
+                    continue;
+                }
+            };
+            if entry.protected_header.crit.is_some() {
+                last_err = SignatureVerificationError::Other(
+                    "critical JWT header fields are unsupported".to_string(),
+                );
+                continue;
+            }
+
+            let kid = entry.key_id();
+            let signing_input = format!("{}.{}", entry.protected_base64, self.payload_base64);
+            for key in keys.signing_keys_for_alg(alg, kid.as_ref()) {
+                match key.verify_signature(alg, signing_input.as_bytes(), &entry.signature) {
+                    Ok(()) => return Ok(&self.payload),
+                    Err(err) => last_err = err,
+                }
+            }
+        }
+        Err(ClaimsVerificationError::SignatureVerification(last_err))
+    }
+
+    ///
+    /// Returns this JWS using the flattened JWS JSON Serialization syntax, or `None` if it does
+    /// not have exactly one signature.
+    ///
+    pub fn to_flattened_json(&self) -> Option<serde_json::Value> {
+        match self.signatures.as_slice() {
+            [entry] => Some(
+                serde_json::to_value(RawJsonWebSignature {
+                    payload: self.payload_base64.clone(),
+                    signatures: None,
+                    flattened: Some(entry.to_raw()),
+                })
+                .expect("failed to serialize JWS"),
+            ),
+            _ => None,
+        }
+    }
+}
+impl<JE, JS, JT, P, S> Serialize for JsonWebSignature<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    fn serialize<SE>(&self, serializer: SE) -> Result<SE::Ok, SE::Error>
+    where
+        SE: Serializer,
+    {
+        RawJsonWebSignature {
+            payload: self.payload_base64.clone(),
+            signatures: Some(
+                self.signatures
+                    .iter()
+                    .map(JsonWebSignatureEntry::to_raw)
+                    .collect(),
+            ),
+            flattened: None,
+        }
+        .serialize(serializer)
+    }
+}
+impl<'de, JE, JS, JT, P, S> Deserialize<'de> for JsonWebSignature<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawJsonWebSignature::deserialize(deserializer)?;
+        let raw_signatures = match (raw.signatures, raw.flattened) {
+            (Some(signatures), None) => signatures,
+            (None, Some(signature)) => vec![signature],
+            _ => {
+                return Err(D::Error::custom(
+                    "JWS must use either the general or the flattened JSON syntax",
+                ))
+            }
+        };
+
+        let raw_payload =
+            base64::decode_config(&raw.payload, crate::core::base64_url_safe_no_pad()).map_err(
+                |err| D::Error::custom(format!("Invalid base64url payload encoding: {:?}", err)),
+            )?;
+        let payload = S::deserialize::<D::Error>(&raw_payload)?;
+
+        let signatures = raw_signatures
+            .into_iter()
+            .map(|raw_signature| {
+                let protected_json = base64::decode_config(
+                    &raw_signature.protected,
+                    crate::core::base64_url_safe_no_pad(),
+                )
+                .map_err(|err| {
+                    D::Error::custom(format!("Invalid base64url header encoding: {:?}", err))
+                })?;
+                Ok(JsonWebSignatureEntry {
+                    protected_header: serde_json::from_slice(&protected_json).map_err(|err| {
+                        D::Error::custom(format!("Failed to parse header JSON: {:?}", err))
+                    })?,
+                    protected_base64: raw_signature.protected,
+                    unprotected_header: raw_signature.header,
+                    signature: base64::decode_config(
+                        &raw_signature.signature,
+                        crate::core::base64_url_safe_no_pad(),
+                    )
+                    .map_err(|err| {
+                        D::Error::custom(format!("Invalid base64url signature encoding: {:?}", err))
+                    })?,
+                })
+            })
+            .collect::<Result<Vec<_>, D::Error>>()?;
+
+        Ok(Self {
+            payload,
+            payload_base64: raw.payload,
+            signatures,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::{
+        CoreEcPrivateSigningKey, CoreJsonCurveType, CoreJsonWebSignature, CoreJwsSigningAlgorithm,
+        CoreRsaPrivateSigningKey,
+    };
+    use crate::jwt::tests::TEST_RSA_PRIV_KEY;
+    use crate::{
+        ClaimsVerificationError, JsonWebKeyId, JsonWebKeySet, PrivateSigningKey,
+        SignatureVerificationError,
+    };
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    struct EntityStatement {
+        iss: String,
+        sub: String,
+    }
+
+    #[test]
+    fn test_json_web_signature() {
+        let rsa_key = CoreRsaPrivateSigningKey::from_pem(
+            TEST_RSA_PRIV_KEY,
+            Some(JsonWebKeyId::new("rsa".to_string())),
+        )
+        .unwrap();
+        let ec_key = CoreEcPrivateSigningKey::generate(
+            CoreJsonCurveType::P256,
+            Some(JsonWebKeyId::new("ec".to_string())),
+        );
+        let statement = EntityStatement {
+            iss: "https://op.example".to_string(),
+            sub: "https://op.example".to_string(),
+        };
+        let all_algs = [
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            CoreJwsSigningAlgorithm::EcdsaP256Sha256,
+        ];
+
+        let jws = CoreJsonWebSignature::new(statement.clone())
+            .unwrap()
+            .sign(&rsa_key, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+            .unwrap()
+            .sign(&ec_key, &CoreJwsSigningAlgorithm::EcdsaP256Sha256)
+            .unwrap();
+        let serialized = serde_json::to_value(&jws).unwrap();
+        assert_eq!(serialized["signatures"].as_array().unwrap().len(), 2);
+        assert!(jws.to_flattened_json().is_none());
+
+        let parsed: CoreJsonWebSignature<EntityStatement> =
+            serde_json::from_value(serialized).unwrap();
+        assert_eq!(parsed, jws);
+        assert_eq!(parsed.signatures()[1].key_id().unwrap().as_str(), "ec");
+
+        // Any one valid signature is sufficient.
+        let ec_only = JsonWebKeySet::new(vec![ec_key.as_verification_key()]);
+        assert_eq!(parsed.claims(&ec_only, &all_algs).unwrap(), &statement);
+        let rsa_only = JsonWebKeySet::new(vec![rsa_key.as_verification_key()]);
+        assert_eq!(parsed.claims(&rsa_only, &all_algs).unwrap(), &statement);
+        match parsed.claims(&ec_only, &[CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256]) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(alg),
+            )) => assert_eq!(alg, "ES256"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Flattened syntax.
+        let single = CoreJsonWebSignature::new(statement.clone())
+            .unwrap()
+            .sign(&ec_key, &CoreJwsSigningAlgorithm::EcdsaP256Sha256)
+            .unwrap();
+        let mut flattened = single.to_flattened_json().unwrap();
+        assert!(flattened.get("signatures").is_none());
+        assert!(flattened.get("protected").is_some());
+        let parsed: CoreJsonWebSignature<EntityStatement> =
+            serde_json::from_value(flattened.clone()).unwrap();
+        assert_eq!(parsed.claims(&ec_only, &all_algs).unwrap(), &statement);
+
+        // Tampered payloads are rejected.
+        flattened["payload"] = base64::encode_config(
+            r#"{"iss":"https://evil.example","sub":"https://op.example"}"#,
+            base64::URL_SAFE_NO_PAD,
+        )
+        .into();
+        let tampered: CoreJsonWebSignature<EntityStatement> =
+            serde_json::from_value(flattened).unwrap();
+        match tampered.claims(&ec_only, &all_algs) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::CryptoError(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(
+            serde_json::from_str::<CoreJsonWebSignature<EntityStatement>>(r#"{"payload":"e30"}"#)
+                .is_err()
+        );
+    }
+}
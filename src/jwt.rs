@@ -2,26 +2,38 @@ use std::fmt::{Debug, Formatter, Result as FormatterResult};
 use std::marker::PhantomData;
 use std::ops::Deref;
 use std::str;
+use std::sync::OnceLock;
 
 use serde::de::{DeserializeOwned, Error as _, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
 use super::{
+    ClaimsVerificationError, DecryptionError, DecryptionKey, EncryptionError, EncryptionKey,
     JsonWebKey, JsonWebKeyId, JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm,
-    JwsSigningAlgorithm, PrivateSigningKey, SignatureVerificationError, SigningError,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, PrivateSigningKey, SignatureVerificationError,
+    SigningError,
 };
 
 new_type![
+    ///
+    /// Content type of a JSON Web Token (i.e., the `cty` JOSE header field).
+    ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     JsonWebTokenContentType(String)
 ];
 
 new_type![
+    ///
+    /// Media type of a JSON Web Token (i.e., the `typ` JOSE header field).
+    ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     JsonWebTokenType(String)
 ];
 
+///
+/// Algorithm used to sign or encrypt a JSON Web Token (i.e., the `alg` JOSE header field).
+///
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub enum JsonWebTokenAlgorithm<JE, JS, JT>
@@ -30,8 +42,13 @@ where
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
 {
+    ///
+    /// JSON Web Encryption (JWE) algorithm.
+    ///
     Encryption(JE),
-    // This is ugly, but we don't expose this module via the public API, so it's fine.
+    ///
+    /// JSON Web Signature (JWS) algorithm.
+    ///
     Signature(JS, PhantomData<JT>),
     ///
     /// No digital signature or MAC performed.
@@ -95,6 +112,9 @@ where
     }
 }
 
+///
+/// JOSE header of a JSON Web Token.
+///
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct JsonWebTokenHeader<JE, JS, JT>
 where
@@ -102,6 +122,9 @@ where
     JS: JwsSigningAlgorithm<JT>,
     JT: JsonWebKeyType,
 {
+    ///
+    /// Algorithm used to sign or encrypt the token (`alg`).
+    ///
     #[serde(
         bound = "JE: JweContentEncryptionAlgorithm<JT>, JS: JwsSigningAlgorithm<JT>, JT: JsonWebKeyType"
     )]
@@ -110,29 +133,91 @@ where
     // we don't understand any such extensions, we reject any JWT with this value present (the
     // spec specifically prohibits including public (standard) headers in this field).
     // See https://tools.ietf.org/html/rfc7515#section-4.1.11.
+    ///
+    /// Critical header parameters that must be understood by the recipient (`crit`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub crit: Option<Vec<String>>,
+    ///
+    /// Content type of the token's payload (`cty`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cty: Option<JsonWebTokenContentType>,
+    ///
+    /// ID of the key used to sign or encrypt the token (`kid`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kid: Option<JsonWebKeyId>,
+    ///
+    /// Media type of the token (`typ`).
+    ///
     #[serde(skip_serializing_if = "Option::is_none")]
     pub typ: Option<JsonWebTokenType>,
-    // Other JOSE header fields are omitted since the OpenID Connect spec specifically says that
-    // the "x5u", "x5c", "jku", "jwk" header parameter fields SHOULD NOT be used.
-    // See http://openid.net/specs/openid-connect-core-1_0-final.html#IDToken.
+    // Other JOSE header fields aren't exposed as typed fields since the OpenID Connect spec
+    // specifically says that the "x5u", "x5c", "jku", "jwk" header parameter fields SHOULD NOT be
+    // used. See http://openid.net/specs/openid-connect-core-1_0-final.html#IDToken. Their raw
+    // values are retained (see `additional_params`) so that critical header parameters (see
+    // `crit`) and provider-specific extensions can be processed.
+    #[serde(flatten)]
+    pub(crate) other: serde_json::Map<String, serde_json::Value>,
     #[serde(skip)]
     _phantom_jt: PhantomData<JT>,
 }
+impl<JE, JS, JT> JsonWebTokenHeader<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Returns the header parameters other than those exposed as fields of this struct (e.g.,
+    /// `x5t`, `jku`, or vendor-specific extensions), as raw JSON values.
+    ///
+    pub fn additional_params(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.other
+    }
+
+    ///
+    /// Returns the raw JSON value of the given header parameter other than those exposed as
+    /// fields of this struct, if present.
+    ///
+    pub fn additional_param(&self, name: &str) -> Option<&serde_json::Value> {
+        self.other.get(name)
+    }
 
+    pub(crate) fn new(alg: JsonWebTokenAlgorithm<JE, JS, JT>, kid: Option<JsonWebKeyId>) -> Self {
+        Self {
+            alg,
+            crit: None,
+            cty: None,
+            kid,
+            typ: None,
+            other: serde_json::Map::new(),
+            _phantom_jt: PhantomData,
+        }
+    }
+}
+
+///
+/// Encoding of a [`JsonWebToken`] payload.
+///
 pub trait JsonWebTokenPayloadSerde<P>: Debug
 where
     P: Debug + DeserializeOwned + Serialize,
 {
+    ///
+    /// Decodes the (base64url-decoded) payload.
+    ///
     fn deserialize<DE: serde::de::Error>(payload: &[u8]) -> Result<P, DE>;
+    ///
+    /// Encodes the payload prior to base64url encoding.
+    ///
     fn serialize(payload: &P) -> Result<String, serde_json::Error>;
 }
 
+///
+/// JSON encoding of a [`JsonWebToken`] payload (i.e., a JWT claims set).
+///
 #[derive(Clone, Debug, PartialEq)]
 pub struct JsonWebTokenJsonPayloadSerde;
 impl<P> JsonWebTokenPayloadSerde<P> for JsonWebTokenJsonPayloadSerde
@@ -161,21 +246,25 @@ where
     type ReturnType;
 
     fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT>;
-    fn unverified_payload(self) -> Self::ReturnType;
-    fn unverified_payload_ref(&self) -> &P;
+    fn unverified_payload(self) -> Result<Self::ReturnType, ClaimsVerificationError>;
+    fn unverified_payload_ref(&self) -> Result<&P, ClaimsVerificationError>;
+    // Like `unverified_payload_ref`, but if the payload fails to decode because its `iss` claim
+    // lacks a URL scheme, decodes it as if the issuer used the `https` scheme. This is only used
+    // when the verifier opts into issuer scheme normalization.
+    fn unverified_payload_ref_normalizing_issuer(&self) -> Result<&P, ClaimsVerificationError>;
 
     fn payload<JU, JW>(
         self,
         signature_alg: &JS,
         key: &JW,
-    ) -> Result<Self::ReturnType, SignatureVerificationError>
+    ) -> Result<Self::ReturnType, ClaimsVerificationError>
     where
         JU: JsonWebKeyUse,
         JW: JsonWebKey<JS, JT, JU>;
 }
 
 ///
-/// Error creating a JSON Web Token.
+/// Error creating or decrypting a JSON Web Token.
 ///
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -190,10 +279,72 @@ pub enum JsonWebTokenError {
     ///
     #[error("Failed to sign JWT")]
     SigningError(#[source] SigningError),
+    ///
+    /// Failed to encrypt JWT.
+    ///
+    #[error("Failed to encrypt JWT")]
+    EncryptionError(#[source] EncryptionError),
+    ///
+    /// Failed to decrypt a nested JWT.
+    ///
+    #[error("Failed to decrypt JWT")]
+    DecryptionError(#[source] DecryptionError),
+    ///
+    /// The claims are not valid for the type of JWT being created.
+    ///
+    #[error("Invalid claims: {0}")]
+    InvalidClaims(String),
+    ///
+    /// The JOSE header parameters are not valid for the JWT being created.
+    ///
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct JsonWebToken<JE, JS, JT, P, S>
+///
+/// JSON Web Token (JWT) using the JWS Compact Serialization.
+///
+/// This type can be used to sign and verify JWTs other than those defined by OpenID Connect
+/// (e.g., client assertions or application-specific tokens) using the same keys and algorithms.
+/// The payload `P` is typically a struct implementing [`Deserialize`] and [`Serialize`] for the
+/// token's claims. JWTs are parsed and serialized via [`Deserialize`] and [`Serialize`] as JSON
+/// strings (e.g., using `serde_json::from_value(serde_json::Value::String(jwt))`).
+///
+/// [`claims`](Self::claims) only verifies the signature; any claims such as `iss`, `aud`, or `exp`
+/// must be validated by the application.
+///
+/// # Example
+///
+/// ```
+/// # use openidconnect::core::{CoreHmacKey, CoreJsonWebToken, CoreJwsSigningAlgorithm};
+/// # use openidconnect::PrivateSigningKey;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Debug, Deserialize, PartialEq, Serialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// # fn main() -> Result<(), anyhow::Error> {
+/// let key = CoreHmacKey::new("secret");
+/// let jwt = CoreJsonWebToken::new(
+///     Claims { sub: "alice".to_string() },
+///     &key,
+///     &CoreJwsSigningAlgorithm::HmacSha256,
+/// )?;
+/// let serialized = serde_json::to_value(&jwt)?;
+///
+/// let parsed: CoreJsonWebToken<Claims> = serde_json::from_value(serialized)?;
+/// let claims = parsed.claims(
+///     &CoreJwsSigningAlgorithm::HmacSha256,
+///     &key.as_verification_key(),
+/// )?;
+/// assert_eq!(claims.sub, "alice");
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Clone)]
+pub struct JsonWebToken<JE, JS, JT, P, S = JsonWebTokenJsonPayloadSerde>
 where
     JE: JweContentEncryptionAlgorithm<JT>,
     JS: JwsSigningAlgorithm<JT>,
@@ -202,7 +353,10 @@ where
     S: JsonWebTokenPayloadSerde<P>,
 {
     header: JsonWebTokenHeader<JE, JS, JT>,
-    payload: P,
+    raw_payload: Vec<u8>,
+    // The payload is decoded lazily on first access, which avoids parsing the claims of tokens that
+    // are only forwarded (or rejected before their claims are needed).
+    payload: OnceLock<P>,
     signature: Vec<u8>,
     signing_input: String,
     _phantom: PhantomData<S>,
@@ -215,28 +369,89 @@ where
     P: Debug + DeserializeOwned + Serialize,
     S: JsonWebTokenPayloadSerde<P>,
 {
+    ///
+    /// Signs the given payload using the specified key and algorithm.
+    ///
+    /// The JOSE header includes the signing key's ID (if any).
+    ///
     pub fn new<JU, K, SK>(payload: P, signing_key: &SK, alg: &JS) -> Result<Self, JsonWebTokenError>
     where
         JU: JsonWebKeyUse,
         K: JsonWebKey<JS, JT, JU>,
         SK: PrivateSigningKey<JS, JT, JU, K>,
     {
-        let header = JsonWebTokenHeader::<JE, _, _> {
-            alg: JsonWebTokenAlgorithm::Signature(alg.clone(), PhantomData),
-            crit: None,
-            cty: None,
-            kid: signing_key.as_verification_key().key_id().cloned(),
-            typ: None,
-            _phantom_jt: PhantomData,
-        };
+        let header = JsonWebTokenHeader::<JE, _, _>::new(
+            JsonWebTokenAlgorithm::Signature(alg.clone(), PhantomData),
+            signing_key.as_verification_key().key_id().cloned(),
+        );
+        Self::new_with_header(payload, signing_key, alg, header)
+    }
+
+    ///
+    /// Signs the given payload using the specified key and algorithm, including the given
+    /// additional parameters in the JOSE header.
+    ///
+    /// Registered header parameters that this crate exposes as fields of [`JsonWebTokenHeader`]
+    /// (i.e., `typ`, `cty`, and `crit`) may also be specified. The `alg` and `kid` parameters are
+    /// determined by the signing algorithm and key, respectively, and must not be specified.
+    ///
+    pub fn new_with_header_params<JU, K, SK>(
+        payload: P,
+        signing_key: &SK,
+        alg: &JS,
+        header_params: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        SK: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let mut header_json = header_params;
+        for name in ["alg", "kid"] {
+            if header_json.contains_key(name) {
+                return Err(JsonWebTokenError::InvalidHeader(format!(
+                    "header parameter `{}` cannot be overridden",
+                    name
+                )));
+            }
+        }
+        header_json.insert(
+            "alg".to_string(),
+            serde_json::to_value(JsonWebTokenAlgorithm::<JE, _, _>::Signature(
+                alg.clone(),
+                PhantomData,
+            ))
+            .map_err(JsonWebTokenError::SerializationError)?,
+        );
+        if let Some(kid) = signing_key.as_verification_key().key_id() {
+            header_json.insert(
+                "kid".to_string(),
+                serde_json::Value::String(kid.to_string()),
+            );
+        }
+        let header = serde_json::from_value(serde_json::Value::Object(header_json))
+            .map_err(|err| JsonWebTokenError::InvalidHeader(err.to_string()))?;
+        Self::new_with_header(payload, signing_key, alg, header)
+    }
 
+    fn new_with_header<JU, K, SK>(
+        payload: P,
+        signing_key: &SK,
+        alg: &JS,
+        header: JsonWebTokenHeader<JE, JS, JT>,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        SK: PrivateSigningKey<JS, JT, JU, K>,
+    {
         let header_json =
             serde_json::to_string(&header).map_err(JsonWebTokenError::SerializationError)?;
         let header_base64 = base64::encode_config(header_json, base64::URL_SAFE_NO_PAD);
 
         let serialized_payload =
             S::serialize(&payload).map_err(JsonWebTokenError::SerializationError)?;
-        let payload_base64 = base64::encode_config(serialized_payload, base64::URL_SAFE_NO_PAD);
+        let payload_base64 = base64::encode_config(&serialized_payload, base64::URL_SAFE_NO_PAD);
 
         let signing_input = format!("{}.{}", header_base64, payload_base64);
 
@@ -246,12 +461,277 @@ where
 
         Ok(JsonWebToken {
             header,
-            payload,
+            raw_payload: serialized_payload.into_bytes(),
+            payload: OnceLock::from(payload),
             signature,
             signing_input,
             _phantom: PhantomData,
         })
     }
+
+    ///
+    /// Returns the raw (base64-decoded but otherwise unparsed) payload of this token.
+    ///
+    pub fn raw_payload(&self) -> &[u8] {
+        &self.raw_payload
+    }
+
+    ///
+    /// Returns the JOSE header of this token.
+    ///
+    /// The header has not been verified, so the `kid` and `alg` fields may only be used to select
+    /// the key and algorithm passed to [`claims`](Self::claims), which must themselves be trusted.
+    ///
+    pub fn header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        &self.header
+    }
+
+    ///
+    /// Returns the payload of this token **without verifying its signature**.
+    ///
+    pub fn unverified_claims(&self) -> Result<&P, ClaimsVerificationError> {
+        self.decoded_payload()
+    }
+
+    ///
+    /// Verifies that this token was signed by the given key using the given algorithm, and
+    /// returns its payload.
+    ///
+    /// The `alg` header field must match `signature_alg`.
+    ///
+    pub fn claims<JU, JW>(
+        &self,
+        signature_alg: &JS,
+        key: &JW,
+    ) -> Result<&P, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        JW: JsonWebKey<JS, JT, JU>,
+    {
+        match self.header.alg {
+            JsonWebTokenAlgorithm::Signature(ref alg, _) if alg == signature_alg => {}
+            ref other => {
+                return Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::DisallowedAlg(format!(
+                        "expected `{}` but token uses `{}`",
+                        serde_plain::to_string(signature_alg)
+                            .unwrap_or_else(|_| format!("{:?}", signature_alg)),
+                        serde_plain::to_string(other).unwrap_or_else(|_| format!("{:?}", other)),
+                    )),
+                ))
+            }
+        }
+        self.verify_signature(signature_alg, key)?;
+        self.decoded_payload()
+    }
+
+    ///
+    /// Encrypts this signed JWT using the given key and algorithms, returning the resulting
+    /// nested JWT in JWE Compact Serialization.
+    ///
+    /// As required by [Section 5.2 of RFC 7519](https://tools.ietf.org/html/rfc7519#section-5.2),
+    /// the JWE's `cty` header parameter is set to `JWT`. Nested JWTs are always signed and then
+    /// encrypted, which is the order required by the OpenID Connect Core spec for ID tokens,
+    /// user info responses, request objects, and logout tokens.
+    ///
+    pub fn encrypt<JK, EK>(
+        &self,
+        encryption_key: &EK,
+        alg: &JK,
+        enc: &JE,
+    ) -> Result<String, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        EK: EncryptionKey<JE, JK, JT>,
+    {
+        let signature_base64 = base64::encode_config(&self.signature, base64::URL_SAFE_NO_PAD);
+        encryption_key
+            .encrypt(
+                alg,
+                enc,
+                Some("JWT"),
+                format!("{}.{}", self.signing_input, signature_base64).as_bytes(),
+            )
+            .map_err(JsonWebTokenError::EncryptionError)
+    }
+
+    ///
+    /// Encrypts this signed JWT in the same manner as [`encrypt`](Self::encrypt), compressing it
+    /// using DEFLATE (`zip=DEF`) before encryption.
+    ///
+    pub fn encrypt_compressed<JK, EK>(
+        &self,
+        encryption_key: &EK,
+        alg: &JK,
+        enc: &JE,
+    ) -> Result<String, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        EK: EncryptionKey<JE, JK, JT>,
+    {
+        let signature_base64 = base64::encode_config(&self.signature, base64::URL_SAFE_NO_PAD);
+        encryption_key
+            .encrypt_compressed(
+                alg,
+                enc,
+                Some("JWT"),
+                format!("{}.{}", self.signing_input, signature_base64).as_bytes(),
+            )
+            .map_err(JsonWebTokenError::EncryptionError)
+    }
+
+    ///
+    /// Decrypts a nested JWT (i.e., a signed JWT encrypted as a JWE) using the given key, returning
+    /// the inner signed JWT.
+    ///
+    /// The JWE's `cty` header parameter must be `JWT` (compared case-insensitively) if present.
+    /// Some providers omit it, so a JWE without a `cty` is accepted as long as its plaintext is a
+    /// signed JWT.
+    ///    /// Compressed plaintexts are decompressed up to
+    /// [`DEFAULT_MAX_DECOMPRESSED_JWE_LEN`](crate::DEFAULT_MAX_DECOMPRESSED_JWE_LEN) bytes.
+    ///
+    /// The signature of the returned JWT has **not** been verified.
+    ///
+    pub fn decrypt<JK, DK>(nested_jwt: &str, decryption_key: &DK) -> Result<Self, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        DK: DecryptionKey<JE, JK, JT>,
+    {
+        #[derive(Deserialize)]
+        struct NestedJwtHeader {
+            cty: Option<JsonWebTokenContentType>,
+        }
+
+        let header = nested_jwt
+            .split('.')
+            .next()
+            .and_then(|header| {
+                base64::decode_config(header, crate::core::base64_url_safe_no_pad()).ok()
+            })
+            .and_then(|header| serde_json::from_slice::<NestedJwtHeader>(&header).ok())
+            .ok_or_else(|| {
+                JsonWebTokenError::DecryptionError(DecryptionError::Malformed(
+                    "invalid header".to_string(),
+                ))
+            })?;
+        match header.cty {
+            Some(ref cty) if !cty.eq_ignore_ascii_case("JWT") => {
+                return Err(JsonWebTokenError::DecryptionError(
+                    DecryptionError::Malformed(format!(
+                        "expected nested JWT content type `JWT` (found `{}`)",
+                        **cty
+                    )),
+                ))
+            }
+            _ => {}
+        }
+
+        let plaintext = decryption_key
+            .decrypt(nested_jwt)
+            .map_err(JsonWebTokenError::DecryptionError)?;
+        let inner_jwt = String::from_utf8(plaintext).map_err(|_| {
+            JsonWebTokenError::DecryptionError(DecryptionError::Malformed(
+                "nested JWT is not valid UTF-8".to_string(),
+            ))
+        })?;
+        serde_json::from_value(serde_json::Value::String(inner_jwt)).map_err(|err| {
+            JsonWebTokenError::DecryptionError(DecryptionError::Malformed(format!(
+                "invalid nested JWT: {}",
+                err
+            )))
+        })
+    }
+
+    fn decoded_payload(&self) -> Result<&P, ClaimsVerificationError> {
+        if let Some(payload) = self.payload.get() {
+            return Ok(payload);
+        }
+        let payload = Self::decode_payload(&self.raw_payload)?;
+        Ok(self.payload.get_or_init(|| payload))
+    }
+
+    fn decoded_payload_normalizing_issuer(&self) -> Result<&P, ClaimsVerificationError> {
+        let err = match self.decoded_payload() {
+            Ok(payload) => return Ok(payload),
+            Err(err) => err,
+        };
+
+        // Some providers (notably Google) have historically issued tokens whose `iss` claim omits
+        // the `https://` scheme (e.g., `accounts.google.com`), which isn't a valid issuer URL.
+        let mut claims =
+            serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(&self.raw_payload)
+                .map_err(|_| err.clone())?;
+        match claims.get_mut("iss") {
+            Some(serde_json::Value::String(issuer)) if !issuer.contains("://") => {
+                *issuer = format!("https://{}", issuer);
+            }
+            _ => return Err(err),
+        }
+        let normalized_payload = serde_json::to_vec(&claims).map_err(|_| err.clone())?;
+        let payload = Self::decode_payload(&normalized_payload)?;
+        Ok(self.payload.get_or_init(|| payload))
+    }
+
+    fn into_decoded_payload(self) -> Result<P, ClaimsVerificationError> {
+        match self.payload.into_inner() {
+            Some(payload) => Ok(payload),
+            None => Self::decode_payload(&self.raw_payload),
+        }
+    }
+
+    fn decode_payload(raw_payload: &[u8]) -> Result<P, ClaimsVerificationError> {
+        S::deserialize::<serde::de::value::Error>(raw_payload)
+            .map_err(|err| ClaimsVerificationError::MalformedClaims(err.to_string()))
+    }
+
+    fn verify_signature<JU, JW>(
+        &self,
+        signature_alg: &JS,
+        key: &JW,
+    ) -> Result<(), ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        JW: JsonWebKey<JS, JT, JU>,
+    {
+        key.verify_signature(
+            signature_alg,
+            self.signing_input.as_bytes(),
+            &self.signature,
+        )
+        .map_err(ClaimsVerificationError::SignatureVerification)
+    }
+}
+impl<JE, JS, JT, P, S> Debug for JsonWebToken<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.debug_struct("JsonWebToken")
+            .field("header", &self.header)
+            .field("payload", &self.decoded_payload())
+            // Redact the signature so that logging a token doesn't leak a replayable credential.
+            .field("signature", &"[redacted]")
+            .finish_non_exhaustive()
+    }
+}
+// Two tokens are equal if they have the same encoding, regardless of whether their payloads have
+// been decoded.
+impl<JE, JS, JT, P, S> PartialEq for JsonWebToken<JE, JS, JT, P, S>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    P: Debug + DeserializeOwned + Serialize,
+    S: JsonWebTokenPayloadSerde<P>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        // The signing input is the encoded header and payload.
+        self.signing_input == other.signing_input && self.signature == other.signature
+    }
 }
 // Owned JWT.
 impl<JE, JS, JT, P, S> JsonWebTokenAccess<JE, JS, JT, P> for JsonWebToken<JE, JS, JT, P, S>
@@ -266,27 +746,26 @@ where
     fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
         &self.header
     }
-    fn unverified_payload(self) -> Self::ReturnType {
-        self.payload
+    fn unverified_payload(self) -> Result<Self::ReturnType, ClaimsVerificationError> {
+        self.into_decoded_payload()
     }
-    fn unverified_payload_ref(&self) -> &P {
-        &self.payload
+    fn unverified_payload_ref(&self) -> Result<&P, ClaimsVerificationError> {
+        self.decoded_payload()
+    }
+    fn unverified_payload_ref_normalizing_issuer(&self) -> Result<&P, ClaimsVerificationError> {
+        self.decoded_payload_normalizing_issuer()
     }
     fn payload<JU, JW>(
         self,
         signature_alg: &JS,
         key: &JW,
-    ) -> Result<Self::ReturnType, SignatureVerificationError>
+    ) -> Result<Self::ReturnType, ClaimsVerificationError>
     where
         JU: JsonWebKeyUse,
         JW: JsonWebKey<JS, JT, JU>,
     {
-        key.verify_signature(
-            signature_alg,
-            self.signing_input.as_bytes(),
-            &self.signature,
-        )?;
-        Ok(self.payload)
+        self.verify_signature(signature_alg, key)?;
+        self.into_decoded_payload()
     }
 }
 // Borrowed JWT.
@@ -302,27 +781,26 @@ where
     fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
         &self.header
     }
-    fn unverified_payload(self) -> Self::ReturnType {
-        &self.payload
+    fn unverified_payload(self) -> Result<Self::ReturnType, ClaimsVerificationError> {
+        self.decoded_payload()
+    }
+    fn unverified_payload_ref(&self) -> Result<&P, ClaimsVerificationError> {
+        self.decoded_payload()
     }
-    fn unverified_payload_ref(&self) -> &P {
-        &self.payload
+    fn unverified_payload_ref_normalizing_issuer(&self) -> Result<&P, ClaimsVerificationError> {
+        self.decoded_payload_normalizing_issuer()
     }
     fn payload<JU, JW>(
         self,
         signature_alg: &JS,
         key: &JW,
-    ) -> Result<Self::ReturnType, SignatureVerificationError>
+    ) -> Result<Self::ReturnType, ClaimsVerificationError>
     where
         JU: JsonWebKeyUse,
         JW: JsonWebKey<JS, JT, JU>,
     {
-        key.verify_signature(
-            signature_alg,
-            self.signing_input.as_bytes(),
-            &self.signature,
-        )?;
-        Ok(&self.payload)
+        self.verify_signature(signature_alg, key)?;
+        self.decoded_payload()
     }
 }
 impl<'de, JE, JS, JT, P, S> Deserialize<'de> for JsonWebToken<JE, JS, JT, P, S>
@@ -370,7 +848,7 @@ where
             {
                 let raw_token = v.to_string();
                 let header: JsonWebTokenHeader<JE, JS, JT>;
-                let payload: P;
+                let raw_payload;
                 let signature;
                 let signing_input;
 
@@ -395,12 +873,12 @@ where
                         DE::custom(format!("Failed to parse header JSON: {:?}", err))
                     })?;
 
-                    let raw_payload =
+                    // The payload is decoded lazily (see `JsonWebToken::decoded_payload`).
+                    raw_payload =
                         base64::decode_config(parts[1], crate::core::base64_url_safe_no_pad())
                             .map_err(|err| {
                                 DE::custom(format!("Invalid base64url payload encoding: {:?}", err))
                             })?;
-                    payload = S::deserialize::<DE>(&raw_payload)?;
 
                     signature =
                         base64::decode_config(parts[2], crate::core::base64_url_safe_no_pad())
@@ -416,7 +894,8 @@ where
 
                 Ok(JsonWebToken {
                     header,
-                    payload,
+                    raw_payload,
+                    payload: OnceLock::new(),
                     signature,
                     signing_input,
                     _phantom: PhantomData,
@@ -458,11 +937,11 @@ pub mod tests {
         CoreJsonWebKey, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
         CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
     };
-    use crate::JsonWebKeyId;
+    use crate::{ClaimsVerificationError, JsonWebKey, JsonWebKeyId, SignatureVerificationError};
 
     use super::{
-        JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenJsonPayloadSerde,
-        JsonWebTokenPayloadSerde,
+        JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenError,
+        JsonWebTokenJsonPayloadSerde, JsonWebTokenPayloadSerde,
     };
 
     type CoreAlgorithm = JsonWebTokenAlgorithm<
@@ -661,7 +1140,10 @@ pub mod tests {
                 );
                 assert_eq!(header.typ, None);
             }
-            assert_eq!(jwt_access.unverified_payload_ref(), expected_payload);
+            assert_eq!(
+                jwt_access.unverified_payload_ref().unwrap(),
+                expected_payload
+            );
 
             assert_eq!(
                 jwt_access
@@ -690,7 +1172,10 @@ pub mod tests {
         );
 
         verify_jwt(&jwt, &key, TEST_JWT_PAYLOAD);
-        assert_eq!((&jwt).unverified_payload(), TEST_JWT_PAYLOAD);
+        assert_eq!((&jwt).unverified_payload().unwrap(), TEST_JWT_PAYLOAD);
+
+        let signature = TEST_JWT.rsplit('.').next().unwrap();
+        assert!(!format!("{:?}", jwt).contains(signature));
 
         verify_jwt(jwt, &key, TEST_JWT_PAYLOAD);
     }
@@ -722,6 +1207,202 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_public_jwt_api() {
+        use crate::core::{CoreHmacKey, CoreJsonWebToken};
+        use crate::PrivateSigningKey;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct AssertionClaims {
+            iss: String,
+            jti: String,
+        }
+
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(
+            TEST_RSA_PRIV_KEY,
+            Some(JsonWebKeyId::new("signing-key".to_string())),
+        )
+        .unwrap();
+        let verification_key = signing_key.as_verification_key();
+        let jwt = CoreJsonWebToken::new(
+            AssertionClaims {
+                iss: "client".to_string(),
+                jti: "abc".to_string(),
+            },
+            &signing_key,
+            &CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+        )
+        .unwrap();
+
+        let parsed: CoreJsonWebToken<AssertionClaims> =
+            serde_json::from_value(serde_json::to_value(&jwt).unwrap()).unwrap();
+        assert_eq!(parsed, jwt);
+        assert_eq!(
+            parsed.header().alg,
+            CoreAlgorithm::Signature(CoreJwsSigningAlgorithm::RsaSsaPssSha256, PhantomData)
+        );
+        assert_eq!(parsed.header().kid.as_ref(), verification_key.key_id());
+        assert_eq!(parsed.unverified_claims().unwrap().jti, "abc");
+        assert_eq!(
+            parsed
+                .claims(&CoreJwsSigningAlgorithm::RsaSsaPssSha256, &verification_key)
+                .unwrap(),
+            &AssertionClaims {
+                iss: "client".to_string(),
+                jti: "abc".to_string(),
+            }
+        );
+
+        // The expected algorithm must match the header.
+        match parsed.claims(
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            &verification_key,
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(msg),
+            )) => assert_eq!(msg, "expected `RS256` but token uses `PS256`"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // The token must be signed by the given key.
+        parsed
+            .claims(
+                &CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+                &CoreHmacKey::new("secret").as_verification_key(),
+            )
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_nested_jwt() {
+        use crate::core::{
+            CoreEcPrivateSigningKey, CoreJsonCurveType, CoreJsonWebKeyUse, CoreJsonWebToken,
+            CoreJweKeyManagementAlgorithm,
+        };
+        use crate::{DecryptionError, EncryptionKey, PrivateSigningKey};
+
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let jwt = CoreJsonWebToken::new(
+            "payload".to_string(),
+            &signing_key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+
+        let decryption_key = CoreEcPrivateSigningKey::generate(CoreJsonCurveType::P256, None);
+        let encryption_key = CoreJsonWebKey {
+            use_: Some(CoreJsonWebKeyUse::Encryption),
+            ..decryption_key.as_verification_key()
+        };
+        let nested_jwt = jwt
+            .encrypt(
+                &encryption_key,
+                &CoreJweKeyManagementAlgorithm::EcdhEsAesKeyWrap256,
+                &CoreJweContentEncryptionAlgorithm::Aes256Gcm,
+            )
+            .unwrap();
+        assert_eq!(nested_jwt.split('.').count(), 5);
+
+        let decrypted: CoreJsonWebToken<String> =
+            JsonWebToken::decrypt(&nested_jwt, &decryption_key).unwrap();
+        assert_eq!(decrypted, jwt);
+        assert_eq!(
+            decrypted
+                .claims(
+                    &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    &signing_key.as_verification_key()
+                )
+                .unwrap(),
+            "payload"
+        );
+
+        // The plaintext must be a JWT.
+        let not_nested = encryption_key
+            .encrypt(
+                &CoreJweKeyManagementAlgorithm::EcdhEs,
+                &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                Some("json"),
+                b"{}",
+            )
+            .unwrap();
+        match CoreJsonWebToken::<String>::decrypt(&not_nested, &decryption_key) {
+            Err(JsonWebTokenError::DecryptionError(DecryptionError::Malformed(msg))) => {
+                assert_eq!(msg, "expected nested JWT content type `JWT` (found `json`)")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let not_nested = encryption_key
+            .encrypt(
+                &CoreJweKeyManagementAlgorithm::EcdhEs,
+                &CoreJweContentEncryptionAlgorithm::Aes128Gcm,
+                None,
+                b"{}",
+            )
+            .unwrap();
+        match CoreJsonWebToken::<String>::decrypt(&not_nested, &decryption_key) {
+            Err(JsonWebTokenError::DecryptionError(DecryptionError::Malformed(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_jwt_header_params() {
+        use crate::core::CoreJsonWebToken;
+        use crate::{JsonWebTokenType, PrivateSigningKey};
+
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(
+            TEST_RSA_PRIV_KEY,
+            Some(JsonWebKeyId::new("key1".to_string())),
+        )
+        .unwrap();
+        let header_params = serde_json::json!({"x5t": "abc", "typ": "JWT"})
+            .as_object()
+            .unwrap()
+            .clone();
+        let jwt = CoreJsonWebToken::new_with_header_params(
+            "payload".to_string(),
+            &signing_key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            header_params,
+        )
+        .unwrap();
+
+        let parsed: CoreJsonWebToken<String> =
+            serde_json::from_value(serde_json::to_value(&jwt).unwrap()).unwrap();
+        let header = parsed.unverified_header();
+        assert_eq!(header.typ, Some(JsonWebTokenType::new("JWT".to_string())));
+        assert_eq!(header.kid, Some(JsonWebKeyId::new("key1".to_string())));
+        assert_eq!(
+            header.additional_param("x5t"),
+            Some(&serde_json::Value::String("abc".to_string()))
+        );
+        assert_eq!(header.additional_params().len(), 1);
+        assert_eq!(
+            parsed
+                .claims(
+                    &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    &signing_key.as_verification_key()
+                )
+                .unwrap(),
+            "payload"
+        );
+
+        let header_params = serde_json::json!({"alg": "none"})
+            .as_object()
+            .unwrap()
+            .clone();
+        match CoreJsonWebToken::new_with_header_params(
+            "payload".to_string(),
+            &signing_key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            header_params,
+        ) {
+            Err(JsonWebTokenError::InvalidHeader(msg)) => {
+                assert_eq!(msg, "header parameter `alg` cannot be overridden")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_invalid_signature() {
         let corrupted_jwt_str = TEST_JWT
@@ -799,15 +1480,47 @@ pub mod tests {
             "Invalid base64url payload encoding",
         );
 
+        // The payload is decoded lazily, so invalid payload JSON is only detected when the
+        // payload is accessed.
+        fn expect_payload_err<I: Into<String>>(jwt_str: I, pattern: &str) {
+            let jwt = serde_json::from_value::<
+                JsonWebToken<
+                    CoreJweContentEncryptionAlgorithm,
+                    CoreJwsSigningAlgorithm,
+                    CoreJsonWebKeyType,
+                    TestPayload,
+                    JsonWebTokenJsonPayloadSerde,
+                >,
+            >(serde_json::Value::String(jwt_str.into()))
+            .expect("failed to deserialize");
+
+            let err = jwt
+                .unverified_payload_ref()
+                .expect_err("payload decoding should have failed");
+            assert!(
+                matches!(err, ClaimsVerificationError::MalformedClaims(_)),
+                "unexpected error: {:?}",
+                err
+            );
+            assert!(
+                err.to_string().contains(pattern),
+                "Error `{}` must contain string `{}`",
+                err,
+                pattern,
+            );
+            // Owned access reports the same error.
+            assert_eq!(jwt.unverified_payload().unwrap_err(), err);
+        }
+
         // Invalid payload utf-8 (after base64 decoding)
-        expect_deserialization_err(
-            format!("{}.gA.c", valid_header),
+        expect_payload_err(
+            format!("{}.gA.e2FiY30", valid_header),
             "Error(\"expected value\", line: 1, column: 1)",
         );
 
         // Invalid payload JSON
-        expect_deserialization_err(
-            format!("{}.bm90X2pzb24.c", valid_header),
+        expect_payload_err(
+            format!("{}.bm90X2pzb24.e2FiY30", valid_header),
             "Failed to parse payload JSON",
         );
 
@@ -832,6 +1545,6 @@ pub mod tests {
             valid_header, valid_body
         )))
         .expect("failed to deserialize");
-        assert_eq!(deserialized.unverified_payload().foo, "bar");
+        assert_eq!(deserialized.unverified_payload().unwrap().foo, "bar");
     }
 }
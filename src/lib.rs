@@ -38,6 +38,10 @@
 //!
 //!    Asynchronous client: [`reqwest::async_http_client`]
 //!
+//!    The clients above construct a new `reqwest` client for each request. To reuse connections
+//!    across requests, wrap a long-lived client using [`reqwest::pooled_http_client`] or
+//!    [`reqwest::pooled_async_http_client`].
+//!
 //!  * **[`curl`]**
 //!
 //!    The `curl` HTTP client only supports the synchronous HTTP client mode and can be enabled in
@@ -149,7 +153,7 @@
 //!     .add_scope(Scope::new("write".to_string()))
 //!     // Set the PKCE code challenge.
 //!     .set_pkce_challenge(pkce_challenge)
-//!     .url();
+//!     .url()?;
 //!
 //! // This is the URL you should redirect the user to, in order to trigger the authorization
 //! // process.
@@ -229,7 +233,6 @@
 //! ```rust,no_run
 //! use openidconnect::{
 //!     AuthUrl,
-//!     EmptyAdditionalProviderMetadata,
 //!     IssuerUrl,
 //!     JsonWebKeySetUrl,
 //!     ResponseTypes,
@@ -248,62 +251,66 @@
 //! use anyhow;
 //!
 //! # fn err_wrapper() -> Result<String, anyhow::Error> {
-//! let provider_metadata = CoreProviderMetadata::new(
+//! // OpenID Connect Providers may supply custom metadata by providing a struct that implements
+//! // the AdditionalProviderMetadata trait and passing it to ProviderMetadataBuilder::new. This
+//! // requires manually using the generic ProviderMetadata struct rather than the
+//! // CoreProviderMetadata type alias, however.
+//! let provider_metadata = CoreProviderMetadata::builder()
 //!     // Parameters required by the OpenID Connect Discovery spec.
-//!     IssuerUrl::new("https://accounts.example.com".to_string())?,
-//!     AuthUrl::new("https://accounts.example.com/authorize".to_string())?,
+//!     .issuer(IssuerUrl::new("https://accounts.example.com".to_string())?)
+//!     .authorization_endpoint(AuthUrl::new(
+//!         "https://accounts.example.com/authorize".to_string(),
+//!     )?)
 //!     // Use the JsonWebKeySet struct to serve the JWK Set at this URL.
-//!     JsonWebKeySetUrl::new("https://accounts.example.com/jwk".to_string())?,
+//!     .jwks_uri(JsonWebKeySetUrl::new("https://accounts.example.com/jwk".to_string())?)
 //!     // Supported response types (flows).
-//!     vec![
+//!     .response_types_supported(vec![
 //!         // Recommended: support the code flow.
 //!         ResponseTypes::new(vec![CoreResponseType::Code]),
 //!         // Optional: support the implicit flow.
 //!         ResponseTypes::new(vec![CoreResponseType::Token, CoreResponseType::IdToken])
 //!         // Other flows including hybrid flows may also be specified here.
-//!     ],
+//!     ])
 //!     // For user privacy, the Pairwise subject identifier type is preferred. This prevents
 //!     // distinct relying parties (clients) from knowing whether their users represent the same
 //!     // real identities. This identifier type is only useful for relying parties that don't
 //!     // receive the 'email', 'profile' or other personally-identifying scopes.
 //!     // The Public subject identifier type is also supported.
-//!     vec![CoreSubjectIdentifierType::Pairwise],
+//!     .subject_types_supported(vec![CoreSubjectIdentifierType::Pairwise])
 //!     // Support the RS256 signature algorithm.
-//!     vec![CoreJwsSigningAlgorithm::RsaSsaPssSha256],
-//!     // OpenID Connect Providers may supply custom metadata by providing a struct that
-//!     // implements the AdditionalProviderMetadata trait. This requires manually using the
-//!     // generic ProviderMetadata struct rather than the CoreProviderMetadata type alias,
-//!     // however.
-//!     EmptyAdditionalProviderMetadata {},
-//! )
-//! // Specify the token endpoint (required for the code flow).
-//! .set_token_endpoint(Some(TokenUrl::new("https://accounts.example.com/token".to_string())?))
-//! // Recommended: support the UserInfo endpoint.
-//! .set_userinfo_endpoint(
-//!     Some(UserInfoUrl::new("https://accounts.example.com/userinfo".to_string())?)
-//! )
-//! // Recommended: specify the supported scopes.
-//! .set_scopes_supported(Some(vec![
-//!     Scope::new("openid".to_string()),
-//!     Scope::new("email".to_string()),
-//!     Scope::new("profile".to_string()),
-//! ]))
-//! // Recommended: specify the supported ID token claims.
-//! .set_claims_supported(Some(vec![
-//!     // Providers may also define an enum instead of using CoreClaimName.
-//!     CoreClaimName::new("sub".to_string()),
-//!     CoreClaimName::new("aud".to_string()),
-//!     CoreClaimName::new("email".to_string()),
-//!     CoreClaimName::new("email_verified".to_string()),
-//!     CoreClaimName::new("exp".to_string()),
-//!     CoreClaimName::new("iat".to_string()),
-//!     CoreClaimName::new("iss".to_string()),
-//!     CoreClaimName::new("name".to_string()),
-//!     CoreClaimName::new("given_name".to_string()),
-//!     CoreClaimName::new("family_name".to_string()),
-//!     CoreClaimName::new("picture".to_string()),
-//!     CoreClaimName::new("locale".to_string()),
-//! ]));
+//!     .id_token_signing_alg_values_supported(vec![CoreJwsSigningAlgorithm::RsaSsaPssSha256])
+//!     // Returns an error if any of the required parameters above is missing.
+//!     .build()?
+//!     // Specify the token endpoint (required for the code flow).
+//!     .set_token_endpoint(Some(TokenUrl::new(
+//!         "https://accounts.example.com/token".to_string(),
+//!     )?))
+//!     // Recommended: support the UserInfo endpoint.
+//!     .set_userinfo_endpoint(
+//!         Some(UserInfoUrl::new("https://accounts.example.com/userinfo".to_string())?)
+//!     )
+//!     // Recommended: specify the supported scopes.
+//!     .set_scopes_supported(Some(vec![
+//!         Scope::new("openid".to_string()),
+//!         Scope::new("email".to_string()),
+//!         Scope::new("profile".to_string()),
+//!     ]))
+//!     // Recommended: specify the supported ID token claims.
+//!     .set_claims_supported(Some(vec![
+//!         // Providers may also define an enum instead of using CoreClaimName.
+//!         CoreClaimName::new("sub".to_string()),
+//!         CoreClaimName::new("aud".to_string()),
+//!         CoreClaimName::new("email".to_string()),
+//!         CoreClaimName::new("email_verified".to_string()),
+//!         CoreClaimName::new("exp".to_string()),
+//!         CoreClaimName::new("iat".to_string()),
+//!         CoreClaimName::new("iss".to_string()),
+//!         CoreClaimName::new("name".to_string()),
+//!         CoreClaimName::new("given_name".to_string()),
+//!         CoreClaimName::new("family_name".to_string()),
+//!         CoreClaimName::new("picture".to_string()),
+//!         CoreClaimName::new("locale".to_string()),
+//!     ]));
 //!
 //! serde_json::to_string(&provider_metadata).map_err(From::from)
 //! # }
@@ -517,7 +524,7 @@
 //!     .add_scope(Scope::new("write".to_string()))
 //!     // Set the PKCE code challenge.
 //!     .set_pkce_challenge(pkce_challenge)
-//!     .url();
+//!     .url()?;
 //!
 //! // This is the URL you should redirect the user to, in order to trigger the authorization
 //! // process.
@@ -580,10 +587,13 @@ use oauth2::ResponseType as OAuth2ResponseType;
 use url::Url;
 
 use std::borrow::Cow;
+use std::future::Future;
 use std::marker::PhantomData;
 use std::str;
 use std::time::Duration;
 
+use thiserror::Error;
+
 pub use oauth2::{
     AccessToken, AuthType, AuthUrl, AuthorizationCode, ClientCredentialsTokenRequest, ClientId,
     ClientSecret, CodeTokenRequest, ConfigurationError, CsrfToken, DeviceAccessTokenRequest,
@@ -612,46 +622,77 @@ pub use oauth2::curl;
 compile_error!("wasm32 is not supported with the `curl` feature. Use the `reqwest` backend or a custom backend for wasm32 support");
 
 #[cfg(feature = "reqwest")]
-pub use oauth2::reqwest;
+pub mod reqwest;
 
 #[cfg(feature = "ureq")]
 pub use oauth2::ureq;
 
+pub use access_token::{JwtAccessToken, JwtAccessTokenClaims};
+pub use authorization_response::{AuthorizationResponseError, ParsedAuthorizationResponse};
 pub use claims::{
-    AdditionalClaims, AddressClaim, EmptyAdditionalClaims, GenderClaim, StandardClaims,
+    AdditionalClaims, AddressClaim, AllOtherClaims, EmptyAdditionalClaims, GenderClaim,
+    MergedClaims, StandardClaims,
 };
+pub use client_builder::{ClientBuilder, ClientBuilderError};
+pub use client_config::{ClientConfig, ClientConfigError};
 pub use discovery::{
-    AdditionalProviderMetadata, DiscoveryError, EmptyAdditionalProviderMetadata, ProviderMetadata,
+    AdditionalProviderMetadata, DiscoveryCache, DiscoveryError, EmptyAdditionalProviderMetadata,
+    IssuerMismatchPolicy, ProviderMetadata, ProviderMetadataBuilder, ProviderMetadataBuilderError,
+    ProviderMetadataSnapshot, ProviderMetadataWarning, SignedMetadataError,
 };
-pub use id_token::IdTokenFields;
+pub use id_token::{ExtendedTokenFields, IdTokenFields};
 pub use id_token::{IdToken, IdTokenClaims};
-pub use jwt::JsonWebTokenError;
-use jwt::{JsonWebToken, JsonWebTokenAccess, JsonWebTokenAlgorithm, JsonWebTokenHeader};
-pub use logout::{LogoutProviderMetadata, LogoutRequest, ProviderMetadataWithLogout};
+pub use jws::{JsonWebSignature, JsonWebSignatureEntry};
+use jwt::JsonWebTokenAccess;
+pub use jwt::{
+    JsonWebToken, JsonWebTokenAlgorithm, JsonWebTokenContentType, JsonWebTokenError,
+    JsonWebTokenHeader, JsonWebTokenJsonPayloadSerde, JsonWebTokenPayloadSerde, JsonWebTokenType,
+};
+pub use login::{LoginCallback, LoginError, OidcLoginHandler, PendingLogin, VerifiedSession};
+pub use logout::{
+    LogoutProviderMetadata, LogoutRequest, LogoutToken, LogoutTokenClaims,
+    ProviderMetadataWithLogout,
+};
+pub use pending_authorization::{
+    PendingAuthorization, PendingAuthorizationError, ResumedAuthorization,
+};
+pub use provider::{
+    verify_pkce_code_challenge, verify_pkce_code_verifier, AuthorizationErrorResponse,
+    PkceChallengeError, TokenRequest, TokenRequestClientAuthentication, TokenRequestError,
+    TokenRequestGrant,
+};
+pub use sealed_login::{PendingLoginSealError, PendingLoginSealer, DEFAULT_MAX_SEALED_LOGIN_LEN};
+pub use session::{SessionState, SessionStatus};
+pub use stateless::{StateToken, StatelessSigner, StatelessTokenError};
+pub use token_manager::TokenManager;
+pub use token_response::{TokenResponseValidationError, TokenResponseValidator};
 // Flatten the module hierarchy involving types. They're only separated to improve code
 // organization.
 pub use types::{
     AccessTokenHash, AddressCountry, AddressLocality, AddressPostalCode, AddressRegion,
     ApplicationType, Audience, AuthDisplay, AuthPrompt, AuthenticationContextClass,
     AuthenticationMethodReference, AuthorizationCodeHash, ClaimName, ClaimType, ClientAuthMethod,
-    ClientConfigUrl, ClientContactEmail, ClientName, ClientUrl, EndSessionUrl, EndUserBirthday,
-    EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName,
-    EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone,
-    EndUserUsername, EndUserWebsiteUrl, FormattedAddress, GrantType, InitiateLoginUrl, IssuerUrl,
+    ClientConfigUrl, ClientContactEmail, ClientName, ClientUrl, DecryptionError, DecryptionKey,
+    EncryptionError, EncryptionKey, EndSessionUrl, EndUserBirthday, EndUserEmail,
+    EndUserFamilyName, EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname,
+    EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername,
+    EndUserWebsiteUrl, ExposeSecret, FormattedAddress, GrantType, InitiateLoginUrl, IssuerUrl,
     JsonWebKey, JsonWebKeyId, JsonWebKeySet, JsonWebKeySetUrl, JsonWebKeyType, JsonWebKeyUse,
-    JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag,
-    LocalizedClaim, LoginHint, LogoUrl, LogoutHint, Nonce, OpPolicyUrl, OpTosUrl, PolicyUrl,
-    PostLogoutRedirectUrl, PrivateSigningKey, RegistrationAccessToken, RegistrationUrl, RequestUrl,
-    ResponseMode, ResponseType, ResponseTypes, SectorIdentifierUrl, ServiceDocUrl, SigningError,
-    StreetAddress, SubjectIdentifier, SubjectIdentifierType, ToSUrl,
+    JsonWebTokenId, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    LanguageTag, LocalizedClaim, LoginHint, LogoUrl, LogoutHint, Nonce, OpPolicyUrl, OpTosUrl,
+    PhoneNumberError, PolicyUrl, PostLogoutRedirectUrl, PrivateSigningKey, RegistrationAccessToken,
+    RegistrationUrl, RequestUrl, ResponseMode, ResponseType, ResponseTypes, SectorIdentifierUrl,
+    ServiceDocUrl, SessionIdentifier, SigningError, StreetAddress, SubjectIdentifier,
+    SubjectIdentifierType, ToSUrl, TokenHashAlgorithm, DEFAULT_MAX_DECOMPRESSED_JWE_LEN,
 };
 pub use user_info::{
     UserInfoClaims, UserInfoError, UserInfoJsonWebToken, UserInfoRequest, UserInfoUrl,
 };
 use verification::{AudiencesClaim, IssuerClaim};
 pub use verification::{
-    ClaimsVerificationError, IdTokenVerifier, NonceVerifier, SignatureVerificationError,
-    UserInfoVerifier,
+    ClaimsValidator, ClaimsVerificationError, IdTokenVerifier, JwtAccessTokenVerifier,
+    NonceVerifier, SignatureVerificationError, SignedMetadataVerifier, UserInfoVerifier,
+    VerifiedSignature,
 };
 
 // Defined first since other modules need the macros, and definition order is significant for
@@ -662,16 +703,46 @@ mod macros;
 /// Baseline OpenID Connect implementation and types.
 pub mod core;
 
+/// Azure AD (Microsoft identity platform) compatibility.
+pub mod azure;
+
 /// OpenID Connect Dynamic Client Registration.
 pub mod registration;
 
+/// HTTP client wrappers for customizing and instrumenting outgoing requests (e.g., adding headers
+/// or recording metrics).
+pub mod middleware;
+
+/// OpenID Foundation Relying Party conformance test harness (requires the `conformance`
+/// feature).
+#[cfg(any(test, feature = "conformance"))]
+pub mod conformance;
+
+/// Test utilities, including an in-process mock OpenID Connect Provider and HTTP fixture
+/// record/replay (requires the `test-util` feature).
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
 // Private modules since we may move types between different modules; these are exported publicly
 // via the pub use above.
+mod access_token;
+mod authorization_response;
 mod claims;
+mod client_builder;
+mod client_config;
 mod discovery;
 mod helpers;
 mod id_token;
+mod jws;
+mod login;
 mod logout;
+mod pending_authorization;
+mod provider;
+mod sealed_login;
+mod session;
+mod stateless;
+mod token_manager;
+mod token_response;
 pub(crate) mod types;
 mod user_info;
 mod verification;
@@ -806,10 +877,16 @@ where
     client_id: ClientId,
     client_secret: Option<ClientSecret>,
     issuer: IssuerUrl,
+    additional_issuers: Vec<IssuerUrl>,
     userinfo_endpoint: Option<UserInfoUrl>,
+    end_session_endpoint: Option<EndSessionUrl>,
+    registration_endpoint: Option<RegistrationUrl>,
+    redirect_uris: Vec<RedirectUrl>,
     jwks: JsonWebKeySet<JS, JT, JU, K>,
     id_token_signing_algs: Option<Vec<JS>>,
     use_openid_scope: bool,
+    default_scopes: Vec<Scope>,
+    require_pkce_s256: bool,
     _phantom: PhantomData<(AC, AD, GC, JE, P)>,
 }
 impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>
@@ -853,10 +930,16 @@ where
             client_id,
             client_secret,
             issuer,
+            additional_issuers: Vec::new(),
             userinfo_endpoint,
+            end_session_endpoint: None,
+            registration_endpoint: None,
+            redirect_uris: Vec::new(),
             jwks,
             id_token_signing_algs: None,
             use_openid_scope: true,
+            default_scopes: Vec::new(),
+            require_pkce_s256: false,
             _phantom: PhantomData,
         }
     }
@@ -867,6 +950,11 @@ where
     /// Use [`ProviderMetadata::discover`] or
     /// [`ProviderMetadata::discover_async`] to fetch the provider metadata.
     ///
+    /// In addition to the issuer, authorization, token, and user info endpoints and the JSON Web
+    /// Key Set, the client captures any introspection, revocation, device authorization, and
+    /// registration endpoints advertised in the metadata, along with the end session endpoint if
+    /// the additional metadata provides one (e.g., [`LogoutProviderMetadata`]).
+    ///
     pub fn from_provider_metadata<A, CA, CN, CT, G, JK, RM, RS, S>(
         provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RS, S>,
         client_id: ClientId,
@@ -883,17 +971,37 @@ where
         RS: ResponseType,
         S: SubjectIdentifierType,
     {
+        let mut oauth2_client = oauth2::Client::new(
+            client_id.clone(),
+            client_secret.clone(),
+            provider_metadata.authorization_endpoint().clone(),
+            provider_metadata.token_endpoint().cloned(),
+        );
+        if let Some(introspection_endpoint) = provider_metadata.introspection_endpoint() {
+            oauth2_client = oauth2_client.set_introspection_uri(introspection_endpoint.clone());
+        }
+        if let Some(revocation_endpoint) = provider_metadata.revocation_endpoint() {
+            oauth2_client = oauth2_client.set_revocation_uri(revocation_endpoint.clone());
+        }
+        if let Some(device_authorization_endpoint) =
+            provider_metadata.device_authorization_endpoint()
+        {
+            oauth2_client =
+                oauth2_client.set_device_authorization_url(device_authorization_endpoint.clone());
+        }
         Client {
-            oauth2_client: oauth2::Client::new(
-                client_id.clone(),
-                client_secret.clone(),
-                provider_metadata.authorization_endpoint().clone(),
-                provider_metadata.token_endpoint().cloned(),
-            ),
+            oauth2_client,
             client_id,
             client_secret,
             issuer: provider_metadata.issuer().clone(),
+            additional_issuers: Vec::new(),
             userinfo_endpoint: provider_metadata.userinfo_endpoint().cloned(),
+            end_session_endpoint: provider_metadata
+                .additional_metadata()
+                .end_session_endpoint()
+                .cloned(),
+            registration_endpoint: provider_metadata.registration_endpoint().cloned(),
+            redirect_uris: Vec::new(),
             jwks: provider_metadata.jwks().to_owned(),
             id_token_signing_algs: Some(
                 provider_metadata
@@ -901,10 +1009,80 @@ where
                     .to_owned(),
             ),
             use_openid_scope: true,
+            default_scopes: Vec::new(),
+            require_pkce_s256: false,
             _phantom: PhantomData,
         }
     }
 
+    ///
+    /// Initializes an OpenID Connect client from a [`ClientConfig`] and the provider's OpenID
+    /// Connect Discovery metadata.
+    ///
+    /// Endpoints specified in the configuration override those advertised in the provider
+    /// metadata. Use [`ProviderMetadata::discover`] (or
+    /// [`ProviderMetadata::discover_async`]) with [`ClientConfig::issuer_url`] to fetch the
+    /// provider metadata. [`ClientConfig::scopes`] are requested by every authorization request
+    /// (see [`Client::set_default_scopes`]).
+    ///
+    pub fn from_config<A, CA, CN, CT, G, JK, RM, RS, S>(
+        config: &ClientConfig,
+        provider_metadata: ProviderMetadata<A, AD, CA, CN, CT, G, JE, JK, JS, JT, JU, K, RM, RS, S>,
+    ) -> Result<Self, ClientConfigError>
+    where
+        A: AdditionalProviderMetadata,
+        CA: ClientAuthMethod,
+        CN: ClaimName,
+        CT: ClaimType,
+        G: GrantType,
+        JK: JweKeyManagementAlgorithm,
+        RM: ResponseMode,
+        RS: ResponseType,
+        S: SubjectIdentifierType,
+    {
+        if provider_metadata.issuer() != config.issuer_url() {
+            return Err(ClientConfigError::IssuerMismatch(
+                provider_metadata.issuer().to_string(),
+                config.issuer_url().to_string(),
+            ));
+        }
+        let auth_type = config.auth_type()?;
+
+        let mut provider_metadata = provider_metadata;
+        if let Some(authorization_endpoint) = config.authorization_endpoint() {
+            provider_metadata =
+                provider_metadata.set_authorization_endpoint(authorization_endpoint.clone());
+        }
+        if let Some(token_endpoint) = config.token_endpoint() {
+            provider_metadata = provider_metadata.set_token_endpoint(Some(token_endpoint.clone()));
+        }
+        if let Some(userinfo_endpoint) = config.userinfo_endpoint() {
+            provider_metadata =
+                provider_metadata.set_userinfo_endpoint(Some(userinfo_endpoint.clone()));
+        }
+
+        let mut client = Self::from_provider_metadata(
+            provider_metadata,
+            config.client_id().clone(),
+            config.client_secret().cloned(),
+        )
+        .set_additional_issuers(config.additional_issuers().clone())
+        .set_default_scopes(config.scopes().clone());
+        if let Some(auth_type) = auth_type {
+            client = client.set_auth_type(auth_type);
+        }
+        if let Some(redirect_uri) = config.redirect_uri() {
+            client = client.set_redirect_uri(redirect_uri.clone());
+        }
+        if let Some(introspection_endpoint) = config.introspection_endpoint() {
+            client = client.set_introspection_uri(introspection_endpoint.clone());
+        }
+        if let Some(revocation_endpoint) = config.revocation_endpoint() {
+            client = client.set_revocation_uri(revocation_endpoint.clone());
+        }
+        Ok(client)
+    }
+
     ///
     /// Configures the type of client authentication used for communicating with the authorization
     /// server.
@@ -920,14 +1098,90 @@ where
         self
     }
 
+    ///
+    /// Returns the type of client authentication used for communicating with the authorization
+    /// server.
+    ///
+    pub fn auth_type(&self) -> &AuthType {
+        self.oauth2_client.auth_type()
+    }
+
+    ///
+    /// Returns a copy of this client that uses the specified type of client authentication,
+    /// leaving this client unchanged.
+    ///
+    /// This allows overriding the client authentication method for individual endpoints when a
+    /// provider is inconsistent across them (e.g., requiring HTTP Basic authentication at the
+    /// token endpoint but `client_secret_post` at the revocation endpoint). The request builders
+    /// returned by the `oauth2` crate (e.g., [`CodeTokenRequest`] and [`RevocationRequest`])
+    /// borrow the client's authentication type and don't support overriding it, so the override
+    /// is applied to a copy of the client instead. Since this copies the entire client (including
+    /// its JSON Web Key Set), applications should create the copy once and reuse it:
+    ///
+    /// ```rust,no_run
+    /// # use openidconnect::core::CoreClient;
+    /// # use openidconnect::reqwest::http_client;
+    /// # use openidconnect::{AccessToken, AuthType};
+    /// # fn err_wrapper(client: CoreClient, token: AccessToken) -> Result<(), anyhow::Error> {
+    /// let revocation_client = client.with_auth_type(AuthType::RequestBody);
+    ///
+    /// revocation_client
+    ///     .revoke_token(token.into())?
+    ///     .request(http_client)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn with_auth_type(&self, auth_type: AuthType) -> Self
+    where
+        Self: Clone,
+    {
+        self.clone().set_auth_type(auth_type)
+    }
+
     ///
     /// Sets the the redirect URL used by the authorization endpoint.
     ///
+    /// This replaces any redirect URLs previously set via
+    /// [`set_redirect_uris`](Self::set_redirect_uris).
+    ///
     pub fn set_redirect_uri(mut self, redirect_url: RedirectUrl) -> Self {
+        self.redirect_uris = vec![redirect_url.clone()];
         self.oauth2_client = self.oauth2_client.set_redirect_uri(redirect_url);
         self
     }
 
+    ///
+    /// Sets the redirect URLs registered for this client (e.g., for applications serving multiple
+    /// hostnames, or native applications using both loopback and custom-scheme redirects).
+    ///
+    /// The first redirect URL is used by default. Use
+    /// [`AuthorizationRequest::select_redirect_uri`] and
+    /// [`exchange_code_with_redirect_uri`](Self::exchange_code_with_redirect_uri) to choose a
+    /// different one for an individual login.
+    ///
+    /// Returns [`RedirectUriError::Empty`] if no redirect URLs are given, since the default
+    /// redirect URL can't be unset once configured.
+    ///
+    pub fn set_redirect_uris(
+        mut self,
+        redirect_urls: Vec<RedirectUrl>,
+    ) -> Result<Self, RedirectUriError> {
+        let default_redirect_url = redirect_urls.first().ok_or(RedirectUriError::Empty)?;
+        self.oauth2_client = self
+            .oauth2_client
+            .set_redirect_uri(default_redirect_url.clone());
+        self.redirect_uris = redirect_urls;
+        Ok(self)
+    }
+
+    ///
+    /// Returns the redirect URLs registered for this client, starting with the default.
+    ///
+    pub fn redirect_uris(&self) -> &[RedirectUrl] {
+        &self.redirect_uris
+    }
+
     ///
     /// Sets the introspection URL for contacting the ([RFC 7662](https://tools.ietf.org/html/rfc7662))
     /// introspection endpoint.
@@ -947,6 +1201,62 @@ where
         self
     }
 
+    ///
+    /// Sets the end session URL for contacting the end session endpoint
+    /// ([OpenID Connect RP-Initiated Logout 1.0](https://openid.net/specs/openid-connect-rpinitiated-1_0.html)).
+    ///
+    /// See: [`logout_request()`](Self::logout_request())
+    ///
+    pub fn set_end_session_uri(mut self, end_session_url: EndSessionUrl) -> Self {
+        self.end_session_endpoint = Some(end_session_url);
+        self
+    }
+
+    ///
+    /// Sets the registration URL for contacting the
+    /// [dynamic client registration](https://openid.net/specs/openid-connect-registration-1_0.html)
+    /// endpoint.
+    ///
+    pub fn set_registration_uri(mut self, registration_url: RegistrationUrl) -> Self {
+        self.registration_endpoint = Some(registration_url);
+        self
+    }
+
+    ///
+    /// Returns the introspection endpoint, if configured.
+    ///
+    pub fn introspection_uri(&self) -> Option<&IntrospectionUrl> {
+        self.oauth2_client.introspection_url()
+    }
+
+    ///
+    /// Returns the revocation endpoint, if configured.
+    ///
+    pub fn revocation_uri(&self) -> Option<&RevocationUrl> {
+        self.oauth2_client.revocation_url()
+    }
+
+    ///
+    /// Returns the device authorization endpoint, if configured.
+    ///
+    pub fn device_authorization_uri(&self) -> Option<&DeviceAuthorizationUrl> {
+        self.oauth2_client.device_authorization_url()
+    }
+
+    ///
+    /// Returns the end session endpoint, if configured.
+    ///
+    pub fn end_session_uri(&self) -> Option<&EndSessionUrl> {
+        self.end_session_endpoint.as_ref()
+    }
+
+    ///
+    /// Returns the registration endpoint, if configured.
+    ///
+    pub fn registration_uri(&self) -> Option<&RegistrationUrl> {
+        self.registration_endpoint.as_ref()
+    }
+
     ///
     /// Sets the device authorization URL for contacting the device authorization endpoint ([RFC 8628](https://tools.ietf.org/html/rfc8628)).
     ///
@@ -960,6 +1270,19 @@ where
         self
     }
 
+    ///
+    /// Sets additional issuers whose tokens this client trusts, alongside the provider's issuer.
+    ///
+    /// Each issuer URL, including the provider's own issuer URL, may contain `{...}` placeholders
+    /// to support multi-tenant providers (e.g.,
+    /// `https://login.microsoftonline.com/{tenantid}/v2.0`). See
+    /// [`IssuerUrl::matches_template`] for details.
+    ///
+    pub fn set_additional_issuers(mut self, additional_issuers: Vec<IssuerUrl>) -> Self {
+        self.additional_issuers = additional_issuers;
+        self
+    }
+
     ///
     /// Enables the `openid` scope to be requested automatically.
     ///
@@ -979,6 +1302,41 @@ where
         self
     }
 
+    ///
+    /// Sets the scopes requested by every authorization request created via
+    /// [`authorize_url`](Self::authorize_url), in addition to the `openid` scope.
+    ///
+    /// An `openid` entry in `default_scopes` is ignored unless the `openid` scope has been disabled
+    /// via [`disable_openid_scope`](Self::disable_openid_scope), so that it's only requested once.
+    ///
+    /// Additional scopes may be requested via [`AuthorizationRequest::add_scope`].
+    ///
+    pub fn set_default_scopes(mut self, default_scopes: Vec<Scope>) -> Self {
+        self.default_scopes = default_scopes;
+        self
+    }
+
+    ///
+    /// Returns the scopes requested by every authorization request.
+    ///
+    pub fn default_scopes(&self) -> &[Scope] {
+        &self.default_scopes
+    }
+
+    ///
+    /// Specifies whether authorization requests must include a PKCE code challenge using the
+    /// `S256` method, as required by OAuth 2.1.
+    ///
+    /// This policy is recommended for public clients. When enabled, [`AuthorizationRequest::url`]
+    /// (and the other methods that build the request, such as
+    /// [`AuthorizationRequest::url_and_pending_authorization`]) return an error if no code
+    /// challenge was set or if it uses the `plain` method. Disabled by default.
+    ///
+    pub fn require_pkce_s256(mut self, require_pkce_s256: bool) -> Self {
+        self.require_pkce_s256 = require_pkce_s256;
+        self
+    }
+
     ///
     /// Returns an ID token verifier for use with the [`IdToken::claims`] method.
     ///
@@ -996,7 +1354,8 @@ where
                 self.issuer.clone(),
                 self.jwks.clone(),
             )
-        };
+        }
+        .set_other_issuer_verifier_fn(self.other_issuer_verifier_fn());
 
         if let Some(id_token_signing_algs) = self.id_token_signing_algs.clone() {
             verifier.set_allowed_algs(id_token_signing_algs)
@@ -1005,6 +1364,17 @@ where
         }
     }
 
+    fn other_issuer_verifier_fn(&self) -> impl Fn(&IssuerUrl) -> bool + Send + Sync + 'static {
+        let trusted_issuers = std::iter::once(self.issuer.clone())
+            .chain(self.additional_issuers.iter().cloned())
+            .collect::<Vec<_>>();
+        move |issuer| {
+            trusted_issuers
+                .iter()
+                .any(|trusted_issuer| trusted_issuer.matches_template(issuer))
+        }
+    }
+
     ///
     /// Generates an authorization URL for a new authorization request.
     ///
@@ -1048,6 +1418,7 @@ where
     {
         let request = AuthorizationRequest {
             inner: self.oauth2_client.authorize_url(state_fn),
+            registered_redirect_uris: &self.redirect_uris,
             acr_values: Vec::new(),
             authentication_flow,
             claims_locales: Vec::new(),
@@ -1057,13 +1428,23 @@ where
             max_age: None,
             nonce: nonce_fn(),
             prompts: Vec::new(),
+            pkce_challenge: None,
+            require_pkce_s256: self.require_pkce_s256,
+            response_mode: None,
             ui_locales: Vec::new(),
         };
-        if self.use_openid_scope {
+        let request = if self.use_openid_scope {
             request.add_scope(Scope::new(OPENID_SCOPE.to_string()))
         } else {
             request
-        }
+        };
+        // Avoid requesting the `openid` scope twice if it's also one of the default scopes.
+        request.add_scopes(
+            self.default_scopes
+                .iter()
+                .filter(|scope| !self.use_openid_scope || scope.as_str() != OPENID_SCOPE)
+                .cloned(),
+        )
     }
 
     ///
@@ -1078,6 +1459,98 @@ where
         self.oauth2_client.exchange_code(code)
     }
 
+    ///
+    /// Creates a request builder for exchanging an authorization code for an access token, using
+    /// one of the redirect URLs registered via [`set_redirect_uris`](Self::set_redirect_uris).
+    ///
+    /// The redirect URL must match the one selected for the authorization request (see
+    /// [`AuthorizationRequest::select_redirect_uri`]). Returns
+    /// [`RedirectUriError::Unregistered`] if it is not registered with this client.
+    ///
+    pub fn exchange_code_with_redirect_uri(
+        &self,
+        code: AuthorizationCode,
+        redirect_url: &RedirectUrl,
+    ) -> Result<CodeTokenRequest<'_, TE, TR, TT>, RedirectUriError> {
+        let registered_redirect_uri = self
+            .redirect_uris
+            .iter()
+            .find(|registered| *registered == redirect_url)
+            .ok_or_else(|| RedirectUriError::Unregistered(redirect_url.to_string()))?;
+        Ok(self
+            .exchange_code(code)
+            .set_redirect_uri(Cow::Borrowed(registered_redirect_uri)))
+    }
+
+    ///
+    /// Exchanges an authorization code for tokens using the specified synchronous HTTP client,
+    /// and verifies the resulting token response.
+    ///
+    /// This function performs all of the checks required by the
+    /// [Authorization Code Flow](https://openid.net/specs/openid-connect-core-1_0.html#CodeFlowAuth):
+    ///  * the token response must have the `Bearer` token type and include an ID token (see
+    ///    [`TokenResponseValidator`]),
+    ///  * the ID token must be verified by [`id_token_verifier`](Self::id_token_verifier)
+    ///    (including its issuer, audiences, expiration time, and signature) and contain the
+    ///    given nonce, and
+    ///  * the `at_hash` and `c_hash` claims, if present, must match the access token and
+    ///    authorization code, respectively.
+    ///
+    /// The `pkce_verifier` must be provided if a PKCE challenge was included in the
+    /// authorization request.
+    ///
+    pub fn exchange_code_and_verify<HC, RE>(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: Option<PkceCodeVerifier>,
+        nonce: &Nonce,
+        http_client: HC,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        AC: Clone,
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let mut request = self.exchange_code(code.clone());
+        if let Some(pkce_verifier) = pkce_verifier {
+            request = request.set_pkce_verifier(pkce_verifier);
+        }
+        let token_response = request
+            .request(http_client)
+            .map_err(LoginError::TokenRequest)?;
+        login::verify_code_token_response(&self.id_token_verifier(), token_response, &code, nonce)
+    }
+
+    ///
+    /// Exchanges an authorization code for tokens using the specified asynchronous HTTP client,
+    /// and verifies the resulting token response.
+    ///
+    /// See [`exchange_code_and_verify`](Self::exchange_code_and_verify) for details.
+    ///
+    pub async fn exchange_code_and_verify_async<C, F, RE>(
+        &self,
+        code: AuthorizationCode,
+        pkce_verifier: Option<PkceCodeVerifier>,
+        nonce: &Nonce,
+        http_client: C,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        AC: Clone,
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let mut request = self.exchange_code(code.clone());
+        if let Some(pkce_verifier) = pkce_verifier {
+            request = request.set_pkce_verifier(pkce_verifier);
+        }
+        let token_response = request
+            .request_async(http_client)
+            .await
+            .map_err(LoginError::TokenRequest)?;
+        login::verify_code_token_response(&self.id_token_verifier(), token_response, &code, nonce)
+    }
+
     ///
     /// Creates a request builder for device authorization.
     ///
@@ -1156,6 +1629,28 @@ where
         self.oauth2_client.exchange_client_credentials()
     }
 
+    ///
+    /// Creates a request builder for exchanging client credentials for an access token intended
+    /// for the given audience (e.g., the identifier of an API).
+    ///
+    /// The audience is passed as the `audience` parameter, which is supported by many
+    /// authorization servers for machine-to-machine access tokens. Scopes may be requested using
+    /// [`ClientCredentialsTokenRequest::add_scope`].
+    ///
+    /// See <https://tools.ietf.org/html/rfc6749#section-4.4>
+    ///
+    pub fn exchange_client_credentials_for_audience<'a, 'b>(
+        &'a self,
+        audience: &'b Audience,
+    ) -> ClientCredentialsTokenRequest<'b, TE, TR, TT>
+    where
+        'a: 'b,
+    {
+        self.oauth2_client
+            .exchange_client_credentials()
+            .add_extra_param("audience", audience.as_str())
+    }
+
     ///
     /// Creates a request builder for info about the user associated with the given access token.
     ///
@@ -1182,15 +1677,34 @@ where
                 .ok_or(ConfigurationError::MissingUrl("userinfo"))?,
             access_token,
             require_signed_response: false,
+            use_post: false,
+            access_token_in_body: false,
+            extra_headers: http::HeaderMap::new(),
             signed_response_verifier: UserInfoVerifier::new(
                 self.client_id.clone(),
                 self.issuer.clone(),
                 self.jwks.clone(),
                 expected_subject,
-            ),
+            )
+            .set_other_issuer_verifier_fn(self.other_issuer_verifier_fn()),
         })
     }
 
+    ///
+    /// Creates a request builder for logging the user out via the end session endpoint.
+    ///
+    /// This function requires that this [`Client`] be configured with an end session endpoint
+    /// (see [`set_end_session_uri`](Self::set_end_session_uri)). Otherwise, it returns the
+    /// [`ConfigurationError`] error.
+    ///
+    pub fn logout_request(&self) -> Result<LogoutRequest, ConfigurationError> {
+        Ok(LogoutRequest::from(
+            self.end_session_endpoint
+                .clone()
+                .ok_or(ConfigurationError::MissingUrl("end session"))?,
+        ))
+    }
+
     ///
     /// Creates a request builder for obtaining metadata about a previously received token.
     ///
@@ -1240,7 +1754,11 @@ where
     login_hint: Option<LoginHint>,
     max_age: Option<Duration>,
     nonce: Nonce,
+    pkce_challenge: Option<PkceCodeChallenge>,
     prompts: Vec<P>,
+    registered_redirect_uris: &'a [RedirectUrl],
+    require_pkce_s256: bool,
+    response_mode: Option<String>,
     ui_locales: Vec<LanguageTag>,
 }
 impl<'a, AD, P, RT> AuthorizationRequest<'a, AD, P, RT>
@@ -1292,6 +1810,24 @@ where
         self
     }
 
+    ///
+    /// Appends a collection of extra params to the authorization URL (e.g., provider-specific
+    /// parameters loaded from configuration).
+    ///
+    /// See [`add_extra_param`](Self::add_extra_param) for restrictions on the parameter names.
+    ///
+    pub fn add_extra_params<I, N, V>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = (N, V)>,
+        N: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        for (name, value) in params {
+            self.inner = self.inner.add_extra_param(name, value);
+        }
+        self
+    }
+
     ///
     /// Enables the use of [Proof Key for Code Exchange](https://tools.ietf.org/html/rfc7636)
     /// (PKCE).
@@ -1301,6 +1837,7 @@ where
     /// such as in a native, mobile app, or browser app).
     ///
     pub fn set_pkce_challenge(mut self, pkce_code_challenge: PkceCodeChallenge) -> Self {
+        self.pkce_challenge = Some(pkce_code_challenge.clone());
         self.inner = self.inner.set_pkce_challenge(pkce_code_challenge);
         self
     }
@@ -1392,6 +1929,44 @@ where
         self
     }
 
+    ///
+    /// Specifies the mechanism the OpenID Connect Provider should use for returning parameters
+    /// from the authorization endpoint (e.g., `form_post`).
+    ///
+    /// If unset, the provider uses the default response mode for the requested response type
+    /// (see [OAuth 2.0 Multiple Response Type Encoding Practices](
+    ///     http://openid.net/specs/oauth-v2-multiple-response-types-1_0.html#ResponseModes)).
+    /// Responses returned using the `form_post` response mode may be parsed using
+    /// [`ParsedAuthorizationResponse::from_form_post`].
+    ///
+    /// Returns [`ResponseModeError::IncompatibleFlow`] if the response mode encodes the response
+    /// in the query string (i.e., `query` or `query.jwt`) and the authentication flow returns an
+    /// ID token or access token from the authorization endpoint, since
+    /// [OAuth 2.0 Multiple Response Type Encoding Practices](
+    ///     http://openid.net/specs/oauth-v2-multiple-response-types-1_0.html#Combinations)
+    /// forbids returning tokens in the query string.
+    ///
+    pub fn set_response_mode<RM>(mut self, response_mode: &RM) -> Result<Self, ResponseModeError>
+    where
+        RM: ResponseMode + AsRef<str>,
+    {
+        let response_mode = response_mode.as_ref();
+        let returns_tokens = match self.authentication_flow {
+            AuthenticationFlow::AuthorizationCode => false,
+            AuthenticationFlow::Implicit(_) => true,
+            AuthenticationFlow::Hybrid(ref response_types) => response_types
+                .iter()
+                .any(|response_type| response_type.as_ref() != "code"),
+        };
+        if returns_tokens && (response_mode == "query" || response_mode == "query.jwt") {
+            return Err(ResponseModeError::IncompatibleFlow(
+                response_mode.to_string(),
+            ));
+        }
+        self.response_mode = Some(response_mode.to_string());
+        Ok(self)
+    }
+
     ///
     /// Requests the preferred languages for the user interface presented by the OpenID Connect
     /// Provider.
@@ -1411,11 +1986,43 @@ where
         self
     }
 
+    ///
+    /// Selects one of the redirect URLs registered via [`Client::set_redirect_uris`] for this
+    /// authorization request.
+    ///
+    /// Returns [`RedirectUriError::Unregistered`] if the redirect URL is not registered with the
+    /// client. The same redirect URL must be passed to
+    /// [`Client::exchange_code_with_redirect_uri`] when exchanging the authorization code.
+    ///
+    pub fn select_redirect_uri(self, redirect_url: &RedirectUrl) -> Result<Self, RedirectUriError> {
+        let registered_redirect_uri = self
+            .registered_redirect_uris
+            .iter()
+            .find(|registered| *registered == redirect_url)
+            .ok_or_else(|| RedirectUriError::Unregistered(redirect_url.to_string()))?;
+        Ok(self.set_redirect_uri(Cow::Borrowed(registered_redirect_uri)))
+    }
+
     ///
     /// Returns the full authorization URL and CSRF state for this authorization
     /// request.
     ///
-    pub fn url(self) -> (Url, CsrfToken, Nonce) {
+    /// Returns an error if the request violates the client's PKCE policy (see
+    /// [`Client::require_pkce_s256`]), which is never the case unless that policy is enabled.
+    ///
+    pub fn url(self) -> Result<(Url, CsrfToken, Nonce), PkceChallengeError> {
+        if self.require_pkce_s256 {
+            verify_pkce_code_challenge(
+                self.pkce_challenge
+                    .as_ref()
+                    .map(|challenge| challenge.as_str()),
+                self.pkce_challenge
+                    .as_ref()
+                    .map(|challenge| challenge.method()),
+                true,
+            )?;
+        }
+
         let response_type = match self.authentication_flow {
             AuthenticationFlow::AuthorizationCode => core::CoreResponseType::Code.to_oauth2(),
             AuthenticationFlow::Implicit(include_token) => {
@@ -1469,12 +2076,144 @@ where
         if !self.prompts.is_empty() {
             inner = inner.add_extra_param("prompt", join_vec(&self.prompts));
         }
+        if let Some(ref response_mode) = self.response_mode {
+            inner = inner.add_extra_param("response_mode", response_mode);
+        }
         if !self.ui_locales.is_empty() {
             inner = inner.add_extra_param("ui_locales", join_vec(&self.ui_locales));
         }
 
         let (url, state) = inner.url();
-        (url, state, nonce)
+        Ok((url, state, nonce))
+    }
+
+    ///
+    /// Returns the full set of authorization request parameters as the JSON claims of a
+    /// [request object](https://openid.net/specs/openid-connect-core-1_0.html#RequestObject),
+    /// along with the CSRF state and nonce for this authorization request.
+    ///
+    /// The claims contain exactly the parameters that [`url`](Self::url) would encode in the
+    /// authorization URL (including scopes, prompts, PKCE parameters, and any extra params), with
+    /// `max_age` represented as a JSON number. Callers should add the `iss` and `aud` claims
+    /// before signing the request object (e.g., using [`JsonWebToken::new`]).
+    ///
+    /// Returns an error if the request violates the client's PKCE policy (see
+    /// [`Client::require_pkce_s256`]).
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn request_object_claims(
+        self,
+    ) -> Result<(serde_json::Map<String, serde_json::Value>, CsrfToken, Nonce), PkceChallengeError>
+    {
+        let (url, state, nonce) = self.url()?;
+        let claims = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = match (name.as_ref(), value.parse::<u64>()) {
+                    ("max_age", Ok(max_age)) => serde_json::Value::from(max_age),
+                    _ => serde_json::Value::String(value.into_owned()),
+                };
+                (name.into_owned(), value)
+            })
+            .collect();
+        Ok((claims, state, nonce))
+    }
+
+    ///
+    /// Returns the full authorization URL along with a [`PendingAuthorization`] capturing the
+    /// CSRF state, nonce, redirect URI, and requested scopes of this authorization request.
+    ///
+    /// If a PKCE challenge was set, the corresponding verifier should be added using
+    /// [`PendingAuthorization::set_pkce_verifier`] before persisting the pending authorization.
+    ///
+    /// Returns an error if the request violates the client's PKCE policy (see
+    /// [`Client::require_pkce_s256`]).
+    ///
+    pub fn url_and_pending_authorization(
+        self,
+    ) -> Result<(Url, PendingAuthorization), PkceChallengeError> {
+        let (url, state, nonce) = self.url()?;
+        let mut redirect_uri = None;
+        let mut scopes = Vec::new();
+        for (name, value) in url.query_pairs() {
+            match name.as_ref() {
+                "redirect_uri" => redirect_uri = RedirectUrl::new(value.into_owned()).ok(),
+                "scope" => {
+                    scopes = value
+                        .split(' ')
+                        .filter(|scope| !scope.is_empty())
+                        .map(|scope| Scope::new(scope.to_string()))
+                        .collect()
+                }
+                _ => {}
+            }
+        }
+        let pending = PendingAuthorization::new(state, nonce)
+            .set_redirect_uri(redirect_uri)
+            .set_scopes(scopes);
+        Ok((url, pending))
+    }
+}
+///
+/// Error setting the response mode of an [`AuthorizationRequest`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResponseModeError {
+    ///
+    /// The response mode cannot be used with the authentication flow of the request.
+    ///
+    #[error("Response mode `{0}` cannot be used with a flow that returns tokens")]
+    IncompatibleFlow(String),
+}
+
+///
+/// Error configuring the redirect URLs of a [`Client`] or selecting one for an individual request.
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RedirectUriError {
+    ///
+    /// No redirect URLs were given.
+    ///
+    #[error("At least one redirect URI is required")]
+    Empty,
+    ///
+    /// The redirect URL is not registered with the client.
+    ///
+    #[error("Redirect URI `{0}` is not registered with the client")]
+    Unregistered(String),
+}
+
+impl<'a, AD, RT> AuthorizationRequest<'a, AD, core::CoreAuthPrompt, RT>
+where
+    AD: AuthDisplay,
+    RT: ResponseType,
+{
+    ///
+    /// Requests offline access (i.e., a refresh token) by adding the `offline_access` scope.
+    ///
+    /// As required by
+    /// [Section 11](https://openid.net/specs/openid-connect-core-1_0.html#OfflineAccess), this
+    /// also requests the `consent` prompt unless it has already been requested, or unless the
+    /// `none` prompt has been requested (which may not be combined with other prompts).
+    ///
+    /// The OpenID Connect Provider ignores this request unless the authentication flow returns an
+    /// authorization code, and may decline it regardless. Use
+    /// [`TokenResponse::refresh_token_granted`] to check whether a refresh token was actually
+    /// granted.
+    ///
+    pub fn request_offline_access(mut self) -> Self {
+        self = self.add_scope(core::CoreScope::OfflineAccess.into());
+        if !self.prompts.iter().any(|prompt| {
+            matches!(
+                prompt,
+                core::CoreAuthPrompt::Consent | core::CoreAuthPrompt::None
+            )
+        }) {
+            self.prompts.push(core::CoreAuthPrompt::Consent);
+        }
+        self
     }
 }
 
@@ -1497,6 +2236,17 @@ where
     /// to allow for interoperability with authorization servers that only support OAuth2.
     ///
     fn id_token(&self) -> Option<&IdToken<AC, GC, JE, JS, JT>>;
+
+    ///
+    /// Returns `true` if the authorization server granted a refresh token.
+    ///
+    /// Authorization servers may decline requests for offline access (see
+    /// [`AuthorizationRequest::request_offline_access`]), so clients that depend on long-lived
+    /// access should check this rather than assuming a refresh token was issued.
+    ///
+    fn refresh_token_granted(&self) -> bool {
+        self.refresh_token().is_some()
+    }
 }
 
 impl<AC, EF, GC, JE, JS, JT, TT> TokenResponse<AC, GC, JE, JS, JT, TT>
@@ -1531,14 +2281,22 @@ mod tests {
     use std::borrow::Cow;
     use std::time::Duration;
 
-    use oauth2::{AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenUrl};
+    use http::header::AUTHORIZATION;
+    use oauth2::{
+        AuthType, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge,
+        RedirectUrl, Scope, TokenUrl,
+    };
 
     use crate::core::CoreAuthenticationFlow;
-    use crate::core::{CoreAuthDisplay, CoreAuthPrompt, CoreClient, CoreIdToken, CoreResponseType};
+    use crate::core::{
+        CoreAuthDisplay, CoreAuthPrompt, CoreClient, CoreIdToken, CoreResponseMode,
+        CoreResponseType, CoreTokenResponse,
+    };
     use crate::IssuerUrl;
     use crate::{
-        AuthenticationContextClass, AuthenticationFlow, JsonWebKeySet, LanguageTag, LoginHint,
-        Nonce,
+        AuthenticationContextClass, AuthenticationFlow, ConfigurationError, HttpRequest,
+        HttpResponse, JsonWebKeySet, LanguageTag, LoginHint, Nonce, PkceChallengeError,
+        ProviderMetadataWithLogout, RedirectUriError, ResponseModeError, TokenResponse,
     };
 
     fn new_client() -> CoreClient {
@@ -1564,7 +2322,8 @@ mod tests {
                 || CsrfToken::new("CSRF123".to_string()),
                 || Nonce::new("NONCE456".to_string()),
             )
-            .url();
+            .url()
+            .unwrap();
 
         assert_eq!(
             "https://example/authorize?response_type=code&client_id=aaa&\
@@ -1573,6 +2332,144 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_authorize_url_default_scopes() {
+        let default_scopes = vec![
+            Scope::new("openid".to_string()),
+            Scope::new("email".to_string()),
+        ];
+        for client in [
+            new_client().set_default_scopes(default_scopes.clone()),
+            new_client()
+                .disable_openid_scope()
+                .set_default_scopes(default_scopes),
+        ] {
+            let (authorize_url, _, _) = client
+                .authorize_url(
+                    AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                    || CsrfToken::new("CSRF123".to_string()),
+                    || Nonce::new("NONCE456".to_string()),
+                )
+                .url()
+                .unwrap();
+
+            assert_eq!(
+                "https://example/authorize?response_type=code&client_id=aaa&\
+                 state=CSRF123&scope=openid+email&nonce=NONCE456",
+                authorize_url.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_authorize_url_offline_access() {
+        let client = new_client();
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_prompt(CoreAuthPrompt::Login)
+            .request_offline_access()
+            .url()
+            .unwrap();
+
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=openid+offline_access&nonce=NONCE456&prompt=login+consent",
+            authorize_url.to_string()
+        );
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::AuthorizationCode::<CoreResponseType>,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_prompt(CoreAuthPrompt::None)
+            .request_offline_access()
+            .url()
+            .unwrap();
+
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&\
+             state=CSRF123&scope=openid+offline_access&nonce=NONCE456&prompt=none",
+            authorize_url.to_string()
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_response_mode() {
+        let client = new_client();
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                AuthenticationFlow::<CoreResponseType>::Implicit(false),
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .set_response_mode(&CoreResponseMode::FormPost)
+            .unwrap()
+            .url()
+            .unwrap();
+
+        assert_eq!(
+            "https://example/authorize?response_type=id_token&client_id=aaa&\
+             state=CSRF123&scope=openid&nonce=NONCE456&response_mode=form_post",
+            authorize_url.to_string()
+        );
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .set_response_mode(&CoreResponseMode::QueryJwt)
+            .unwrap()
+            .url()
+            .unwrap();
+        assert!(authorize_url
+            .to_string()
+            .ends_with("&response_mode=query.jwt"));
+
+        for flow in [
+            AuthenticationFlow::<CoreResponseType>::Implicit(false),
+            AuthenticationFlow::Hybrid(vec![CoreResponseType::Code, CoreResponseType::IdToken]),
+        ] {
+            for response_mode in [CoreResponseMode::Query, CoreResponseMode::QueryJwt] {
+                match client
+                    .authorize_url(
+                        flow.clone(),
+                        || CsrfToken::new("CSRF123".to_string()),
+                        || Nonce::new("NONCE456".to_string()),
+                    )
+                    .set_response_mode(&response_mode)
+                {
+                    Err(ResponseModeError::IncompatibleFlow(mode)) => {
+                        assert_eq!(mode, response_mode.as_ref())
+                    }
+                    Ok(_) => panic!("expected response mode to be rejected"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_refresh_token_granted() {
+        let response: CoreTokenResponse = serde_json::from_str(
+            "{\"access_token\":\"foo\",\"token_type\":\"bearer\",\"refresh_token\":\"bar\"}",
+        )
+        .unwrap();
+        assert!(response.refresh_token_granted());
+
+        let response: CoreTokenResponse =
+            serde_json::from_str("{\"access_token\":\"foo\",\"token_type\":\"bearer\"}").unwrap();
+        assert!(!response.refresh_token_granted());
+    }
+
     #[test]
     fn test_authorize_url_implicit_with_access_token() {
         let client = new_client();
@@ -1583,7 +2480,8 @@ mod tests {
                 || CsrfToken::new("CSRF123".to_string()),
                 || Nonce::new("NONCE456".to_string()),
             )
-            .url();
+            .url()
+            .unwrap();
 
         assert_eq!(
             "https://example/authorize?response_type=id_token+token&client_id=aaa&\
@@ -1605,7 +2503,8 @@ mod tests {
                 || CsrfToken::new("CSRF123".to_string()),
                 || Nonce::new("NONCE456".to_string()),
             )
-            .url();
+            .url()
+            .unwrap();
 
         assert_eq!(
             "https://example/authorize?response_type=code+other&client_id=aaa&\
@@ -1641,7 +2540,8 @@ mod tests {
             .add_auth_context_value(AuthenticationContextClass::new(
                 "urn:mace:incommon:iap:silver".to_string(),
             ))
-            .url();
+            .url()
+            .unwrap();
         assert_eq!(
             "https://example/authorize?response_type=code&client_id=aaa&\
              state=CSRF123&redirect_uri=http%3A%2F%2Flocalhost%3A8888%2F&scope=openid+email&\
@@ -1679,7 +2579,8 @@ mod tests {
                 "urn:mace:incommon:iap:silver".to_string(),
             ))
             .add_extra_param("foo", "bar")
-            .url();
+            .url()
+            .unwrap();
         assert_eq!(
             format!(
                 "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
@@ -1711,7 +2612,8 @@ mod tests {
                 "urn:mace:incommon:iap:silver".to_string(),
             ))
             .add_extra_param("foo", "bar")
-            .url();
+            .url()
+            .unwrap();
         assert_eq!(
             format!(
                 "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
@@ -1725,6 +2627,288 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_authorize_request_object_claims() {
+        let client = new_client()
+            .set_redirect_uri(RedirectUrl::new("http://localhost:8888/".to_string()).unwrap());
+        let (_, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let pkce_challenge = PkceCodeChallenge::from_code_verifier_sha256(&pkce_verifier);
+
+        let (claims, state, nonce) = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_scope(Scope::new("email".to_string()))
+            .add_prompt(CoreAuthPrompt::Login)
+            .add_prompt(CoreAuthPrompt::Consent)
+            .set_max_age(Duration::from_secs(1800))
+            .set_pkce_challenge(pkce_challenge.clone())
+            .add_extra_param("foo", "bar")
+            .request_object_claims()
+            .unwrap();
+        assert_eq!(state.secret(), "CSRF123");
+        assert_eq!(nonce.secret(), "NONCE456");
+        assert_eq!(
+            serde_json::Value::Object(claims),
+            serde_json::json!({
+                "response_type": "code",
+                "client_id": "aaa",
+                "state": "CSRF123",
+                "redirect_uri": "http://localhost:8888/",
+                "scope": "openid email",
+                "code_challenge": pkce_challenge.as_str(),
+                "code_challenge_method": "S256",
+                "foo": "bar",
+                "nonce": "NONCE456",
+                "max_age": 1800,
+                "prompt": "login consent",
+            })
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_require_pkce_s256() {
+        let client = new_client().require_pkce_s256(true);
+        let new_request = || {
+            client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        };
+
+        assert_eq!(
+            new_request().url().unwrap_err(),
+            PkceChallengeError::MissingChallenge
+        );
+        assert_eq!(
+            new_request().request_object_claims().unwrap_err(),
+            PkceChallengeError::MissingChallenge
+        );
+        assert_eq!(
+            new_request().url_and_pending_authorization().unwrap_err(),
+            PkceChallengeError::MissingChallenge
+        );
+
+        let (_, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let plain_challenge = serde_json::from_value::<PkceCodeChallenge>(serde_json::json!({
+            "code_challenge": pkce_verifier.secret(),
+            "code_challenge_method": "plain",
+        }))
+        .unwrap();
+        assert_eq!(
+            new_request()
+                .set_pkce_challenge(plain_challenge)
+                .url()
+                .unwrap_err(),
+            PkceChallengeError::UnsupportedMethod("plain".to_string())
+        );
+
+        let (authorize_url, _, _) = new_request()
+            .set_pkce_challenge(PkceCodeChallenge::from_code_verifier_sha256(&pkce_verifier))
+            .url()
+            .unwrap();
+        assert!(authorize_url
+            .to_string()
+            .contains("&code_challenge_method=S256"));
+    }
+
+    #[test]
+    fn test_with_auth_type() {
+        let client = new_client();
+        let request_body_client = client.with_auth_type(AuthType::RequestBody);
+        assert!(matches!(client.auth_type(), AuthType::BasicAuth));
+        assert!(matches!(
+            request_body_client.auth_type(),
+            AuthType::RequestBody
+        ));
+
+        let result = request_body_client
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    assert!(request.headers.get(AUTHORIZATION).is_none());
+                    let body = String::from_utf8(request.body).unwrap();
+                    assert!(body.contains("client_id=aaa"));
+                    assert!(body.contains("client_secret=bbb"));
+                    Err(std::io::Error::other("done"))
+                },
+            );
+        assert!(result.is_err());
+
+        let result = client
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    assert!(request.headers.get(AUTHORIZATION).is_some());
+                    Err(std::io::Error::other("done"))
+                },
+            );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_from_provider_metadata_endpoints() {
+        let provider_metadata =
+            serde_json::from_value::<ProviderMetadataWithLogout>(serde_json::json!({
+                "issuer": "https://example.com",
+                "authorization_endpoint": "https://example.com/authorize",
+                "token_endpoint": "https://example.com/token",
+                "jwks_uri": "https://example.com/jwks",
+                "registration_endpoint": "https://example.com/register",
+                "revocation_endpoint": "https://example.com/revoke",
+                "introspection_endpoint": "https://example.com/introspect",
+                "device_authorization_endpoint": "https://example.com/device",
+                "end_session_endpoint": "https://example.com/logout",
+                "response_types_supported": ["code"],
+                "subject_types_supported": ["public"],
+                "id_token_signing_alg_values_supported": ["RS256"],
+            }))
+            .unwrap();
+
+        let client = CoreClient::from_provider_metadata(
+            provider_metadata,
+            ClientId::new("aaa".to_string()),
+            None,
+        );
+        assert_eq!(
+            client.introspection_uri().map(|url| url.as_str()),
+            Some("https://example.com/introspect")
+        );
+        assert_eq!(
+            client.revocation_uri().map(|url| url.as_str()),
+            Some("https://example.com/revoke")
+        );
+        assert_eq!(
+            client.device_authorization_uri().map(|url| url.as_str()),
+            Some("https://example.com/device")
+        );
+        assert_eq!(
+            client.registration_uri().map(|url| url.as_str()),
+            Some("https://example.com/register")
+        );
+        assert_eq!(
+            client.end_session_uri().map(|url| url.as_str()),
+            Some("https://example.com/logout")
+        );
+        assert!(client
+            .logout_request()
+            .unwrap()
+            .http_get_url()
+            .as_str()
+            .starts_with("https://example.com/logout"));
+
+        let client = new_client();
+        assert!(client.end_session_uri().is_none());
+        assert!(client.revocation_uri().is_none());
+        assert!(matches!(
+            client.logout_request(),
+            Err(ConfigurationError::MissingUrl("end session"))
+        ));
+    }
+
+    #[test]
+    fn test_set_empty_redirect_uris() {
+        let redirect_uri = RedirectUrl::new("http://localhost:8888/".to_string()).unwrap();
+        let client = new_client().set_redirect_uri(redirect_uri.clone());
+        assert_eq!(
+            client.clone().set_redirect_uris(vec![]).unwrap_err(),
+            RedirectUriError::Empty
+        );
+        assert_eq!(client.redirect_uris(), &[redirect_uri]);
+    }
+
+    #[test]
+    fn test_multiple_redirect_uris() {
+        let loopback = RedirectUrl::new("http://127.0.0.1:8888/cb".to_string()).unwrap();
+        let custom_scheme = RedirectUrl::new("com.example.app:/cb".to_string()).unwrap();
+        let client = new_client()
+            .set_redirect_uris(vec![loopback.clone(), custom_scheme.clone()])
+            .unwrap();
+        assert_eq!(
+            client.redirect_uris(),
+            &[loopback.clone(), custom_scheme.clone()]
+        );
+
+        let new_request = || {
+            client.authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+        };
+        let (authorize_url, _, _) = new_request().url().unwrap();
+        assert!(authorize_url
+            .to_string()
+            .contains("&redirect_uri=http%3A%2F%2F127.0.0.1%3A8888%2Fcb&"));
+
+        let (authorize_url, _, _) = new_request()
+            .select_redirect_uri(&custom_scheme)
+            .unwrap()
+            .url()
+            .unwrap();
+        assert!(authorize_url
+            .to_string()
+            .contains("&redirect_uri=com.example.app%3A%2Fcb&"));
+
+        let unregistered = RedirectUrl::new("https://evil.example.com/cb".to_string()).unwrap();
+        match new_request().select_redirect_uri(&unregistered) {
+            Err(RedirectUriError::Unregistered(uri)) => {
+                assert_eq!(uri, "https://evil.example.com/cb")
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("expected unregistered redirect URI to be rejected"),
+        }
+        assert!(client
+            .exchange_code_with_redirect_uri(
+                AuthorizationCode::new("code".to_string()),
+                &unregistered
+            )
+            .is_err());
+
+        let result = client
+            .exchange_code_with_redirect_uri(
+                AuthorizationCode::new("code".to_string()),
+                &custom_scheme,
+            )
+            .unwrap()
+            .request(
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    let body = String::from_utf8(request.body).unwrap();
+                    assert!(body.contains("redirect_uri=com.example.app%3A%2Fcb"));
+                    Err(std::io::Error::other("done"))
+                },
+            );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_authorize_url_extra_params() {
+        let client = new_client();
+        let params = vec![
+            ("domain_hint".to_string(), "example.com".to_string()),
+            ("connection".to_string(), "github".to_string()),
+        ];
+
+        let (authorize_url, _, _) = client
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_extra_params(params)
+            .add_extra_params([("foo", "bar")])
+            .url()
+            .unwrap();
+        assert_eq!(
+            "https://example/authorize?response_type=code&client_id=aaa&state=CSRF123&\
+             scope=openid&domain_hint=example.com&connection=github&foo=bar&nonce=NONCE456",
+            authorize_url.to_string()
+        );
+    }
+
     #[test]
     fn test_authorize_url_redirect_url_override() {
         let client = new_client()
@@ -1755,7 +2939,8 @@ mod tests {
             .set_redirect_uri(Cow::Owned(
                 RedirectUrl::new("http://localhost:8888/alternative".to_string()).unwrap(),
             ))
-            .url();
+            .url()
+            .unwrap();
         assert_eq!(
             "https://example/authorize?response_type=code&client_id=aaa&\
              state=CSRF123&redirect_uri=http%3A%2F%2Flocalhost%3A8888%2Falternative&scope=openid+email&\
@@ -1764,4 +2949,73 @@ mod tests {
             authorize_url.to_string()
         );
     }
+
+    #[test]
+    fn test_exchange_client_credentials_for_audience() {
+        use http::header::CONTENT_TYPE;
+        use http::{HeaderMap, HeaderValue, StatusCode};
+
+        use crate::{Audience, HttpRequest, HttpResponse, OAuth2TokenResponse};
+
+        let client = new_client();
+        let audience = Audience::new("https://api.example".to_string());
+        let token_response = client
+            .exchange_client_credentials_for_audience(&audience)
+            .add_scope(Scope::new("read:items".to_string()))
+            .request(
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    assert_eq!(
+                        String::from_utf8(request.body).unwrap(),
+                        "grant_type=client_credentials&scope=read%3Aitems&\
+                     audience=https%3A%2F%2Fapi.example"
+                    );
+                    Ok(HttpResponse {
+                        status_code: StatusCode::OK,
+                        headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                            .into_iter()
+                            .collect::<HeaderMap>(),
+                        body: br#"{"access_token":"the_token","token_type":"bearer"}"#.to_vec(),
+                    })
+                },
+            )
+            .unwrap();
+        assert_eq!(token_response.access_token().secret(), "the_token");
+        assert!(token_response.id_token().is_none());
+    }
+
+    #[test]
+    fn test_error_source_chains() {
+        use std::error::Error;
+
+        use crate::{
+            ClaimsVerificationError, DiscoveryError, JsonWebTokenError, SignatureVerificationError,
+            SigningError, UserInfoError,
+        };
+
+        // All public error types must compose with `anyhow`, `eyre`, and similar crates.
+        fn assert_error<E: Error + Send + Sync + 'static>() {}
+        assert_error::<ClaimsVerificationError>();
+        assert_error::<DiscoveryError<std::io::Error>>();
+        assert_error::<JsonWebTokenError>();
+        assert_error::<SignatureVerificationError>();
+        assert_error::<SigningError>();
+        assert_error::<UserInfoError<std::io::Error>>();
+
+        let err = anyhow::Error::new(UserInfoError::<std::io::Error>::ClaimsVerification(
+            ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            ),
+        ));
+        assert_eq!(
+            err.chain().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "Failed to verify claims",
+                "Signature verification failed",
+                "No matching key found",
+            ]
+        );
+
+        let err = JsonWebTokenError::SigningError(SigningError::CryptoError);
+        assert_eq!(err.source().unwrap().to_string(), "Crypto error");
+    }
 }
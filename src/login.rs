@@ -0,0 +1,1179 @@
+use std::future::Future;
+
+use oauth2::{
+    AuthorizationCode, CsrfToken, ErrorResponse, PkceCodeChallenge, PkceCodeVerifier, RefreshToken,
+    RequestTokenError, RevocableToken, Scope, TokenIntrospectionResponse, TokenType,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+use crate::core::{CoreParsedAuthorizationResponse, CoreResponseType};
+use crate::{
+    AdditionalClaims, AuthDisplay, AuthPrompt, AuthenticationFlow, AuthorizationResponseError,
+    ClaimsVerificationError, Client, GenderClaim, HttpRequest, HttpResponse, IdToken,
+    IdTokenClaims, IdTokenVerifier, JsonWebKey, JsonWebKeyType, JsonWebKeyUse,
+    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, Nonce, OAuth2TokenResponse, TokenResponse,
+    TokenResponseValidationError, TokenResponseValidator,
+};
+
+///
+/// High-level relying party login handler implementing the
+/// [Authorization Code Flow](https://openid.net/specs/openid-connect-core-1_0.html#CodeFlowAuth)
+/// with PKCE.
+///
+/// This type wraps a [`Client`] and performs all of the state, nonce, PKCE and access token hash
+/// checks required to log a user in:
+///  1. [`begin_login`](Self::begin_login) returns the URL to which the user should be redirected,
+///     along with a [`PendingLogin`] that must be stored (e.g., in the user's session) until the
+///     user returns to the redirect URI.
+///  2. [`complete_login`](Self::complete_login) (or
+///     [`complete_login_async`](Self::complete_login_async)) verifies the [`LoginCallback`]
+///     parameters received at the redirect URI against the [`PendingLogin`], exchanges the
+///     authorization code, and verifies the returned ID token.
+///  3. [`refresh`](Self::refresh) (or [`refresh_async`](Self::refresh_async)) uses the refresh
+///     token of a [`VerifiedSession`] to obtain fresh tokens.
+///
+/// Applications that store the pending login in a server-side session store can instead use
+/// [`PendingLogin::to_blob`] (or [`PendingLoginSealer`](crate::PendingLoginSealer) for browser
+/// cookies) and pass the redirect URI's raw query string to
+/// [`complete_login_from_params`](Self::complete_login_from_params) (or
+/// [`complete_login_from_params_async`](Self::complete_login_from_params_async)).
+///
+/// Applications requiring finer-grained control should use [`Client`] directly.
+///
+#[derive(Clone, Debug)]
+pub struct OidcLoginHandler<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+    TIR: TokenIntrospectionResponse<TT>,
+    RT: RevocableToken,
+    TRE: ErrorResponse,
+{
+    client: Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>,
+    scopes: Vec<Scope>,
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>
+    OidcLoginHandler<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>
+where
+    AC: AdditionalClaims + Clone,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+    TIR: TokenIntrospectionResponse<TT>,
+    RT: RevocableToken,
+    TRE: ErrorResponse + 'static,
+{
+    ///
+    /// Initializes a login handler wrapping the given client.
+    ///
+    /// The client must be configured with a redirect URI (see [`Client::set_redirect_uri`]).
+    ///
+    pub fn new(client: Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>) -> Self {
+        Self {
+            client,
+            scopes: Vec::new(),
+        }
+    }
+
+    ///
+    /// Appends a scope to request in addition to the `openid` scope.
+    ///
+    pub fn add_scope(mut self, scope: Scope) -> Self {
+        self.scopes.push(scope);
+        self
+    }
+
+    ///
+    /// Returns the wrapped client.
+    ///
+    pub fn client(&self) -> &Client<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE> {
+        &self.client
+    }
+
+    ///
+    /// Begins a new login, returning the URL to which the user should be redirected and the
+    /// pending login state.
+    ///
+    /// The returned [`PendingLogin`] contains secrets and must be stored securely (e.g., in a
+    /// server-side session or an encrypted cookie) until it's passed to
+    /// [`complete_login`](Self::complete_login).
+    ///
+    pub fn begin_login(&self) -> (Url, PendingLogin) {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let (url, csrf_state, nonce) = self
+            .client
+            .authorize_url(
+                AuthenticationFlow::<CoreResponseType>::AuthorizationCode,
+                CsrfToken::new_random,
+                Nonce::new_random,
+            )
+            .add_scopes(self.scopes.iter().cloned())
+            .set_pkce_challenge(pkce_challenge)
+            .url()
+            // This should never arise, since the request always includes an S256 code challenge.
+            .expect("login request violates PKCE policy");
+        (
+            url,
+            PendingLogin {
+                csrf_state,
+                nonce,
+                pkce_verifier,
+                redirect_target: None,
+            },
+        )
+    }
+
+    ///
+    /// Completes a login using the specified synchronous HTTP client.
+    ///
+    pub fn complete_login<HC, RE>(
+        &self,
+        callback: LoginCallback,
+        pending_login: PendingLogin,
+        http_client: HC,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        Self::verify_callback_state(&callback, &pending_login)?;
+        self.client.exchange_code_and_verify(
+            callback.code,
+            Some(pending_login.pkce_verifier),
+            &pending_login.nonce,
+            http_client,
+        )
+    }
+
+    ///
+    /// Completes a login using the specified asynchronous HTTP client.
+    ///
+    pub async fn complete_login_async<C, F, RE>(
+        &self,
+        callback: LoginCallback,
+        pending_login: PendingLogin,
+        http_client: C,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        Self::verify_callback_state(&callback, &pending_login)?;
+        self.client
+            .exchange_code_and_verify_async(
+                callback.code,
+                Some(pending_login.pkce_verifier),
+                &pending_login.nonce,
+                http_client,
+            )
+            .await
+    }
+
+    ///
+    /// Completes a login from the redirect URI's raw query string (or `form_post` body) and a
+    /// pending login blob returned by [`PendingLogin::to_blob`], using the specified synchronous
+    /// HTTP client.
+    ///
+    /// If the authorization server redirected with an error response, it is returned as
+    /// [`AuthorizationResponseError::ErrorResponse`] wrapped in
+    /// [`LoginError::AuthorizationResponse`].
+    ///
+    pub fn complete_login_from_params<HC, RE>(
+        &self,
+        callback_params: &str,
+        pending_login: &str,
+        http_client: HC,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let (callback, pending_login) = Self::decode_params(callback_params, pending_login)?;
+        self.complete_login(callback, pending_login, http_client)
+    }
+
+    ///
+    /// Completes a login from the redirect URI's raw query string (or `form_post` body) and a
+    /// pending login blob, using the specified asynchronous HTTP client.
+    ///
+    /// See [`complete_login_from_params`](Self::complete_login_from_params) for details.
+    ///
+    pub async fn complete_login_from_params_async<C, F, RE>(
+        &self,
+        callback_params: &str,
+        pending_login: &str,
+        http_client: C,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let (callback, pending_login) = Self::decode_params(callback_params, pending_login)?;
+        self.complete_login_async(callback, pending_login, http_client)
+            .await
+    }
+
+    ///
+    /// Refreshes a session's tokens using the specified synchronous HTTP client.
+    ///
+    /// If the token response includes a new ID token, it is verified and must identify the same
+    /// user as the original ID token. Otherwise, the original ID token claims are retained.
+    ///
+    /// The refresh request omits the `scope` parameter so that the provider grants the same
+    /// scopes (including `openid`) as the original login, as specified in
+    /// [Section 6 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-6).
+    ///
+    pub fn refresh<HC, RE>(
+        &self,
+        session: &VerifiedSession<AC, GC, TR>,
+        http_client: HC,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let refresh_token = session
+            .refresh_token()
+            .ok_or(LoginError::MissingRefreshToken)?;
+        let token_response = self
+            .client
+            .exchange_refresh_token(refresh_token)
+            .request(http_client)
+            .map_err(LoginError::TokenRequest)?;
+        self.refreshed_session(session, token_response)
+    }
+
+    ///
+    /// Refreshes a session's tokens using the specified asynchronous HTTP client.
+    ///
+    /// See [`refresh`](Self::refresh) for details.
+    ///
+    pub async fn refresh_async<C, F, RE>(
+        &self,
+        session: &VerifiedSession<AC, GC, TR>,
+        http_client: C,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let refresh_token = session
+            .refresh_token()
+            .ok_or(LoginError::MissingRefreshToken)?;
+        let token_response = self
+            .client
+            .exchange_refresh_token(refresh_token)
+            .request_async(http_client)
+            .await
+            .map_err(LoginError::TokenRequest)?;
+        self.refreshed_session(session, token_response)
+    }
+
+    fn decode_params<RE>(
+        callback_params: &str,
+        pending_login: &str,
+    ) -> Result<(LoginCallback, PendingLogin), LoginError<RE, TE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let pending_login =
+            PendingLogin::from_blob(pending_login).map_err(LoginError::InvalidPendingLogin)?;
+        let callback = LoginCallback::from_params(callback_params)
+            .map_err(LoginError::AuthorizationResponse)?;
+        Ok((callback, pending_login))
+    }
+
+    fn verify_callback_state<RE>(
+        callback: &LoginCallback,
+        pending_login: &PendingLogin,
+    ) -> Result<(), LoginError<RE, TE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        use subtle::ConstantTimeEq;
+        if bool::from(
+            callback
+                .state
+                .secret()
+                .as_bytes()
+                .ct_eq(pending_login.csrf_state.secret().as_bytes()),
+        ) {
+            Ok(())
+        } else {
+            Err(LoginError::InvalidState)
+        }
+    }
+
+    fn refreshed_session<RE>(
+        &self,
+        session: &VerifiedSession<AC, GC, TR>,
+        token_response: TR,
+    ) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        let id_token_claims = if let Some(id_token) = token_response.id_token() {
+            // Refreshed ID tokens aren't required to contain a nonce (see
+            // https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse).
+            // Refreshed ID tokens must have the same subject as the original ID token.
+            let id_token_claims = id_token
+                .claims(
+                    &self
+                        .client
+                        .id_token_verifier()
+                        .set_expected_subject(session.id_token_claims.subject().clone()),
+                    |_: Option<&Nonce>| Ok(()),
+                )
+                .map_err(LoginError::ClaimsVerification)?
+                .clone();
+            if id_token_claims.issuer() != session.id_token_claims.issuer() {
+                return Err(LoginError::ClaimsVerification(
+                    ClaimsVerificationError::InvalidIssuer(
+                        "refreshed ID token issuer does not match original ID token".to_string(),
+                    ),
+                ));
+            }
+            verify_token_hashes(&token_response, id_token, &id_token_claims, None)
+                .map_err(LoginError::ClaimsVerification)?;
+            id_token_claims
+        } else {
+            session.id_token_claims.clone()
+        };
+
+        let retained_refresh_token = if token_response.refresh_token().is_some() {
+            None
+        } else {
+            session.refresh_token().cloned()
+        };
+        Ok(VerifiedSession {
+            token_response,
+            id_token_claims,
+            retained_refresh_token,
+        })
+    }
+}
+
+// Validates the token response to an authorization code exchange and verifies its ID token,
+// including the `at_hash` and `c_hash` claims (if present).
+pub(crate) fn verify_code_token_response<AC, GC, JE, JS, JT, JU, K, RE, TE, TR, TT>(
+    verifier: &IdTokenVerifier<JS, JT, JU, K>,
+    token_response: TR,
+    code: &AuthorizationCode,
+    nonce: &Nonce,
+) -> Result<VerifiedSession<AC, GC, TR>, LoginError<RE, TE>>
+where
+    AC: AdditionalClaims + Clone,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RE: std::error::Error + 'static,
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType,
+{
+    TokenResponseValidator::new()
+        .validate(&token_response, &[])
+        .map_err(|err| match err {
+            TokenResponseValidationError::MissingIdToken => LoginError::MissingIdToken,
+            err => LoginError::TokenResponseValidation(err),
+        })?;
+    let id_token = token_response
+        .id_token()
+        .ok_or(LoginError::MissingIdToken)?;
+    let id_token_claims = id_token
+        .claims(verifier, nonce)
+        .map_err(LoginError::ClaimsVerification)?
+        .clone();
+    verify_token_hashes(&token_response, id_token, &id_token_claims, Some(code))
+        .map_err(LoginError::ClaimsVerification)?;
+
+    Ok(VerifiedSession {
+        token_response,
+        id_token_claims,
+        retained_refresh_token: None,
+    })
+}
+
+// Verifies the `at_hash` claim against the access token and, if a code is given, the `c_hash`
+// claim against the authorization code.
+fn verify_token_hashes<AC, GC, JE, JS, JT, TR, TT>(
+    token_response: &TR,
+    id_token: &IdToken<AC, GC, JE, JS, JT>,
+    id_token_claims: &IdTokenClaims<AC, GC>,
+    code: Option<&AuthorizationCode>,
+) -> Result<(), ClaimsVerificationError>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType,
+{
+    let expected_code_hash = code.and_then(|code| {
+        id_token_claims
+            .code_hash()
+            .map(|code_hash| (code, code_hash))
+    });
+    if id_token_claims.access_token_hash().is_none() && expected_code_hash.is_none() {
+        return Ok(());
+    }
+    let alg = id_token
+        .signing_alg()
+        .map_err(|err| ClaimsVerificationError::Unsupported(err.to_string()))?;
+    if let Some(expected_access_token_hash) = id_token_claims.access_token_hash() {
+        expected_access_token_hash.verify(token_response.access_token(), &alg)?;
+    }
+    if let Some((code, expected_code_hash)) = expected_code_hash {
+        expected_code_hash.verify(code, &alg)?;
+    }
+    Ok(())
+}
+
+///
+/// State of a login begun by [`OidcLoginHandler::begin_login`].
+///
+/// This type contains secrets and must be stored securely until the login is completed. It
+/// implements [`Serialize`] and [`Deserialize`] so that it can be stored in a session.
+///
+#[derive(Deserialize, Serialize)]
+pub struct PendingLogin {
+    pub(crate) csrf_state: CsrfToken,
+    pub(crate) nonce: Nonce,
+    pub(crate) pkce_verifier: PkceCodeVerifier,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) redirect_target: Option<String>,
+}
+impl PendingLogin {
+    ///
+    /// Returns the CSRF `state` parameter sent to the authorization endpoint.
+    ///
+    pub fn csrf_state(&self) -> &CsrfToken {
+        &self.csrf_state
+    }
+
+    ///
+    /// Returns the nonce sent to the authorization endpoint.
+    ///
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    ///
+    /// Specifies where the application should send the user once the login is completed (e.g.,
+    /// the path of the page that required authentication).
+    ///
+    /// The target is not validated. To avoid open redirects, applications should only accept
+    /// relative paths or URLs on their own origin.
+    ///
+    pub fn set_redirect_target(mut self, redirect_target: String) -> Self {
+        self.redirect_target = Some(redirect_target);
+        self
+    }
+
+    ///
+    /// Returns where the application should send the user once the login is completed, if
+    /// specified.
+    ///
+    pub fn redirect_target(&self) -> Option<&str> {
+        self.redirect_target.as_deref()
+    }
+
+    ///
+    /// Serializes the pending login into a URL-safe string.
+    ///
+    /// The string is merely base64url-encoded JSON: it is neither encrypted nor authenticated, so
+    /// anyone holding it can read the CSRF state, nonce, and PKCE code verifier, and can forge or
+    /// tamper with it. It must only be stored somewhere the user can't read or modify, such as a
+    /// server-side session. Use [`PendingLoginSealer`](crate::PendingLoginSealer) instead to store
+    /// a pending login in a browser cookie.
+    ///
+    pub fn to_blob(&self) -> String {
+        base64::encode_config(
+            serde_json::to_vec(self).expect("failed to serialize pending login"),
+            base64::URL_SAFE_NO_PAD,
+        )
+    }
+
+    ///
+    /// Deserializes a pending login previously serialized with [`to_blob`](Self::to_blob).
+    ///
+    pub fn from_blob(blob: &str) -> Result<Self, serde_json::Error> {
+        let json = base64::decode_config(blob, base64::URL_SAFE_NO_PAD)
+            .map_err(<serde_json::Error as serde::de::Error>::custom)?;
+        serde_json::from_slice(&json)
+    }
+}
+impl std::fmt::Debug for PendingLogin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingLogin")
+            .field("csrf_state", &self.csrf_state)
+            .field("nonce", &self.nonce)
+            .field("pkce_verifier", &"[redacted]")
+            .field("redirect_target", &self.redirect_target)
+            .finish()
+    }
+}
+
+///
+/// Parameters passed by the authorization server to the redirect URI.
+///
+/// This type may be deserialized directly from the redirect URI's query string.
+///
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LoginCallback {
+    code: AuthorizationCode,
+    state: CsrfToken,
+}
+impl LoginCallback {
+    ///
+    /// Initializes login callback parameters.
+    ///
+    pub fn new(code: AuthorizationCode, state: CsrfToken) -> Self {
+        Self { code, state }
+    }
+
+    ///
+    /// Parses login callback parameters from the redirect URI's query string or a `form_post`
+    /// request body.
+    ///
+    /// If the response contains an `error` parameter, the error response is returned as
+    /// [`AuthorizationResponseError::ErrorResponse`].
+    ///
+    pub fn from_params(params: &str) -> Result<Self, AuthorizationResponseError> {
+        let response = CoreParsedAuthorizationResponse::from_params(params)?;
+        let code = response
+            .code()
+            .cloned()
+            .ok_or(AuthorizationResponseError::MissingCode)?;
+        let state = response
+            .state()
+            .cloned()
+            .ok_or(AuthorizationResponseError::MissingState)?;
+        Ok(Self { code, state })
+    }
+}
+
+///
+/// Session established by a successful login.
+///
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VerifiedSession<AC, GC, TR>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    #[serde(bound(deserialize = "TR: DeserializeOwned", serialize = "TR: Serialize"))]
+    token_response: TR,
+    #[serde(bound = "AC: AdditionalClaims, GC: GenderClaim")]
+    id_token_claims: IdTokenClaims<AC, GC>,
+    // Refresh token retained from an earlier token response when the latest token response
+    // omits it (i.e., the provider doesn't rotate refresh tokens).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retained_refresh_token: Option<RefreshToken>,
+}
+impl<AC, GC, TR> VerifiedSession<AC, GC, TR>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    ///
+    /// Returns the refresh token used to refresh this session, if any.
+    ///
+    /// Providers aren't required to issue a new refresh token when refreshing a session (see
+    /// [RFC 6749 Section 6](https://www.rfc-editor.org/rfc/rfc6749#section-6)), in which case the
+    /// refresh token from the earlier token response continues to be used.
+    ///
+    pub fn refresh_token<TT>(&self) -> Option<&RefreshToken>
+    where
+        TR: OAuth2TokenResponse<TT>,
+        TT: TokenType,
+    {
+        self.token_response
+            .refresh_token()
+            .or(self.retained_refresh_token.as_ref())
+    }
+
+    ///
+    /// Returns the token response.
+    ///
+    pub fn token_response(&self) -> &TR {
+        &self.token_response
+    }
+
+    ///
+    /// Returns the verified ID token claims.
+    ///
+    pub fn id_token_claims(&self) -> &IdTokenClaims<AC, GC> {
+        &self.id_token_claims
+    }
+}
+
+///
+/// Error completing a login or refreshing a session.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum LoginError<RE, TE>
+where
+    RE: std::error::Error + 'static,
+    TE: ErrorResponse + 'static,
+{
+    ///
+    /// The authorization response passed to the redirect URI is invalid or indicates an error.
+    ///
+    #[error("Invalid authorization response")]
+    AuthorizationResponse(#[source] AuthorizationResponseError),
+    ///
+    /// Failed to verify the ID token claims.
+    ///
+    #[error("Failed to verify claims")]
+    ClaimsVerification(#[source] ClaimsVerificationError),
+    ///
+    /// The pending login blob could not be decoded.
+    ///
+    #[error("Invalid pending login")]
+    InvalidPendingLogin(#[source] serde_json::Error),
+    ///
+    /// The `state` parameter passed to the redirect URI does not match the pending login.
+    ///
+    #[error("CSRF state mismatch")]
+    InvalidState,
+    ///
+    /// The token response did not include an ID token.
+    ///
+    #[error("Server did not return an ID token")]
+    MissingIdToken,
+    ///
+    /// The session has no refresh token.
+    ///
+    #[error("Session has no refresh token")]
+    MissingRefreshToken,
+    ///
+    /// The token request failed.
+    ///
+    #[error("Token request failed")]
+    TokenRequest(#[source] RequestTokenError<RE, TE>),
+    ///
+    /// The token response is invalid (e.g., it has an unexpected token type).
+    ///
+    #[error("Invalid token response")]
+    TokenResponseValidation(#[source] TokenResponseValidationError),
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::cell::RefCell;
+
+    use chrono::{Duration, Utc};
+    use http::header::CONTENT_TYPE;
+    use http::{HeaderMap, HeaderValue, StatusCode};
+    use oauth2::{
+        AccessToken, AuthUrl, AuthorizationCode, ClientId, CsrfToken, RedirectUrl, RefreshToken,
+        Scope, TokenUrl,
+    };
+
+    use crate::core::{
+        CoreAuthErrorResponseType, CoreClient, CoreIdToken, CoreIdTokenClaims, CoreJsonWebKeySet,
+        CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey, CoreTokenResponse,
+    };
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+    use crate::{
+        Audience, AuthorizationResponseError, ClaimsVerificationError, HttpRequest, HttpResponse,
+        IssuerUrl, Nonce, OAuth2TokenResponse, StandardClaims, SubjectIdentifier,
+        TokenResponseValidationError,
+    };
+
+    use super::{LoginCallback, LoginError, OidcLoginHandler, PendingLogin};
+
+    pub(crate) fn new_handler() -> OidcLoginHandler<
+        crate::EmptyAdditionalClaims,
+        crate::core::CoreAuthDisplay,
+        crate::core::CoreGenderClaim,
+        crate::core::CoreJweContentEncryptionAlgorithm,
+        CoreJwsSigningAlgorithm,
+        crate::core::CoreJsonWebKeyType,
+        crate::core::CoreJsonWebKeyUse,
+        crate::core::CoreJsonWebKey,
+        crate::core::CoreAuthPrompt,
+        crate::StandardErrorResponse<crate::core::CoreErrorResponseType>,
+        CoreTokenResponse,
+        crate::core::CoreTokenType,
+        crate::core::CoreTokenIntrospectionResponse,
+        crate::core::CoreRevocableToken,
+        crate::core::CoreRevocationErrorResponse,
+    > {
+        let rsa_pub_key = serde_json::from_str(TEST_RSA_PUB_KEY).expect("deserialization failed");
+        OidcLoginHandler::new(
+            CoreClient::new(
+                ClientId::new("my_client".to_string()),
+                None,
+                IssuerUrl::new("https://example.com".to_string()).unwrap(),
+                AuthUrl::new("https://example.com/authorize".to_string()).unwrap(),
+                Some(TokenUrl::new("https://example.com/token".to_string()).unwrap()),
+                None,
+                CoreJsonWebKeySet::new(vec![rsa_pub_key]),
+            )
+            .set_redirect_uri(RedirectUrl::new("https://app.example.com/cb".to_string()).unwrap()),
+        )
+    }
+
+    pub(crate) fn new_id_token(
+        nonce: Option<&Nonce>,
+        access_token: Option<&AccessToken>,
+        code: Option<&AuthorizationCode>,
+    ) -> CoreIdToken {
+        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                IssuerUrl::new("https://example.com".to_string()).unwrap(),
+                vec![Audience::new("my_client".to_string())],
+                Utc::now() + Duration::seconds(300),
+                Utc::now(),
+                StandardClaims::new(SubjectIdentifier::new("the_subject".to_string())),
+                Default::default(),
+            )
+            .set_nonce(nonce.cloned()),
+            &rsa_priv_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            access_token,
+            code,
+        )
+        .unwrap()
+    }
+
+    pub(crate) fn token_response(
+        id_token: Option<CoreIdToken>,
+        access_token: &AccessToken,
+    ) -> HttpResponse {
+        let mut body = serde_json::json!({
+            "access_token": access_token.secret(),
+            "token_type": "bearer",
+            "refresh_token": "the_refresh_token",
+        });
+        if let Some(id_token) = id_token {
+            body["id_token"] = serde_json::Value::String(id_token.to_string());
+        }
+        HttpResponse {
+            status_code: StatusCode::OK,
+            headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                .into_iter()
+                .collect::<HeaderMap>(),
+            body: serde_json::to_vec(&body).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_login() {
+        let handler = new_handler();
+        let (url, pending_login) = handler.begin_login();
+
+        let query = url.query_pairs().collect::<Vec<_>>();
+        let param = |name: &str| {
+            query
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        assert_eq!(param("response_type").unwrap(), "code");
+        assert_eq!(param("code_challenge_method").unwrap(), "S256");
+        assert_eq!(
+            param("state").unwrap(),
+            *pending_login.csrf_state().secret()
+        );
+        assert_eq!(param("nonce").unwrap(), *pending_login.nonce().secret());
+
+        // The pending login must survive a serialization round trip (e.g., via a session store).
+        let pending_login =
+            serde_json::from_str::<PendingLogin>(&serde_json::to_string(&pending_login).unwrap())
+                .unwrap();
+
+        // CSRF state mismatch
+        match handler.complete_login(
+            LoginCallback::new(
+                AuthorizationCode::new("the_code".to_string()),
+                CsrfToken::new("wrong_state".to_string()),
+            ),
+            serde_json::from_str::<PendingLogin>(&serde_json::to_string(&pending_login).unwrap())
+                .unwrap(),
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                panic!("unexpected token request")
+            },
+        ) {
+            Err(LoginError::InvalidState) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let access_token = AccessToken::new("the_access_token".to_string());
+        let nonce = pending_login.nonce().clone();
+        let requests = RefCell::new(Vec::new());
+        let session = handler
+            .complete_login(
+                LoginCallback::new(
+                    AuthorizationCode::new("the_code".to_string()),
+                    pending_login.csrf_state().clone(),
+                ),
+                pending_login,
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    requests.borrow_mut().push(request);
+                    Ok(token_response(
+                        Some(new_id_token(Some(&nonce), Some(&access_token), None)),
+                        &access_token,
+                    ))
+                },
+            )
+            .expect("login should succeed");
+        let body = String::from_utf8(requests.borrow()[0].body.clone()).unwrap();
+        assert!(body.contains("code=the_code"));
+        assert!(body.contains("code_verifier="));
+        assert_eq!(session.id_token_claims().subject().as_str(), "the_subject");
+
+        // Substituted access token (at_hash mismatch)
+        let (_, pending_login) = handler.begin_login();
+        let nonce = pending_login.nonce().clone();
+        match handler.complete_login(
+            LoginCallback::new(
+                AuthorizationCode::new("the_code".to_string()),
+                pending_login.csrf_state().clone(),
+            ),
+            pending_login,
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(token_response(
+                    Some(new_id_token(Some(&nonce), Some(&access_token), None)),
+                    &AccessToken::new("other_access_token".to_string()),
+                ))
+            },
+        ) {
+            Err(LoginError::ClaimsVerification(
+                ClaimsVerificationError::InvalidAccessTokenHash(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Refresh without a new ID token retains the original claims
+        let new_access_token = AccessToken::new("new_access_token".to_string());
+        let refreshed = handler
+            .refresh(
+                &session,
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    let body = String::from_utf8(request.body).unwrap();
+                    assert!(body.contains("refresh_token=the_refresh_token"));
+                    Ok(token_response(None, &new_access_token))
+                },
+            )
+            .expect("refresh should succeed");
+        assert_eq!(
+            refreshed.token_response().access_token().secret(),
+            new_access_token.secret()
+        );
+        assert_eq!(refreshed.id_token_claims(), session.id_token_claims());
+        assert_eq!(
+            refreshed
+                .token_response()
+                .refresh_token()
+                .map(RefreshToken::secret),
+            Some(&"the_refresh_token".to_string())
+        );
+
+        // Refresh with a new ID token (no nonce)
+        handler
+            .refresh(
+                &session,
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Ok(token_response(
+                        Some(new_id_token(None, Some(&new_access_token), None)),
+                        &new_access_token,
+                    ))
+                },
+            )
+            .expect("refresh should succeed");
+    }
+
+    #[test]
+    fn test_refresh_omits_scopes() {
+        let handler = new_handler().add_scope(Scope::new("email".to_string()));
+        let (url, pending_login) = handler.begin_login();
+        assert!(url
+            .query_pairs()
+            .any(|(name, value)| name == "scope" && value == "openid email"));
+
+        let access_token = AccessToken::new("the_access_token".to_string());
+        let nonce = pending_login.nonce().clone();
+        let session = handler
+            .complete_login(
+                LoginCallback::new(
+                    AuthorizationCode::new("the_code".to_string()),
+                    pending_login.csrf_state().clone(),
+                ),
+                pending_login,
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Ok(token_response(
+                        Some(new_id_token(Some(&nonce), Some(&access_token), None)),
+                        &access_token,
+                    ))
+                },
+            )
+            .expect("login should succeed");
+
+        // Requesting only the additional scopes would narrow the grant and drop `openid`.
+        handler
+            .refresh(
+                &session,
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    let body = String::from_utf8(request.body).unwrap();
+                    assert!(body.contains("refresh_token=the_refresh_token"));
+                    assert!(!body.contains("scope="));
+                    Ok(token_response(None, &access_token))
+                },
+            )
+            .expect("refresh should succeed");
+    }
+
+    #[test]
+    fn test_refresh_without_refresh_token_rotation() {
+        let handler = new_handler();
+        let (_, pending_login) = handler.begin_login();
+        let access_token = AccessToken::new("the_access_token".to_string());
+        let nonce = pending_login.nonce().clone();
+        let session = handler
+            .complete_login(
+                LoginCallback::new(
+                    AuthorizationCode::new("the_code".to_string()),
+                    pending_login.csrf_state().clone(),
+                ),
+                pending_login,
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Ok(token_response(
+                        Some(new_id_token(Some(&nonce), Some(&access_token), None)),
+                        &access_token,
+                    ))
+                },
+            )
+            .expect("login should succeed");
+
+        // The provider doesn't rotate refresh tokens, so refresh responses omit `refresh_token`.
+        let new_access_token = AccessToken::new("new_access_token".to_string());
+        let refresh_http_client = |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+            let body = String::from_utf8(request.body).unwrap();
+            assert!(body.contains("refresh_token=the_refresh_token"));
+            let mut response = token_response(None, &new_access_token);
+            let mut body = serde_json::from_slice::<serde_json::Value>(&response.body).unwrap();
+            body.as_object_mut().unwrap().remove("refresh_token");
+            response.body = serde_json::to_vec(&body).unwrap();
+            Ok(response)
+        };
+
+        let refreshed = handler
+            .refresh(&session, refresh_http_client)
+            .expect("first refresh should succeed");
+        assert!(refreshed.token_response().refresh_token().is_none());
+        assert_eq!(
+            refreshed.refresh_token().map(RefreshToken::secret),
+            Some(&"the_refresh_token".to_string())
+        );
+
+        // The retained refresh token must survive a serialization round trip.
+        let refreshed = serde_json::from_str(&serde_json::to_string(&refreshed).unwrap()).unwrap();
+        let refreshed = handler
+            .refresh(&refreshed, refresh_http_client)
+            .expect("second refresh should succeed");
+        assert_eq!(
+            refreshed.token_response().access_token().secret(),
+            new_access_token.secret()
+        );
+        assert_eq!(
+            refreshed.refresh_token().map(RefreshToken::secret),
+            Some(&"the_refresh_token".to_string())
+        );
+    }
+
+    #[test]
+    fn test_exchange_code_and_verify() {
+        let handler = new_handler();
+        let client = handler.client();
+        let code = AuthorizationCode::new("the_code".to_string());
+        let access_token = AccessToken::new("the_access_token".to_string());
+        let nonce = Nonce::new("the_nonce".to_string());
+
+        let session = client
+            .exchange_code_and_verify(
+                code.clone(),
+                None,
+                &nonce,
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    let body = String::from_utf8(request.body).unwrap();
+                    assert!(body.contains("code=the_code"));
+                    assert!(!body.contains("code_verifier="));
+                    Ok(token_response(
+                        Some(new_id_token(Some(&nonce), Some(&access_token), Some(&code))),
+                        &access_token,
+                    ))
+                },
+            )
+            .expect("exchange should succeed");
+        assert_eq!(session.id_token_claims().subject().as_str(), "the_subject");
+
+        // Substituted authorization code (c_hash mismatch)
+        match client.exchange_code_and_verify(
+            code.clone(),
+            None,
+            &nonce,
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(token_response(
+                    Some(new_id_token(
+                        Some(&nonce),
+                        Some(&access_token),
+                        Some(&AuthorizationCode::new("other_code".to_string())),
+                    )),
+                    &access_token,
+                ))
+            },
+        ) {
+            Err(LoginError::ClaimsVerification(
+                ClaimsVerificationError::InvalidAuthorizationCodeHash(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Nonce mismatch
+        match client.exchange_code_and_verify(
+            code.clone(),
+            None,
+            &Nonce::new("other_nonce".to_string()),
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(token_response(
+                    Some(new_id_token(Some(&nonce), Some(&access_token), None)),
+                    &access_token,
+                ))
+            },
+        ) {
+            Err(LoginError::ClaimsVerification(ClaimsVerificationError::InvalidNonce(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Missing ID token
+        match client.exchange_code_and_verify(
+            code.clone(),
+            None,
+            &nonce,
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(token_response(None, &access_token))
+            },
+        ) {
+            Err(LoginError::MissingIdToken) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Unexpected token type
+        match client.exchange_code_and_verify(
+            code,
+            None,
+            &nonce,
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                let mut response = token_response(
+                    Some(new_id_token(Some(&nonce), Some(&access_token), None)),
+                    &access_token,
+                );
+                response.body = String::from_utf8(response.body)
+                    .unwrap()
+                    .replace("\"bearer\"", "\"mac\"")
+                    .into_bytes();
+                Ok(response)
+            },
+        ) {
+            Err(LoginError::TokenResponseValidation(
+                TokenResponseValidationError::UnexpectedTokenType(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_complete_login_from_params() {
+        let handler = new_handler();
+        let (_, pending_login) = handler.begin_login();
+        let blob = pending_login.to_blob();
+        let state = pending_login.csrf_state().secret().clone();
+        let nonce = pending_login.nonce().clone();
+        let access_token = AccessToken::new("the_access_token".to_string());
+
+        let params = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("code", "the_code")
+            .append_pair("state", &state)
+            .finish();
+        let session = handler
+            .complete_login_from_params(
+                &params,
+                &blob,
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    let body = String::from_utf8(request.body).unwrap();
+                    assert!(body.contains("code=the_code"));
+                    assert!(body.contains("code_verifier="));
+                    Ok(token_response(
+                        Some(new_id_token(Some(&nonce), Some(&access_token), None)),
+                        &access_token,
+                    ))
+                },
+            )
+            .expect("login should succeed");
+        assert_eq!(session.id_token_claims().subject().as_str(), "the_subject");
+
+        let no_request = |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+            panic!("unexpected token request")
+        };
+        match handler.complete_login_from_params(
+            &format!("error=access_denied&state={}", state),
+            &blob,
+            no_request,
+        ) {
+            Err(LoginError::AuthorizationResponse(AuthorizationResponseError::ErrorResponse(
+                err,
+            ))) => assert_eq!(*err.error(), CoreAuthErrorResponseType::AccessDenied),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match handler.complete_login_from_params(&format!("state={}", state), &blob, no_request) {
+            Err(LoginError::AuthorizationResponse(AuthorizationResponseError::MissingCode)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match handler.complete_login_from_params(&params, "not a blob", no_request) {
+            Err(LoginError::InvalidPendingLogin(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match handler.complete_login_from_params(
+            "code=the_code&state=wrong_state",
+            &blob,
+            no_request,
+        ) {
+            Err(LoginError::InvalidState) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -1,7 +1,13 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use oauth2::{ClientId, CsrfToken};
 use serde_with::skip_serializing_none;
 use url::Url;
 
+use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
+use crate::types::helpers::{deserialize_string_or_vec, serde_utc_seconds};
+use crate::verification::{AudiencesClaim, IssuerClaim};
 use crate::{
     core::{
         CoreAuthDisplay, CoreClaimName, CoreClaimType, CoreClientAuthMethod, CoreGrantType,
@@ -11,9 +17,11 @@ use crate::{
     },
     join_vec,
     types::{LogoutHint, PostLogoutRedirectUrl},
-    AdditionalClaims, AdditionalProviderMetadata, EmptyAdditionalProviderMetadata, EndSessionUrl,
-    GenderClaim, IdToken, JsonWebKeyType, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
-    LanguageTag, ProviderMetadata,
+    AdditionalClaims, AdditionalProviderMetadata, Audience, ClaimsVerificationError, DecryptionKey,
+    EmptyAdditionalProviderMetadata, EncryptionKey, EndSessionUrl, GenderClaim, IdToken,
+    IdTokenVerifier, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken,
+    JsonWebTokenId, JweContentEncryptionAlgorithm, JweKeyManagementAlgorithm, JwsSigningAlgorithm,
+    LanguageTag, PrivateSigningKey, ProviderMetadata, SessionIdentifier, SubjectIdentifier,
 };
 
 ///
@@ -38,7 +46,14 @@ where
     ///
     pub additional_metadata: A,
 }
-impl<A> AdditionalProviderMetadata for LogoutProviderMetadata<A> where A: AdditionalProviderMetadata {}
+impl<A> AdditionalProviderMetadata for LogoutProviderMetadata<A>
+where
+    A: AdditionalProviderMetadata,
+{
+    fn end_session_endpoint(&self) -> Option<&EndSessionUrl> {
+        self.end_session_endpoint.as_ref()
+    }
+}
 
 ///
 /// Provider metadata returned by [OpenID Connect Discovery](
@@ -198,21 +213,284 @@ impl LogoutRequest {
     }
 }
 
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+// Explicit JWT type of logout tokens (see
+// https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken).
+pub(crate) const LOGOUT_TOKEN_TYPE: &str = "logout+jwt";
+
+///
+/// Logout token claims, as described in
+/// [OpenID Connect Back-Channel Logout](
+///     https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LogoutTokenClaims {
+    #[serde(rename = "iss")]
+    issuer: IssuerUrl,
+    #[serde(rename = "aud", deserialize_with = "deserialize_string_or_vec")]
+    audiences: Vec<Audience>,
+    #[serde(rename = "iat", with = "serde_utc_seconds")]
+    issue_time: DateTime<Utc>,
+    #[serde(rename = "exp", with = "serde_utc_seconds")]
+    expiration: DateTime<Utc>,
+    #[serde(rename = "jti")]
+    jwt_id: JsonWebTokenId,
+    #[serde(rename = "sub", default, skip_serializing_if = "Option::is_none")]
+    subject: Option<SubjectIdentifier>,
+    #[serde(rename = "sid", default, skip_serializing_if = "Option::is_none")]
+    session_id: Option<SessionIdentifier>,
+    events: serde_json::Map<String, serde_json::Value>,
+    // Logout tokens are prohibited from containing a nonce. This field is only deserialized so that
+    // tokens containing one can be rejected during verification.
+    #[serde(default, skip_serializing)]
+    nonce: Option<serde_json::Value>,
+}
+impl LogoutTokenClaims {
+    ///
+    /// Initializes new logout token claims containing the Back-Channel Logout event.
+    ///
+    /// At least one of the `sub` and `sid` claims must be set (via
+    /// [`set_subject`](Self::set_subject) and [`set_session_id`](Self::set_session_id),
+    /// respectively) before the claims are signed with [`LogoutToken::new`].
+    ///
+    pub fn new(
+        issuer: IssuerUrl,
+        audiences: Vec<Audience>,
+        issue_time: DateTime<Utc>,
+        expiration: DateTime<Utc>,
+        jwt_id: JsonWebTokenId,
+    ) -> Self {
+        let mut events = serde_json::Map::new();
+        events.insert(
+            BACKCHANNEL_LOGOUT_EVENT.to_string(),
+            serde_json::Value::Object(serde_json::Map::new()),
+        );
+        Self {
+            issuer,
+            audiences,
+            issue_time,
+            expiration,
+            jwt_id,
+            subject: None,
+            session_id: None,
+            events,
+            nonce: None,
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["claim"] {
+            set_issuer -> issuer[IssuerUrl] ["iss"],
+            set_audiences -> audiences[Vec<Audience>] ["aud"],
+            set_issue_time -> issue_time[DateTime<Utc>] ["iat"],
+            set_expiration -> expiration[DateTime<Utc>] ["exp"],
+            set_jwt_id -> jwt_id[JsonWebTokenId] ["jti"],
+            set_subject -> subject[Option<SubjectIdentifier>] ["sub"],
+            set_session_id -> session_id[Option<SessionIdentifier>] ["sid"],
+        }
+    ];
+
+    ///
+    /// Returns the `events` claim.
+    ///
+    pub fn events(&self) -> &serde_json::Map<String, serde_json::Value> {
+        &self.events
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.subject.is_none() && self.session_id.is_none() {
+            return Err(
+                "logout token must contain a `sub` claim, a `sid` claim, or both".to_string(),
+            );
+        }
+        if !matches!(
+            self.events.get(BACKCHANNEL_LOGOUT_EVENT),
+            Some(serde_json::Value::Object(_))
+        ) {
+            return Err(format!(
+                "logout token `events` claim must contain a `{}` member whose value is an object",
+                BACKCHANNEL_LOGOUT_EVENT
+            ));
+        }
+        if self.nonce.is_some() {
+            return Err("logout token must not contain a `nonce` claim".to_string());
+        }
+        Ok(())
+    }
+}
+impl AudiencesClaim for LogoutTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(LogoutTokenClaims::audiences(self))
+    }
+}
+impl AudiencesClaim for &LogoutTokenClaims {
+    fn audiences(&self) -> Option<&Vec<Audience>> {
+        Some(LogoutTokenClaims::audiences(self))
+    }
+}
+impl IssuerClaim for LogoutTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(LogoutTokenClaims::issuer(self))
+    }
+}
+impl IssuerClaim for &LogoutTokenClaims {
+    fn issuer(&self) -> Option<&IssuerUrl> {
+        Some(LogoutTokenClaims::issuer(self))
+    }
+}
+
+///
+/// Logout token sent by an OpenID Connect Provider to a client's `backchannel_logout_uri`, as
+/// described in [OpenID Connect Back-Channel Logout](
+///     https://openid.net/specs/openid-connect-backchannel-1_0.html).
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LogoutToken<
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+>(#[serde(bound = "")] JsonWebToken<JE, JS, JT, LogoutTokenClaims, JsonWebTokenJsonPayloadSerde>);
+impl<JE, JS, JT> FromStr for LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    type Err = serde_json::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_string()))
+    }
+}
+impl<JE, JS, JT> LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Initializes a logout token with the specified claims, signed using the given signing key
+    /// and algorithm.
+    ///
+    /// The token is explicitly typed using the `logout+jwt` JOSE header `typ` value, which
+    /// prevents it from being mistaken for another kind of token.
+    ///
+    /// Returns [`JsonWebTokenError::InvalidClaims`] if the claims contain neither a `sub` nor a
+    /// `sid` claim.
+    ///
+    pub fn new<JU, K, S>(
+        claims: LogoutTokenClaims,
+        signing_key: &S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        claims
+            .validate()
+            .map_err(JsonWebTokenError::InvalidClaims)?;
+        let mut header_params = serde_json::Map::new();
+        header_params.insert(
+            "typ".to_string(),
+            serde_json::Value::String(LOGOUT_TOKEN_TYPE.to_string()),
+        );
+        JsonWebToken::new_with_header_params(claims, signing_key, &alg, header_params).map(Self)
+    }
+
+    ///
+    /// Verifies and returns a reference to the logout token claims.
+    ///
+    /// The token's signature, issuer, audience, and expiration are verified in the same manner as
+    /// for ID tokens, using the given ID token verifier. Additionally, the `events` claim must
+    /// contain the Back-Channel Logout event, at least one of the `sub` and `sid` claims must be
+    /// present, and the `nonce` claim must be absent. The JOSE header `typ` value must be
+    /// `logout+jwt` or `JWT`, or omitted (see [`IdTokenVerifier::require_explicit_type`]).
+    ///
+    pub fn claims<'a, JU, K>(
+        &'a self,
+        verifier: &IdTokenVerifier<JS, JT, JU, K>,
+    ) -> Result<&'a LogoutTokenClaims, ClaimsVerificationError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+    {
+        verifier.verified_logout_token_claims(&self.0)
+    }
+
+    ///
+    /// Encrypts this signed logout token using the given key and algorithms, returning the
+    /// resulting nested JWT in JWE Compact Serialization.
+    ///
+    pub fn encrypt<JK, EK>(
+        &self,
+        encryption_key: &EK,
+        alg: &JK,
+        enc: &JE,
+    ) -> Result<String, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        EK: EncryptionKey<JE, JK, JT>,
+    {
+        self.0.encrypt(encryption_key, alg, enc)
+    }
+
+    ///
+    /// Decrypts an encrypted logout token (i.e., a nested JWT) using the given key, returning the
+    /// inner signed logout token.
+    ///
+    /// The returned logout token must still be verified using [`claims`](Self::claims).
+    ///
+    pub fn decrypt<JK, DK>(nested_jwt: &str, decryption_key: &DK) -> Result<Self, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        DK: DecryptionKey<JE, JK, JT>,
+    {
+        JsonWebToken::decrypt(nested_jwt, decryption_key).map(Self)
+    }
+}
+impl<JE, JS, JT> std::fmt::Display for LogoutToken<JE, JS, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let serialized = serde_json::to_value(self)
+            // This should never arise, since we're just asking serde_json to serialize the
+            // signing input concatenated with the signature, both of which are precomputed.
+            .expect("logout token serialization failed");
+        f.write_str(
+            serialized
+                .as_str()
+                // This should also never arise, since the JWT serializer always calls
+                // serialize_str.
+                .expect("logout token serializer did not produce a str"),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use chrono::{TimeZone, Utc};
     use oauth2::{AuthUrl, ClientId, CsrfToken};
     use url::Url;
 
+    use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
     use crate::{
         core::{
-            CoreGenderClaim, CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm,
-            CoreJwsSigningAlgorithm,
+            CoreGenderClaim, CoreIdTokenVerifier, CoreJsonWebKey, CoreJsonWebKeySet,
+            CoreJsonWebKeyType, CoreJweContentEncryptionAlgorithm, CoreJwsSigningAlgorithm,
+            CoreLogoutToken, CoreRsaPrivateSigningKey,
         },
         types::{LogoutHint, PostLogoutRedirectUrl},
-        EmptyAdditionalClaims, EndSessionUrl, IdToken, IssuerUrl, JsonWebKeySetUrl, LanguageTag,
-        LogoutProviderMetadata, LogoutRequest, ProviderMetadataWithLogout,
+        Audience, ClaimsVerificationError, EmptyAdditionalClaims, EndSessionUrl, IdToken,
+        IssuerUrl, JsonWebKeySetUrl, JsonWebTokenError, JsonWebTokenId, LanguageTag,
+        LogoutProviderMetadata, LogoutRequest, LogoutTokenClaims, ProviderMetadataWithLogout,
+        SessionIdentifier, SubjectIdentifier,
     };
 
     #[test]
@@ -337,4 +615,204 @@ mod tests {
             logout_url
         );
     }
+
+    #[test]
+    fn test_logout_token() {
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let client_id = ClientId::new("my_client".to_string());
+        let claims = LogoutTokenClaims::new(
+            issuer.clone(),
+            vec![Audience::new("my_client".to_string())],
+            Utc.timestamp_opt(1544928549, 0).single().unwrap(),
+            Utc.timestamp_opt(1544932149, 0).single().unwrap(),
+            JsonWebTokenId::new("bWJq".to_string()),
+        );
+
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        match CoreLogoutToken::new(
+            claims.clone(),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ) {
+            Err(JsonWebTokenError::InvalidClaims(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let claims = claims.set_session_id(Some(SessionIdentifier::new(
+            "08a5019c-17e1-4977-8f42-65a12843ea02".to_string(),
+        )));
+        let logout_token = CoreLogoutToken::new(
+            claims.clone(),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+
+        let payload = logout_token
+            .to_string()
+            .split('.')
+            .nth(1)
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(
+                &base64::decode_config(payload, base64::URL_SAFE_NO_PAD).unwrap()
+            )
+            .unwrap(),
+            serde_json::json!({
+                "iss": "https://example.com",
+                "aud": ["my_client"],
+                "iat": 1544928549,
+                "exp": 1544932149,
+                "jti": "bWJq",
+                "sid": "08a5019c-17e1-4977-8f42-65a12843ea02",
+                "events": {
+                    "http://schemas.openid.net/event/backchannel-logout": {}
+                },
+            })
+        );
+
+        let logout_token = CoreLogoutToken::from_str(&logout_token.to_string()).unwrap();
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            client_id,
+            issuer,
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        )
+        .set_time_fn(|| Utc.timestamp_opt(1544928550, 0).single().unwrap());
+        assert_eq!(logout_token.claims(&verifier).unwrap(), &claims);
+
+        let verifier = verifier.set_time_fn(|| Utc.timestamp_opt(1544932149, 0).single().unwrap());
+        match logout_token.claims(&verifier) {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_logout_token_type() {
+        use super::LogoutToken;
+        use crate::jwt::JsonWebTokenAccess;
+        use crate::JsonWebToken;
+
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let claims = LogoutTokenClaims::new(
+            issuer.clone(),
+            vec![Audience::new("my_client".to_string())],
+            Utc.timestamp_opt(1544928549, 0).single().unwrap(),
+            Utc.timestamp_opt(1544932149, 0).single().unwrap(),
+            JsonWebTokenId::new("bWJq".to_string()),
+        )
+        .set_subject(Some(SubjectIdentifier::new("the_subject".to_string())));
+        let signing_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let sign = |typ: Option<&str>| -> CoreLogoutToken {
+            let mut header_params = serde_json::Map::new();
+            if let Some(typ) = typ {
+                header_params.insert("typ".to_string(), typ.into());
+            }
+            LogoutToken(
+                JsonWebToken::new_with_header_params(
+                    claims.clone(),
+                    &signing_key,
+                    &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+                    header_params,
+                )
+                .unwrap(),
+            )
+        };
+
+        let logout_token = CoreLogoutToken::new(
+            claims.clone(),
+            &signing_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+        assert_eq!(
+            logout_token
+                .0
+                .unverified_header()
+                .typ
+                .as_ref()
+                .map(|typ| typ.as_str()),
+            Some("logout+jwt")
+        );
+
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            ClientId::new("my_client".to_string()),
+            issuer,
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        )
+        .set_time_fn(|| Utc.timestamp_opt(1544928550, 0).single().unwrap());
+        let strict_verifier = verifier.clone().require_explicit_type(true);
+
+        for typ in [Some("logout+jwt"), Some("application/Logout+JWT")] {
+            assert_eq!(sign(typ).claims(&strict_verifier).unwrap(), &claims);
+        }
+        for typ in [None, Some("JWT")] {
+            assert_eq!(sign(typ).claims(&verifier).unwrap(), &claims);
+            match sign(typ).claims(&strict_verifier) {
+                Err(ClaimsVerificationError::Unsupported(_)) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        match sign(Some("at+jwt")).claims(&verifier) {
+            Err(ClaimsVerificationError::Unsupported(msg)) => {
+                assert_eq!(msg, "unexpected or unsupported JWT type `at+jwt`")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_logout_token_claims_validation() {
+        let claims = serde_json::from_str::<LogoutTokenClaims>(
+            "{
+                \"iss\": \"https://example.com\",
+                \"aud\": \"my_client\",
+                \"iat\": 1544928549,
+                \"exp\": 1544932149,
+                \"jti\": \"bWJq\",
+                \"sub\": \"the_subject\",
+                \"events\": {
+                    \"http://schemas.openid.net/event/backchannel-logout\": {}
+                }
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            claims.subject(),
+            Some(&SubjectIdentifier::new("the_subject".to_string()))
+        );
+        claims.validate().unwrap();
+
+        let with_nonce = serde_json::from_value::<LogoutTokenClaims>(
+            serde_json::to_value(&claims)
+                .map(|mut value| {
+                    value["nonce"] = serde_json::Value::String("n-0S6_WzA2Mj".to_string());
+                    value
+                })
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            with_nonce.validate(),
+            Err("logout token must not contain a `nonce` claim".to_string())
+        );
+
+        let without_event = serde_json::from_value::<LogoutTokenClaims>(
+            serde_json::to_value(&claims)
+                .map(|mut value| {
+                    value["events"] = serde_json::json!({});
+                    value
+                })
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(without_event.validate().is_err());
+    }
 }
@@ -248,6 +248,11 @@ macro_rules! new_secret_type {
                 write!(f, concat!(stringify!($name), "([redacted])"))
             }
         }
+        impl crate::ExposeSecret for $name {
+            type Secret = $type;
+
+            fn expose_secret(&self) -> &$type { &self.0 }
+        }
     };
 }
 
@@ -0,0 +1,794 @@
+//!
+//! Wrappers that customize the requests sent by HTTP client functions.
+//!
+//! Every network operation in this crate (discovery, JWKS retrieval, token, user info, and
+//! registration requests) accepts an HTTP client function. The wrappers in this module take such a
+//! function and return a new one that can be passed anywhere the original is accepted:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "reqwest")]
+//! # fn example() -> Result<(), anyhow::Error> {
+//! use openidconnect::core::CoreProviderMetadata;
+//! use openidconnect::http::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+//! use openidconnect::middleware::with_headers;
+//! use openidconnect::reqwest::http_client;
+//! use openidconnect::IssuerUrl;
+//!
+//! let mut headers = HeaderMap::new();
+//! headers.insert(USER_AGENT, HeaderValue::from_static("my-app/1.0"));
+//! headers.insert(
+//!     HeaderName::from_static("x-api-key"),
+//!     HeaderValue::from_static("gateway-key"),
+//! );
+//!
+//! let provider_metadata = CoreProviderMetadata::discover(
+//!     &IssuerUrl::new("https://accounts.example.com".to_string())?,
+//!     with_headers(http_client, headers),
+//! )?;
+//! # let _ = provider_metadata;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! More general customizations (e.g., request signing, auditing, or response rewriting) may be
+//! applied uniformly by implementing the [`HttpInterceptor`] trait and wrapping the HTTP client
+//! with [`intercept`] or [`intercept_async`].
+//!
+//! # Capturing responses
+//!
+//! Errors returned by some operations, such as
+//! [`RequestTokenError::Parse`](crate::RequestTokenError::Parse), do not include the HTTP status
+//! code of the response that caused them. A [`ResponseRecorder`] captures the status code and body
+//! of the most recent response so that they can be attached to such errors:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "reqwest")]
+//! # fn example(
+//! #     client: openidconnect::core::CoreClient,
+//! #     code: openidconnect::AuthorizationCode,
+//! # ) -> Result<(), anyhow::Error> {
+//! use openidconnect::middleware::{intercept, ResponseRecorder};
+//! use openidconnect::reqwest::http_client;
+//!
+//! let recorder = ResponseRecorder::new();
+//! let token_response = client
+//!     .exchange_code(code)
+//!     .request(intercept(http_client, &recorder))
+//!     .map_err(|err| recorder.attach(err))
+//!     .map_err(|err| {
+//!         eprintln!(
+//!             "token request failed with HTTP status {:?}: {}",
+//!             err.status_code(),
+//!             String::from_utf8_lossy(err.body().unwrap_or_default()),
+//!         );
+//!         err
+//!     })?;
+//! # let _ = token_response;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Metrics
+//!
+//! Applications exporting metrics (e.g., to Prometheus) may implement the [`Metrics`] trait and
+//! wrap each HTTP client with [`with_metrics`] or [`with_metrics_async`], which report the
+//! operation name, duration, HTTP status, and [`Outcome`] of every request. Operations that do not
+//! involve a network call, such as ID token verification, may be measured with [`measure`]:
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "reqwest")]
+//! # fn example(
+//! #     client: openidconnect::core::CoreClient,
+//! #     code: openidconnect::AuthorizationCode,
+//! #     nonce: openidconnect::Nonce,
+//! #     metrics: impl openidconnect::middleware::Metrics,
+//! # ) -> Result<(), anyhow::Error> {
+//! use openidconnect::middleware::{measure, with_metrics};
+//! use openidconnect::reqwest::http_client;
+//! use openidconnect::TokenResponse;
+//!
+//! let token_response = client
+//!     .exchange_code(code)
+//!     .request(with_metrics(http_client, "token", &metrics))?;
+//! let id_token = token_response.id_token().ok_or_else(|| anyhow::anyhow!("missing ID token"))?;
+//! let claims = measure(&metrics, "id_token_verification", || {
+//!     id_token.claims(&client.id_token_verifier(), &nonce)
+//! })?;
+//! # let _ = claims;
+//! # Ok(())
+//! # }
+//! ```
+//!
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use http::header::HeaderMap;
+use http::{Method, StatusCode};
+use thiserror::Error;
+use url::Url;
+
+use crate::{HttpRequest, HttpResponse};
+
+///
+/// Hook invoked around every request sent by an HTTP client wrapped with [`intercept`] or
+/// [`intercept_async`].
+///
+/// Both methods have default implementations that return their input unchanged, so implementers
+/// need only override the hooks they require.
+///
+pub trait HttpInterceptor {
+    ///
+    /// Called with each outgoing request before it is sent, returning the request to send.
+    ///
+    fn intercept_request(&self, request: HttpRequest) -> HttpRequest {
+        request
+    }
+
+    ///
+    /// Called with each response received from the server before it is processed by this crate,
+    /// returning the response to process.
+    ///
+    /// The `method` and `url` of the corresponding request are provided for context. This method
+    /// is not called if the HTTP client returns an error.
+    ///
+    fn intercept_response(
+        &self,
+        method: &Method,
+        url: &Url,
+        response: HttpResponse,
+    ) -> HttpResponse {
+        let _ = (method, url);
+        response
+    }
+}
+impl<I> HttpInterceptor for &I
+where
+    I: HttpInterceptor + ?Sized,
+{
+    fn intercept_request(&self, request: HttpRequest) -> HttpRequest {
+        (**self).intercept_request(request)
+    }
+
+    fn intercept_response(
+        &self,
+        method: &Method,
+        url: &Url,
+        response: HttpResponse,
+    ) -> HttpResponse {
+        (**self).intercept_response(method, url, response)
+    }
+}
+impl<I> HttpInterceptor for Arc<I>
+where
+    I: HttpInterceptor + ?Sized,
+{
+    fn intercept_request(&self, request: HttpRequest) -> HttpRequest {
+        (**self).intercept_request(request)
+    }
+
+    fn intercept_response(
+        &self,
+        method: &Method,
+        url: &Url,
+        response: HttpResponse,
+    ) -> HttpResponse {
+        (**self).intercept_response(method, url, response)
+    }
+}
+
+///
+/// Interceptor that records the HTTP status code and body of the most recent response.
+///
+/// Recorders are cheap to clone, and clones share the same recorded response. See the
+/// [module documentation](self#capturing-responses) for an example.
+///
+#[derive(Clone, Debug, Default)]
+pub struct ResponseRecorder {
+    last_response: Arc<Mutex<Option<(StatusCode, Vec<u8>)>>>,
+}
+impl ResponseRecorder {
+    ///
+    /// Instantiates a new recorder with no recorded response.
+    ///
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// Returns the HTTP status code of the most recent response, if any.
+    ///
+    pub fn status_code(&self) -> Option<StatusCode> {
+        self.lock().as_ref().map(|(status_code, _)| *status_code)
+    }
+
+    ///
+    /// Returns the body of the most recent response, if any.
+    ///
+    pub fn body(&self) -> Option<Vec<u8>> {
+        self.lock().as_ref().map(|(_, body)| body.clone())
+    }
+
+    ///
+    /// Wraps the given error together with the status code and body of the most recent response.
+    ///
+    pub fn attach<E>(&self, error: E) -> ResponseError<E> {
+        let last_response = self.lock().clone();
+        let (status_code, body) = last_response.map_or((None, None), |(status_code, body)| {
+            (Some(status_code), Some(body))
+        });
+        ResponseError {
+            error,
+            status_code,
+            body,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<(StatusCode, Vec<u8>)>> {
+        // A panic while holding the lock can't leave the response in an inconsistent state, so
+        // it's safe to ignore poisoning.
+        self.last_response
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+impl HttpInterceptor for ResponseRecorder {
+    fn intercept_request(&self, request: HttpRequest) -> HttpRequest {
+        *self.lock() = None;
+        request
+    }
+
+    fn intercept_response(
+        &self,
+        _method: &Method,
+        _url: &Url,
+        response: HttpResponse,
+    ) -> HttpResponse {
+        *self.lock() = Some((response.status_code, response.body.clone()));
+        response
+    }
+}
+
+///
+/// Error annotated with the HTTP status code and body of the response that caused it.
+///
+/// Returned by [`ResponseRecorder::attach`]. The status code and body are `None` if no response
+/// was received (e.g., due to a network error).
+///
+#[derive(Debug, Error)]
+#[error("{error}{}", status_code_suffix(.status_code))]
+pub struct ResponseError<E> {
+    #[source]
+    error: E,
+    status_code: Option<StatusCode>,
+    body: Option<Vec<u8>>,
+}
+impl<E> ResponseError<E> {
+    ///
+    /// Returns the underlying error.
+    ///
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    ///
+    /// Returns the HTTP status code of the response, if one was received.
+    ///
+    pub fn status_code(&self) -> Option<StatusCode> {
+        self.status_code
+    }
+
+    ///
+    /// Returns the raw body of the response, if one was received.
+    ///
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    ///
+    /// Consumes this error and returns the underlying error.
+    ///
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+fn status_code_suffix(status_code: &Option<StatusCode>) -> String {
+    status_code
+        .map(|status_code| format!(" (HTTP status code {})", status_code))
+        .unwrap_or_default()
+}
+
+///
+/// Future returned by HTTP clients created via [`intercept_async`].
+///
+pub type InterceptedHttpClientFuture<'a, RE> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, RE>> + Send + 'a>>;
+
+///
+/// Returns a synchronous HTTP client that passes every request and response through the given
+/// interceptor.
+///
+pub fn intercept<HC, I, RE>(
+    http_client: HC,
+    interceptor: I,
+) -> impl Fn(HttpRequest) -> Result<HttpResponse, RE>
+where
+    HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+    I: HttpInterceptor,
+{
+    move |request| {
+        let request = interceptor.intercept_request(request);
+        let (method, url) = (request.method.clone(), request.url.clone());
+        http_client(request).map(|response| interceptor.intercept_response(&method, &url, response))
+    }
+}
+
+///
+/// Returns an asynchronous HTTP client that passes every request and response through the given
+/// interceptor.
+///
+pub fn intercept_async<'a, F, HC, I, RE>(
+    http_client: HC,
+    interceptor: I,
+) -> impl Fn(HttpRequest) -> InterceptedHttpClientFuture<'a, RE> + 'a
+where
+    F: Future<Output = Result<HttpResponse, RE>> + Send + 'a,
+    HC: Fn(HttpRequest) -> F + 'a,
+    I: HttpInterceptor + Send + Sync + 'a,
+    RE: 'a,
+{
+    let interceptor = Arc::new(interceptor);
+    move |request| {
+        let request = interceptor.intercept_request(request);
+        let (method, url) = (request.method.clone(), request.url.clone());
+        let response = http_client(request);
+        let interceptor = interceptor.clone();
+        Box::pin(async move {
+            response
+                .await
+                .map(|response| interceptor.intercept_response(&method, &url, response))
+        })
+    }
+}
+
+///
+/// Outcome of an operation reported to [`Metrics`].
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Outcome {
+    ///
+    /// The operation succeeded (for HTTP requests, a response with a `2xx` status code was
+    /// received).
+    ///
+    Success,
+    ///
+    /// The operation failed, either because the HTTP client returned an error, the server
+    /// responded with a non-`2xx` status code, or the measured function returned an error.
+    ///
+    Failure,
+}
+
+///
+/// Receiver of metrics reported by HTTP clients wrapped with [`with_metrics`] or
+/// [`with_metrics_async`], and by operations measured with [`measure`].
+///
+pub trait Metrics {
+    ///
+    /// Records a completed operation.
+    ///
+    /// The `operation` is the name supplied when wrapping the HTTP client (e.g., `"token"`), and
+    /// `status` is the HTTP status code of the response, if one was received.
+    ///
+    fn record(
+        &self,
+        operation: &str,
+        duration: Duration,
+        status: Option<StatusCode>,
+        outcome: Outcome,
+    );
+}
+impl<M> Metrics for &M
+where
+    M: Metrics + ?Sized,
+{
+    fn record(
+        &self,
+        operation: &str,
+        duration: Duration,
+        status: Option<StatusCode>,
+        outcome: Outcome,
+    ) {
+        (**self).record(operation, duration, status, outcome)
+    }
+}
+impl<M> Metrics for Arc<M>
+where
+    M: Metrics + ?Sized,
+{
+    fn record(
+        &self,
+        operation: &str,
+        duration: Duration,
+        status: Option<StatusCode>,
+        outcome: Outcome,
+    ) {
+        (**self).record(operation, duration, status, outcome)
+    }
+}
+
+fn record_response<M, RE>(
+    metrics: &M,
+    operation: &str,
+    start: Instant,
+    result: &Result<HttpResponse, RE>,
+) where
+    M: Metrics + ?Sized,
+{
+    let status = result.as_ref().ok().map(|response| response.status_code);
+    let outcome = match status {
+        Some(status) if status.is_success() => Outcome::Success,
+        _ => Outcome::Failure,
+    };
+    metrics.record(operation, start.elapsed(), status, outcome);
+}
+
+///
+/// Returns a synchronous HTTP client that reports every request to `metrics` under the given
+/// operation name.
+///
+pub fn with_metrics<HC, M, RE>(
+    http_client: HC,
+    operation: &'static str,
+    metrics: M,
+) -> impl Fn(HttpRequest) -> Result<HttpResponse, RE>
+where
+    HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+    M: Metrics,
+{
+    move |request| {
+        let start = Instant::now();
+        let result = http_client(request);
+        record_response(&metrics, operation, start, &result);
+        result
+    }
+}
+
+///
+/// Returns an asynchronous HTTP client that reports every request to `metrics` under the given
+/// operation name.
+///
+pub fn with_metrics_async<'a, F, HC, M, RE>(
+    http_client: HC,
+    operation: &'static str,
+    metrics: M,
+) -> impl Fn(HttpRequest) -> InterceptedHttpClientFuture<'a, RE> + 'a
+where
+    F: Future<Output = Result<HttpResponse, RE>> + Send + 'a,
+    HC: Fn(HttpRequest) -> F + 'a,
+    M: Metrics + Send + Sync + 'a,
+    RE: 'a,
+{
+    let metrics = Arc::new(metrics);
+    move |request| {
+        let start = Instant::now();
+        let response = http_client(request);
+        let metrics = metrics.clone();
+        Box::pin(async move {
+            let result = response.await;
+            record_response(&metrics, operation, start, &result);
+            result
+        })
+    }
+}
+
+///
+/// Invokes `f` and reports its duration and outcome to `metrics` under the given operation name.
+///
+/// This is intended for operations that do not involve an HTTP request, such as ID token
+/// verification.
+///
+pub fn measure<F, M, T, E>(metrics: &M, operation: &str, f: F) -> Result<T, E>
+where
+    F: FnOnce() -> Result<T, E>,
+    M: Metrics + ?Sized,
+{
+    let start = Instant::now();
+    let result = f();
+    metrics.record(
+        operation,
+        start.elapsed(),
+        None,
+        if result.is_ok() {
+            Outcome::Success
+        } else {
+            Outcome::Failure
+        },
+    );
+    result
+}
+
+///
+/// Returns a synchronous HTTP client that adds the given headers to every request before passing
+/// it to `http_client`.
+///
+/// Headers in `headers` replace any headers of the same name set by this crate (e.g., to override
+/// the `Accept` header); all other request headers are preserved.
+///
+pub fn with_headers<HC, RE>(
+    http_client: HC,
+    headers: HeaderMap,
+) -> impl Fn(HttpRequest) -> Result<HttpResponse, RE>
+where
+    HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+{
+    move |request| http_client(add_headers(request, &headers))
+}
+
+///
+/// Returns an asynchronous HTTP client that adds the given headers to every request before passing
+/// it to `http_client`.
+///
+/// See [`with_headers`] for details.
+///
+pub fn with_headers_async<F, HC, RE>(
+    http_client: HC,
+    headers: HeaderMap,
+) -> impl Fn(HttpRequest) -> F
+where
+    F: Future<Output = Result<HttpResponse, RE>>,
+    HC: Fn(HttpRequest) -> F,
+{
+    move |request| http_client(add_headers(request, &headers))
+}
+
+fn add_headers(mut request: HttpRequest, headers: &HeaderMap) -> HttpRequest {
+    request.headers.extend(headers.clone());
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use http::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, USER_AGENT};
+    use http::{Method, StatusCode};
+
+    use crate::{HttpRequest, HttpResponse, Url};
+
+    use super::{
+        intercept, measure, with_headers, with_metrics, HttpInterceptor, Metrics, Outcome,
+        ResponseRecorder,
+    };
+
+    #[test]
+    fn test_with_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("application/jwt"));
+        headers.insert(USER_AGENT, HeaderValue::from_static("my-app/1.0"));
+        headers.insert(
+            HeaderName::from_static("x-correlation-id"),
+            HeaderValue::from_static("abc123"),
+        );
+
+        let http_client = with_headers(
+            |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                assert_eq!(request.headers.len(), 4);
+                assert_eq!(request.headers[ACCEPT], "application/jwt");
+                assert_eq!(request.headers[USER_AGENT], "my-app/1.0");
+                assert_eq!(request.headers["x-correlation-id"], "abc123");
+                assert_eq!(request.headers["x-existing"], "1");
+                Ok(HttpResponse {
+                    status_code: StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: Vec::new(),
+                })
+            },
+            headers,
+        );
+
+        let mut request_headers = HeaderMap::new();
+        request_headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        request_headers.insert(
+            HeaderName::from_static("x-existing"),
+            HeaderValue::from_static("1"),
+        );
+        for _ in 0..2 {
+            http_client(HttpRequest {
+                url: Url::parse("https://example/token").unwrap(),
+                method: Method::POST,
+                headers: request_headers.clone(),
+                body: Vec::new(),
+            })
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_intercept() {
+        #[derive(Default)]
+        struct AuditInterceptor {
+            log: Mutex<Vec<String>>,
+        }
+        impl HttpInterceptor for AuditInterceptor {
+            fn intercept_request(&self, mut request: HttpRequest) -> HttpRequest {
+                request.headers.insert(
+                    HeaderName::from_static("x-signature"),
+                    HeaderValue::from_static("signed"),
+                );
+                request
+            }
+
+            fn intercept_response(
+                &self,
+                method: &Method,
+                url: &Url,
+                mut response: HttpResponse,
+            ) -> HttpResponse {
+                self.log
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {} {}", method, url, response.status_code));
+                response.body = b"rewritten".to_vec();
+                response
+            }
+        }
+
+        let interceptor = AuditInterceptor::default();
+        let http_client = intercept(
+            |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                assert_eq!(request.headers["x-signature"], "signed");
+                Ok(HttpResponse {
+                    status_code: StatusCode::NOT_FOUND,
+                    headers: HeaderMap::new(),
+                    body: b"original".to_vec(),
+                })
+            },
+            &interceptor,
+        );
+
+        let response = http_client(HttpRequest {
+            url: Url::parse("https://example/jwks").unwrap(),
+            method: Method::GET,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        })
+        .unwrap();
+        assert_eq!(response.body, b"rewritten");
+        assert_eq!(
+            *interceptor.log.lock().unwrap(),
+            vec!["GET https://example/jwks 404 Not Found".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        #[derive(Default)]
+        struct TestMetrics {
+            records: Mutex<Vec<(String, Option<StatusCode>, Outcome)>>,
+        }
+        impl Metrics for TestMetrics {
+            fn record(
+                &self,
+                operation: &str,
+                _duration: Duration,
+                status: Option<StatusCode>,
+                outcome: Outcome,
+            ) {
+                self.records
+                    .lock()
+                    .unwrap()
+                    .push((operation.to_string(), status, outcome));
+            }
+        }
+
+        let metrics = TestMetrics::default();
+        let http_client = with_metrics(
+            |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                match request.url.path() {
+                    "/ok" => Ok(HttpResponse {
+                        status_code: StatusCode::OK,
+                        headers: HeaderMap::new(),
+                        body: Vec::new(),
+                    }),
+                    "/unauthorized" => Ok(HttpResponse {
+                        status_code: StatusCode::UNAUTHORIZED,
+                        headers: HeaderMap::new(),
+                        body: Vec::new(),
+                    }),
+                    _ => Err(std::io::Error::other("failed")),
+                }
+            },
+            "userinfo",
+            &metrics,
+        );
+        for path in &["ok", "unauthorized", "error"] {
+            let _ = http_client(HttpRequest {
+                url: Url::parse(&format!("https://example/{}", path)).unwrap(),
+                method: Method::GET,
+                headers: HeaderMap::new(),
+                body: Vec::new(),
+            });
+        }
+
+        assert_eq!(measure(&metrics, "verify", || Ok::<_, ()>(1)), Ok(1));
+        assert_eq!(measure(&metrics, "verify", || Err::<(), _>(())), Err(()));
+
+        assert_eq!(
+            *metrics.records.lock().unwrap(),
+            vec![
+                (
+                    "userinfo".to_string(),
+                    Some(StatusCode::OK),
+                    Outcome::Success
+                ),
+                (
+                    "userinfo".to_string(),
+                    Some(StatusCode::UNAUTHORIZED),
+                    Outcome::Failure
+                ),
+                ("userinfo".to_string(), None, Outcome::Failure),
+                ("verify".to_string(), None, Outcome::Success),
+                ("verify".to_string(), None, Outcome::Failure),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_response_recorder() {
+        use crate::core::CoreClient;
+        use crate::{AuthUrl, AuthorizationCode, ClientId, IssuerUrl, JsonWebKeySet, TokenUrl};
+
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            Some(TokenUrl::new("https://example/token".to_string()).unwrap()),
+            None,
+            JsonWebKeySet::default(),
+        );
+
+        let recorder = ResponseRecorder::new();
+        let err = client
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(intercept(
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Ok(HttpResponse {
+                        status_code: StatusCode::BAD_GATEWAY,
+                        headers: HeaderMap::new(),
+                        body: b"<html>Bad Gateway</html>".to_vec(),
+                    })
+                },
+                &recorder,
+            ))
+            .map_err(|err| recorder.attach(err))
+            .unwrap_err();
+        assert_eq!(err.status_code(), Some(StatusCode::BAD_GATEWAY));
+        assert_eq!(err.body(), Some(&b"<html>Bad Gateway</html>"[..]));
+        assert!(err
+            .to_string()
+            .ends_with(" (HTTP status code 502 Bad Gateway)"));
+        assert!(matches!(
+            err.into_inner(),
+            crate::RequestTokenError::Parse(_, _)
+        ));
+
+        // Requests that fail without a response clear any previously recorded response.
+        let err = client
+            .exchange_code(AuthorizationCode::new("code".to_string()))
+            .request(intercept(
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Err(std::io::Error::other("connection refused"))
+                },
+                &recorder,
+            ))
+            .map_err(|err| recorder.attach(err))
+            .unwrap_err();
+        assert_eq!(err.status_code(), None);
+        assert_eq!(err.body(), None);
+    }
+}
@@ -0,0 +1,340 @@
+use chrono::{DateTime, Utc};
+use oauth2::{CsrfToken, PkceCodeVerifier, RedirectUrl, Scope};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::types::helpers::{serde_utc_seconds, serde_utc_seconds_opt};
+use crate::Nonce;
+
+///
+/// State of an authorization request that must be retained between redirecting the user to the
+/// authorization endpoint and handling the callback at the redirect URI.
+///
+/// This type implements [`Serialize`] and [`Deserialize`] so that it can be persisted to a
+/// database or distributed cache. It contains secrets (including the PKCE code verifier, if any)
+/// and must be stored securely. Once the user returns to the redirect URI, call
+/// [`resume`](Self::resume) with the callback's `state` parameter to recover the values needed
+/// to exchange the authorization code.
+///
+#[derive(Deserialize, Serialize)]
+pub struct PendingAuthorization {
+    csrf_state: CsrfToken,
+    nonce: Nonce,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pkce_verifier: Option<PkceCodeVerifier>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    redirect_uri: Option<RedirectUrl>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    scopes: Vec<Scope>,
+    #[serde(with = "serde_utc_seconds")]
+    created_at: DateTime<Utc>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "serde_utc_seconds_opt"
+    )]
+    expires_at: Option<DateTime<Utc>>,
+}
+impl PendingAuthorization {
+    ///
+    /// Creates a new pending authorization with the given CSRF state and nonce, created at the
+    /// current time.
+    ///
+    /// Prefer [`AuthorizationRequest::url_and_pending_authorization`](
+    /// crate::AuthorizationRequest::url_and_pending_authorization), which also captures the
+    /// requested scopes and redirect URI.
+    ///
+    pub fn new(csrf_state: CsrfToken, nonce: Nonce) -> Self {
+        Self {
+            csrf_state,
+            nonce,
+            pkce_verifier: None,
+            redirect_uri: None,
+            scopes: Vec::new(),
+            created_at: Utc::now(),
+            expires_at: None,
+        }
+    }
+
+    ///
+    /// Returns the CSRF `state` parameter sent to the authorization endpoint.
+    ///
+    pub fn csrf_state(&self) -> &CsrfToken {
+        &self.csrf_state
+    }
+
+    ///
+    /// Returns the nonce sent to the authorization endpoint.
+    ///
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    ///
+    /// Sets the PKCE code verifier corresponding to the challenge sent to the authorization
+    /// endpoint.
+    ///
+    pub fn set_pkce_verifier(mut self, pkce_verifier: PkceCodeVerifier) -> Self {
+        self.pkce_verifier = Some(pkce_verifier);
+        self
+    }
+
+    ///
+    /// Sets the redirect URI sent to the authorization endpoint, which must also be sent when
+    /// exchanging the authorization code.
+    ///
+    pub fn set_redirect_uri(mut self, redirect_uri: Option<RedirectUrl>) -> Self {
+        self.redirect_uri = redirect_uri;
+        self
+    }
+
+    ///
+    /// Returns the redirect URI sent to the authorization endpoint, if any.
+    ///
+    pub fn redirect_uri(&self) -> Option<&RedirectUrl> {
+        self.redirect_uri.as_ref()
+    }
+
+    ///
+    /// Sets the scopes requested from the authorization endpoint.
+    ///
+    pub fn set_scopes(mut self, scopes: Vec<Scope>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    ///
+    /// Returns the scopes requested from the authorization endpoint.
+    ///
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    ///
+    /// Returns when the authorization request was created.
+    ///
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    ///
+    /// Sets when the pending authorization expires, after which [`resume`](Self::resume) fails.
+    ///
+    pub fn set_expires_at(mut self, expires_at: Option<DateTime<Utc>>) -> Self {
+        self.expires_at = expires_at;
+        self
+    }
+
+    ///
+    /// Returns when the pending authorization expires, if ever.
+    ///
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    ///
+    /// Resumes the authorization once the user returns to the redirect URI.
+    ///
+    /// The `state` parameter received at the redirect URI must match the CSRF state of this
+    /// pending authorization, and the pending authorization must not have expired.
+    ///
+    pub fn resume(
+        self,
+        callback_state: &CsrfToken,
+    ) -> Result<ResumedAuthorization, PendingAuthorizationError> {
+        use subtle::ConstantTimeEq;
+        if !bool::from(
+            callback_state
+                .secret()
+                .as_bytes()
+                .ct_eq(self.csrf_state.secret().as_bytes()),
+        ) {
+            return Err(PendingAuthorizationError::InvalidState);
+        }
+        if let Some(expires_at) = self.expires_at {
+            if Utc::now() >= expires_at {
+                return Err(PendingAuthorizationError::Expired(expires_at.to_rfc3339()));
+            }
+        }
+        Ok(ResumedAuthorization {
+            nonce: self.nonce,
+            pkce_verifier: self.pkce_verifier,
+            redirect_uri: self.redirect_uri,
+            scopes: self.scopes,
+        })
+    }
+}
+impl std::fmt::Debug for PendingAuthorization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingAuthorization")
+            .field("csrf_state", &self.csrf_state)
+            .field("nonce", &self.nonce)
+            .field(
+                "pkce_verifier",
+                &self.pkce_verifier.as_ref().map(|_| "[redacted]"),
+            )
+            .field("redirect_uri", &self.redirect_uri)
+            .field("scopes", &self.scopes)
+            .field("created_at", &self.created_at)
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+///
+/// Authorization state recovered by [`PendingAuthorization::resume`].
+///
+/// The nonce and PKCE code verifier should be passed to
+/// [`Client::exchange_code_and_verify`](crate::Client::exchange_code_and_verify).
+///
+pub struct ResumedAuthorization {
+    nonce: Nonce,
+    pkce_verifier: Option<PkceCodeVerifier>,
+    redirect_uri: Option<RedirectUrl>,
+    scopes: Vec<Scope>,
+}
+impl ResumedAuthorization {
+    ///
+    /// Returns the nonce the ID token must contain.
+    ///
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    ///
+    /// Returns the redirect URI sent to the authorization endpoint, if any.
+    ///
+    pub fn redirect_uri(&self) -> Option<&RedirectUrl> {
+        self.redirect_uri.as_ref()
+    }
+
+    ///
+    /// Returns the scopes requested from the authorization endpoint.
+    ///
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    ///
+    /// Takes the PKCE code verifier, if any, to be sent when exchanging the authorization code.
+    ///
+    pub fn take_pkce_verifier(&mut self) -> Option<PkceCodeVerifier> {
+        self.pkce_verifier.take()
+    }
+}
+impl std::fmt::Debug for ResumedAuthorization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResumedAuthorization")
+            .field("nonce", &self.nonce)
+            .field(
+                "pkce_verifier",
+                &self.pkce_verifier.as_ref().map(|_| "[redacted]"),
+            )
+            .field("redirect_uri", &self.redirect_uri)
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+///
+/// Error resuming a [`PendingAuthorization`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PendingAuthorizationError {
+    ///
+    /// The pending authorization expired at the given time.
+    ///
+    #[error("Pending authorization expired at {0}")]
+    Expired(String),
+    ///
+    /// The `state` parameter passed to the redirect URI does not match the pending
+    /// authorization.
+    ///
+    #[error("CSRF state mismatch")]
+    InvalidState,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use oauth2::{CsrfToken, PkceCodeChallenge, RedirectUrl, Scope};
+
+    use crate::core::CoreAuthenticationFlow;
+    use crate::login::tests::new_handler;
+    use crate::Nonce;
+
+    use super::{PendingAuthorization, PendingAuthorizationError};
+
+    #[test]
+    fn test_pending_authorization() {
+        let handler = new_handler();
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let (url, pending) = handler
+            .client()
+            .authorize_url(
+                CoreAuthenticationFlow::AuthorizationCode,
+                || CsrfToken::new("CSRF123".to_string()),
+                || Nonce::new("NONCE456".to_string()),
+            )
+            .add_scope(Scope::new("email".to_string()))
+            .set_pkce_challenge(pkce_challenge)
+            .url_and_pending_authorization()
+            .unwrap();
+        let pending = pending
+            .set_pkce_verifier(pkce_verifier)
+            .set_expires_at(Some(Utc::now() + Duration::minutes(10)));
+        assert!(url.as_str().contains("state=CSRF123"));
+        assert_eq!(pending.csrf_state().secret(), "CSRF123");
+        assert_eq!(
+            pending.scopes(),
+            &[
+                Scope::new("openid".to_string()),
+                Scope::new("email".to_string())
+            ]
+        );
+        assert_eq!(
+            pending.redirect_uri(),
+            Some(&RedirectUrl::new("https://app.example.com/cb".to_string()).unwrap())
+        );
+        assert!(!format!("{:?}", pending).contains(
+            pending
+                .pkce_verifier
+                .as_ref()
+                .map(|verifier| verifier.secret().as_str())
+                .unwrap()
+        ));
+
+        let serialized = serde_json::to_string(&pending).unwrap();
+        let deserialized = serde_json::from_str::<PendingAuthorization>(&serialized).unwrap();
+        assert_eq!(
+            deserialized.created_at().timestamp(),
+            pending.created_at().timestamp()
+        );
+
+        match serde_json::from_str::<PendingAuthorization>(&serialized)
+            .unwrap()
+            .resume(&CsrfToken::new("wrong".to_string()))
+        {
+            Err(PendingAuthorizationError::InvalidState) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut resumed = deserialized
+            .resume(&CsrfToken::new("CSRF123".to_string()))
+            .unwrap();
+        assert_eq!(resumed.nonce().secret(), "NONCE456");
+        assert_eq!(resumed.scopes().len(), 2);
+        assert!(resumed.take_pkce_verifier().is_some());
+
+        let expired = PendingAuthorization::new(
+            CsrfToken::new("CSRF123".to_string()),
+            Nonce::new("NONCE456".to_string()),
+        )
+        .set_expires_at(Some(Utc::now() - Duration::seconds(1)));
+        match expired.resume(&CsrfToken::new("CSRF123".to_string())) {
+            Err(PendingAuthorizationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,1152 @@
+use std::collections::HashMap;
+
+use http::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
+use oauth2::{
+    AuthorizationCode, ClientId, ClientSecret, CsrfToken, DeviceCode, PkceCodeChallenge,
+    PkceCodeChallengeMethod, PkceCodeVerifier, RedirectUrl, RefreshToken, ResourceOwnerPassword,
+    ResourceOwnerUsername, Scope, StandardErrorResponse,
+};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use url::Url;
+
+use crate::core::{
+    CoreAuthErrorResponseType, CoreErrorResponseType, CoreResponseMode, CoreResponseType,
+};
+use crate::http_utils::MIME_TYPE_FORM_URLENCODED;
+use crate::{HttpRequest, IssuerUrl, ResponseTypes};
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+///
+/// Token endpoint request received by an OpenID Connect Provider, parsed according to
+/// [Section 4.1.3 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.3) and related
+/// specifications.
+///
+/// Parsing validates the structure of the request (e.g., required parameters, duplicate
+/// parameters, and the use of at most one client authentication method) but does not
+/// authenticate the client or validate the grant, both of which require provider state.
+///
+#[derive(Debug)]
+pub struct TokenRequest {
+    client_authentication: TokenRequestClientAuthentication,
+    grant: TokenRequestGrant,
+}
+impl TokenRequest {
+    ///
+    /// Parses a token request from its HTTP request headers and form-encoded body.
+    ///
+    pub fn parse(headers: &HeaderMap, body: &[u8]) -> Result<Self, TokenRequestError> {
+        if let Some(content_type) = headers.get(CONTENT_TYPE) {
+            let content_type = content_type.to_str().unwrap_or_default();
+            let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+            if !mime_type.eq_ignore_ascii_case(MIME_TYPE_FORM_URLENCODED) {
+                return Err(TokenRequestError::InvalidRequest(format!(
+                    "unexpected content type `{}`",
+                    content_type
+                )));
+            }
+        }
+
+        let mut params = HashMap::new();
+        for (name, value) in url::form_urlencoded::parse(body) {
+            // Parameters sent without a value MUST be treated as if they were omitted.
+            if value.is_empty() {
+                continue;
+            }
+            if params.insert(name.to_string(), value.to_string()).is_some() {
+                return Err(TokenRequestError::InvalidRequest(format!(
+                    "duplicate parameter `{}`",
+                    name
+                )));
+            }
+        }
+
+        let client_authentication = Self::parse_client_authentication(headers, &mut params)?;
+        let grant = Self::parse_grant(params)?;
+
+        Ok(Self {
+            client_authentication,
+            grant,
+        })
+    }
+
+    ///
+    /// Parses a token request from an [`HttpRequest`].
+    ///
+    pub fn from_http_request(http_request: &HttpRequest) -> Result<Self, TokenRequestError> {
+        Self::parse(&http_request.headers, &http_request.body)
+    }
+
+    ///
+    /// Returns the client authentication presented with the request.
+    ///
+    pub fn client_authentication(&self) -> &TokenRequestClientAuthentication {
+        &self.client_authentication
+    }
+
+    ///
+    /// Returns the client ID presented with the request, if any.
+    ///
+    pub fn client_id(&self) -> Option<&ClientId> {
+        self.client_authentication.client_id()
+    }
+
+    ///
+    /// Returns the requested grant.
+    ///
+    pub fn grant(&self) -> &TokenRequestGrant {
+        &self.grant
+    }
+
+    ///
+    /// Consumes the request, returning the client authentication and the requested grant.
+    ///
+    pub fn into_parts(self) -> (TokenRequestClientAuthentication, TokenRequestGrant) {
+        (self.client_authentication, self.grant)
+    }
+
+    fn parse_client_authentication(
+        headers: &HeaderMap,
+        params: &mut HashMap<String, String>,
+    ) -> Result<TokenRequestClientAuthentication, TokenRequestError> {
+        let basic_auth = headers
+            .get(AUTHORIZATION)
+            .map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.strip_prefix("Basic "))
+                    .and_then(|credentials| base64::decode(credentials.trim()).ok())
+                    .and_then(|credentials| String::from_utf8(credentials).ok())
+                    .and_then(|credentials| {
+                        credentials
+                            .split_once(':')
+                            .map(|(client_id, client_secret)| {
+                                // Client credentials are form-encoded before being
+                                // Base64-encoded (see
+                                // https://tools.ietf.org/html/rfc6749#section-2.3.1).
+                                (form_decode(client_id), form_decode(client_secret))
+                            })
+                    })
+                    .ok_or_else(|| {
+                        TokenRequestError::InvalidClient(
+                            "malformed Authorization header".to_string(),
+                        )
+                    })
+            })
+            .transpose()?;
+
+        let client_id = params.remove("client_id").map(ClientId::new);
+        let client_secret = params.remove("client_secret").map(ClientSecret::new);
+        let client_assertion_type = params.remove("client_assertion_type");
+        let client_assertion = params.remove("client_assertion");
+
+        let multiple_methods = || {
+            TokenRequestError::InvalidRequest(
+                "multiple client authentication methods used".to_string(),
+            )
+        };
+        match (
+            basic_auth,
+            client_secret,
+            client_assertion_type,
+            client_assertion,
+        ) {
+            (Some((basic_client_id, client_secret)), None, None, None) => {
+                if let Some(client_id) = client_id {
+                    if *client_id != basic_client_id {
+                        return Err(TokenRequestError::InvalidClient(
+                            "client_id does not match Authorization header".to_string(),
+                        ));
+                    }
+                }
+                Ok(TokenRequestClientAuthentication::ClientSecretBasic {
+                    client_id: ClientId::new(basic_client_id),
+                    client_secret: ClientSecret::new(client_secret),
+                })
+            }
+            (Some(_), _, _, _) => Err(multiple_methods()),
+            (None, Some(client_secret), None, None) => {
+                Ok(TokenRequestClientAuthentication::ClientSecretPost {
+                    client_id: client_id.ok_or_else(|| {
+                        TokenRequestError::InvalidRequest("missing client_id".to_string())
+                    })?,
+                    client_secret,
+                })
+            }
+            (None, Some(_), _, _) => Err(multiple_methods()),
+            (None, None, Some(assertion_type), Some(assertion)) => {
+                Ok(TokenRequestClientAuthentication::ClientAssertion {
+                    client_id,
+                    assertion_type,
+                    assertion,
+                })
+            }
+            (None, None, Some(_), None) => Err(TokenRequestError::InvalidRequest(
+                "missing client_assertion".to_string(),
+            )),
+            (None, None, None, Some(_)) => Err(TokenRequestError::InvalidRequest(
+                "missing client_assertion_type".to_string(),
+            )),
+            (None, None, None, None) => Ok(TokenRequestClientAuthentication::None { client_id }),
+        }
+    }
+
+    fn parse_grant(
+        mut params: HashMap<String, String>,
+    ) -> Result<TokenRequestGrant, TokenRequestError> {
+        let grant_type = params
+            .remove("grant_type")
+            .ok_or_else(|| TokenRequestError::InvalidRequest("missing grant_type".to_string()))?;
+        let mut required = |name: &str| {
+            params
+                .remove(name)
+                .ok_or_else(|| TokenRequestError::InvalidRequest(format!("missing {}", name)))
+        };
+
+        let grant = match grant_type.as_str() {
+            "authorization_code" => TokenRequestGrant::AuthorizationCode {
+                code: AuthorizationCode::new(required("code")?),
+                redirect_uri: params
+                    .remove("redirect_uri")
+                    .map(|redirect_uri| {
+                        RedirectUrl::new(redirect_uri).map_err(|err| {
+                            TokenRequestError::InvalidRequest(format!(
+                                "invalid redirect_uri: {}",
+                                err
+                            ))
+                        })
+                    })
+                    .transpose()?,
+                code_verifier: params.remove("code_verifier").map(PkceCodeVerifier::new),
+            },
+            "refresh_token" => TokenRequestGrant::RefreshToken {
+                refresh_token: RefreshToken::new(required("refresh_token")?),
+                scopes: parse_scopes(params.remove("scope")),
+            },
+            "client_credentials" => TokenRequestGrant::ClientCredentials {
+                scopes: parse_scopes(params.remove("scope")),
+            },
+            "password" => TokenRequestGrant::Password {
+                username: ResourceOwnerUsername::new(required("username")?),
+                password: ResourceOwnerPassword::new(required("password")?),
+                scopes: parse_scopes(params.remove("scope")),
+            },
+            DEVICE_CODE_GRANT_TYPE => TokenRequestGrant::DeviceCode {
+                device_code: DeviceCode::new(required("device_code")?),
+            },
+            _ => TokenRequestGrant::Extension { grant_type, params },
+        };
+        Ok(grant)
+    }
+}
+
+fn form_decode(value: &str) -> String {
+    url::form_urlencoded::parse(format!("v={}", value).as_bytes())
+        .next()
+        .map(|(_, value)| value.to_string())
+        .unwrap_or_default()
+}
+
+fn parse_scopes(scope: Option<String>) -> Option<Vec<Scope>> {
+    scope.map(|scope| {
+        scope
+            .split(' ')
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| Scope::new(scope.to_string()))
+            .collect()
+    })
+}
+
+///
+/// Client authentication presented with a [`TokenRequest`].
+///
+/// The provider must verify the presented credentials against the client's registration.
+///
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TokenRequestClientAuthentication {
+    ///
+    /// Client ID and secret presented using HTTP Basic authentication (`client_secret_basic`).
+    ///
+    ClientSecretBasic {
+        ///
+        /// Client ID.
+        ///
+        client_id: ClientId,
+        ///
+        /// Client secret.
+        ///
+        client_secret: ClientSecret,
+    },
+    ///
+    /// Client ID and secret presented in the request body (`client_secret_post`).
+    ///
+    ClientSecretPost {
+        ///
+        /// Client ID.
+        ///
+        client_id: ClientId,
+        ///
+        /// Client secret.
+        ///
+        client_secret: ClientSecret,
+    },
+    ///
+    /// JWT client assertion (`client_secret_jwt` or `private_key_jwt`), as defined in
+    /// [RFC 7523](https://tools.ietf.org/html/rfc7523#section-2.2).
+    ///
+    ClientAssertion {
+        ///
+        /// Client ID, if included in the request.
+        ///
+        client_id: Option<ClientId>,
+        ///
+        /// Client assertion type (e.g., `urn:ietf:params:oauth:client-assertion-type:jwt-bearer`).
+        ///
+        assertion_type: String,
+        ///
+        /// Client assertion.
+        ///
+        assertion: String,
+    },
+    ///
+    /// No client authentication (e.g., a public client).
+    ///
+    None {
+        ///
+        /// Client ID, if included in the request.
+        ///
+        client_id: Option<ClientId>,
+    },
+}
+impl TokenRequestClientAuthentication {
+    ///
+    /// Returns the client ID presented with the request, if any.
+    ///
+    pub fn client_id(&self) -> Option<&ClientId> {
+        match self {
+            TokenRequestClientAuthentication::ClientSecretBasic { client_id, .. }
+            | TokenRequestClientAuthentication::ClientSecretPost { client_id, .. } => {
+                Some(client_id)
+            }
+            TokenRequestClientAuthentication::ClientAssertion { client_id, .. }
+            | TokenRequestClientAuthentication::None { client_id } => client_id.as_ref(),
+        }
+    }
+}
+
+///
+/// Grant requested by a [`TokenRequest`].
+///
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TokenRequestGrant {
+    ///
+    /// Authorization code grant
+    /// ([Section 4.1.3 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.3)).
+    ///
+    AuthorizationCode {
+        ///
+        /// Authorization code.
+        ///
+        code: AuthorizationCode,
+        ///
+        /// Redirect URI, which must be identical to the one included in the authorization
+        /// request, if any.
+        ///
+        redirect_uri: Option<RedirectUrl>,
+        ///
+        /// PKCE code verifier (see [`verify_pkce_code_verifier`]).
+        ///
+        code_verifier: Option<PkceCodeVerifier>,
+    },
+    ///
+    /// Refresh token grant
+    /// ([Section 6 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-6)).
+    ///
+    RefreshToken {
+        ///
+        /// Refresh token.
+        ///
+        refresh_token: RefreshToken,
+        ///
+        /// Requested scopes, if any.
+        ///
+        scopes: Option<Vec<Scope>>,
+    },
+    ///
+    /// Client credentials grant
+    /// ([Section 4.4.2 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.4.2)).
+    ///
+    ClientCredentials {
+        ///
+        /// Requested scopes, if any.
+        ///
+        scopes: Option<Vec<Scope>>,
+    },
+    ///
+    /// Resource owner password credentials grant
+    /// ([Section 4.3.2 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.3.2)).
+    ///
+    Password {
+        ///
+        /// Resource owner username.
+        ///
+        username: ResourceOwnerUsername,
+        ///
+        /// Resource owner password.
+        ///
+        password: ResourceOwnerPassword,
+        ///
+        /// Requested scopes, if any.
+        ///
+        scopes: Option<Vec<Scope>>,
+    },
+    ///
+    /// Device authorization grant
+    /// ([Section 3.4 of RFC 8628](https://tools.ietf.org/html/rfc8628#section-3.4)).
+    ///
+    DeviceCode {
+        ///
+        /// Device code.
+        ///
+        device_code: DeviceCode,
+    },
+    ///
+    /// Extension grant type
+    /// ([Section 4.5 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.5)).
+    ///
+    Extension {
+        ///
+        /// Grant type.
+        ///
+        grant_type: String,
+        ///
+        /// Remaining request parameters (excluding client authentication parameters).
+        ///
+        params: HashMap<String, String>,
+    },
+}
+
+///
+/// Verifies a PKCE code verifier against the code challenge stored with an authorization code,
+/// as defined in [Section 4.6 of RFC 7636](https://tools.ietf.org/html/rfc7636#section-4.6).
+///
+/// If the authorization request included a code challenge, the token request MUST include a
+/// valid code verifier. If `require_s256` is `true`, the code challenge must use the `S256`
+/// method, as required by OAuth 2.1; otherwise, the `plain` method is also accepted. Providers
+/// enforcing this policy should also pass `require_s256` to [`verify_pkce_code_challenge`] when
+/// handling the authorization request.
+///
+pub fn verify_pkce_code_verifier(
+    code_verifier: Option<&PkceCodeVerifier>,
+    code_challenge: &str,
+    code_challenge_method: &PkceCodeChallengeMethod,
+    require_s256: bool,
+) -> Result<(), TokenRequestError> {
+    let code_verifier = code_verifier
+        .ok_or_else(|| TokenRequestError::InvalidGrant("missing code_verifier".to_string()))?;
+    let secret = code_verifier.secret();
+    if secret.len() < 43
+        || secret.len() > 128
+        || !secret
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-._~".contains(&b))
+    {
+        return Err(TokenRequestError::InvalidGrant(
+            "malformed code_verifier".to_string(),
+        ));
+    }
+
+    let expected_challenge = match code_challenge_method.as_str() {
+        "S256" => PkceCodeChallenge::from_code_verifier_sha256(code_verifier)
+            .as_str()
+            .to_string(),
+        "plain" if !require_s256 => secret.clone(),
+        other => {
+            return Err(TokenRequestError::InvalidGrant(format!(
+                "unsupported code_challenge_method `{}`",
+                other
+            )))
+        }
+    };
+    if bool::from(
+        expected_challenge
+            .as_bytes()
+            .ct_eq(code_challenge.as_bytes()),
+    ) {
+        Ok(())
+    } else {
+        Err(TokenRequestError::InvalidGrant(
+            "code_verifier does not match code_challenge".to_string(),
+        ))
+    }
+}
+
+///
+/// Verifies the PKCE code challenge of an authorization request, as defined in
+/// [Section 4.4.1 of RFC 7636](https://tools.ietf.org/html/rfc7636#section-4.4.1).
+///
+/// If `require_s256` is `true`, the request must include a code challenge using the `S256`
+/// method, as required by OAuth 2.1. Otherwise, the code challenge is optional and may use either
+/// the `S256` or `plain` method (which is the default if the method is omitted).
+///
+/// This crate doesn't parse authorization requests, so nothing calls this function on the
+/// provider's behalf: providers must call it themselves while handling each authorization
+/// request, before issuing an authorization code.
+///
+pub fn verify_pkce_code_challenge(
+    code_challenge: Option<&str>,
+    code_challenge_method: Option<&PkceCodeChallengeMethod>,
+    require_s256: bool,
+) -> Result<(), PkceChallengeError> {
+    let code_challenge = match code_challenge {
+        Some(code_challenge) => code_challenge,
+        None if require_s256 => return Err(PkceChallengeError::MissingChallenge),
+        None => return Ok(()),
+    };
+    match code_challenge_method.map(|method| method.as_str()) {
+        Some("S256") => {}
+        Some("plain") | None if !require_s256 => {}
+        method => {
+            return Err(PkceChallengeError::UnsupportedMethod(
+                method.unwrap_or("plain").to_string(),
+            ))
+        }
+    }
+    if code_challenge.len() < 43
+        || code_challenge.len() > 128
+        || !code_challenge
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-._~".contains(&b))
+    {
+        return Err(PkceChallengeError::MalformedChallenge);
+    }
+    Ok(())
+}
+
+///
+/// Error verifying the PKCE code challenge of an authorization request.
+///
+/// Providers should respond with the `invalid_request` error code (see
+/// [`error_type`](Self::error_type)).
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PkceChallengeError {
+    ///
+    /// The code challenge is malformed.
+    ///
+    #[error("Malformed code_challenge")]
+    MalformedChallenge,
+    ///
+    /// The request does not include a code challenge, but one is required.
+    ///
+    #[error("Missing code_challenge")]
+    MissingChallenge,
+    ///
+    /// The code challenge method is not supported (or not allowed by policy).
+    ///
+    #[error("Unsupported code_challenge_method `{0}`")]
+    UnsupportedMethod(String),
+}
+impl PkceChallengeError {
+    ///
+    /// Returns the OAuth2 error code corresponding to this error.
+    ///
+    pub fn error_type(&self) -> CoreAuthErrorResponseType {
+        CoreAuthErrorResponseType::InvalidRequest
+    }
+}
+
+///
+/// Error parsing or validating a token request.
+///
+/// Each variant corresponds to an
+/// [error code](https://tools.ietf.org/html/rfc6749#section-5.2) to be returned to the client;
+/// use [`TokenRequestError::error_response`] to build the response body.
+///
+#[derive(Clone, Debug, Error)]
+#[non_exhaustive]
+pub enum TokenRequestError {
+    ///
+    /// Client authentication failed (`invalid_client`).
+    ///
+    #[error("Invalid client: {0}")]
+    InvalidClient(String),
+    ///
+    /// The authorization grant or refresh token is invalid (`invalid_grant`).
+    ///
+    #[error("Invalid grant: {0}")]
+    InvalidGrant(String),
+    ///
+    /// The request is malformed (`invalid_request`).
+    ///
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+    ///
+    /// The grant type is not supported (`unsupported_grant_type`).
+    ///
+    /// This error is never returned by [`TokenRequest::parse`], which returns unrecognized grant
+    /// types as [`TokenRequestGrant::Extension`]; providers should return it for any grant types
+    /// they don't support.
+    ///
+    #[error("Unsupported grant type: {0}")]
+    UnsupportedGrantType(String),
+}
+impl TokenRequestError {
+    ///
+    /// Returns the OAuth2 error code corresponding to this error.
+    ///
+    pub fn error_type(&self) -> CoreErrorResponseType {
+        match self {
+            TokenRequestError::InvalidClient(_) => CoreErrorResponseType::InvalidClient,
+            TokenRequestError::InvalidGrant(_) => CoreErrorResponseType::InvalidGrant,
+            TokenRequestError::InvalidRequest(_) => CoreErrorResponseType::InvalidRequest,
+            TokenRequestError::UnsupportedGrantType(_) => {
+                CoreErrorResponseType::UnsupportedGrantType
+            }
+        }
+    }
+
+    ///
+    /// Returns the error response to return to the client.
+    ///
+    /// Per [Section 5.2 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-5.2), the
+    /// response should use HTTP status 400 (Bad Request), except for `invalid_client` errors in
+    /// response to HTTP Basic authentication, which must use 401 (Unauthorized) along with a
+    /// `WWW-Authenticate` header.
+    ///
+    pub fn error_response(&self) -> StandardErrorResponse<CoreErrorResponseType> {
+        let description = match self {
+            TokenRequestError::InvalidClient(description)
+            | TokenRequestError::InvalidGrant(description)
+            | TokenRequestError::InvalidRequest(description)
+            | TokenRequestError::UnsupportedGrantType(description) => description.clone(),
+        };
+        StandardErrorResponse::new(self.error_type(), Some(description), None)
+    }
+}
+
+///
+/// Error response returned by an OpenID Connect Provider's authorization endpoint by redirecting
+/// the User Agent to the client's redirect URI.
+///
+/// See [Section 4.1.2.1 of RFC 6749](https://tools.ietf.org/html/rfc6749#section-4.1.2.1) and
+/// [Section 3.1.2.6](https://openid.net/specs/openid-connect-core-1_0.html#AuthError) of the
+/// OpenID Connect Core spec.
+///
+/// Errors should only be returned in this way once the provider has validated the client's
+/// redirect URI. If the redirect URI is missing or invalid, the provider should instead inform
+/// the End-User of the error and must not redirect to the invalid URI.
+///
+#[derive(Clone, Debug)]
+pub struct AuthorizationErrorResponse {
+    redirect_uri: RedirectUrl,
+    response_mode: CoreResponseMode,
+    error: CoreAuthErrorResponseType,
+    error_description: Option<String>,
+    error_uri: Option<String>,
+    state: Option<CsrfToken>,
+    issuer: Option<IssuerUrl>,
+}
+impl AuthorizationErrorResponse {
+    ///
+    /// Initializes an error response to an authorization request with the specified
+    /// `response_type`.
+    ///
+    /// The response mode defaults to the one prescribed by
+    /// [OAuth 2.0 Multiple Response Type Encoding Practices](
+    ///     http://openid.net/specs/oauth-v2-multiple-response-types-1_0.html#ResponseTypesAndModes)
+    /// for the requested response type: `query` for the `code` and `none` response types, and
+    /// `fragment` for any response type that includes `token` or `id_token`. If the request
+    /// included a valid `response_mode` parameter, use
+    /// [`set_response_mode`](Self::set_response_mode) to override the default.
+    ///
+    pub fn new(
+        redirect_uri: RedirectUrl,
+        response_type: &ResponseTypes<CoreResponseType>,
+        error: CoreAuthErrorResponseType,
+    ) -> Self {
+        Self {
+            redirect_uri,
+            response_mode: Self::default_response_mode(response_type),
+            error,
+            error_description: None,
+            error_uri: None,
+            state: None,
+            issuer: None,
+        }
+    }
+
+    ///
+    /// Returns the default response mode for the specified response type.
+    ///
+    pub fn default_response_mode(
+        response_type: &ResponseTypes<CoreResponseType>,
+    ) -> CoreResponseMode {
+        if response_type
+            .iter()
+            .any(|rt| matches!(rt, CoreResponseType::IdToken | CoreResponseType::Token))
+        {
+            CoreResponseMode::Fragment
+        } else {
+            CoreResponseMode::Query
+        }
+    }
+
+    field_getters_setters![
+        pub self [self] ["error response value"] {
+            set_redirect_uri -> redirect_uri[RedirectUrl],
+            set_response_mode -> response_mode[CoreResponseMode],
+            set_error -> error[CoreAuthErrorResponseType],
+            set_error_description -> error_description[Option<String>],
+            set_error_uri -> error_uri[Option<String>],
+            set_state -> state[Option<CsrfToken>],
+            set_issuer -> issuer[Option<IssuerUrl>] ["iss"],
+        }
+    ];
+
+    ///
+    /// Returns the response parameters, in the order in which they are encoded.
+    ///
+    /// The `iss` parameter is only included if set via [`set_issuer`](Self::set_issuer), as
+    /// described in [RFC 9207](https://www.rfc-editor.org/rfc/rfc9207).
+    ///
+    pub fn params(&self) -> Vec<(&str, &str)> {
+        let mut params = vec![("error", self.error.as_ref())];
+        if let Some(ref error_description) = self.error_description {
+            params.push(("error_description", error_description.as_str()));
+        }
+        if let Some(ref error_uri) = self.error_uri {
+            params.push(("error_uri", error_uri.as_str()));
+        }
+        if let Some(ref state) = self.state {
+            params.push(("state", state.secret().as_str()));
+        }
+        if let Some(ref issuer) = self.issuer {
+            params.push(("iss", issuer.as_str()));
+        }
+        params
+    }
+
+    ///
+    /// Returns the URL to which the User Agent should be redirected (e.g., via a `302 Found`
+    /// response with a `Location` header), with the response parameters encoded in the query
+    /// string or fragment according to the response mode.
+    ///
+    /// Returns `None` if the response mode is `form_post` (see
+    /// [`form_post_html`](Self::form_post_html)), one of the JWT-secured (JARM) response modes,
+    /// or an unsupported extension.
+    ///
+    pub fn redirect_url(&self) -> Option<Url> {
+        let mut url = self.redirect_uri.url().clone();
+        match self.response_mode {
+            CoreResponseMode::Query => {
+                url.query_pairs_mut().extend_pairs(self.params());
+            }
+            CoreResponseMode::Fragment => {
+                let fragment = url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(self.params())
+                    .finish();
+                url.set_fragment(Some(&fragment));
+            }
+            CoreResponseMode::FormPost
+            | CoreResponseMode::Jwt
+            | CoreResponseMode::QueryJwt
+            | CoreResponseMode::FragmentJwt
+            | CoreResponseMode::FormPostJwt
+            | CoreResponseMode::Extension(_) => return None,
+        }
+        Some(url)
+    }
+
+    ///
+    /// Returns an HTML document that auto-submits the response parameters to the client's
+    /// redirect URI, as described in
+    /// [OAuth 2.0 Form Post Response Mode](
+    ///     http://openid.net/specs/oauth-v2-form-post-response-mode-1_0.html#FormPostResponseMode).
+    ///
+    /// The document should be returned with a `Cache-Control: no-store` header, since the response
+    /// must not be stored or reused by the User Agent.
+    ///
+    pub fn form_post_html(&self) -> String {
+        let inputs = self
+            .params()
+            .into_iter()
+            .map(|(name, value)| {
+                format!(
+                    "<input type=\"hidden\" name=\"{}\" value=\"{}\"/>",
+                    escape_html(name),
+                    escape_html(value)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("");
+        format!(
+            "<!DOCTYPE html><html><head><title>Submit This Form</title></head>\
+             <body onload=\"javascript:document.forms[0].submit()\">\
+             <form method=\"post\" action=\"{}\">{}</form></body></html>",
+            escape_html(self.redirect_uri.as_str()),
+            inputs
+        )
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+    use oauth2::{CsrfToken, PkceCodeChallenge, PkceCodeChallengeMethod, PkceCodeVerifier};
+
+    use crate::core::{
+        CoreAuthErrorResponseType, CoreErrorResponseType, CoreResponseMode, CoreResponseType,
+    };
+    use crate::{IssuerUrl, RedirectUrl, ResponseTypes};
+
+    use super::{
+        verify_pkce_code_challenge, verify_pkce_code_verifier, AuthorizationErrorResponse,
+        PkceChallengeError, TokenRequest, TokenRequestClientAuthentication, TokenRequestError,
+        TokenRequestGrant,
+    };
+
+    fn form_headers() -> HeaderMap {
+        vec![(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        )]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_token_request() {
+        let mut headers = form_headers();
+        headers.insert(
+            AUTHORIZATION,
+            // s6BhdRkqt3:gX1fBat3bV
+            HeaderValue::from_static("Basic czZCaGRSa3F0MzpnWDFmQmF0M2JW"),
+        );
+        let request = TokenRequest::parse(
+            &headers,
+            b"grant_type=authorization_code&code=SplxlOBeZQQYbYS6WxSbIA\
+              &redirect_uri=https%3A%2F%2Fclient%2Eexample%2Ecom%2Fcb&code_verifier=",
+        )
+        .unwrap();
+        match request.client_authentication() {
+            TokenRequestClientAuthentication::ClientSecretBasic {
+                client_id,
+                client_secret,
+            } => {
+                assert_eq!(client_id.as_str(), "s6BhdRkqt3");
+                assert_eq!(client_secret.secret(), "gX1fBat3bV");
+            }
+            other => panic!("unexpected client authentication: {:?}", other),
+        }
+        match request.grant() {
+            TokenRequestGrant::AuthorizationCode {
+                code,
+                redirect_uri,
+                code_verifier,
+            } => {
+                assert_eq!(code.secret(), "SplxlOBeZQQYbYS6WxSbIA");
+                assert_eq!(
+                    redirect_uri.as_ref().unwrap().as_str(),
+                    "https://client.example.com/cb"
+                );
+                assert!(code_verifier.is_none());
+            }
+            other => panic!("unexpected grant: {:?}", other),
+        }
+
+        let request = TokenRequest::parse(
+            &form_headers(),
+            b"grant_type=refresh_token&refresh_token=tGzv3JOkF0XG5Qx2TlKWIA&scope=openid+email\
+              &client_id=s6BhdRkqt3&client_secret=7Fjfp0ZBr1KtDRbnfVdmIw",
+        )
+        .unwrap();
+        assert!(matches!(
+            request.client_authentication(),
+            TokenRequestClientAuthentication::ClientSecretPost { .. }
+        ));
+        assert_eq!(request.client_id().unwrap().as_str(), "s6BhdRkqt3");
+        match request.grant() {
+            TokenRequestGrant::RefreshToken { scopes, .. } => assert_eq!(
+                scopes
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .map(|scope| scope.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["openid", "email"]
+            ),
+            other => panic!("unexpected grant: {:?}", other),
+        }
+
+        let request = TokenRequest::parse(
+            &form_headers(),
+            b"grant_type=client_credentials&client_assertion_type=\
+              urn%3Aietf%3Aparams%3Aoauth%3Aclient-assertion-type%3Ajwt-bearer\
+              &client_assertion=eyJhbGciOiJSUzI1NiJ9.e30.c2ln",
+        )
+        .unwrap();
+        match request.client_authentication() {
+            TokenRequestClientAuthentication::ClientAssertion {
+                client_id,
+                assertion_type,
+                assertion,
+            } => {
+                assert!(client_id.is_none());
+                assert_eq!(
+                    assertion_type,
+                    "urn:ietf:params:oauth:client-assertion-type:jwt-bearer"
+                );
+                assert_eq!(assertion, "eyJhbGciOiJSUzI1NiJ9.e30.c2ln");
+            }
+            other => panic!("unexpected client authentication: {:?}", other),
+        }
+
+        let request = TokenRequest::parse(
+            &form_headers(),
+            b"grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Atoken-exchange\
+              &subject_token=abc&client_id=public",
+        )
+        .unwrap();
+        assert!(matches!(
+            request.client_authentication(),
+            TokenRequestClientAuthentication::None { client_id: Some(_) }
+        ));
+        match request.grant() {
+            TokenRequestGrant::Extension { grant_type, params } => {
+                assert_eq!(
+                    grant_type,
+                    "urn:ietf:params:oauth:grant-type:token-exchange"
+                );
+                assert_eq!(params.get("subject_token").unwrap(), "abc");
+                assert!(!params.contains_key("client_id"));
+            }
+            other => panic!("unexpected grant: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_token_request_errors() {
+        let parse_error =
+            |headers: &HeaderMap, body: &[u8]| match TokenRequest::parse(headers, body) {
+                Err(err) => err,
+                Ok(request) => panic!("unexpected success: {:?}", request),
+            };
+
+        let err = parse_error(&form_headers(), b"code=abc");
+        assert_eq!(err.error_type(), CoreErrorResponseType::InvalidRequest);
+        assert_eq!(
+            err.error_response().error_description().unwrap(),
+            "missing grant_type"
+        );
+
+        assert!(matches!(
+            parse_error(&form_headers(), b"grant_type=authorization_code"),
+            TokenRequestError::InvalidRequest(ref msg) if msg == "missing code"
+        ));
+        assert!(matches!(
+            parse_error(
+                &form_headers(),
+                b"grant_type=authorization_code&code=a&code=b"
+            ),
+            TokenRequestError::InvalidRequest(ref msg) if msg == "duplicate parameter `code`"
+        ));
+        assert!(matches!(
+            parse_error(
+                &vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                    .into_iter()
+                    .collect(),
+                b"{}"
+            ),
+            TokenRequestError::InvalidRequest(_)
+        ));
+
+        let mut headers = form_headers();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_static("Basic czZCaGRSa3F0MzpnWDFmQmF0M2JW"),
+        );
+        assert!(matches!(
+            parse_error(
+                &headers,
+                b"grant_type=client_credentials&client_secret=other"
+            ),
+            TokenRequestError::InvalidRequest(ref msg)
+                if msg == "multiple client authentication methods used"
+        ));
+        assert!(matches!(
+            parse_error(&headers, b"grant_type=client_credentials&client_id=other"),
+            TokenRequestError::InvalidClient(_)
+        ));
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic !!!"));
+        assert!(matches!(
+            parse_error(&headers, b"grant_type=client_credentials"),
+            TokenRequestError::InvalidClient(_)
+        ));
+    }
+
+    #[test]
+    fn test_verify_pkce_code_verifier() {
+        // From https://tools.ietf.org/html/rfc7636#appendix-B.
+        let code_verifier =
+            PkceCodeVerifier::new("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        let code_challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+        let s256 = PkceCodeChallengeMethod::new("S256".to_string());
+
+        verify_pkce_code_verifier(Some(&code_verifier), code_challenge, &s256, false).unwrap();
+        verify_pkce_code_verifier(Some(&code_verifier), code_challenge, &s256, true).unwrap();
+        assert!(matches!(
+            verify_pkce_code_verifier(None, code_challenge, &s256, false),
+            Err(TokenRequestError::InvalidGrant(_))
+        ));
+        assert!(matches!(
+            verify_pkce_code_verifier(
+                Some(&code_verifier),
+                "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cN",
+                &s256,
+                false
+            ),
+            Err(TokenRequestError::InvalidGrant(_))
+        ));
+        assert!(matches!(
+            verify_pkce_code_verifier(
+                Some(&PkceCodeVerifier::new("too-short".to_string())),
+                "too-short",
+                &PkceCodeChallengeMethod::new("plain".to_string()),
+                false
+            ),
+            Err(TokenRequestError::InvalidGrant(_))
+        ));
+
+        // The `plain` method is only accepted unless S256 is required.
+        let plain = PkceCodeChallengeMethod::new("plain".to_string());
+        verify_pkce_code_verifier(Some(&code_verifier), code_verifier.secret(), &plain, false)
+            .unwrap();
+        match verify_pkce_code_verifier(Some(&code_verifier), code_verifier.secret(), &plain, true)
+        {
+            Err(TokenRequestError::InvalidGrant(message)) => {
+                assert_eq!(message, "unsupported code_challenge_method `plain`")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+        verify_pkce_code_verifier(
+            Some(&verifier),
+            challenge.as_str(),
+            challenge.method(),
+            true,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_verify_pkce_code_challenge() {
+        let (challenge, _) = PkceCodeChallenge::new_random_sha256();
+        let plain = PkceCodeChallengeMethod::new("plain".to_string());
+
+        verify_pkce_code_challenge(None, None, false).unwrap();
+        verify_pkce_code_challenge(Some(challenge.as_str()), None, false).unwrap();
+        verify_pkce_code_challenge(Some(challenge.as_str()), Some(&plain), false).unwrap();
+        verify_pkce_code_challenge(Some(challenge.as_str()), Some(challenge.method()), true)
+            .unwrap();
+
+        assert_eq!(
+            verify_pkce_code_challenge(None, None, true),
+            Err(PkceChallengeError::MissingChallenge)
+        );
+        assert_eq!(
+            verify_pkce_code_challenge(Some(challenge.as_str()), Some(&plain), true),
+            Err(PkceChallengeError::UnsupportedMethod("plain".to_string()))
+        );
+        assert_eq!(
+            verify_pkce_code_challenge(Some(challenge.as_str()), None, true),
+            Err(PkceChallengeError::UnsupportedMethod("plain".to_string()))
+        );
+        assert_eq!(
+            verify_pkce_code_challenge(
+                Some(challenge.as_str()),
+                Some(&PkceCodeChallengeMethod::new("S512".to_string())),
+                false
+            ),
+            Err(PkceChallengeError::UnsupportedMethod("S512".to_string()))
+        );
+        assert_eq!(
+            verify_pkce_code_challenge(Some("too-short"), Some(challenge.method()), true),
+            Err(PkceChallengeError::MalformedChallenge)
+        );
+        assert_eq!(
+            PkceChallengeError::MissingChallenge.error_type(),
+            CoreAuthErrorResponseType::InvalidRequest
+        );
+    }
+
+    #[test]
+    fn test_authorization_error_response() {
+        let redirect_uri = RedirectUrl::new("https://app.example.com/cb?foo=bar".to_string())
+            .expect("invalid redirect URI");
+
+        let response = AuthorizationErrorResponse::new(
+            redirect_uri.clone(),
+            &ResponseTypes::new(vec![CoreResponseType::Code]),
+            CoreAuthErrorResponseType::LoginRequired,
+        )
+        .set_error_description(Some("End-User is not logged in".to_string()))
+        .set_state(Some(CsrfToken::new("the_state".to_string())));
+        assert_eq!(response.response_mode(), &CoreResponseMode::Query);
+        assert_eq!(
+            response.redirect_url().unwrap().as_str(),
+            "https://app.example.com/cb?foo=bar&error=login_required\
+             &error_description=End-User+is+not+logged+in&state=the_state"
+        );
+
+        let response = AuthorizationErrorResponse::new(
+            redirect_uri.clone(),
+            &ResponseTypes::new(vec![CoreResponseType::Code, CoreResponseType::IdToken]),
+            CoreAuthErrorResponseType::ConsentRequired,
+        )
+        .set_state(Some(CsrfToken::new("the_state".to_string())))
+        .set_issuer(Some(
+            IssuerUrl::new("https://example.com".to_string()).unwrap(),
+        ));
+        assert_eq!(response.response_mode(), &CoreResponseMode::Fragment);
+        assert_eq!(
+            response.redirect_url().unwrap().as_str(),
+            "https://app.example.com/cb?foo=bar#error=consent_required&state=the_state\
+             &iss=https%3A%2F%2Fexample.com"
+        );
+
+        let response = response.set_response_mode(CoreResponseMode::FormPost);
+        assert_eq!(response.redirect_url(), None);
+        let html = response.form_post_html();
+        assert!(html.contains("action=\"https://app.example.com/cb?foo=bar\""));
+        let error_input = "<input type=\"hidden\" name=\"error\" value=\"consent_required\"/>";
+        assert!(html.contains(error_input));
+
+        let response = response.set_state(Some(CsrfToken::new("\"><script>".to_string())));
+        assert!(response
+            .form_post_html()
+            .contains("value=\"&quot;&gt;&lt;script&gt;\""));
+    }
+}
@@ -4,7 +4,7 @@ use std::marker::PhantomData;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
-use http::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use http::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
 use http::method::Method;
 use http::status::StatusCode;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer, MapAccess, Visitor};
@@ -422,6 +422,8 @@ where
 {
     client_metadata: ClientMetadata<AC, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
     initial_access_token: Option<AccessToken>,
+    verify_response: bool,
+    extra_headers: HeaderMap,
     _phantom: PhantomData<(AR, ET)>,
 }
 impl<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
@@ -449,6 +451,8 @@ where
         Self {
             client_metadata: ClientMetadata::new(redirect_uris, additional_metadata),
             initial_access_token: None,
+            verify_response: true,
+            extra_headers: HeaderMap::new(),
             _phantom: PhantomData,
         }
     }
@@ -473,7 +477,7 @@ where
             .and_then(|http_request| {
                 http_client(http_request).map_err(ClientRegistrationError::Request)
             })
-            .and_then(Self::register_response)
+            .and_then(|http_response| self.verified_registration_response(http_response))
     }
 
     ///
@@ -497,7 +501,7 @@ where
         let http_response = http_client(http_request)
             .await
             .map_err(ClientRegistrationError::Request)?;
-        Self::register_response(http_response)
+        self.verified_registration_response(http_response)
     }
 
     fn prepare_registration<RE>(
@@ -519,6 +523,7 @@ where
         if let Some((header, value)) = auth_header_opt {
             headers.append(header, value);
         }
+        headers.extend(self.extra_headers.clone());
 
         Ok(HttpRequest {
             url: registration_endpoint.url().clone(),
@@ -528,12 +533,143 @@ where
         })
     }
 
-    fn register_response<RE>(
+    fn verified_registration_response<RE>(
+        &self,
         http_response: HttpResponse,
     ) -> Result<
         ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
         ClientRegistrationError<ET, RE>,
     >
+    where
+        RE: std::error::Error + 'static,
+    {
+        let response_body = if self.verify_response {
+            http_response.body.clone()
+        } else {
+            Vec::new()
+        };
+        let registration_response =
+            Self::registration_response(http_response, StatusCode::CREATED)?;
+        if self.verify_response {
+            let mismatches = self.metadata_mismatches(&registration_response);
+            if !mismatches.is_empty() {
+                return Err(ClientRegistrationError::MetadataMismatch(
+                    mismatches,
+                    response_body,
+                ));
+            }
+        }
+        Ok(registration_response)
+    }
+
+    ///
+    /// Compares the client metadata in a registration response to the metadata requested by
+    /// this request, returning any security-relevant differences.
+    ///
+    /// The following fields are compared (using the default values defined in
+    /// [RFC 7591](https://tools.ietf.org/html/rfc7591#section-2) and
+    /// [OpenID Connect Dynamic Client Registration](https://openid.net/specs/openid-connect-registration-1_0.html#ClientMetadata)
+    /// for fields omitted from the response): `redirect_uris`, `response_types`, `grant_types`,
+    /// `token_endpoint_auth_method`, and `id_token_signed_response_alg`. Fields that were not
+    /// requested are not compared.
+    ///
+    /// Only downgrades are reported: an array field mismatches if the response omits any
+    /// requested value (additional values granted by the server are accepted, and the order of
+    /// values is ignored), while a single-valued field mismatches if the registered value differs
+    /// from the requested one.
+    ///
+    pub fn metadata_mismatches(
+        &self,
+        response: &ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+    ) -> Vec<ClientMetadataMismatch> {
+        fn to_value<T: Serialize>(value: &T) -> serde_json::Value {
+            serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+        }
+        fn normalize(value: serde_json::Value) -> serde_json::Value {
+            match value {
+                serde_json::Value::Array(mut values) => {
+                    values.sort_by_key(|value| value.to_string());
+                    values.dedup();
+                    serde_json::Value::Array(values)
+                }
+                other => other,
+            }
+        }
+        fn compare<T: Serialize>(
+            mismatches: &mut Vec<ClientMetadataMismatch>,
+            field: &str,
+            requested: Option<&T>,
+            registered: Option<&T>,
+            default: serde_json::Value,
+        ) {
+            if let Some(requested) = requested {
+                let requested = normalize(to_value(requested));
+                let registered = normalize(registered.map(to_value).unwrap_or(default));
+                let is_downgrade = match (&requested, &registered) {
+                    (
+                        serde_json::Value::Array(requested_values),
+                        serde_json::Value::Array(registered_values),
+                    ) => requested_values
+                        .iter()
+                        .any(|value| !registered_values.contains(value)),
+                    _ => requested != registered,
+                };
+                if is_downgrade {
+                    mismatches.push(ClientMetadataMismatch {
+                        field: field.to_string(),
+                        requested,
+                        registered,
+                    });
+                }
+            }
+        }
+
+        let mut mismatches = Vec::new();
+        compare(
+            &mut mismatches,
+            "redirect_uris",
+            Some(self.redirect_uris()),
+            Some(response.redirect_uris()),
+            serde_json::Value::Null,
+        );
+        compare(
+            &mut mismatches,
+            "response_types",
+            self.response_types(),
+            response.response_types(),
+            serde_json::json!(["code"]),
+        );
+        compare(
+            &mut mismatches,
+            "grant_types",
+            self.grant_types(),
+            response.grant_types(),
+            serde_json::json!(["authorization_code"]),
+        );
+        compare(
+            &mut mismatches,
+            "token_endpoint_auth_method",
+            self.token_endpoint_auth_method(),
+            response.token_endpoint_auth_method(),
+            serde_json::json!("client_secret_basic"),
+        );
+        compare(
+            &mut mismatches,
+            "id_token_signed_response_alg",
+            self.id_token_signed_response_alg(),
+            response.id_token_signed_response_alg(),
+            serde_json::json!("RS256"),
+        );
+        mismatches
+    }
+
+    fn registration_response<RE>(
+        http_response: HttpResponse,
+        success_status: StatusCode,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
     where
         RE: std::error::Error + 'static,
     {
@@ -541,10 +677,11 @@ where
         //   https://tools.ietf.org/html/rfc6750#section-3)
         // TODO: other necessary response validation? check spec
 
-        // Spec says that a successful response SHOULD use 201 Created, and a registration error
-        // condition returns (no "SHOULD") 400 Bad Request. For now, only accept these two status
-        // codes. We may need to relax the success status to improve interoperability.
-        if http_response.status_code != StatusCode::CREATED
+        // Spec says that a successful response SHOULD use 201 Created (or 200 OK for client
+        // configuration requests), and a registration error condition returns (no "SHOULD") 400
+        // Bad Request. For now, only accept these two status codes. We may need to relax the
+        // success status to improve interoperability.
+        if http_response.status_code != success_status
             && http_response.status_code != StatusCode::BAD_REQUEST
         {
             return Err(ClientRegistrationError::Response(
@@ -602,6 +739,40 @@ where
         self
     }
 
+    ///
+    /// Adds an additional header to the registration request (e.g., a correlation ID or an API
+    /// gateway key).
+    ///
+    /// Any header of the same name set by this crate is replaced.
+    ///
+    pub fn add_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.extra_headers.append(name, value);
+        self
+    }
+
+    ///
+    /// Returns whether the registration response is verified against this request.
+    ///
+    pub fn verify_response(&self) -> bool {
+        self.verify_response
+    }
+    ///
+    /// Sets whether to verify the registration response against this request (enabled by
+    /// default).
+    ///
+    /// When enabled, [`register`](Self::register) and [`register_async`](Self::register_async)
+    /// return [`ClientRegistrationError::MetadataMismatch`] if the registered client metadata
+    /// downgrades the requested metadata (see
+    /// [`metadata_mismatches`](Self::metadata_mismatches)).
+    /// Authorization servers may silently substitute metadata values (e.g., a weaker token
+    /// endpoint authentication method), so this should only be disabled if the application
+    /// performs its own verification.
+    ///
+    pub fn set_verify_response(mut self, verify_response: bool) -> Self {
+        self.verify_response = verify_response;
+        self
+    }
+
     field_getters_setters![
         pub self [self.client_metadata.standard_metadata] ["client metadata value"] {
             set_redirect_uris -> redirect_uris[Vec<RedirectUrl>],
@@ -842,7 +1013,391 @@ where
     }
 }
 
-// TODO: implement client configuration endpoint request (Section 4)
+impl<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>
+    ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>
+where
+    AC: AdditionalClientMetadata,
+    AR: AdditionalClientRegistrationResponse,
+    AT: ApplicationType,
+    CA: ClientAuthMethod,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+{
+    ///
+    /// Returns a request for managing this client's registration via the client configuration
+    /// endpoint ([RFC 7592](https://tools.ietf.org/html/rfc7592)), or `None` if the response did
+    /// not include a `registration_client_uri` and `registration_access_token`.
+    ///
+    pub fn configuration_request<ET>(
+        &self,
+    ) -> Option<ClientConfigurationRequest<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>>
+    where
+        ET: RegisterErrorResponseType + Send + Sync,
+        S: Send + Sync,
+    {
+        match (
+            self.registration_client_uri.as_ref(),
+            self.registration_access_token.as_ref(),
+        ) {
+            (Some(client_config_url), Some(registration_access_token)) => {
+                Some(ClientConfigurationRequest::new(
+                    client_config_url.clone(),
+                    registration_access_token.clone(),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+///
+/// Request to the client configuration endpoint for reading, updating, or deleting a dynamically
+/// registered client, as defined in [RFC 7592](https://tools.ietf.org/html/rfc7592).
+///
+/// Each request is authenticated using the [`RegistrationAccessToken`] returned in the
+/// [`ClientRegistrationResponse`]. Note that the authorization server may rotate the registration
+/// access token and/or client secret in the response to any of these requests.
+///
+#[derive(Clone, Debug)]
+pub struct ClientConfigurationRequest<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
+where
+    AC: AdditionalClientMetadata,
+    AR: AdditionalClientRegistrationResponse,
+    AT: ApplicationType,
+    CA: ClientAuthMethod,
+    ET: RegisterErrorResponseType,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RT: ResponseType,
+    S: SubjectIdentifierType,
+{
+    client_config_url: ClientConfigUrl,
+    registration_access_token: RegistrationAccessToken,
+    #[allow(clippy::type_complexity)]
+    _phantom: PhantomData<(AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S)>,
+}
+impl<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
+    ClientConfigurationRequest<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>
+where
+    AC: AdditionalClientMetadata,
+    AR: AdditionalClientRegistrationResponse,
+    AT: ApplicationType,
+    CA: ClientAuthMethod,
+    ET: RegisterErrorResponseType + Send + Sync,
+    G: GrantType,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    RT: ResponseType,
+    S: SubjectIdentifierType + Send + Sync,
+{
+    ///
+    /// Instantiates a new client configuration request.
+    ///
+    pub fn new(
+        client_config_url: ClientConfigUrl,
+        registration_access_token: RegistrationAccessToken,
+    ) -> Self {
+        Self {
+            client_config_url,
+            registration_access_token,
+            _phantom: PhantomData,
+        }
+    }
+
+    ///
+    /// Returns the client configuration endpoint URL.
+    ///
+    pub fn client_config_url(&self) -> &ClientConfigUrl {
+        &self.client_config_url
+    }
+
+    ///
+    /// Returns the registration access token used to authenticate requests.
+    ///
+    pub fn registration_access_token(&self) -> &RegistrationAccessToken {
+        &self.registration_access_token
+    }
+
+    ///
+    /// Reads the client's current configuration
+    /// ([Section 2.1](https://tools.ietf.org/html/rfc7592#section-2.1)) using the specified
+    /// synchronous HTTP client.
+    ///
+    pub fn read<HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client(self.prepare_request(Method::GET, Vec::new()))
+            .map_err(ClientRegistrationError::Request)
+            .and_then(Self::configuration_response)
+    }
+
+    ///
+    /// Reads the client's current configuration
+    /// ([Section 2.1](https://tools.ietf.org/html/rfc7592#section-2.1)) using the specified
+    /// asynchronous HTTP client.
+    ///
+    pub async fn read_async<F, HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: FnOnce(HttpRequest) -> F,
+        RE: std::error::Error + 'static,
+    {
+        let http_response = http_client(self.prepare_request(Method::GET, Vec::new()))
+            .await
+            .map_err(ClientRegistrationError::Request)?;
+        Self::configuration_response(http_response)
+    }
+
+    ///
+    /// Replaces the client's configuration
+    /// ([Section 2.2](https://tools.ietf.org/html/rfc7592#section-2.2)) using the specified
+    /// synchronous HTTP client.
+    ///
+    /// The request includes all of the specified client metadata, which replaces the client's
+    /// existing metadata. Omitted fields are treated as null by the authorization server. The
+    /// `client_secret` should be included only if the client wishes to confirm its current
+    /// secret.
+    ///
+    pub fn update<HC, RE>(
+        &self,
+        client_id: &ClientId,
+        client_secret: Option<&ClientSecret>,
+        client_metadata: &ClientMetadata<AC, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        self.prepare_update(client_id, client_secret, client_metadata)
+            .and_then(|http_request| {
+                http_client(http_request).map_err(ClientRegistrationError::Request)
+            })
+            .and_then(Self::configuration_response)
+    }
+
+    ///
+    /// Replaces the client's configuration
+    /// ([Section 2.2](https://tools.ietf.org/html/rfc7592#section-2.2)) using the specified
+    /// asynchronous HTTP client.
+    ///
+    /// See [`update`](Self::update) for details.
+    ///
+    pub async fn update_async<F, HC, RE>(
+        &self,
+        client_id: &ClientId,
+        client_secret: Option<&ClientSecret>,
+        client_metadata: &ClientMetadata<AC, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        http_client: HC,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: FnOnce(HttpRequest) -> F,
+        RE: std::error::Error + 'static,
+    {
+        let http_request = self.prepare_update(client_id, client_secret, client_metadata)?;
+        let http_response = http_client(http_request)
+            .await
+            .map_err(ClientRegistrationError::Request)?;
+        Self::configuration_response(http_response)
+    }
+
+    ///
+    /// Deregisters the client ([Section 2.3](https://tools.ietf.org/html/rfc7592#section-2.3))
+    /// using the specified synchronous HTTP client.
+    ///
+    pub fn delete<HC, RE>(&self, http_client: HC) -> Result<(), ClientRegistrationError<ET, RE>>
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client(self.prepare_request(Method::DELETE, Vec::new()))
+            .map_err(ClientRegistrationError::Request)
+            .and_then(Self::delete_response)
+    }
+
+    ///
+    /// Deregisters the client ([Section 2.3](https://tools.ietf.org/html/rfc7592#section-2.3))
+    /// using the specified asynchronous HTTP client.
+    ///
+    pub async fn delete_async<F, HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<(), ClientRegistrationError<ET, RE>>
+    where
+        F: Future<Output = Result<HttpResponse, RE>>,
+        HC: FnOnce(HttpRequest) -> F,
+        RE: std::error::Error + 'static,
+    {
+        let http_response = http_client(self.prepare_request(Method::DELETE, Vec::new()))
+            .await
+            .map_err(ClientRegistrationError::Request)?;
+        Self::delete_response(http_response)
+    }
+
+    fn prepare_request(&self, method: Method, body: Vec<u8>) -> HttpRequest {
+        let (auth_header, auth_value) = auth_bearer(&AccessToken::new(
+            self.registration_access_token.secret().clone(),
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.append(ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON));
+        if !body.is_empty() {
+            headers.append(CONTENT_TYPE, HeaderValue::from_static(MIME_TYPE_JSON));
+        }
+        headers.append(auth_header, auth_value);
+
+        HttpRequest {
+            url: self.client_config_url.url().clone(),
+            method,
+            headers,
+            body,
+        }
+    }
+
+    fn prepare_update<RE>(
+        &self,
+        client_id: &ClientId,
+        client_secret: Option<&ClientSecret>,
+        client_metadata: &ClientMetadata<AC, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+    ) -> Result<HttpRequest, ClientRegistrationError<ET, RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        // The request MUST NOT include the registration_access_token, registration_client_uri,
+        // client_secret_expires_at, or client_id_issued_at fields.
+        #[derive(Serialize)]
+        struct ClientUpdate<'a, M: Serialize> {
+            client_id: &'a ClientId,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            client_secret: Option<&'a ClientSecret>,
+            #[serde(flatten)]
+            client_metadata: &'a M,
+        }
+
+        let request_json = serde_json::to_string(&ClientUpdate {
+            client_id,
+            client_secret,
+            client_metadata,
+        })
+        .map_err(ClientRegistrationError::Serialize)?
+        .into_bytes();
+
+        Ok(self.prepare_request(Method::PUT, request_json))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn configuration_response<RE>(
+        http_response: HttpResponse,
+    ) -> Result<
+        ClientRegistrationResponse<AC, AR, AT, CA, G, JE, JK, JS, JT, JU, K, RT, S>,
+        ClientRegistrationError<ET, RE>,
+    >
+    where
+        RE: std::error::Error + 'static,
+    {
+        ClientRegistrationRequest::<AC, AR, AT, CA, ET, G, JE, JK, JS, JT, JU, K, RT, S>::registration_response(
+            http_response,
+            StatusCode::OK,
+        )
+    }
+
+    fn delete_response<RE>(
+        http_response: HttpResponse,
+    ) -> Result<(), ClientRegistrationError<ET, RE>>
+    where
+        RE: std::error::Error + 'static,
+    {
+        if http_response.status_code == StatusCode::NO_CONTENT {
+            Ok(())
+        } else {
+            Err(ClientRegistrationError::Response(
+                http_response.status_code,
+                http_response.body,
+                "unexpected HTTP status code".to_string(),
+            ))
+        }
+    }
+}
+
+///
+/// Difference between a requested client metadata field and the value registered by the
+/// authorization server.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClientMetadataMismatch {
+    field: String,
+    requested: serde_json::Value,
+    registered: serde_json::Value,
+}
+impl ClientMetadataMismatch {
+    ///
+    /// Returns the name of the client metadata field.
+    ///
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    ///
+    /// Returns the requested value (with array values sorted).
+    ///
+    pub fn requested(&self) -> &serde_json::Value {
+        &self.requested
+    }
+
+    ///
+    /// Returns the registered value (with array values sorted), or the field's default value if
+    /// it was omitted from the registration response.
+    ///
+    pub fn registered(&self) -> &serde_json::Value {
+        &self.registered
+    }
+}
+impl std::fmt::Display for ClientMetadataMismatch {
+    fn fmt(&self, f: &mut Formatter) -> FormatterResult {
+        write!(
+            f,
+            "`{}` requested {} but registered {}",
+            self.field, self.requested, self.registered
+        )
+    }
+}
 
 ///
 /// Trait representing an error returned by the dynamic client registration endpoint.
@@ -886,12 +1441,167 @@ where
     #[error("Failed to serialize client metadata")]
     Serialize(#[source] serde_json::Error),
     ///
+    /// The client was registered, but the registered client metadata downgrades the requested
+    /// metadata. The raw registration response body is included so that the client can be
+    /// inspected or deregistered.
+    ///
+    #[error(
+        "Registered client metadata does not match request: {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    MetadataMismatch(Vec<ClientMetadataMismatch>, Vec<u8>),
+    ///
     /// Server returned an error.
     ///
     #[error("Server returned error")]
     ServerResponse(StandardErrorResponse<T>),
 }
 
+///
+/// Fetches the JSON document at the given `sector_identifier_uri` and validates that it contains
+/// each of the given redirect URIs, as described in
+/// [Section 5](https://openid.net/specs/openid-connect-registration-1_0.html#SectorIdentifierValidation)
+/// of the OpenID Connect Dynamic Client Registration spec.
+///
+/// OpenID Connect Providers must perform this validation when registering clients that specify a
+/// `sector_identifier_uri`. Relying Parties can use it to diagnose registration failures. Returns
+/// the redirect URIs listed in the document.
+///
+pub fn validate_sector_identifier_uri<HC, RE>(
+    sector_identifier_uri: &SectorIdentifierUrl,
+    redirect_uris: &[RedirectUrl],
+    http_client: HC,
+) -> Result<Vec<String>, SectorIdentifierError<RE>>
+where
+    HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+    RE: std::error::Error + 'static,
+{
+    let http_request = sector_identifier_request(sector_identifier_uri)?;
+    http_client(http_request)
+        .map_err(SectorIdentifierError::Request)
+        .and_then(|http_response| sector_identifier_response(http_response, redirect_uris))
+}
+
+///
+/// Asynchronously fetches the JSON document at the given `sector_identifier_uri` and validates
+/// that it contains each of the given redirect URIs (see [`validate_sector_identifier_uri`]).
+///
+pub async fn validate_sector_identifier_uri_async<F, HC, RE>(
+    sector_identifier_uri: &SectorIdentifierUrl,
+    redirect_uris: &[RedirectUrl],
+    http_client: HC,
+) -> Result<Vec<String>, SectorIdentifierError<RE>>
+where
+    F: Future<Output = Result<HttpResponse, RE>>,
+    HC: FnOnce(HttpRequest) -> F,
+    RE: std::error::Error + 'static,
+{
+    let http_request = sector_identifier_request(sector_identifier_uri)?;
+    let http_response = http_client(http_request)
+        .await
+        .map_err(SectorIdentifierError::Request)?;
+    sector_identifier_response(http_response, redirect_uris)
+}
+
+fn sector_identifier_request<RE>(
+    sector_identifier_uri: &SectorIdentifierUrl,
+) -> Result<HttpRequest, SectorIdentifierError<RE>>
+where
+    RE: std::error::Error + 'static,
+{
+    if sector_identifier_uri.url().scheme() != "https" {
+        return Err(SectorIdentifierError::Other(format!(
+            "sector identifier URI must use the https scheme (found `{}`)",
+            sector_identifier_uri.url().scheme()
+        )));
+    }
+    Ok(HttpRequest {
+        url: sector_identifier_uri.url().clone(),
+        method: Method::GET,
+        headers: vec![(ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON))]
+            .into_iter()
+            .collect(),
+        body: Vec::new(),
+    })
+}
+
+fn sector_identifier_response<RE>(
+    http_response: HttpResponse,
+    redirect_uris: &[RedirectUrl],
+) -> Result<Vec<String>, SectorIdentifierError<RE>>
+where
+    RE: std::error::Error + 'static,
+{
+    if http_response.status_code != StatusCode::OK {
+        return Err(SectorIdentifierError::Response(
+            http_response.status_code,
+            http_response.body,
+            format!("HTTP status code {}", http_response.status_code),
+        ));
+    }
+
+    // The Content-Type isn't checked, since sector identifier documents are often served as
+    // static files with generic content types.
+    let listed_uris = serde_path_to_error::deserialize::<_, Vec<String>>(
+        &mut serde_json::Deserializer::from_slice(&http_response.body),
+    )
+    .map_err(SectorIdentifierError::Parse)?;
+
+    let missing_uris = redirect_uris
+        .iter()
+        .filter(|redirect_uri| {
+            !listed_uris
+                .iter()
+                .any(|listed_uri| listed_uri == redirect_uri.as_str())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if !missing_uris.is_empty() {
+        return Err(SectorIdentifierError::MissingRedirectUris(missing_uris));
+    }
+    Ok(listed_uris)
+}
+
+///
+/// Error fetching or validating a sector identifier document.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SectorIdentifierError<RE>
+where
+    RE: std::error::Error + 'static,
+{
+    ///
+    /// The sector identifier document does not contain some of the redirect URIs.
+    ///
+    #[error(
+        "Sector identifier document does not contain redirect URIs: {}",
+        .0.iter().map(|uri| format!("`{}`", uri.as_str())).collect::<Vec<_>>().join(", ")
+    )]
+    MissingRedirectUris(Vec<RedirectUrl>),
+    ///
+    /// An unexpected error occurred.
+    ///
+    #[error("Other error: {0}")]
+    Other(String),
+    ///
+    /// Failed to parse the sector identifier document, which must be a JSON array of strings.
+    ///
+    #[error("Failed to parse sector identifier document")]
+    Parse(#[source] serde_path_to_error::Error<serde_json::Error>),
+    ///
+    /// An error occurred while sending the request or receiving the response (e.g., network
+    /// connectivity failed).
+    ///
+    #[error("Request failed")]
+    Request(#[source] RE),
+    ///
+    /// Server returned an invalid response.
+    ///
+    #[error("Server returned invalid response: {2}")]
+    Response(StatusCode, Vec<u8>, String),
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -1538,4 +2248,289 @@ mod tests {
             deserialized.additional_response,
         );
     }
+
+    #[test]
+    fn test_client_configuration_requests() {
+        use http::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+        use http::method::Method;
+        use http::status::StatusCode;
+
+        use crate::core::{CoreClientConfigurationRequest, CoreRegisterErrorResponseType};
+        use crate::registration::ClientRegistrationError;
+        use crate::{HttpRequest, HttpResponse, RegistrationAccessToken};
+
+        let registration_response: CoreClientRegistrationResponse = serde_json::from_str(
+            "{
+                \"client_id\": \"s6BhdRkqt3\",
+                \"client_secret\": \"cf136dc3c1fc93f31185e5885805d\",
+                \"registration_access_token\": \"reg-23410913-abewfq.123483\",
+                \"registration_client_uri\": \"https://server.example.com/register/s6BhdRkqt3\",
+                \"redirect_uris\": [\"https://client.example.org/callback\"]
+            }",
+        )
+        .unwrap();
+        let configuration_request: CoreClientConfigurationRequest = registration_response
+            .configuration_request::<CoreRegisterErrorResponseType>()
+            .unwrap();
+
+        let json_response = |status_code: StatusCode, body: &str| HttpResponse {
+            status_code,
+            headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                .into_iter()
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        };
+        let check_request = |request: &HttpRequest, method: Method| {
+            assert_eq!(request.method, method);
+            assert_eq!(
+                request.url.as_str(),
+                "https://server.example.com/register/s6BhdRkqt3"
+            );
+            assert_eq!(
+                request.headers.get(AUTHORIZATION).unwrap(),
+                "Bearer reg-23410913-abewfq.123483"
+            );
+        };
+
+        let read_response = configuration_request
+            .read(
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    check_request(&request, Method::GET);
+                    assert!(request.body.is_empty());
+                    Ok(json_response(
+                        StatusCode::OK,
+                        "{
+                        \"client_id\": \"s6BhdRkqt3\",
+                        \"redirect_uris\": [\"https://client.example.org/callback\"]
+                    }",
+                    ))
+                },
+            )
+            .unwrap();
+        assert_eq!(read_response.client_id().as_str(), "s6BhdRkqt3");
+
+        let client_metadata = CoreClientMetadata::new(
+            vec![RedirectUrl::new("https://client.example.org/callback2".to_string()).unwrap()],
+            Default::default(),
+        );
+        let update_response = configuration_request
+            .update(
+                registration_response.client_id(),
+                None,
+                &client_metadata,
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    check_request(&request, Method::PUT);
+                    assert_eq!(
+                        String::from_utf8(request.body).unwrap(),
+                        "{\"client_id\":\"s6BhdRkqt3\",\
+                         \"redirect_uris\":[\"https://client.example.org/callback2\"]}"
+                    );
+                    Ok(json_response(
+                        StatusCode::OK,
+                        "{
+                            \"client_id\": \"s6BhdRkqt3\",
+                            \"registration_access_token\": \"rotated\",
+                            \"redirect_uris\": [\"https://client.example.org/callback2\"]
+                        }",
+                    ))
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            update_response
+                .registration_access_token()
+                .map(RegistrationAccessToken::secret),
+            Some(&"rotated".to_string())
+        );
+
+        match configuration_request.update(
+            registration_response.client_id(),
+            None,
+            &client_metadata,
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(json_response(
+                    StatusCode::BAD_REQUEST,
+                    "{\"error\": \"invalid_redirect_uri\"}",
+                ))
+            },
+        ) {
+            Err(ClientRegistrationError::ServerResponse(err)) => assert_eq!(
+                *err.error(),
+                CoreRegisterErrorResponseType::InvalidRedirectUri
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        configuration_request
+            .delete(
+                |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    check_request(&request, Method::DELETE);
+                    Ok(HttpResponse {
+                        status_code: StatusCode::NO_CONTENT,
+                        headers: Default::default(),
+                        body: Vec::new(),
+                    })
+                },
+            )
+            .unwrap();
+        match configuration_request.delete(
+            |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(json_response(StatusCode::UNAUTHORIZED, ""))
+            },
+        ) {
+            Err(ClientRegistrationError::Response(StatusCode::UNAUTHORIZED, _, _)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_registration_response_verification() {
+        use http::header::{HeaderValue, CONTENT_TYPE};
+        use http::status::StatusCode;
+
+        use crate::core::CoreClientRegistrationRequest;
+        use crate::registration::ClientRegistrationError;
+        use crate::{HttpRequest, HttpResponse, RegistrationUrl};
+
+        let registration_url =
+            RegistrationUrl::new("https://server.example.com/register".to_string()).unwrap();
+        let request = CoreClientRegistrationRequest::new(
+            vec![RedirectUrl::new("https://client.example.org/callback".to_string()).unwrap()],
+            Default::default(),
+        )
+        .set_grant_types(Some(vec![CoreGrantType::AuthorizationCode]))
+        .set_token_endpoint_auth_method(Some(CoreClientAuthMethod::PrivateKeyJwt));
+
+        let http_client = |body: &'static str| {
+            move |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                Ok(HttpResponse {
+                    status_code: StatusCode::CREATED,
+                    headers: vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))]
+                        .into_iter()
+                        .collect(),
+                    body: body.as_bytes().to_vec(),
+                })
+            }
+        };
+
+        request
+            .register(
+                &registration_url,
+                http_client(
+                    "{
+                        \"client_id\": \"s6BhdRkqt3\",
+                        \"redirect_uris\": [\"https://client.example.org/callback\"],
+                        \"token_endpoint_auth_method\": \"private_key_jwt\"
+                    }",
+                ),
+            )
+            .unwrap();
+
+        // Servers may grant more than was requested (e.g., adding `refresh_token`).
+        request
+            .register(
+                &registration_url,
+                http_client(
+                    "{
+                        \"client_id\": \"s6BhdRkqt3\",
+                        \"redirect_uris\": [\"https://client.example.org/callback\"],
+                        \"grant_types\": [\"refresh_token\", \"authorization_code\"],
+                        \"response_types\": [\"code\", \"id_token\"],
+                        \"token_endpoint_auth_method\": \"private_key_jwt\"
+                    }",
+                ),
+            )
+            .unwrap();
+
+        let downgraded_response = "{
+            \"client_id\": \"s6BhdRkqt3\",
+            \"redirect_uris\": [\"https://client.example.org/callback\"],
+            \"grant_types\": [\"implicit\"]
+        }";
+        match request.register(&registration_url, http_client(downgraded_response)) {
+            Err(ClientRegistrationError::MetadataMismatch(mismatches, body)) => {
+                assert_eq!(body, downgraded_response.as_bytes());
+                assert_eq!(
+                    mismatches
+                        .iter()
+                        .map(|mismatch| mismatch.to_string())
+                        .collect::<Vec<_>>(),
+                    vec![
+                        "`grant_types` requested [\"authorization_code\"] but registered \
+                         [\"implicit\"]",
+                        "`token_endpoint_auth_method` requested \"private_key_jwt\" but \
+                         registered \"client_secret_basic\"",
+                    ]
+                );
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        request
+            .set_verify_response(false)
+            .register(&registration_url, http_client(downgraded_response))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_validate_sector_identifier_uri() {
+        use http::StatusCode;
+
+        use super::{validate_sector_identifier_uri, SectorIdentifierError};
+        use crate::{HttpRequest, HttpResponse};
+
+        let sector_identifier_uri =
+            SectorIdentifierUrl::new("https://example.com/sector.json".to_string()).unwrap();
+        let redirect_uris = vec![
+            RedirectUrl::new("https://example.com/cb1".to_string()).unwrap(),
+            RedirectUrl::new("https://example.com/cb2".to_string()).unwrap(),
+        ];
+        let http_client = |body: &'static str| {
+            move |request: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                assert_eq!(request.url.as_str(), "https://example.com/sector.json");
+                Ok(HttpResponse {
+                    status_code: StatusCode::OK,
+                    headers: Default::default(),
+                    body: body.as_bytes().to_vec(),
+                })
+            }
+        };
+
+        let listed = validate_sector_identifier_uri(
+            &sector_identifier_uri,
+            &redirect_uris,
+            http_client(r#"["https://example.com/cb1","https://example.com/cb2","https://other"]"#),
+        )
+        .unwrap();
+        assert_eq!(listed.len(), 3);
+
+        match validate_sector_identifier_uri(
+            &sector_identifier_uri,
+            &redirect_uris,
+            http_client(r#"["https://example.com/cb1"]"#),
+        ) {
+            Err(SectorIdentifierError::MissingRedirectUris(missing)) => {
+                assert_eq!(missing, vec![redirect_uris[1].clone()])
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match validate_sector_identifier_uri(
+            &sector_identifier_uri,
+            &redirect_uris,
+            http_client(r#"{"redirect_uris":[]}"#),
+        ) {
+            Err(SectorIdentifierError::Parse(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match validate_sector_identifier_uri(
+            &SectorIdentifierUrl::new("http://example.com/sector.json".to_string()).unwrap(),
+            &redirect_uris,
+            http_client("[]"),
+        ) {
+            Err(SectorIdentifierError::Other(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,330 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Arc;
+use std::time::Duration;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::Aes256Gcm;
+use chrono::{DateTime, Utc};
+use oauth2::{CsrfToken, PkceCodeVerifier};
+use rand::RngCore;
+use thiserror::Error;
+
+use crate::{Nonce, PendingLogin};
+
+// Associated data bound into each sealed value so that values sealed for other purposes using
+// the same key are rejected.
+const SEALED_LOGIN_AAD: &[u8] = b"openidconnect pending login v1";
+
+// Length of the AES-GCM nonce prepended to each sealed value.
+const IV_LEN: usize = 12;
+
+///
+/// Default maximum length of a sealed pending login.
+///
+/// Browsers limit each cookie (including its name and attributes) to 4096 bytes, so this leaves
+/// room for a cookie name and the usual `Path`, `Secure`, `HttpOnly`, `SameSite`, and `Max-Age`
+/// attributes.
+///
+pub const DEFAULT_MAX_SEALED_LOGIN_LEN: usize = 3800;
+
+///
+/// Error sealing or unsealing a [`PendingLogin`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PendingLoginSealError {
+    ///
+    /// The sealed value is not validly encoded.
+    ///
+    #[error("Malformed sealed login")]
+    Malformed,
+    ///
+    /// The sealed value could not be decrypted (e.g., it was tampered with or sealed using
+    /// another key).
+    ///
+    #[error("Failed to decrypt sealed login")]
+    Decryption,
+    ///
+    /// The pending login has expired.
+    ///
+    #[error("Pending login expired at {0}")]
+    Expired(DateTime<Utc>),
+    ///
+    /// The sealed value exceeds the maximum length.
+    ///
+    #[error("Sealed login is {0} bytes long, which exceeds the maximum length")]
+    TooLarge(usize),
+}
+
+// Compact representation of a pending login, using short field names to minimize the size of the
+// sealed value.
+#[derive(Deserialize, Serialize)]
+struct SealedPendingLogin {
+    #[serde(rename = "s")]
+    csrf_state: CsrfToken,
+    #[serde(rename = "n")]
+    nonce: Nonce,
+    #[serde(rename = "v")]
+    pkce_verifier: PkceCodeVerifier,
+    #[serde(rename = "r", default, skip_serializing_if = "Option::is_none")]
+    redirect_target: Option<String>,
+    #[serde(rename = "e")]
+    expiration: i64,
+}
+
+///
+/// Seals a [`PendingLogin`] into a compact, encrypted value that is safe to store in a browser
+/// cookie, and unseals it when the user returns to the redirect URI.
+///
+/// Sealed values are encrypted and authenticated using AES-256-GCM with a random 96-bit nonce,
+/// so they reveal nothing about the CSRF state, nonce, PKCE code verifier, or redirect target,
+/// and they can't be tampered with. Each sealed value also carries an expiration time (10
+/// minutes by default) and is limited to [`DEFAULT_MAX_SEALED_LOGIN_LEN`] bytes by default.
+///
+/// The cookie should still be marked `Secure`, `HttpOnly`, and `SameSite=Lax`, and should be
+/// deleted once the login is completed.
+///
+#[derive(Clone)]
+pub struct PendingLoginSealer {
+    cipher: Aes256Gcm,
+    max_age: Duration,
+    max_len: usize,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+}
+impl PendingLoginSealer {
+    ///
+    /// Initializes a sealer using the given 256-bit key.
+    ///
+    /// The key should be generated using a cryptographically secure random number generator and
+    /// shared by all instances of the application.
+    ///
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(&key.into()),
+            max_age: Duration::from_secs(600),
+            max_len: DEFAULT_MAX_SEALED_LOGIN_LEN,
+            // By default, use the current system time.
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
+    ///
+    /// Specifies how long a sealed pending login remains valid.
+    ///
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    ///
+    /// Specifies the maximum length of a sealed pending login, in bytes.
+    ///
+    pub fn set_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for setting and verifying expiration times.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Seals the given pending login into a URL-safe value suitable for a cookie.
+    ///
+    pub fn seal(&self, pending_login: &PendingLogin) -> Result<String, PendingLoginSealError> {
+        let expiration = chrono::Duration::from_std(self.max_age)
+            .ok()
+            .and_then(|max_age| (*self.time_fn)().checked_add_signed(max_age))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let plaintext = serde_json::to_vec(&SealedPendingLogin {
+            csrf_state: pending_login.csrf_state.clone(),
+            nonce: pending_login.nonce.clone(),
+            pkce_verifier: PkceCodeVerifier::new(pending_login.pkce_verifier.secret().clone()),
+            redirect_target: pending_login.redirect_target.clone(),
+            expiration: expiration.timestamp(),
+        })
+        .expect("failed to serialize pending login");
+
+        let mut iv = [0; IV_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut iv);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &iv.into(),
+                Payload {
+                    msg: &plaintext,
+                    aad: SEALED_LOGIN_AAD,
+                },
+            )
+            .expect("AES-GCM encryption failed");
+
+        let sealed =
+            base64::encode_config([&iv[..], &ciphertext].concat(), base64::URL_SAFE_NO_PAD);
+        if sealed.len() > self.max_len {
+            return Err(PendingLoginSealError::TooLarge(sealed.len()));
+        }
+        Ok(sealed)
+    }
+
+    ///
+    /// Unseals a pending login previously sealed using [`seal`](Self::seal), verifying that it
+    /// was sealed using the same key and has not expired.
+    ///
+    pub fn unseal(&self, sealed: &str) -> Result<PendingLogin, PendingLoginSealError> {
+        // Check the length before decoding to bound the work done for oversized cookies.
+        if sealed.len() > self.max_len {
+            return Err(PendingLoginSealError::TooLarge(sealed.len()));
+        }
+        let sealed = base64::decode_config(sealed, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| PendingLoginSealError::Malformed)?;
+        if sealed.len() < IV_LEN {
+            return Err(PendingLoginSealError::Malformed);
+        }
+        let (iv, ciphertext) = sealed.split_at(IV_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(
+                iv.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad: SEALED_LOGIN_AAD,
+                },
+            )
+            .map_err(|_| PendingLoginSealError::Decryption)?;
+        let sealed_login = serde_json::from_slice::<SealedPendingLogin>(&plaintext)
+            .map_err(|_| PendingLoginSealError::Malformed)?;
+
+        let expiration = DateTime::<Utc>::from_timestamp(sealed_login.expiration, 0)
+            .ok_or(PendingLoginSealError::Malformed)?;
+        if (*self.time_fn)() >= expiration {
+            return Err(PendingLoginSealError::Expired(expiration));
+        }
+        Ok(PendingLogin {
+            csrf_state: sealed_login.csrf_state,
+            nonce: sealed_login.nonce,
+            pkce_verifier: sealed_login.pkce_verifier,
+            redirect_target: sealed_login.redirect_target,
+        })
+    }
+}
+impl Debug for PendingLoginSealer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.debug_struct("PendingLoginSealer")
+            .field("cipher", &"[redacted]")
+            .field("max_age", &self.max_age)
+            .field("max_len", &self.max_len)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{PendingLoginSealError, PendingLoginSealer};
+    use crate::core::CoreClient;
+    use crate::{AuthUrl, ClientId, IssuerUrl, JsonWebKeySet, OidcLoginHandler, RedirectUrl};
+
+    #[test]
+    fn test_pending_login_sealer() {
+        let now = || Utc.timestamp_opt(1700000000, 0).single().unwrap();
+        let later = || Utc.timestamp_opt(1700000600, 0).single().unwrap();
+        let handler = OidcLoginHandler::new(
+            CoreClient::new(
+                ClientId::new("aaa".to_string()),
+                None,
+                IssuerUrl::new("https://example".to_string()).unwrap(),
+                AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+                None,
+                None,
+                JsonWebKeySet::default(),
+            )
+            .set_redirect_uri(
+                RedirectUrl::new("https://app.example/callback".to_string()).unwrap(),
+            ),
+        );
+        let (_, pending_login) = handler.begin_login();
+        let pending_login = pending_login.set_redirect_target("/dashboard?tab=1".to_string());
+        let sealer = PendingLoginSealer::new([7; 32]).set_time_fn(now);
+
+        let sealed = sealer.seal(&pending_login).unwrap();
+        assert!(sealed.len() < 512);
+        assert!(!sealed.contains(pending_login.csrf_state().secret()));
+        assert_ne!(sealed, sealer.seal(&pending_login).unwrap());
+
+        let unsealed = sealer.unseal(&sealed).unwrap();
+        assert_eq!(
+            unsealed.csrf_state().secret(),
+            pending_login.csrf_state().secret()
+        );
+        assert_eq!(unsealed.nonce(), pending_login.nonce());
+        assert_eq!(
+            unsealed.pkce_verifier.secret(),
+            pending_login.pkce_verifier.secret()
+        );
+        assert_eq!(unsealed.redirect_target(), Some("/dashboard?tab=1"));
+
+        // Values sealed using other keys or tampered with are rejected.
+        assert_eq!(
+            PendingLoginSealer::new([8; 32])
+                .set_time_fn(now)
+                .unseal(&sealed)
+                .unwrap_err(),
+            PendingLoginSealError::Decryption
+        );
+        let mut tampered = base64::decode_config(&sealed, base64::URL_SAFE_NO_PAD).unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            sealer
+                .unseal(&base64::encode_config(tampered, base64::URL_SAFE_NO_PAD))
+                .unwrap_err(),
+            PendingLoginSealError::Decryption
+        );
+        assert_eq!(
+            sealer.unseal("not a sealed login").unwrap_err(),
+            PendingLoginSealError::Malformed
+        );
+
+        assert_eq!(
+            sealer
+                .clone()
+                .set_time_fn(later)
+                .unseal(&sealed)
+                .unwrap_err(),
+            PendingLoginSealError::Expired(later())
+        );
+        assert!(sealer
+            .clone()
+            .set_time_fn(later)
+            .set_max_age(Duration::from_secs(3600))
+            .unseal(
+                &sealer
+                    .clone()
+                    .set_max_age(Duration::from_secs(3600))
+                    .seal(&pending_login)
+                    .unwrap()
+            )
+            .is_ok());
+
+        let small_sealer = sealer.set_max_len(64);
+        assert_eq!(
+            small_sealer.seal(&pending_login).unwrap_err(),
+            PendingLoginSealError::TooLarge(sealed.len())
+        );
+        assert_eq!(
+            small_sealer.unseal(&sealed).unwrap_err(),
+            PendingLoginSealError::TooLarge(sealed.len())
+        );
+    }
+}
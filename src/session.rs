@@ -0,0 +1,218 @@
+use std::fmt::{Display, Error as FormatterError, Formatter};
+use std::ops::Deref;
+use std::str::FromStr;
+
+use oauth2::ClientId;
+use rand::{thread_rng, Rng};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+new_type![
+    ///
+    /// Session state returned by an OpenID Provider that implements
+    /// [Session Management](https://openid.net/specs/openid-connect-session-1_0.html).
+    ///
+    /// The session state has the form `<hash>.<salt>`, where `<hash>` is the hex-encoded SHA-256
+    /// hash of the client ID, the origin of the Relying Party, the OP browser state, and the salt,
+    /// each separated by a space (see
+    /// [Section 3.3](https://openid.net/specs/openid-connect-session-1_0.html#OPiframe)).
+    ///
+    #[derive(Deserialize, Eq, Hash, Serialize)]
+    SessionState(String)
+    impl {
+        ///
+        /// Computes the session state for the given client ID, Relying Party origin (e.g.,
+        /// `https://rp.example.com`), OP browser state, and salt.
+        ///
+        pub fn compute(
+            client_id: &ClientId,
+            origin: &str,
+            op_browser_state: &str,
+            salt: &str,
+        ) -> Self {
+            let hash = Sha256::digest(
+                format!("{} {} {} {}", **client_id, origin, op_browser_state, salt).as_bytes(),
+            );
+            let hash_hex = hash
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            if salt.is_empty() {
+                SessionState::new(hash_hex)
+            } else {
+                SessionState::new(format!("{}.{}", hash_hex, salt))
+            }
+        }
+
+        ///
+        /// Computes the session state for the given client ID, Relying Party origin, and OP
+        /// browser state using a new random salt.
+        ///
+        /// This is intended for OpenID Providers returning the `session_state` parameter in
+        /// authentication responses.
+        ///
+        pub fn new_salted(client_id: &ClientId, origin: &str, op_browser_state: &str) -> Self {
+            let salt = (0..16)
+                .map(|_| format!("{:02x}", thread_rng().gen::<u8>()))
+                .collect::<String>();
+            Self::compute(client_id, origin, op_browser_state, &salt)
+        }
+
+        ///
+        /// Returns the salt included in the session state, if any.
+        ///
+        pub fn salt(&self) -> Option<&str> {
+            self.0.split_once('.').map(|(_, salt)| salt)
+        }
+
+        ///
+        /// Returns `true` if this session state was computed from the given client ID, Relying
+        /// Party origin, and OP browser state.
+        ///
+        /// OpenID Providers use this check to determine whether the End-User's login status has
+        /// changed since the session state was issued.
+        ///
+        pub fn matches(&self, client_id: &ClientId, origin: &str, op_browser_state: &str) -> bool {
+            let expected =
+                Self::compute(client_id, origin, op_browser_state, self.salt().unwrap_or(""));
+            expected.0.as_bytes().ct_eq(self.0.as_bytes()).into()
+        }
+
+        ///
+        /// Returns the message that the Relying Party posts to the OP iframe to check whether
+        /// the End-User's login status has changed (i.e., the client ID and the session state,
+        /// separated by a space).
+        ///
+        /// The OP iframe responds with a message that can be parsed as a [`SessionStatus`].
+        ///
+        pub fn check_session_message(&self, client_id: &ClientId) -> String {
+            format!("{} {}", **client_id, self.0)
+        }
+    }
+];
+
+///
+/// Login status reported by the OP iframe of an OpenID Provider that implements
+/// [Session Management](https://openid.net/specs/openid-connect-session-1_0.html).
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SessionStatus {
+    ///
+    /// The End-User's login status has not changed (`unchanged`).
+    ///
+    Unchanged,
+    ///
+    /// The End-User's login status has changed (`changed`). The Relying Party should perform a
+    /// new authentication request with `prompt=none` to obtain the current session state.
+    ///
+    Changed,
+    ///
+    /// The message posted to the OP iframe was malformed (`error`).
+    ///
+    Error,
+}
+impl SessionStatus {
+    ///
+    /// Evaluates a message posted to the OP iframe (see
+    /// [`SessionState::check_session_message`]) against the current OP browser state.
+    ///
+    /// This implements the OP iframe's check in server-side code, which is useful for
+    /// OpenID Providers that serve the iframe's responses from a backend.
+    ///
+    pub fn evaluate(message: &str, origin: &str, op_browser_state: &str) -> Self {
+        match message.split_once(' ') {
+            Some((client_id, session_state))
+                if !client_id.is_empty() && !session_state.is_empty() =>
+            {
+                let session_state = SessionState::new(session_state.to_string());
+                if session_state.matches(
+                    &ClientId::new(client_id.to_string()),
+                    origin,
+                    op_browser_state,
+                ) {
+                    SessionStatus::Unchanged
+                } else {
+                    SessionStatus::Changed
+                }
+            }
+            _ => SessionStatus::Error,
+        }
+    }
+}
+impl AsRef<str> for SessionStatus {
+    fn as_ref(&self) -> &str {
+        match *self {
+            SessionStatus::Unchanged => "unchanged",
+            SessionStatus::Changed => "changed",
+            SessionStatus::Error => "error",
+        }
+    }
+}
+impl Display for SessionStatus {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FormatterError> {
+        write!(f, "{}", self.as_ref())
+    }
+}
+impl FromStr for SessionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unchanged" => Ok(SessionStatus::Unchanged),
+            "changed" => Ok(SessionStatus::Changed),
+            "error" => Ok(SessionStatus::Error),
+            other => Err(format!("unrecognized session status `{}`", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::ClientId;
+
+    use super::{SessionState, SessionStatus};
+
+    #[test]
+    fn test_session_state() {
+        let client_id = ClientId::new("s6BhdRkqt3".to_string());
+        let origin = "https://client.example.org";
+
+        let session_state = SessionState::compute(&client_id, origin, "browser_state", "the_salt");
+        assert_eq!(
+            session_state.as_str(),
+            "28b186e54918a90928f72e72fea3a9735a3ce42460246e149c0340604141447c.the_salt"
+        );
+        assert_eq!(session_state.salt(), Some("the_salt"));
+        assert!(session_state.matches(&client_id, origin, "browser_state"));
+        assert!(!session_state.matches(&client_id, origin, "other_state"));
+        assert!(!session_state.matches(&client_id, "https://evil.example.org", "browser_state"));
+
+        let salted = SessionState::new_salted(&client_id, origin, "browser_state");
+        assert_ne!(
+            salted,
+            SessionState::new_salted(&client_id, origin, "browser_state")
+        );
+        assert!(salted.matches(&client_id, origin, "browser_state"));
+
+        let message = session_state.check_session_message(&client_id);
+        assert_eq!(
+            SessionStatus::evaluate(&message, origin, "browser_state"),
+            SessionStatus::Unchanged
+        );
+        assert_eq!(
+            SessionStatus::evaluate(&message, origin, "new_browser_state"),
+            SessionStatus::Changed
+        );
+        assert_eq!(
+            SessionStatus::evaluate("garbage", origin, "browser_state"),
+            SessionStatus::Error
+        );
+
+        assert_eq!(
+            "changed".parse::<SessionStatus>(),
+            Ok(SessionStatus::Changed)
+        );
+        assert_eq!(SessionStatus::Unchanged.to_string(), "unchanged");
+        assert!("other".parse::<SessionStatus>().is_err());
+    }
+}
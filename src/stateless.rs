@@ -0,0 +1,417 @@
+use std::fmt::{Debug, Formatter, Result as FormatterResult};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use oauth2::CsrfToken;
+use rand::{thread_rng, Rng};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::{Nonce, NonceVerifier};
+
+// Number of random bytes included in each token, prior to base64-encoding.
+const RANDOM_LEN: usize = 16;
+
+// Purposes bound into each token's MAC so that a nonce can't be used as a state value or vice
+// versa.
+const NONCE_PURPOSE: &str = "nonce";
+const STATE_PURPOSE: &str = "state";
+const STATE_TOKEN_PURPOSE: &str = "state_token";
+
+///
+/// Error verifying a value minted by a [`StatelessSigner`].
+///
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StatelessTokenError {
+    ///
+    /// The value was not minted by a [`StatelessSigner`].
+    ///
+    #[error("Malformed token")]
+    Malformed,
+    ///
+    /// The value's signature is invalid (e.g., it was tampered with or signed using another key).
+    ///
+    #[error("Invalid token signature")]
+    InvalidSignature,
+    ///
+    /// The value has expired.
+    ///
+    #[error("Token expired at {0}")]
+    Expired(DateTime<Utc>),
+    ///
+    /// Failed to serialize or deserialize the application payload of a [`StateToken`].
+    ///
+    #[error("Invalid token payload: {0}")]
+    Payload(String),
+}
+
+///
+/// Application payload extracted from a `state` value minted by
+/// [`StatelessSigner::new_state_token`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateToken<T> {
+    expiration: DateTime<Utc>,
+    payload: T,
+}
+impl<T> StateToken<T> {
+    ///
+    /// Returns the application payload.
+    ///
+    pub fn payload(&self) -> &T {
+        &self.payload
+    }
+
+    ///
+    /// Returns the application payload, consuming this token.
+    ///
+    pub fn into_payload(self) -> T {
+        self.payload
+    }
+
+    ///
+    /// Returns the time at which the `state` value expires.
+    ///
+    pub fn expiration(&self) -> DateTime<Utc> {
+        self.expiration
+    }
+}
+
+///
+/// Mints and verifies nonce and `state` values that are signed using HMAC-SHA256 and carry an
+/// expiration time.
+///
+/// Because these values can be verified using only the signing key, login flows that use them
+/// don't require server-side storage of the nonce and `state` between the authorization request
+/// and the redirect back to the application. This allows any instance of a load-balanced
+/// application that shares the same key to complete the flow.
+///
+/// Each value has the form `<random>.<expiration>.<signature>`, where `<random>` is a random
+/// 128-bit value, `<expiration>` is a Unix timestamp, and `<signature>` is the HMAC-SHA256 of
+/// the preceding fields along with the value's purpose (nonce or `state`). The key should consist
+/// of at least 32 random bytes.
+///
+/// A `state` value may also carry an application payload, such as the URL to return to after
+/// login or a tenant ID (see [`new_state_token`](Self::new_state_token)).
+///
+/// # Security Warning
+///
+/// Stateless values are not bound to a particular user agent, and they can be replayed until
+/// they expire. Applications should use short expiration times and, where possible, also bind
+/// the `state` value to the user agent (e.g., by storing a hash of it in a cookie) to protect
+/// against login CSRF.
+///
+#[derive(Clone)]
+pub struct StatelessSigner {
+    key: Vec<u8>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+}
+impl StatelessSigner {
+    ///
+    /// Initializes a signer using the given HMAC key.
+    ///
+    pub fn new(key: Vec<u8>) -> Self {
+        Self {
+            key,
+            // By default, use the current system time.
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for setting and verifying expiration times.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Mints a new nonce that expires after `ttl`.
+    ///
+    pub fn new_nonce(&self, ttl: Duration) -> Nonce {
+        Nonce::new(self.mint(NONCE_PURPOSE, ttl, None))
+    }
+
+    ///
+    /// Mints a new `state` value that expires after `ttl`.
+    ///
+    pub fn new_csrf_token(&self, ttl: Duration) -> CsrfToken {
+        CsrfToken::new(self.mint(STATE_PURPOSE, ttl, None))
+    }
+
+    ///
+    /// Mints a new `state` value that carries the given application payload and expires after
+    /// `ttl`.
+    ///
+    /// The payload is serialized as JSON and signed along with the rest of the value, so it
+    /// can't be tampered with. However, it is **not** encrypted, so it must not contain secrets.
+    /// The payload should also be small, since the `state` value is included in the
+    /// authorization URL.
+    ///
+    pub fn new_state_token<T>(
+        &self,
+        payload: &T,
+        ttl: Duration,
+    ) -> Result<CsrfToken, StatelessTokenError>
+    where
+        T: Serialize,
+    {
+        let payload = serde_json::to_vec(payload)
+            .map_err(|err| StatelessTokenError::Payload(err.to_string()))?;
+        Ok(CsrfToken::new(self.mint(
+            STATE_TOKEN_PURPOSE,
+            ttl,
+            Some(&base64::encode_config(payload, base64::URL_SAFE_NO_PAD)),
+        )))
+    }
+
+    ///
+    /// Verifies that the given nonce was minted by this signer and has not expired.
+    ///
+    pub fn verify_nonce(&self, nonce: &Nonce) -> Result<(), StatelessTokenError> {
+        self.verify_value(NONCE_PURPOSE, nonce.secret()).map(|_| ())
+    }
+
+    ///
+    /// Verifies that the given `state` value was minted by this signer and has not expired.
+    ///
+    pub fn verify_csrf_token(&self, state: &CsrfToken) -> Result<(), StatelessTokenError> {
+        self.verify_value(STATE_PURPOSE, state.secret()).map(|_| ())
+    }
+
+    ///
+    /// Verifies that the given `state` value was minted by
+    /// [`new_state_token`](Self::new_state_token) using this signer and has not expired, and
+    /// returns its application payload.
+    ///
+    pub fn verify_state_token<T>(
+        &self,
+        state: &CsrfToken,
+    ) -> Result<StateToken<T>, StatelessTokenError>
+    where
+        T: DeserializeOwned,
+    {
+        let (expiration, payload) = self.verify_value(STATE_TOKEN_PURPOSE, state.secret())?;
+        let payload = base64::decode_config(
+            payload.ok_or(StatelessTokenError::Malformed)?,
+            base64::URL_SAFE_NO_PAD,
+        )
+        .map_err(|_| StatelessTokenError::Malformed)?;
+        Ok(StateToken {
+            expiration,
+            payload: serde_json::from_slice(&payload)
+                .map_err(|err| StatelessTokenError::Payload(err.to_string()))?,
+        })
+    }
+
+    fn mint(&self, purpose: &str, ttl: Duration, data: Option<&str>) -> String {
+        let random_bytes: Vec<u8> = (0..RANDOM_LEN).map(|_| thread_rng().gen::<u8>()).collect();
+        let now = (*self.time_fn)();
+        let expiration = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let mut payload = format!(
+            "{}.{}",
+            base64::encode_config(random_bytes, base64::URL_SAFE_NO_PAD),
+            expiration.timestamp()
+        );
+        if let Some(data) = data {
+            payload.push('.');
+            payload.push_str(data);
+        }
+        let signature = self.mac(purpose, &payload).finalize().into_bytes();
+        format!(
+            "{}.{}",
+            payload,
+            base64::encode_config(signature, base64::URL_SAFE_NO_PAD)
+        )
+    }
+
+    // Returns the expiration time and the additional data (if any) of a valid value.
+    fn verify_value<'v>(
+        &self,
+        purpose: &str,
+        value: &'v str,
+    ) -> Result<(DateTime<Utc>, Option<&'v str>), StatelessTokenError> {
+        let (payload, signature) = value
+            .rsplit_once('.')
+            .ok_or(StatelessTokenError::Malformed)?;
+        let mut fields = payload.splitn(3, '.').skip(1);
+        let expiration = fields.next().ok_or(StatelessTokenError::Malformed)?;
+        let data = fields.next();
+        let signature = base64::decode_config(signature, base64::URL_SAFE_NO_PAD)
+            .map_err(|_| StatelessTokenError::Malformed)?;
+
+        // Verify the signature before parsing the expiration time so that malformed values
+        // signed using other keys are reported as invalid signatures.
+        self.mac(purpose, payload)
+            .verify_slice(&signature)
+            .map_err(|_| StatelessTokenError::InvalidSignature)?;
+
+        let expiration = expiration
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| DateTime::<Utc>::from_timestamp(secs, 0))
+            .ok_or(StatelessTokenError::Malformed)?;
+        if (*self.time_fn)() >= expiration {
+            return Err(StatelessTokenError::Expired(expiration));
+        }
+        Ok((expiration, data))
+    }
+
+    fn mac(&self, purpose: &str, payload: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(purpose.as_bytes());
+        mac.update(b".");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+impl Debug for StatelessSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatterResult {
+        f.debug_struct("StatelessSigner")
+            .field("key", &"[redacted]")
+            .finish_non_exhaustive()
+    }
+}
+impl NonceVerifier for &StatelessSigner {
+    fn verify(self, nonce: Option<&Nonce>) -> Result<(), String> {
+        let nonce = nonce.ok_or_else(|| "missing nonce claim".to_string())?;
+        self.verify_nonce(nonce).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use oauth2::CsrfToken;
+
+    use super::{StatelessSigner, StatelessTokenError};
+    use crate::{Nonce, NonceVerifier};
+
+    #[test]
+    fn test_stateless_signer() {
+        let now = || Utc.timestamp_opt(1700000000, 0).single().unwrap();
+        let later = || Utc.timestamp_opt(1700000300, 0).single().unwrap();
+        let signer =
+            StatelessSigner::new(b"0123456789abcdef0123456789abcdef".to_vec()).set_time_fn(now);
+
+        let nonce = signer.new_nonce(Duration::from_secs(300));
+        let state = signer.new_csrf_token(Duration::from_secs(300));
+        assert_ne!(nonce.secret(), state.secret());
+        assert_eq!(signer.verify_nonce(&nonce), Ok(()));
+        assert_eq!(signer.verify_csrf_token(&state), Ok(()));
+        assert_eq!((&signer).verify(Some(&nonce)), Ok(()));
+        assert!((&signer).verify(None).is_err());
+
+        // Nonces and state values aren't interchangeable.
+        assert_eq!(
+            signer.verify_nonce(&Nonce::new(state.secret().clone())),
+            Err(StatelessTokenError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify_csrf_token(&CsrfToken::new(nonce.secret().clone())),
+            Err(StatelessTokenError::InvalidSignature)
+        );
+
+        // Values signed using other keys or tampered with are rejected.
+        let other_signer = StatelessSigner::new(b"another key".to_vec()).set_time_fn(now);
+        assert_eq!(
+            other_signer.verify_nonce(&nonce),
+            Err(StatelessTokenError::InvalidSignature)
+        );
+        let (random, rest) = nonce.secret().split_once('.').unwrap();
+        let (_, signature) = rest.split_once('.').unwrap();
+        assert_eq!(
+            signer.verify_nonce(&Nonce::new(format!("{}.1800000000.{}", random, signature))),
+            Err(StatelessTokenError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify_nonce(&Nonce::new("not a token".to_string())),
+            Err(StatelessTokenError::Malformed)
+        );
+
+        let signer = signer.set_time_fn(later);
+        assert_eq!(
+            signer.verify_nonce(&nonce),
+            Err(StatelessTokenError::Expired(later()))
+        );
+    }
+
+    #[test]
+    fn test_state_token() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Payload {
+            return_url: String,
+            tenant_id: String,
+        }
+
+        let now = || Utc.timestamp_opt(1700000000, 0).single().unwrap();
+        let later = || Utc.timestamp_opt(1700000300, 0).single().unwrap();
+        let signer =
+            StatelessSigner::new(b"0123456789abcdef0123456789abcdef".to_vec()).set_time_fn(now);
+        let payload = Payload {
+            return_url: "https://app.example.com/dashboard?tab=1".to_string(),
+            tenant_id: "contoso".to_string(),
+        };
+
+        let state = signer
+            .new_state_token(&payload, Duration::from_secs(300))
+            .unwrap();
+        let token = signer.verify_state_token::<Payload>(&state).unwrap();
+        assert_eq!(*token.payload(), payload);
+        assert_eq!(token.expiration(), later());
+        assert_eq!(token.into_payload(), payload);
+
+        // State tokens aren't interchangeable with plain state values.
+        assert_eq!(
+            signer.verify_csrf_token(&state),
+            Err(StatelessTokenError::InvalidSignature)
+        );
+        assert_eq!(
+            signer.verify_state_token::<Payload>(&signer.new_csrf_token(Duration::from_secs(300))),
+            Err(StatelessTokenError::InvalidSignature)
+        );
+
+        // The payload can't be tampered with.
+        let (payload_prefix, signature) = state.secret().rsplit_once('.').unwrap();
+        let (prefix, _) = payload_prefix.rsplit_once('.').unwrap();
+        let forged = base64::encode_config(
+            r#"{"return_url":"https://attacker.example.com","tenant_id":"contoso"}"#,
+            base64::URL_SAFE_NO_PAD,
+        );
+        assert_eq!(
+            signer.verify_state_token::<Payload>(&CsrfToken::new(format!(
+                "{}.{}.{}",
+                prefix, forged, signature
+            ))),
+            Err(StatelessTokenError::InvalidSignature)
+        );
+
+        match signer.verify_state_token::<u32>(&state) {
+            Err(StatelessTokenError::Payload(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            signer
+                .set_time_fn(later)
+                .verify_state_token::<Payload>(&state),
+            Err(StatelessTokenError::Expired(later()))
+        );
+    }
+}
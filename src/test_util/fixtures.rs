@@ -0,0 +1,490 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::future::{Future, Ready};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use http::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, COOKIE};
+use http::method::Method;
+use http::status::StatusCode;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+use crate::{HttpRequest, HttpResponse};
+
+const REDACTED: &str = "REDACTED";
+
+///
+/// HTTP request and response pair captured by an [`HttpRecorder`].
+///
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct HttpInteraction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+impl HttpInteraction {
+    ///
+    /// Returns the request method.
+    ///
+    pub fn method(&self) -> &str {
+        &self.request.method
+    }
+
+    ///
+    /// Returns the request URL.
+    ///
+    pub fn url(&self) -> &Url {
+        &self.request.url
+    }
+
+    ///
+    /// Returns the recorded response.
+    ///
+    pub fn response(&self) -> Result<HttpResponse, HttpFixtureError> {
+        let status_code = StatusCode::from_u16(self.response.status)
+            .map_err(|err| HttpFixtureError::Invalid(err.to_string()))?;
+        Ok(HttpResponse {
+            status_code,
+            headers: headers_from_fixture(&self.response.headers)?,
+            body: self.response.body.to_bytes()?,
+        })
+    }
+
+    fn matches(&self, request: &HttpRequest) -> bool {
+        self.request.method == request.method.as_str() && self.request.url == request.url
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+struct RecordedRequest {
+    method: String,
+    url: Url,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    body: RecordedBody,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    body: RecordedBody,
+}
+
+// Bodies are stored as text when they're valid UTF-8 so that fixtures remain human-readable and
+// easy to edit, and as base64 otherwise.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+struct RecordedBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
+}
+impl RecordedBody {
+    fn new(body: &[u8]) -> Self {
+        if body.is_empty() {
+            Self::default()
+        } else if let Ok(text) = std::str::from_utf8(body) {
+            Self {
+                body: Some(text.to_string()),
+                body_base64: None,
+            }
+        } else {
+            Self {
+                body: None,
+                body_base64: Some(base64::encode(body)),
+            }
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, HttpFixtureError> {
+        match (&self.body, &self.body_base64) {
+            (Some(body), None) => Ok(body.as_bytes().to_vec()),
+            (None, Some(body_base64)) => base64::decode(body_base64)
+                .map_err(|err| HttpFixtureError::Invalid(format!("invalid body_base64: {}", err))),
+            (None, None) => Ok(Vec::new()),
+            (Some(_), Some(_)) => Err(HttpFixtureError::Invalid(
+                "`body` and `body_base64` are mutually exclusive".to_string(),
+            )),
+        }
+    }
+}
+
+fn headers_to_fixture(
+    headers: &HeaderMap,
+    redacted: &HashSet<HeaderName>,
+) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if redacted.contains(name) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn headers_from_fixture(headers: &[(String, String)]) -> Result<HeaderMap, HttpFixtureError> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            Ok((
+                HeaderName::from_str(name)
+                    .map_err(|err| HttpFixtureError::Invalid(err.to_string()))?,
+                HeaderValue::from_str(value)
+                    .map_err(|err| HttpFixtureError::Invalid(err.to_string()))?,
+            ))
+        })
+        .collect()
+}
+
+///
+/// Wrapper HTTP client that records each request and response for later replay by an
+/// [`HttpReplayer`].
+///
+/// This is useful for capturing discovery, token, and UserInfo interactions with a real provider
+/// once, and replaying them in offline tests. The values of the `Authorization` and `Cookie`
+/// request headers are redacted by default (see [`HttpRecorder::set_redacted_headers`]), but
+/// request and response bodies are recorded verbatim and may contain secrets such as client
+/// secrets, authorization codes, and tokens. Review fixtures before committing them.
+///
+/// Replayed ID tokens will eventually expire; tests should verify them using a fixed time (see
+/// [`IdTokenVerifier::set_time_fn`](crate::IdTokenVerifier::set_time_fn)).
+///
+pub struct HttpRecorder<HC> {
+    inner: HC,
+    redacted_headers: HashSet<HeaderName>,
+    interactions: Mutex<Vec<HttpInteraction>>,
+}
+impl<HC> HttpRecorder<HC> {
+    ///
+    /// Creates a recorder that forwards requests to the specified HTTP client.
+    ///
+    /// The client may be synchronous (see [`HttpRecorder::request`]) or asynchronous (see
+    /// [`HttpRecorder::request_async`]).
+    ///
+    pub fn new(inner: HC) -> Self {
+        Self {
+            inner,
+            redacted_headers: vec![AUTHORIZATION, COOKIE].into_iter().collect(),
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    ///
+    /// Sets the request headers whose values are redacted in the recording.
+    ///
+    pub fn set_redacted_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.redacted_headers = headers.into_iter().collect();
+        self
+    }
+
+    ///
+    /// Returns the interactions recorded so far.
+    ///
+    pub fn interactions(&self) -> Vec<HttpInteraction> {
+        self.lock().clone()
+    }
+
+    ///
+    /// Writes the interactions recorded so far to the specified path as JSON.
+    ///
+    pub fn save<P>(&self, path: P) -> Result<(), HttpFixtureError>
+    where
+        P: AsRef<Path>,
+    {
+        let writer = BufWriter::new(File::create(path).map_err(HttpFixtureError::Io)?);
+        serde_json::to_writer_pretty(writer, &*self.lock()).map_err(HttpFixtureError::Json)
+    }
+
+    ///
+    /// Submits a request using the wrapped synchronous HTTP client, recording the response.
+    ///
+    /// Failed requests (i.e., those for which the HTTP client returns an error) are not recorded.
+    ///
+    pub fn request<RE>(&self, request: HttpRequest) -> Result<HttpResponse, RE>
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let recorded_request = self.record_request(&request);
+        let response = (self.inner)(request)?;
+        self.record_response(recorded_request, &response);
+        Ok(response)
+    }
+
+    ///
+    /// Submits a request using the wrapped asynchronous HTTP client, recording the response.
+    ///
+    /// Failed requests (i.e., those for which the HTTP client returns an error) are not recorded.
+    ///
+    pub async fn request_async<F, RE>(&self, request: HttpRequest) -> Result<HttpResponse, RE>
+    where
+        HC: Fn(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let recorded_request = self.record_request(&request);
+        let response = (self.inner)(request).await?;
+        self.record_response(recorded_request, &response);
+        Ok(response)
+    }
+
+    ///
+    /// Returns a synchronous HTTP client that records each request made through it.
+    ///
+    pub fn http_client<RE>(&self) -> impl Fn(HttpRequest) -> Result<HttpResponse, RE> + '_
+    where
+        HC: Fn(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        move |request| self.request(request)
+    }
+
+    fn record_request(&self, request: &HttpRequest) -> RecordedRequest {
+        RecordedRequest {
+            method: request.method.to_string(),
+            url: request.url.clone(),
+            headers: headers_to_fixture(&request.headers, &self.redacted_headers),
+            body: RecordedBody::new(&request.body),
+        }
+    }
+
+    fn record_response(&self, request: RecordedRequest, response: &HttpResponse) {
+        self.lock().push(HttpInteraction {
+            request,
+            response: RecordedResponse {
+                status: response.status_code.as_u16(),
+                headers: headers_to_fixture(&response.headers, &HashSet::new()),
+                body: RecordedBody::new(&response.body),
+            },
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<HttpInteraction>> {
+        self.interactions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+impl<HC> std::fmt::Debug for HttpRecorder<HC> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpRecorder")
+            .field("redacted_headers", &self.redacted_headers)
+            .field("interactions", &*self.lock())
+            .finish()
+    }
+}
+
+///
+/// HTTP client that deterministically replays interactions captured by an [`HttpRecorder`].
+///
+/// Requests are matched against recorded interactions by method and URL (including the query
+/// string); request headers and bodies are ignored since they often contain values that differ
+/// between runs (e.g., authorization codes and PKCE verifiers). Each recorded interaction is
+/// replayed at most once, in the order recorded, so repeated requests to the same URL (e.g.,
+/// token refreshes) return successive recorded responses.
+///
+#[derive(Debug)]
+pub struct HttpReplayer {
+    interactions: Mutex<Vec<Option<HttpInteraction>>>,
+}
+impl HttpReplayer {
+    ///
+    /// Creates a replayer for the specified interactions.
+    ///
+    pub fn new(interactions: Vec<HttpInteraction>) -> Self {
+        Self {
+            interactions: Mutex::new(interactions.into_iter().map(Some).collect()),
+        }
+    }
+
+    ///
+    /// Loads interactions previously saved by [`HttpRecorder::save`].
+    ///
+    pub fn load<P>(path: P) -> Result<Self, HttpFixtureError>
+    where
+        P: AsRef<Path>,
+    {
+        let reader = BufReader::new(File::open(path).map_err(HttpFixtureError::Io)?);
+        serde_json::from_reader(reader)
+            .map(Self::new)
+            .map_err(HttpFixtureError::Json)
+    }
+
+    ///
+    /// Returns the number of recorded interactions that have not yet been replayed.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.lock().iter().filter(|entry| entry.is_some()).count()
+    }
+
+    ///
+    /// Replays the next recorded response matching the request's method and URL.
+    ///
+    pub fn request(&self, request: HttpRequest) -> Result<HttpResponse, HttpFixtureError> {
+        self.lock()
+            .iter_mut()
+            .find(|entry| matches!(entry, Some(interaction) if interaction.matches(&request)))
+            .and_then(Option::take)
+            .ok_or(HttpFixtureError::NoMatchingInteraction(
+                request.method,
+                request.url,
+            ))?
+            .response()
+    }
+
+    ///
+    /// Returns a synchronous HTTP client that replays recorded interactions.
+    ///
+    pub fn http_client(
+        &self,
+    ) -> impl Fn(HttpRequest) -> Result<HttpResponse, HttpFixtureError> + '_ {
+        move |request| self.request(request)
+    }
+
+    ///
+    /// Returns an asynchronous HTTP client that replays recorded interactions.
+    ///
+    pub fn async_http_client(
+        &self,
+    ) -> impl Fn(HttpRequest) -> Ready<Result<HttpResponse, HttpFixtureError>> + '_ {
+        move |request| std::future::ready(self.request(request))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Option<HttpInteraction>>> {
+        self.interactions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+///
+/// Error recording or replaying HTTP fixtures.
+///
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum HttpFixtureError {
+    ///
+    /// The fixture is malformed.
+    ///
+    #[error("Invalid fixture: {0}")]
+    Invalid(String),
+    ///
+    /// Failed to read or write the fixture file.
+    ///
+    #[error("Failed to access fixture file")]
+    Io(#[source] std::io::Error),
+    ///
+    /// Failed to serialize or deserialize the fixture file.
+    ///
+    #[error("Failed to (de)serialize fixture file")]
+    Json(#[source] serde_json::Error),
+    ///
+    /// No unreplayed interaction matches the request.
+    ///
+    #[error("No recorded interaction matches request {0} {1}")]
+    NoMatchingInteraction(Method, Url),
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{HeaderValue, AUTHORIZATION};
+    use http::method::Method;
+    use oauth2::{AccessToken, ClientId};
+
+    use crate::core::{CoreClient, CoreProviderMetadata, CoreUserInfoClaims};
+    use crate::test_util::MockProvider;
+    use crate::{HttpRequest, IssuerUrl};
+
+    use super::{HttpFixtureError, HttpInteraction, HttpRecorder, HttpReplayer};
+
+    #[test]
+    fn test_record_replay() {
+        let provider =
+            MockProvider::new(IssuerUrl::new("https://op.example.com".to_string()).unwrap());
+        let recorder = HttpRecorder::new(provider.http_client());
+
+        let provider_metadata =
+            CoreProviderMetadata::discover(provider.issuer(), recorder.http_client()).unwrap();
+        let mut request = HttpRequest {
+            url: provider_metadata.userinfo_endpoint().unwrap().url().clone(),
+            method: Method::GET,
+            headers: vec![(AUTHORIZATION, HeaderValue::from_static("Bearer secret"))]
+                .into_iter()
+                .collect(),
+            body: Vec::new(),
+        };
+        assert_eq!(
+            recorder
+                .request(request.clone())
+                .unwrap()
+                .status_code
+                .as_u16(),
+            401
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("openidconnect-fixture-{}.json", std::process::id()));
+        recorder.save(&path).unwrap();
+        let serialized = std::fs::read_to_string(&path).unwrap();
+        assert!(serialized.contains("\"REDACTED\""));
+        assert!(!serialized.contains("secret"));
+        let replayer = HttpReplayer::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replayer.remaining(), 3);
+
+        // Discovery is replayed offline with identical results.
+        let replayed_metadata =
+            CoreProviderMetadata::discover(provider.issuer(), replayer.http_client()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&replayed_metadata).unwrap(),
+            serde_json::to_value(&provider_metadata).unwrap()
+        );
+        assert_eq!(replayed_metadata.jwks(), provider_metadata.jwks());
+
+        // Request bodies and headers don't affect matching, but each interaction is replayed
+        // only once.
+        request.headers.clear();
+        let client = CoreClient::from_provider_metadata(
+            replayed_metadata,
+            ClientId::new("my_client".to_string()),
+            None,
+        );
+        let user_info_result: Result<CoreUserInfoClaims, _> = client
+            .user_info(AccessToken::new("other".to_string()), None)
+            .unwrap()
+            .request(replayer.http_client());
+        assert!(user_info_result.is_err());
+        assert_eq!(replayer.remaining(), 0);
+        match replayer.request(request) {
+            Err(HttpFixtureError::NoMatchingInteraction(method, url)) => {
+                assert_eq!(method, Method::GET);
+                assert_eq!(url.as_str(), "https://op.example.com/userinfo");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Non-UTF-8 bodies round-trip through base64.
+        let interaction: HttpInteraction = serde_json::from_str(
+            "{
+                \"request\": {\"method\": \"GET\", \"url\": \"https://example.com/\"},
+                \"response\": {\"status\": 200, \"body_base64\": \"/w==\"}
+            }",
+        )
+        .unwrap();
+        assert_eq!(interaction.response().unwrap().body, vec![0xff]);
+    }
+}
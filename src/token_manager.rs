@@ -0,0 +1,580 @@
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use oauth2::{AccessToken, ErrorResponse, RevocableToken, TokenIntrospectionResponse, TokenType};
+
+use crate::helpers::block_on;
+use crate::{
+    AdditionalClaims, AuthDisplay, AuthPrompt, GenderClaim, HttpRequest, HttpResponse, JsonWebKey,
+    JsonWebKeyType, JsonWebKeyUse, JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LoginError,
+    OidcLoginHandler, TokenResponse, VerifiedSession,
+};
+
+// Current session along with its expiration time and a counter that's incremented on each
+// refresh, which lets concurrent callers detect that another caller already refreshed it.
+struct ManagedSession<AC, GC, TR>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+{
+    session: Arc<VerifiedSession<AC, GC, TR>>,
+    expires_at: Option<DateTime<Utc>>,
+    generation: u64,
+}
+
+///
+/// Holds a [`VerifiedSession`] and transparently refreshes its tokens before they expire.
+///
+/// The access token's expiration time is computed from the `expires_in` field of the token
+/// response when the session is added to the manager or refreshed. Once the token is about to
+/// expire (within the refresh leeway, which defaults to 30 seconds),
+/// [`access_token`](Self::access_token) and [`access_token_async`](Self::access_token_async)
+/// refresh the session using [`OidcLoginHandler::refresh`], which also verifies any refreshed ID
+/// token. Token responses without an `expires_in` field are assumed not to expire.
+///
+/// Refreshes are de-duplicated: when several threads (or tasks) find that the token has expired,
+/// only one of them sends a refresh request, and the others wait for it and then use the
+/// refreshed token. Synchronous and asynchronous callers share the same refresh lock, so a
+/// manager may be used from both at once.
+///
+pub struct TokenManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>
+where
+    AC: AdditionalClaims,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+    TIR: TokenIntrospectionResponse<TT>,
+    RT: RevocableToken,
+    TRE: ErrorResponse,
+{
+    handler: OidcLoginHandler<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>,
+    state: RwLock<ManagedSession<AC, GC, TR>>,
+    refresh_lock: futures_util::lock::Mutex<()>,
+    refresh_leeway: Duration,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>,
+}
+impl<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>
+    TokenManager<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>
+where
+    AC: AdditionalClaims + Clone,
+    AD: AuthDisplay,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+    P: AuthPrompt,
+    TE: ErrorResponse + 'static,
+    TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+    TT: TokenType + 'static,
+    TIR: TokenIntrospectionResponse<TT>,
+    RT: RevocableToken,
+    TRE: ErrorResponse + 'static,
+{
+    ///
+    /// Initializes a token manager holding the given session, which is refreshed using the given
+    /// login handler.
+    ///
+    pub fn new(
+        handler: OidcLoginHandler<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE>,
+        session: VerifiedSession<AC, GC, TR>,
+    ) -> Self {
+        // By default, use the current system time.
+        let time_fn: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync> = Arc::new(Utc::now);
+        let expires_at = Self::expiration(&session, (*time_fn)());
+        Self {
+            handler,
+            state: RwLock::new(ManagedSession {
+                session: Arc::new(session),
+                expires_at,
+                generation: 0,
+            }),
+            refresh_lock: futures_util::lock::Mutex::new(()),
+            refresh_leeway: Duration::from_secs(30),
+            time_fn,
+        }
+    }
+
+    ///
+    /// Specifies how long before the access token expires it should be refreshed.
+    ///
+    pub fn set_refresh_leeway(mut self, refresh_leeway: Duration) -> Self {
+        self.refresh_leeway = refresh_leeway;
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for computing and checking expiration times. Calling this method
+    /// recomputes the current session's expiration time relative to the new function.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.time_fn = Arc::new(time_fn);
+        let state = self.state.get_mut().expect("token manager lock poisoned");
+        state.expires_at = Self::expiration(&state.session, (*self.time_fn)());
+        self
+    }
+
+    ///
+    /// Returns the login handler used for refreshing the session.
+    ///
+    pub fn handler(
+        &self,
+    ) -> &OidcLoginHandler<AC, AD, GC, JE, JS, JT, JU, K, P, TE, TR, TT, TIR, RT, TRE> {
+        &self.handler
+    }
+
+    ///
+    /// Returns the current session without refreshing it.
+    ///
+    pub fn session(&self) -> Arc<VerifiedSession<AC, GC, TR>> {
+        self.state
+            .read()
+            .expect("token manager lock poisoned")
+            .session
+            .clone()
+    }
+
+    ///
+    /// Returns the time at which the current access token expires, if known.
+    ///
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.state
+            .read()
+            .expect("token manager lock poisoned")
+            .expires_at
+    }
+
+    ///
+    /// Returns `true` if the current access token has expired or will expire within the refresh
+    /// leeway.
+    ///
+    pub fn needs_refresh(&self) -> bool {
+        let state = self.state.read().expect("token manager lock poisoned");
+        self.is_expiring(&state)
+    }
+
+    ///
+    /// Returns a valid access token, refreshing the session using the specified synchronous
+    /// HTTP client if necessary.
+    ///
+    pub fn access_token<HC, RE>(&self, http_client: HC) -> Result<AccessToken, LoginError<RE, TE>>
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        Ok(self
+            .valid_session(http_client)?
+            .token_response()
+            .access_token()
+            .clone())
+    }
+
+    ///
+    /// Returns a valid access token, refreshing the session using the specified asynchronous
+    /// HTTP client if necessary.
+    ///
+    pub async fn access_token_async<C, F, RE>(
+        &self,
+        http_client: C,
+    ) -> Result<AccessToken, LoginError<RE, TE>>
+    where
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        Ok(self
+            .valid_session_async(http_client)
+            .await?
+            .token_response()
+            .access_token()
+            .clone())
+    }
+
+    ///
+    /// Returns the current session, refreshing it using the specified synchronous HTTP client
+    /// if its access token has expired or will expire within the refresh leeway.
+    ///
+    pub fn valid_session<HC, RE>(
+        &self,
+        http_client: HC,
+    ) -> Result<Arc<VerifiedSession<AC, GC, TR>>, LoginError<RE, TE>>
+    where
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        let generation = match self.current_if_valid() {
+            Ok(session) => return Ok(session),
+            Err(generation) => generation,
+        };
+
+        // The refresh lock is shared with asynchronous callers, so block this thread until it's
+        // available.
+        let _guard = block_on(self.refresh_lock.lock());
+        let session = match self.current_if_refreshed(generation) {
+            Ok(session) => return Ok(session),
+            Err(session) => session,
+        };
+        let refreshed = self.handler.refresh(&session, http_client)?;
+        Ok(self.replace_session(refreshed))
+    }
+
+    ///
+    /// Returns the current session, refreshing it using the specified asynchronous HTTP client
+    /// if its access token has expired or will expire within the refresh leeway.
+    ///
+    pub async fn valid_session_async<C, F, RE>(
+        &self,
+        http_client: C,
+    ) -> Result<Arc<VerifiedSession<AC, GC, TR>>, LoginError<RE, TE>>
+    where
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        RE: std::error::Error + 'static,
+    {
+        let generation = match self.current_if_valid() {
+            Ok(session) => return Ok(session),
+            Err(generation) => generation,
+        };
+
+        let _guard = self.refresh_lock.lock().await;
+        let session = match self.current_if_refreshed(generation) {
+            Ok(session) => return Ok(session),
+            Err(session) => session,
+        };
+        let refreshed = self.handler.refresh_async(&session, http_client).await?;
+        Ok(self.replace_session(refreshed))
+    }
+
+    // Returns the current session if it doesn't need to be refreshed, or its generation
+    // otherwise.
+    fn current_if_valid(&self) -> Result<Arc<VerifiedSession<AC, GC, TR>>, u64> {
+        let state = self.state.read().expect("token manager lock poisoned");
+        if self.is_expiring(&state) {
+            Err(state.generation)
+        } else {
+            Ok(state.session.clone())
+        }
+    }
+
+    // Called while holding a refresh lock. Returns `Ok` with the current session if another
+    // caller refreshed it since `generation` was observed, or `Err` with the session to refresh
+    // otherwise.
+    #[allow(clippy::type_complexity)]
+    fn current_if_refreshed(
+        &self,
+        generation: u64,
+    ) -> Result<Arc<VerifiedSession<AC, GC, TR>>, Arc<VerifiedSession<AC, GC, TR>>> {
+        let state = self.state.read().expect("token manager lock poisoned");
+        if state.generation != generation {
+            Ok(state.session.clone())
+        } else {
+            Err(state.session.clone())
+        }
+    }
+
+    fn replace_session(
+        &self,
+        session: VerifiedSession<AC, GC, TR>,
+    ) -> Arc<VerifiedSession<AC, GC, TR>> {
+        let expires_at = Self::expiration(&session, (*self.time_fn)());
+        let session = Arc::new(session);
+        let mut state = self.state.write().expect("token manager lock poisoned");
+        state.session = session.clone();
+        state.expires_at = expires_at;
+        state.generation += 1;
+        session
+    }
+
+    fn is_expiring(&self, state: &ManagedSession<AC, GC, TR>) -> bool {
+        let expires_at = if let Some(expires_at) = state.expires_at {
+            expires_at
+        } else {
+            return false;
+        };
+        let refresh_at = chrono::Duration::from_std(self.refresh_leeway)
+            .ok()
+            .and_then(|leeway| expires_at.checked_sub_signed(leeway))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        (*self.time_fn)() >= refresh_at
+    }
+
+    fn expiration(
+        session: &VerifiedSession<AC, GC, TR>,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        session.token_response().expires_in().map(|expires_in| {
+            chrono::Duration::from_std(expires_in)
+                .ok()
+                .and_then(|expires_in| now.checked_add_signed(expires_in))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use oauth2::{AccessToken, AuthorizationCode};
+
+    use super::TokenManager;
+    use crate::helpers::block_on;
+    use crate::login::tests::{new_handler, new_id_token, token_response};
+    use crate::{HttpRequest, HttpResponse, Nonce, OAuth2TokenResponse};
+
+    fn expiring_token_response(access_token: &AccessToken, with_id_token: bool) -> HttpResponse {
+        let mut response = token_response(
+            if with_id_token {
+                Some(new_id_token(
+                    Some(&Nonce::new("the_nonce".to_string())),
+                    Some(access_token),
+                    None,
+                ))
+            } else {
+                None
+            },
+            access_token,
+        );
+        let mut body = serde_json::from_slice::<serde_json::Value>(&response.body).unwrap();
+        body["expires_in"] = 3600.into();
+        response.body = serde_json::to_vec(&body).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_token_manager() {
+        let handler = new_handler();
+        let session = handler
+            .client()
+            .exchange_code_and_verify(
+                AuthorizationCode::new("the_code".to_string()),
+                None,
+                &Nonce::new("the_nonce".to_string()),
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Ok(expiring_token_response(
+                        &AccessToken::new("the_access_token".to_string()),
+                        true,
+                    ))
+                },
+            )
+            .unwrap();
+
+        let clock = Arc::new(AtomicI64::new(1700000000));
+        let manager = TokenManager::new(handler, session).set_time_fn({
+            let clock = clock.clone();
+            move || {
+                Utc.timestamp_opt(clock.load(Ordering::SeqCst), 0)
+                    .single()
+                    .unwrap()
+            }
+        });
+        assert_eq!(
+            manager.expires_at(),
+            Utc.timestamp_opt(1700003600, 0).single()
+        );
+        assert!(!manager.needs_refresh());
+        let no_request = |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+            panic!("unexpected token request")
+        };
+        assert_eq!(
+            manager.access_token(no_request).unwrap().secret(),
+            "the_access_token"
+        );
+
+        // Concurrent callers share a single refresh once the token is within the leeway.
+        clock.store(1700003580, Ordering::SeqCst);
+        assert!(manager.needs_refresh());
+        let requests = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        manager
+                            .access_token(|request: HttpRequest| {
+                                requests.fetch_add(1, Ordering::SeqCst);
+                                let body = String::from_utf8(request.body).unwrap();
+                                assert!(body.contains("grant_type=refresh_token"));
+                                std::thread::sleep(Duration::from_millis(50));
+                                Ok::<_, std::io::Error>(expiring_token_response(
+                                    &AccessToken::new("new_access_token".to_string()),
+                                    false,
+                                ))
+                            })
+                            .unwrap()
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                assert_eq!(handle.join().unwrap().secret(), "new_access_token");
+            }
+        });
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(!manager.needs_refresh());
+        assert_eq!(
+            manager.expires_at(),
+            Utc.timestamp_opt(1700007180, 0).single()
+        );
+        let session = manager.session();
+        assert_eq!(
+            session.token_response().access_token().secret(),
+            "new_access_token"
+        );
+        assert_eq!(session.id_token_claims().subject().as_str(), "the_subject");
+    }
+
+    #[test]
+    fn test_token_manager_without_refresh_token_rotation() {
+        let handler = new_handler();
+        let session = handler
+            .client()
+            .exchange_code_and_verify(
+                AuthorizationCode::new("the_code".to_string()),
+                None,
+                &Nonce::new("the_nonce".to_string()),
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Ok(expiring_token_response(
+                        &AccessToken::new("the_access_token".to_string()),
+                        true,
+                    ))
+                },
+            )
+            .unwrap();
+
+        let clock = Arc::new(AtomicI64::new(1700000000));
+        let manager = TokenManager::new(handler, session).set_time_fn({
+            let clock = clock.clone();
+            move || {
+                Utc.timestamp_opt(clock.load(Ordering::SeqCst), 0)
+                    .single()
+                    .unwrap()
+            }
+        });
+
+        // The provider doesn't rotate refresh tokens, so refresh responses omit `refresh_token`.
+        let requests = AtomicUsize::new(0);
+        let refresh_http_client = |request: HttpRequest| {
+            requests.fetch_add(1, Ordering::SeqCst);
+            let body = String::from_utf8(request.body).unwrap();
+            assert!(body.contains("refresh_token=the_refresh_token"));
+            let mut response =
+                expiring_token_response(&AccessToken::new("new_access_token".to_string()), false);
+            let mut body = serde_json::from_slice::<serde_json::Value>(&response.body).unwrap();
+            body.as_object_mut().unwrap().remove("refresh_token");
+            response.body = serde_json::to_vec(&body).unwrap();
+            Ok::<_, std::io::Error>(response)
+        };
+
+        for expires_at in [1700003600, 1700007200] {
+            clock.store(expires_at, Ordering::SeqCst);
+            assert!(manager.needs_refresh());
+            assert_eq!(
+                manager.access_token(refresh_http_client).unwrap().secret(),
+                "new_access_token"
+            );
+            assert!(!manager.needs_refresh());
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_token_manager_mixed_sync_and_async() {
+        let handler = new_handler();
+        let session = handler
+            .client()
+            .exchange_code_and_verify(
+                AuthorizationCode::new("the_code".to_string()),
+                None,
+                &Nonce::new("the_nonce".to_string()),
+                |_: HttpRequest| -> Result<HttpResponse, std::io::Error> {
+                    Ok(expiring_token_response(
+                        &AccessToken::new("the_access_token".to_string()),
+                        true,
+                    ))
+                },
+            )
+            .unwrap();
+
+        let clock = Arc::new(AtomicI64::new(1700000000));
+        let manager = TokenManager::new(handler, session).set_time_fn({
+            let clock = clock.clone();
+            move || {
+                Utc.timestamp_opt(clock.load(Ordering::SeqCst), 0)
+                    .single()
+                    .unwrap()
+            }
+        });
+
+        // A caller that finds the token expired while a refresh on the other path is in flight
+        // waits for it instead of sending its own refresh request.
+        let requests = AtomicUsize::new(0);
+        for (expires_at, async_first) in [(1700003600, false), (1700007200, true)] {
+            clock.store(expires_at, Ordering::SeqCst);
+            assert!(manager.needs_refresh());
+            let (started_tx, started_rx) = mpsc::channel();
+            let refresh = |request: HttpRequest| {
+                requests.fetch_add(1, Ordering::SeqCst);
+                assert!(String::from_utf8(request.body)
+                    .unwrap()
+                    .contains("grant_type=refresh_token"));
+                started_tx.send(()).unwrap();
+                std::thread::sleep(Duration::from_millis(50));
+                Ok::<_, std::io::Error>(expiring_token_response(
+                    &AccessToken::new(format!("access_token_{}", expires_at)),
+                    false,
+                ))
+            };
+            let sync_access_token = || manager.access_token(refresh).unwrap();
+            let async_access_token = || {
+                block_on(manager.access_token_async(|request| {
+                    let response = refresh(request);
+                    async move { response }
+                }))
+                .unwrap()
+            };
+
+            std::thread::scope(|scope| {
+                let (first, second) = if async_first {
+                    (
+                        scope.spawn(async_access_token),
+                        scope.spawn(move || {
+                            started_rx.recv().unwrap();
+                            sync_access_token()
+                        }),
+                    )
+                } else {
+                    (
+                        scope.spawn(sync_access_token),
+                        scope.spawn(move || {
+                            started_rx.recv().unwrap();
+                            async_access_token()
+                        }),
+                    )
+                };
+                let expected = format!("access_token_{}", expires_at);
+                assert_eq!(first.join().unwrap().secret(), &expected);
+                assert_eq!(second.join().unwrap().secret(), &expected);
+            });
+            assert!(!manager.needs_refresh());
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+}
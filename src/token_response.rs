@@ -0,0 +1,266 @@
+use oauth2::{Scope, TokenType};
+use thiserror::Error;
+
+use crate::{
+    AdditionalClaims, GenderClaim, JsonWebKeyType, JweContentEncryptionAlgorithm,
+    JwsSigningAlgorithm, TokenResponse,
+};
+
+///
+/// Error validating a token response.
+///
+#[derive(Clone, Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub enum TokenResponseValidationError {
+    ///
+    /// The token response included an unexpected `token_type`.
+    ///
+    #[error("Unexpected token type `{0}`")]
+    UnexpectedTokenType(String),
+    ///
+    /// The token response did not include an ID token.
+    ///
+    #[error("Server did not return an ID token")]
+    MissingIdToken,
+    ///
+    /// The authorization server did not grant all of the requested scopes.
+    ///
+    #[error("Server did not grant the requested scopes: {}", join_scopes(.0))]
+    ScopeDowngrade(Vec<Scope>),
+}
+
+fn join_scopes(scopes: &[Scope]) -> String {
+    scopes
+        .iter()
+        .map(|scope| format!("`{}`", scope.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+///
+/// Validator for OpenID Connect token responses, as described in
+/// [Section 3.1.3.5](https://openid.net/specs/openid-connect-core-1_0.html#TokenResponseValidation)
+/// of the OpenID Connect Core spec.
+///
+/// By default, the validator checks that:
+///  * the `token_type` is `Bearer` (compared case-insensitively), and
+///  * the token response includes an ID token.
+///
+/// The validator also reports any requested scopes that the authorization server did not grant.
+/// Such scope downgrades are permitted by
+/// [Section 3.3](https://tools.ietf.org/html/rfc6749#section-3.3) of RFC 6749, but can be
+/// rejected using [`reject_scope_downgrade`](Self::reject_scope_downgrade).
+///
+/// The ID token itself is not verified; see [`IdToken::claims`](crate::IdToken::claims).
+///
+#[derive(Clone, Debug)]
+pub struct TokenResponseValidator {
+    allowed_token_types: Vec<String>,
+    id_token_required: bool,
+    reject_scope_downgrade: bool,
+}
+impl TokenResponseValidator {
+    ///
+    /// Initializes a validator that accepts `Bearer` tokens.
+    ///
+    pub fn new() -> Self {
+        Self {
+            allowed_token_types: vec!["Bearer".to_string()],
+            id_token_required: true,
+            reject_scope_downgrade: false,
+        }
+    }
+
+    ///
+    /// Specifies which token types (e.g., `Bearer` or `DPoP`) are accepted.
+    ///
+    /// Token types are compared case-insensitively.
+    ///
+    pub fn set_allowed_token_types<I>(mut self, token_types: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.allowed_token_types = token_types.into_iter().collect();
+        self
+    }
+
+    ///
+    /// Specifies whether the token response must include an ID token.
+    ///
+    /// OpenID Connect authentication requests always include the `openid` scope, so the token
+    /// response to an authorization code exchange must include an ID token. This check is enabled
+    /// by default, and should be disabled when validating responses to refresh token requests,
+    /// which may omit the ID token.
+    ///
+    pub fn require_id_token(mut self, id_token_required: bool) -> Self {
+        self.id_token_required = id_token_required;
+        self
+    }
+
+    ///
+    /// Specifies whether to return an error if the authorization server did not grant all of the
+    /// requested scopes.
+    ///
+    pub fn reject_scope_downgrade(mut self, reject_scope_downgrade: bool) -> Self {
+        self.reject_scope_downgrade = reject_scope_downgrade;
+        self
+    }
+
+    ///
+    /// Validates a token response to a request for the given scopes.
+    ///
+    /// Returns the requested scopes that the authorization server did not grant, if any. If the
+    /// token response omits the `scope` field, all of the requested scopes are assumed to have
+    /// been granted.
+    ///
+    pub fn validate<AC, GC, JE, JS, JT, TR, TT>(
+        &self,
+        token_response: &TR,
+        requested_scopes: &[Scope],
+    ) -> Result<Vec<Scope>, TokenResponseValidationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        JS: JwsSigningAlgorithm<JT>,
+        JT: JsonWebKeyType,
+        TR: TokenResponse<AC, GC, JE, JS, JT, TT>,
+        TT: TokenType,
+    {
+        let token_type =
+            serde_plain::to_string(token_response.token_type()).unwrap_or_else(|err| {
+                panic!(
+                    "token type {:?} failed to serialize to a string: {}",
+                    token_response.token_type(),
+                    err
+                )
+            });
+        if !self
+            .allowed_token_types
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&token_type))
+        {
+            return Err(TokenResponseValidationError::UnexpectedTokenType(
+                token_type,
+            ));
+        }
+
+        if self.id_token_required && token_response.id_token().is_none() {
+            return Err(TokenResponseValidationError::MissingIdToken);
+        }
+
+        let denied_scopes = token_response
+            .scopes()
+            .map(|granted_scopes| {
+                requested_scopes
+                    .iter()
+                    .filter(|scope| !granted_scopes.contains(scope))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if self.reject_scope_downgrade && !denied_scopes.is_empty() {
+            return Err(TokenResponseValidationError::ScopeDowngrade(denied_scopes));
+        }
+        Ok(denied_scopes)
+    }
+}
+impl Default for TokenResponseValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oauth2::Scope;
+
+    use crate::core::CoreTokenResponse;
+
+    use super::{TokenResponseValidationError, TokenResponseValidator};
+
+    fn token_response(token_type: &str, scope: Option<&str>, id_token: bool) -> CoreTokenResponse {
+        let mut response = serde_json::json!({
+            "access_token": "the_access_token",
+            "token_type": token_type,
+        });
+        if let Some(scope) = scope {
+            response["scope"] = scope.into();
+        }
+        if id_token {
+            response["id_token"] = concat!(
+                "eyJhbGciOiJSUzI1NiJ9.",
+                "eyJpc3MiOiJodHRwczovL3NlcnZlci5leGFtcGxlLmNvbSIsImF1ZCI6WyJzNkJoZ",
+                "FJrcXQzIl0sImV4cCI6MTMxMTI4MTk3MCwiaWF0IjoxMzExMjgwOTcwLCJzdWIiOi",
+                "IyNDQwMDMyMCJ9.",
+                "aW52YWxpZF9zaWduYXR1cmU"
+            )
+            .into();
+        }
+        serde_json::from_value(response).unwrap()
+    }
+
+    #[test]
+    fn test_token_response_validator() {
+        let requested_scopes = vec![
+            Scope::new("openid".to_string()),
+            Scope::new("email".to_string()),
+        ];
+        let validator = TokenResponseValidator::new();
+
+        assert_eq!(
+            validator.validate(&token_response("bearer", None, true), &requested_scopes),
+            Ok(vec![])
+        );
+        assert_eq!(
+            validator.validate(
+                &token_response("Bearer", Some("openid email profile"), true),
+                &requested_scopes
+            ),
+            Ok(vec![])
+        );
+        assert_eq!(
+            validator.validate(&token_response("mac", None, true), &requested_scopes),
+            Err(TokenResponseValidationError::UnexpectedTokenType(
+                "mac".to_string()
+            ))
+        );
+        assert_eq!(
+            validator.validate(&token_response("bearer", None, false), &requested_scopes),
+            Err(TokenResponseValidationError::MissingIdToken)
+        );
+        assert_eq!(
+            validator
+                .clone()
+                .require_id_token(false)
+                .validate(&token_response("bearer", None, false), &requested_scopes),
+            Ok(vec![])
+        );
+
+        let downgraded = token_response("bearer", Some("openid"), true);
+        assert_eq!(
+            validator.validate(&downgraded, &requested_scopes),
+            Ok(vec![Scope::new("email".to_string())])
+        );
+        let err = validator
+            .clone()
+            .reject_scope_downgrade(true)
+            .validate(&downgraded, &requested_scopes)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            TokenResponseValidationError::ScopeDowngrade(vec![Scope::new("email".to_string())])
+        );
+        assert_eq!(
+            err.to_string(),
+            "Server did not grant the requested scopes: `email`"
+        );
+
+        assert_eq!(
+            validator
+                .set_allowed_token_types(vec!["MAC".to_string()])
+                .validate(&token_response("mac", None, true), &requested_scopes),
+            Ok(vec![])
+        );
+    }
+}
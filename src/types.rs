@@ -6,7 +6,7 @@ use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::ops::Deref;
 
-use http::header::{HeaderValue, ACCEPT};
+use http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
 use http::method::Method;
 use http::status::StatusCode;
 use oauth2::helpers::deserialize_space_delimited_vec;
@@ -19,8 +19,8 @@ use url::Url;
 
 use super::http_utils::{check_content_type, MIME_TYPE_JSON, MIME_TYPE_JWKS};
 use super::{
-    AccessToken, AuthorizationCode, DiscoveryError, HttpRequest, HttpResponse,
-    SignatureVerificationError,
+    AccessToken, AuthorizationCode, ClaimsVerificationError, ClientId, DiscoveryError, HttpRequest,
+    HttpResponse, PkceCodeVerifier, SignatureVerificationError,
 };
 
 ///
@@ -40,11 +40,28 @@ impl<T> LocalizedClaim<T> {
         Self::default()
     }
 
+    ///
+    /// Adds an entry for the specified locale, replacing any existing entry, and returns the
+    /// updated claim.
+    ///
+    /// This is a builder-friendly alternative to [`LocalizedClaim::insert`].
+    ///
+    pub fn with_entry(mut self, locale: Option<LanguageTag>, value: T) -> Self {
+        self.insert(locale, value);
+        self
+    }
+
     ///
     /// Returns true if the claim contains a value for the specified locale.
     ///
-    pub fn contains_key(&self, locale: Option<&LanguageTag>) -> bool {
-        if let Some(l) = locale {
+    /// The locale may be given either as a `&LanguageTag` or as an `Option<&LanguageTag>`, where
+    /// `None` refers to the value without a language tag.
+    ///
+    pub fn contains_key<'a, L>(&self, locale: L) -> bool
+    where
+        L: Into<Option<&'a LanguageTag>>,
+    {
+        if let Some(l) = locale.into() {
             self.0.contains_key(l)
         } else {
             self.1.is_some()
@@ -54,14 +71,56 @@ impl<T> LocalizedClaim<T> {
     ///
     /// Returns the entry for the specified locale or `None` if there is no such entry.
     ///
-    pub fn get(&self, locale: Option<&LanguageTag>) -> Option<&T> {
-        if let Some(l) = locale {
+    /// The locale may be given either as a `&LanguageTag` or as an `Option<&LanguageTag>`, where
+    /// `None` refers to the value without a language tag.
+    ///
+    pub fn get<'a, L>(&self, locale: L) -> Option<&T>
+    where
+        L: Into<Option<&'a LanguageTag>>,
+    {
+        if let Some(l) = locale.into() {
             self.0.get(l)
         } else {
             self.1.as_ref()
         }
     }
 
+    ///
+    /// Returns the value best matching the given list of preferred locales, in order of
+    /// preference.
+    ///
+    /// Locales are matched case-insensitively using the
+    /// [lookup](https://www.rfc-editor.org/rfc/rfc4647#section-3.4) scheme: for each preferred
+    /// locale, an exact match is attempted first, followed by progressively less specific
+    /// prefixes (e.g., `zh-Hant-TW`, then `zh-Hant`, then `zh`). If no preferred locale matches,
+    /// the value without a language tag is returned, if any.
+    ///
+    pub fn preferred(&self, locales: &[LanguageTag]) -> Option<&T> {
+        locales
+            .iter()
+            .find_map(|locale| {
+                let mut range = locale.as_str();
+                loop {
+                    if let Some(value) = self
+                        .0
+                        .iter()
+                        .find(|(tag, _)| tag.eq_ignore_ascii_case(range))
+                        .map(|(_, value)| value)
+                    {
+                        return Some(value);
+                    }
+                    let mut end = range.rfind('-')?;
+                    // Single-character subtags (e.g., private use `x`) are removed along with
+                    // the subtag that follows them.
+                    if end >= 2 && range.as_bytes()[end - 2] == b'-' {
+                        end -= 2;
+                    }
+                    range = &range[..end];
+                }
+            })
+            .or(self.1.as_ref())
+    }
+
     ///
     /// Returns an iterator over the locales and claim value entries.
     ///
@@ -186,6 +245,57 @@ pub trait ClaimType: Debug + DeserializeOwned + Serialize + 'static {}
 ///
 pub trait ClientAuthMethod: Debug + DeserializeOwned + Serialize + 'static {}
 
+///
+/// Secret-bearing value whose `Debug` output is redacted.
+///
+/// Types implementing this trait (e.g., [`ClientSecret`](crate::ClientSecret),
+/// [`AccessToken`], and [`Nonce`]) print a `[redacted]` placeholder when formatted with `{:?}`,
+/// so that accidentally logging a client or token response does not leak credentials. This trait
+/// provides an explicit escape hatch for accessing the underlying secret.
+///
+pub trait ExposeSecret {
+    ///
+    /// Type of the underlying secret.
+    ///
+    type Secret: ?Sized;
+
+    ///
+    /// Returns the underlying secret.
+    ///
+    /// # Security Warning
+    ///
+    /// Leaking this value may compromise the security of the OAuth2 flow.
+    ///
+    fn expose_secret(&self) -> &Self::Secret;
+}
+
+// Secret types defined by the oauth2 crate already redact their Debug output.
+macro_rules! impl_expose_secret {
+    ($($name:ty),*) => {
+        $(
+            impl ExposeSecret for $name {
+                type Secret = String;
+
+                fn expose_secret(&self) -> &String {
+                    self.secret()
+                }
+            }
+        )*
+    };
+}
+impl_expose_secret!(
+    AccessToken,
+    AuthorizationCode,
+    oauth2::ClientSecret,
+    oauth2::CsrfToken,
+    oauth2::DeviceCode,
+    oauth2::PkceCodeVerifier,
+    oauth2::RefreshToken,
+    oauth2::ResourceOwnerPassword,
+    oauth2::UserCode,
+    oauth2::VerificationUriComplete
+);
+
 ///
 /// Grant type.
 ///
@@ -208,6 +318,119 @@ pub enum SigningError {
     Other(String),
 }
 
+///
+/// Error encrypting a message.
+///
+#[derive(Clone, Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub enum EncryptionError {
+    /// Failed to encrypt the message using the given key and parameters.
+    #[error("Crypto error")]
+    CryptoError,
+    /// The key cannot be used with the given algorithms.
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    /// Unsupported key management or content encryption algorithm.
+    #[error("Unsupported encryption algorithm: {0}")]
+    UnsupportedAlg(String),
+    /// An unexpected error occurred.
+    #[error("Other error: {0}")]
+    Other(String),
+}
+
+///
+/// Error decrypting a message.
+///
+#[derive(Clone, Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub enum DecryptionError {
+    /// Failed to decrypt the message using the given key (e.g., the ciphertext or authentication
+    /// tag is invalid).
+    #[error("Crypto error")]
+    CryptoError,
+    /// The key cannot be used with the message's algorithms.
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    /// The message is not a valid JSON Web Encryption (JWE).
+    #[error("Malformed JWE: {0}")]
+    Malformed(String),
+    /// The decompressed plaintext exceeds the maximum length (in bytes).
+    #[error("Decompressed plaintext exceeds the maximum length of {0} bytes")]
+    TooLarge(usize),
+    /// Unsupported key management or content encryption algorithm.
+    #[error("Unsupported encryption algorithm: {0}")]
+    UnsupportedAlg(String),
+    /// An unexpected error occurred.
+    #[error("Other error: {0}")]
+    Other(String),
+}
+
+///
+/// Error parsing an E.164 phone number.
+///
+#[derive(Clone, Debug, Error, PartialEq)]
+#[non_exhaustive]
+pub enum PhoneNumberError {
+    /// The phone number contains a character other than a digit or visual separator.
+    #[error("Invalid character: {0:?}")]
+    InvalidCharacter(char),
+    /// The country code begins with `0`.
+    #[error("Invalid country code")]
+    InvalidCountryCode,
+    /// The extension is empty or contains a character other than a digit.
+    #[error("Invalid extension")]
+    InvalidExtension,
+    /// The phone number is empty or contains more than 15 digits.
+    #[error("Invalid length: {0} digits")]
+    InvalidLength(usize),
+    /// The phone number does not begin with `+` followed by the country code.
+    #[error("Missing `+` prefix")]
+    MissingPlusSign,
+}
+
+///
+/// Hash algorithm used for computing the `at_hash` and `c_hash` claims.
+///
+/// Ordinarily, the hash algorithm is derived from the ID token's signature algorithm (see
+/// [`JwsSigningAlgorithm::hash_bytes`]). This type allows the hash algorithm to be specified
+/// explicitly instead.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TokenHashAlgorithm {
+    /// SHA-256 (used by `RS256`, `PS256`, `ES256` and `HS256`).
+    Sha256,
+    /// SHA-384 (used by `RS384`, `PS384`, `ES384` and `HS384`).
+    Sha384,
+    /// SHA-512 (used by `RS512`, `PS512`, `ES512`, `HS512` and `EdDSA` with Ed25519).
+    ///
+    /// `EdDSA` with Ed448 uses SHAKE256, which is not supported.
+    Sha512,
+}
+impl TokenHashAlgorithm {
+    ///
+    /// Hashes the given `bytes` and returns the hashed bytes.
+    ///
+    pub fn hash_bytes(&self, bytes: &[u8]) -> Vec<u8> {
+        use sha2::{Digest, Sha256, Sha384, Sha512};
+        match *self {
+            TokenHashAlgorithm::Sha256 => Sha256::digest(bytes).to_vec(),
+            TokenHashAlgorithm::Sha384 => Sha384::digest(bytes).to_vec(),
+            TokenHashAlgorithm::Sha512 => Sha512::digest(bytes).to_vec(),
+        }
+    }
+}
+
+// Encodes the left-most half of the hash, as required for the `at_hash` and `c_hash` claims.
+fn left_half_base64url(hash: &[u8]) -> String {
+    base64::encode_config(&hash[0..hash.len() / 2], base64::URL_SAFE_NO_PAD)
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    use subtle::ConstantTimeEq;
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
 ///
 /// JSON Web Key.
 ///
@@ -273,6 +496,100 @@ where
     fn as_verification_key(&self) -> K;
 }
 
+///
+/// Public or symmetric key for encryption.
+///
+pub trait EncryptionKey<JE, JK, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Encrypts the given `plaintext` using the given key management and content encryption
+    /// algorithms, returning the JSON Web Encryption (JWE) Compact Serialization.
+    ///
+    /// If specified, `content_type` is included as the `cty` header parameter (e.g., `JWT` for
+    /// nested JWTs).
+    ///
+    fn encrypt(
+        &self,
+        key_management_alg: &JK,
+        content_encryption_alg: &JE,
+        content_type: Option<&str>,
+        plaintext: &[u8],
+    ) -> Result<String, EncryptionError>;
+
+    ///
+    /// Compresses the given `plaintext` using DEFLATE and encrypts it in the same manner as
+    /// [`encrypt`](Self::encrypt), setting the `zip` header parameter to `DEF`.
+    ///
+    /// Compression reduces the size of large payloads (e.g., user info responses), but may
+    /// reveal information about the plaintext through the ciphertext length.
+    ///
+    /// By default, this returns [`EncryptionError::UnsupportedAlg`].
+    ///
+    fn encrypt_compressed(
+        &self,
+        key_management_alg: &JK,
+        content_encryption_alg: &JE,
+        content_type: Option<&str>,
+        plaintext: &[u8],
+    ) -> Result<String, EncryptionError> {
+        let _ = (
+            key_management_alg,
+            content_encryption_alg,
+            content_type,
+            plaintext,
+        );
+        Err(EncryptionError::UnsupportedAlg("DEF".to_string()))
+    }
+}
+
+///
+/// Default maximum length (in bytes) of the decompressed plaintext of a JWE compressed using
+/// `zip=DEF` (1 MiB).
+///
+/// This limit prevents decompression bombs; see [`DecryptionKey::decrypt_with_limit`].
+///
+pub const DEFAULT_MAX_DECOMPRESSED_JWE_LEN: usize = 1024 * 1024;
+
+///
+/// Private or symmetric key for decryption.
+///
+pub trait DecryptionKey<JE, JK, JT>
+where
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JK: JweKeyManagementAlgorithm,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Decrypts the given JSON Web Encryption (JWE) Compact Serialization, returning the
+    /// plaintext.
+    ///
+    /// The key management and content encryption algorithms are taken from the JWE's header and
+    /// must be supported by this key. Plaintexts compressed using `zip=DEF` are decompressed,
+    /// up to [`DEFAULT_MAX_DECOMPRESSED_JWE_LEN`] bytes.
+    ///
+    fn decrypt(&self, jwe: &str) -> Result<Vec<u8>, DecryptionError> {
+        self.decrypt_with_limit(jwe, DEFAULT_MAX_DECOMPRESSED_JWE_LEN)
+    }
+
+    ///
+    /// Decrypts the given JSON Web Encryption (JWE) Compact Serialization, returning the
+    /// plaintext.
+    ///
+    /// Plaintexts compressed using `zip=DEF` are decompressed, returning
+    /// [`DecryptionError::TooLarge`] if the decompressed plaintext exceeds
+    /// `max_decompressed_len` bytes.
+    ///
+    fn decrypt_with_limit(
+        &self,
+        jwe: &str,
+        max_decompressed_len: usize,
+    ) -> Result<Vec<u8>, DecryptionError>;
+}
+
 ///
 /// Key type (e.g., RSA).
 ///
@@ -403,7 +720,9 @@ new_type![
     ///
     /// Identifier for an authentication method (e.g., `password` or `totp`).
     ///
-    /// Defining specific AMR identifiers is beyond the scope of the OpenID Connect Core spec.
+    /// Defining specific AMR identifiers is beyond the scope of the OpenID Connect Core spec. The
+    /// values registered by [RFC 8176](https://www.rfc-editor.org/rfc/rfc8176) are available
+    /// as [`CoreAuthenticationMethodReference`](crate::core::CoreAuthenticationMethodReference).
     ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     AuthenticationMethodReference(String)
@@ -419,6 +738,9 @@ new_type![
         ///
         /// Initialize a new access token hash from an [`AccessToken`] and signature algorithm.
         ///
+        /// The hash function is the one associated with `alg`, which should be the `alg` header
+        /// parameter of the ID token containing the `at_hash` claim.
+        ///
         pub fn from_token<JS, JT>(
             access_token: &AccessToken,
             alg: &JS
@@ -428,13 +750,49 @@ new_type![
             JT: JsonWebKeyType,
         {
             alg.hash_bytes(access_token.secret().as_bytes())
-                .map(|hash| {
-                    Self::new(
-                        base64::encode_config(&hash[0..hash.len() / 2], base64::URL_SAFE_NO_PAD)
-                    )
-                })
+                .map(|hash| Self::new(left_half_base64url(&hash)))
                 .map_err(SigningError::UnsupportedAlg)
         }
+
+        ///
+        /// Initialize a new access token hash from an [`AccessToken`] using an explicit hash
+        /// algorithm.
+        ///
+        /// This is useful outside of JSON Web Signature contexts, where no signing algorithm is
+        /// available to determine the hash function.
+        ///
+        pub fn from_token_with_hash_alg(
+            access_token: &AccessToken,
+            hash_alg: TokenHashAlgorithm,
+        ) -> Self {
+            Self::new(left_half_base64url(&hash_alg.hash_bytes(access_token.secret().as_bytes())))
+        }
+
+        ///
+        /// Verifies that this hash matches the given [`AccessToken`], using the hash function
+        /// associated with the signature algorithm `alg`.
+        ///
+        /// The comparison is performed in constant time.
+        ///
+        pub fn verify<JS, JT>(
+            &self,
+            access_token: &AccessToken,
+            alg: &JS,
+        ) -> Result<(), ClaimsVerificationError>
+        where
+            JS: JwsSigningAlgorithm<JT>,
+            JT: JsonWebKeyType,
+        {
+            let expected_hash = Self::from_token(access_token, alg)
+                .map_err(|err| ClaimsVerificationError::Unsupported(err.to_string()))?;
+            if constant_time_eq(self, &expected_hash) {
+                Ok(())
+            } else {
+                Err(ClaimsVerificationError::InvalidAccessTokenHash(
+                    "access token does not match `at_hash` claim".to_string(),
+                ))
+            }
+        }
     }
 ];
 
@@ -477,6 +835,31 @@ new_type![
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     Audience(String)
 ];
+impl From<ClientId> for Audience {
+    fn from(client_id: ClientId) -> Self {
+        Self::new(client_id.as_str().to_string())
+    }
+}
+impl From<&ClientId> for Audience {
+    fn from(client_id: &ClientId) -> Self {
+        Self::new(client_id.as_str().to_string())
+    }
+}
+impl From<Audience> for ClientId {
+    fn from(audience: Audience) -> Self {
+        Self::new(audience.0)
+    }
+}
+impl PartialEq<ClientId> for Audience {
+    fn eq(&self, other: &ClientId) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+impl PartialEq<Audience> for ClientId {
+    fn eq(&self, other: &Audience) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
 
 new_type![
     ///
@@ -498,13 +881,46 @@ new_type![
             JT: JsonWebKeyType,
         {
             alg.hash_bytes(code.secret().as_bytes())
-                .map(|hash| {
-                    Self::new(
-                        base64::encode_config(&hash[0..hash.len() / 2], base64::URL_SAFE_NO_PAD)
-                    )
-                })
+                .map(|hash| Self::new(left_half_base64url(&hash)))
                 .map_err(SigningError::UnsupportedAlg)
         }
+
+        ///
+        /// Initialize a new authorization code hash from an [`AuthorizationCode`] using an explicit
+        /// hash algorithm.
+        ///
+        pub fn from_code_with_hash_alg(
+            code: &AuthorizationCode,
+            hash_alg: TokenHashAlgorithm,
+        ) -> Self {
+            Self::new(left_half_base64url(&hash_alg.hash_bytes(code.secret().as_bytes())))
+        }
+
+        ///
+        /// Verifies that this hash matches the given [`AuthorizationCode`], using the hash function
+        /// associated with the signature algorithm `alg`.
+        ///
+        /// The comparison is performed in constant time.
+        ///
+        pub fn verify<JS, JT>(
+            &self,
+            code: &AuthorizationCode,
+            alg: &JS,
+        ) -> Result<(), ClaimsVerificationError>
+        where
+            JS: JwsSigningAlgorithm<JT>,
+            JT: JsonWebKeyType,
+        {
+            let expected_hash = Self::from_code(code, alg)
+                .map_err(|err| ClaimsVerificationError::Unsupported(err.to_string()))?;
+            if constant_time_eq(self, &expected_hash) {
+                Ok(())
+            } else {
+                Err(ClaimsVerificationError::InvalidAuthorizationCodeHash(
+                    "authorization code does not match `c_hash` claim".to_string(),
+                ))
+            }
+        }
     }
 ];
 
@@ -620,8 +1036,70 @@ new_type![
     ///
     /// End user's phone number.
     ///
+    /// OpenID Connect Core
+    /// [recommends](https://openid.net/specs/openid-connect-core-1_0.html#StandardClaims) that
+    /// phone numbers use the [E.164](https://www.itu.int/rec/T-REC-E.164/en) format (e.g.,
+    /// `+1 (425) 555-1212`). Use [`EndUserPhoneNumber::new_e164`] to validate and normalize a
+    /// phone number into this format.
+    ///
     #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
     EndUserPhoneNumber(String)
+    impl {
+        ///
+        /// Validates the given phone number as E.164 and normalizes it to its canonical form.
+        ///
+        /// The phone number must begin with `+` followed by a country code and contain at most 15
+        /// digits. Spaces, hyphens, periods, and parentheses are removed. An
+        /// [RFC 3966](https://www.rfc-editor.org/rfc/rfc3966) extension (e.g., `;ext=5678`) is
+        /// preserved. For example, `+1 (604) 555-1234;ext=5678` is normalized to
+        /// `+16045551234;ext=5678`.
+        ///
+        pub fn new_e164(phone_number: &str) -> Result<Self, PhoneNumberError> {
+            let (number, extension) = match phone_number.split_once(";ext=") {
+                Some((number, extension)) => (number, Some(extension)),
+                None => (phone_number, None),
+            };
+
+            let mut chars = number.trim().chars();
+            if chars.next() != Some('+') {
+                return Err(PhoneNumberError::MissingPlusSign);
+            }
+            let mut normalized = String::from("+");
+            for c in chars {
+                match c {
+                    '0'..='9' => normalized.push(c),
+                    ' ' | '-' | '.' | '(' | ')' => {}
+                    other => return Err(PhoneNumberError::InvalidCharacter(other)),
+                }
+            }
+
+            let num_digits = normalized.len() - 1;
+            if !(1..=15).contains(&num_digits) {
+                return Err(PhoneNumberError::InvalidLength(num_digits));
+            }
+            if normalized.starts_with("+0") {
+                return Err(PhoneNumberError::InvalidCountryCode);
+            }
+
+            if let Some(extension) = extension {
+                if extension.is_empty() || !extension.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(PhoneNumberError::InvalidExtension);
+                }
+                normalized.push_str(";ext=");
+                normalized.push_str(extension);
+            }
+
+            Ok(Self::new(normalized))
+        }
+
+        ///
+        /// Returns true if this phone number is in canonical E.164 format, as produced by
+        /// [`EndUserPhoneNumber::new_e164`].
+        ///
+        pub fn is_e164(&self) -> bool {
+            matches!(Self::new_e164(self), Ok(normalized) if normalized == *self)
+        }
+    }
 ];
 
 new_type![
@@ -704,6 +1182,63 @@ new_url_type![
                 Url::parse(&(self.1.clone() + "/" + suffix))
             }
         }
+
+        ///
+        /// Returns whether `issuer` matches this issuer URL, treating each `{...}` placeholder in
+        /// this URL (e.g., `https://login.microsoftonline.com/{tenantid}/v2.0`) as a wildcard.
+        ///
+        /// A placeholder matches one or more
+        /// [unreserved characters](https://www.rfc-editor.org/rfc/rfc3986#section-2.3): within the
+        /// host, it matches a single DNS label (i.e., no `.`), and within the path, it matches a
+        /// single path segment other than `.` and `..`. Placeholders therefore never match
+        /// characters such as `/`, `?`, `#`, or `@` that would change the structure of the URL.
+        ///
+        /// An issuer URL without any placeholders only matches an identical issuer URL.
+        ///
+        pub fn matches_template(&self, issuer: &IssuerUrl) -> bool {
+            fn is_unreserved(c: char) -> bool {
+                c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')
+            }
+
+            fn matches(template: &str, value: &str, offset: usize, authority_end: usize) -> bool {
+                let placeholder_start = match template.find('{') {
+                    Some(start) => start,
+                    None => return template == value,
+                };
+                let placeholder_end = match template[placeholder_start..].find('}') {
+                    Some(end) => placeholder_start + end,
+                    None => return template == value,
+                };
+                let in_authority = offset + placeholder_start < authority_end;
+
+                let remainder = match value.strip_prefix(&template[..placeholder_start]) {
+                    Some(remainder) => remainder,
+                    None => return false,
+                };
+                remainder
+                    .char_indices()
+                    .take_while(|(_, c)| is_unreserved(*c) && !(in_authority && *c == '.'))
+                    .map(|(i, c)| i + c.len_utf8())
+                    .filter(|len| !matches!(&remainder[..*len], "." | ".."))
+                    .any(|len| {
+                        matches(
+                            &template[placeholder_end + 1..],
+                            &remainder[len..],
+                            offset + placeholder_end + 1,
+                            authority_end,
+                        )
+                    })
+            }
+
+            if self.1 == issuer.1 {
+                return true;
+            }
+            let authority_start = self.1.find("://").map_or(0, |start| start + 3);
+            let authority_end = self.1[authority_start..]
+                .find(['/', '?', '#'])
+                .map_or(self.1.len(), |end| authority_start + end);
+            matches(&self.1, &issuer.1, 0, authority_end)
+        }
     }
 ];
 
@@ -715,11 +1250,21 @@ new_type![
     JsonWebKeyId(String)
 ];
 
+new_type![
+    ///
+    /// Unique identifier for a JSON Web Token (the `jti` claim).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    JsonWebTokenId(String)
+];
+
 ///
 /// JSON Web Key Set.
 ///
-#[serde_as]
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+/// Keys are indexed by key ID (`kid`) when the set is constructed, so looking up the keys eligible
+/// to verify a JWT does not require scanning the entire set.
+///
+#[derive(PartialEq, Serialize)]
 pub struct JsonWebKeySet<JS, JT, JU, K>
 where
     JS: JwsSigningAlgorithm<JT>,
@@ -730,10 +1275,12 @@ where
     // FIXME: write a test that ensures duplicate object member names cause an error
     // (see https://tools.ietf.org/html/rfc7517#section-5)
     #[serde(bound = "K: JsonWebKey<JS, JT, JU>")]
-    // Ignores invalid keys rather than failing. That way, clients can function using the keys that
-    // they do understand, which is fine if they only ever get JWTs signed with those keys.
-    #[serde_as(as = "VecSkipError<_>")]
     keys: Vec<K>,
+    // Maps each key ID to the indices of the keys in `keys` with that ID. Key IDs are supposed to
+    // be unique within a set, but providers occasionally reuse them (e.g., for keys of different
+    // types).
+    #[serde(skip)]
+    keys_by_id: HashMap<JsonWebKeyId, Vec<usize>>,
     #[serde(skip)]
     _phantom: PhantomData<(JS, JT, JU)>,
 }
@@ -748,8 +1295,15 @@ where
     /// Create a new JSON Web Key Set.
     ///
     pub fn new(keys: Vec<K>) -> Self {
+        let mut keys_by_id: HashMap<JsonWebKeyId, Vec<usize>> = HashMap::new();
+        for (index, key) in keys.iter().enumerate() {
+            if let Some(kid) = key.key_id() {
+                keys_by_id.entry(kid.clone()).or_default().push(index);
+            }
+        }
         Self {
             keys,
+            keys_by_id,
             _phantom: PhantomData,
         }
     }
@@ -828,7 +1382,14 @@ where
         serde_path_to_error::deserialize(&mut serde_json::Deserializer::from_slice(
             &http_response.body,
         ))
-        .map_err(DiscoveryError::Parse)
+        .map_err(|err| {
+            DiscoveryError::Parse(
+                http_response.status_code,
+                http_response.headers.get(CONTENT_TYPE).cloned(),
+                http_response.body.clone(),
+                err,
+            )
+        })
     }
 
     ///
@@ -837,6 +1398,94 @@ where
     pub fn keys(&self) -> &Vec<K> {
         &self.keys
     }
+
+    ///
+    /// Return the keys in this JSON Web Key Set with the specified key ID.
+    ///
+    pub fn keys_with_id(&self, kid: &JsonWebKeyId) -> Vec<&K> {
+        self.keys_by_id
+            .get(kid)
+            .map(|indices| indices.iter().map(|index| &self.keys[*index]).collect())
+            .unwrap_or_default()
+    }
+
+    ///
+    /// Return the keys in this JSON Web Key Set that may be used for the specified purpose
+    /// (e.g., signing or encryption).
+    ///
+    /// Keys that don't specify a `use` may be used for any purpose and are included.
+    ///
+    pub fn keys_for_use(&self, key_use: &JU) -> Vec<&K>
+    where
+        JU: PartialEq,
+    {
+        self.keys
+            .iter()
+            .filter(|key| key.key_use().is_none_or(|use_| use_ == key_use))
+            .collect()
+    }
+
+    ///
+    /// Return the keys in this JSON Web Key Set that may be used to verify signatures using the
+    /// specified algorithm.
+    ///
+    /// These are the keys of the type required by the algorithm that either allow signing or
+    /// don't specify a `use`. If a key ID is specified (e.g., from a JWT's `kid` header), only
+    /// keys with that ID are eligible, and they're looked up without scanning the entire set.
+    ///
+    pub fn signing_keys_for_alg(&self, signature_alg: &JS, kid: Option<&JsonWebKeyId>) -> Vec<&K> {
+        let key_type = signature_alg.key_type();
+        let is_eligible = |key: &&K| {
+            Some(key.key_type()) == key_type.as_ref()
+                && key.key_use().is_none_or(|use_| use_.allows_signature())
+        };
+        match kid {
+            Some(kid) => self
+                .keys_with_id(kid)
+                .into_iter()
+                .filter(is_eligible)
+                .collect(),
+            None => self.keys.iter().filter(is_eligible).collect(),
+        }
+    }
+}
+impl<JS, JT, JU, K> Debug for JsonWebKeySet<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FormatterError> {
+        f.debug_struct("JsonWebKeySet")
+            .field("keys", &self.keys)
+            .finish()
+    }
+}
+impl<'de, JS, JT, JU, K> Deserialize<'de> for JsonWebKeySet<JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[serde_as]
+        #[derive(Deserialize)]
+        #[serde(bound = "K: DeserializeOwned")]
+        struct Keys<K> {
+            // Ignores invalid keys rather than failing. That way, clients can function using the
+            // keys that they do understand, which is fine if they only ever get JWTs signed with
+            // those keys.
+            #[serde_as(as = "VecSkipError<_>")]
+            keys: Vec<K>,
+        }
+
+        Keys::<K>::deserialize(deserializer).map(|Keys { keys }| Self::new(keys))
+    }
 }
 impl<JS, JT, JU, K> Clone for JsonWebKeySet<JS, JT, JU, K>
 where
@@ -933,6 +1582,23 @@ new_secret_type![
             let random_bytes: Vec<u8> = (0..num_bytes).map(|_| thread_rng().gen::<u8>()).collect();
             Nonce::new(base64::encode_config(random_bytes, base64::URL_SAFE_NO_PAD))
         }
+        ///
+        /// Derives a nonce deterministically from the given PKCE code verifier.
+        ///
+        /// This allows stateless clients to persist only the PKCE code verifier between
+        /// redirecting the user and handling the callback, while still binding the ID token to
+        /// the authorization request. The nonce is the base64url-encoded SHA-256 hash of the code
+        /// verifier with a fixed domain-separation prefix, so it cannot be computed from the
+        /// (public) `S256` code challenge.
+        ///
+        pub fn from_pkce_verifier(pkce_verifier: &PkceCodeVerifier) -> Self {
+            use sha2::{Digest, Sha256};
+            let hash = Sha256::new()
+                .chain_update(b"openidconnect-nonce:")
+                .chain_update(pkce_verifier.secret().as_bytes())
+                .finalize();
+            Nonce::new(base64::encode_config(hash, base64::URL_SAFE_NO_PAD))
+        }
     }
 ];
 impl PartialEq for Nonce {
@@ -1095,6 +1761,17 @@ new_type![
     StreetAddress(String)
 ];
 
+new_type![
+    ///
+    /// Identifier for a session between the End-User's User Agent and the OpenID Connect Provider
+    /// (the `sid` claim), as described in
+    /// [OpenID Connect Front-Channel Logout](
+    ///     https://openid.net/specs/openid-connect-frontchannel-1_0.html#ClaimsContents).
+    ///
+    #[derive(Deserialize, Eq, Hash, Ord, PartialOrd, Serialize)]
+    SessionIdentifier(String)
+];
+
 new_type![
     ///
     /// Locally unique and never reassigned identifier within the Issuer for the End-User, which is
@@ -1368,7 +2045,99 @@ mod serde_base64url_byte_array {
 
 #[cfg(test)]
 mod tests {
-    use super::IssuerUrl;
+    use oauth2::{
+        AccessToken, AuthorizationCode, ClientId, ClientSecret, PkceCodeChallenge, PkceCodeVerifier,
+    };
+
+    use super::{
+        AccessTokenHash, Audience, AuthorizationCodeHash, EndUserPhoneNumber, ExposeSecret,
+        IssuerUrl, JsonWebKey, JsonWebKeyId, LanguageTag, LocalizedClaim, LoginHint, Nonce,
+        PhoneNumberError, TokenHashAlgorithm,
+    };
+    use crate::core::{
+        CoreJsonWebKey, CoreJsonWebKeySet, CoreJsonWebKeyUse, CoreJwsSigningAlgorithm,
+    };
+    use crate::ClaimsVerificationError;
+
+    #[test]
+    fn test_audience_client_id() {
+        let client_id = ClientId::new("my_client".to_string());
+        let audience = Audience::new("my_client".to_string());
+
+        assert_eq!(audience, client_id);
+        assert_eq!(client_id, audience);
+        assert_ne!(Audience::new("other_client".to_string()), client_id);
+        assert_ne!(client_id, Audience::new("other_client".to_string()));
+
+        assert_eq!(Audience::from(&client_id), audience);
+        assert_eq!(Audience::from(client_id.clone()), audience);
+        assert_eq!(ClientId::from(audience).as_str(), client_id.as_str());
+    }
+
+    #[test]
+    fn test_token_hashes() {
+        let access_token =
+            AccessToken::new("jHkWEdUXMU1BwAsC4vtUsZwnNvTIxEl0z9K3vx5KF0Y".to_string());
+        let at_hash = AccessTokenHash::new("77QmUPtjPfzWtF2AnpK9RQ".to_string());
+
+        assert_eq!(
+            AccessTokenHash::from_token(
+                &access_token,
+                &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
+            )
+            .unwrap(),
+            at_hash
+        );
+        assert_eq!(
+            AccessTokenHash::from_token_with_hash_alg(&access_token, TokenHashAlgorithm::Sha256),
+            at_hash
+        );
+        at_hash
+            .verify(&access_token, &CoreJwsSigningAlgorithm::EcdsaP256Sha256)
+            .expect("verification should succeed");
+        match at_hash.verify(
+            &AccessToken::new("other_token".to_string()),
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ) {
+            Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match at_hash.verify(
+            &access_token,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha384,
+        ) {
+            Err(ClaimsVerificationError::InvalidAccessTokenHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match at_hash.verify(&access_token, &CoreJwsSigningAlgorithm::None) {
+            Err(ClaimsVerificationError::Unsupported(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(
+            AccessTokenHash::from_token(&access_token, &CoreJwsSigningAlgorithm::EdDsa).unwrap(),
+            AccessTokenHash::from_token_with_hash_alg(&access_token, TokenHashAlgorithm::Sha512),
+        );
+
+        let code = AuthorizationCode::new(
+            "Qcb0Orv1zh30vL1MPRsbm-diHiMwcLyZvn1arpZv-Jxf_11jnpEX3Tgfvk".to_string(),
+        );
+        let c_hash = AuthorizationCodeHash::new("LDktKdoQak3Pk0cnXxCltA".to_string());
+        assert_eq!(
+            AuthorizationCodeHash::from_code_with_hash_alg(&code, TokenHashAlgorithm::Sha256),
+            c_hash
+        );
+        c_hash
+            .verify(&code, &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256)
+            .expect("verification should succeed");
+        match c_hash.verify(
+            &AuthorizationCode::new("other_code".to_string()),
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        ) {
+            Err(ClaimsVerificationError::InvalidAuthorizationCodeHash(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 
     #[test]
     fn test_issuer_url_append() {
@@ -1406,6 +2175,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_issuer_url_matches_template() {
+        let template =
+            IssuerUrl::new("https://login.microsoftonline.com/{tenantid}/v2.0".to_string())
+                .unwrap();
+
+        assert!(template.matches_template(
+            &IssuerUrl::new(
+                "https://login.microsoftonline.com/9188040d-6c67-4c5b-b112-36a304b66dad/v2.0"
+                    .to_string()
+            )
+            .unwrap()
+        ));
+        assert!(template.matches_template(&template));
+        assert!(!template.matches_template(
+            &IssuerUrl::new("https://login.microsoftonline.com//v2.0".to_string()).unwrap()
+        ));
+        assert!(!template.matches_template(
+            &IssuerUrl::new("https://login.microsoftonline.com/a/b/v2.0".to_string()).unwrap()
+        ));
+        assert!(!template.matches_template(
+            &IssuerUrl::new("https://attacker.com/tenant/v2.0".to_string()).unwrap()
+        ));
+
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        assert!(issuer.matches_template(&issuer));
+        assert!(!issuer
+            .matches_template(&IssuerUrl::new("https://example.com/other".to_string()).unwrap()));
+
+        let host_template = IssuerUrl::new("https://{tenant}.example.com/".to_string()).unwrap();
+        assert!(host_template.matches_template(
+            &IssuerUrl::new("https://contoso.example.com/".to_string()).unwrap()
+        ));
+        for issuer in [
+            "https://attacker.com@x.example.com/",
+            "https://attacker.com?.example.com/",
+            "https://attacker.com#.example.com/",
+            "https://attacker.com/.example.com/",
+            "https://a.b.example.com/",
+        ] {
+            assert!(
+                !host_template.matches_template(&IssuerUrl::new(issuer.to_string()).unwrap()),
+                "{}",
+                issuer
+            );
+        }
+        for issuer in [
+            "https://login.microsoftonline.com/a@b/v2.0",
+            "https://login.microsoftonline.com/a?b/v2.0",
+            "https://login.microsoftonline.com/a#b/v2.0",
+            "https://login.microsoftonline.com/../v2.0",
+        ] {
+            assert!(
+                !template.matches_template(&IssuerUrl::new(issuer.to_string()).unwrap()),
+                "{}",
+                issuer
+            );
+        }
+    }
+
     #[test]
     fn test_url_serialize() {
         let issuer_url =
@@ -1428,6 +2257,192 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_jwks_keys_with_id() {
+        let jwks: CoreJsonWebKeySet = serde_json::from_str(
+            "{\"keys\": [
+                {\"kty\": \"oct\", \"kid\": \"a\", \"k\": \"c2VjcmV0\"},
+                {\"kty\": \"oct\", \"k\": \"c2VjcmV0\"},
+                {\"kty\": \"unsupported\", \"kid\": \"b\"},
+                {\"kty\": \"oct\", \"kid\": \"a\", \"k\": \"b3RoZXI\"}
+            ]}",
+        )
+        .unwrap();
+        assert_eq!(jwks.keys().len(), 3);
+
+        let keys = jwks.keys_with_id(&JsonWebKeyId::new("a".to_string()));
+        assert_eq!(keys.len(), 2);
+        assert!(keys.iter().all(|key| key.key_id().unwrap().as_str() == "a"));
+        assert_ne!(keys[0], keys[1]);
+        assert!(jwks
+            .keys_with_id(&JsonWebKeyId::new("b".to_string()))
+            .is_empty());
+
+        assert_eq!(
+            jwks.clone()
+                .keys_with_id(&JsonWebKeyId::new("a".to_string()))
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_jwks_key_selection() {
+        let jwks: CoreJsonWebKeySet = serde_json::from_str(
+            "{\"keys\": [
+                {\"kty\": \"oct\", \"kid\": \"a\", \"use\": \"enc\", \"k\": \"c2VjcmV0\"},
+                {\"kty\": \"oct\", \"kid\": \"b\", \"use\": \"sig\", \"k\": \"c2VjcmV0\"},
+                {\"kty\": \"oct\", \"kid\": \"c\", \"k\": \"c2VjcmV0\"},
+                {\"kty\": \"EC\", \"kid\": \"d\", \"use\": \"sig\", \"crv\": \"P-256\",
+                 \"x\": \"kXCGZIr3oI6sKbnT6rRsIdxFXw3_VbLk_cveajgqXk8\",
+                 \"y\": \"StDvKIgXqAxJ6DuebREh-1vgvZRW3dfrOxSIKzBtRI0\"}
+            ]}",
+        )
+        .unwrap();
+        let kids = |keys: Vec<&CoreJsonWebKey>| {
+            keys.into_iter()
+                .map(|key| key.key_id().unwrap().as_str().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kids(jwks.keys_for_use(&CoreJsonWebKeyUse::Encryption)),
+            vec!["a", "c"]
+        );
+        assert_eq!(
+            kids(jwks.keys_for_use(&CoreJsonWebKeyUse::Signature)),
+            vec!["b", "c", "d"]
+        );
+
+        assert_eq!(
+            kids(jwks.signing_keys_for_alg(&CoreJwsSigningAlgorithm::HmacSha256, None)),
+            vec!["b", "c"]
+        );
+        assert_eq!(
+            kids(jwks.signing_keys_for_alg(&CoreJwsSigningAlgorithm::EcdsaP256Sha256, None)),
+            vec!["d"]
+        );
+        assert!(jwks
+            .signing_keys_for_alg(&CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256, None)
+            .is_empty());
+
+        let kid = |kid: &str| JsonWebKeyId::new(kid.to_string());
+        assert_eq!(
+            kids(jwks.signing_keys_for_alg(&CoreJwsSigningAlgorithm::HmacSha256, Some(&kid("c")))),
+            vec!["c"]
+        );
+        // Key "a" matches the key ID but may only be used for encryption.
+        assert!(jwks
+            .signing_keys_for_alg(&CoreJwsSigningAlgorithm::HmacSha256, Some(&kid("a")))
+            .is_empty());
+        assert!(jwks
+            .signing_keys_for_alg(&CoreJwsSigningAlgorithm::HmacSha256, Some(&kid("e")))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_localized_claim() {
+        let tag = |s: &str| LanguageTag::new(s.to_string());
+
+        let claim = LocalizedClaim::from("Default")
+            .with_entry(Some(tag("fr")), "Français")
+            .with_entry(Some(tag("zh-Hant")), "Traditional Chinese")
+            .with_entry(Some(tag("en-US")), "American English");
+
+        assert_eq!(claim.get(None), Some(&"Default"));
+        assert_eq!(claim.get(&tag("fr")), Some(&"Français"));
+        assert_eq!(claim.get(Some(&tag("fr"))), Some(&"Français"));
+        assert_eq!(claim.get(&tag("de")), None);
+        assert!(claim.contains_key(&tag("en-US")));
+        assert!(!claim.contains_key(&tag("en")));
+
+        assert_eq!(claim.preferred(&[tag("fr-CA")]), Some(&"Français"));
+        assert_eq!(
+            claim.preferred(&[tag("ZH-hant-TW"), tag("fr")]),
+            Some(&"Traditional Chinese")
+        );
+        assert_eq!(
+            claim.preferred(&[tag("de"), tag("en-us")]),
+            Some(&"American English")
+        );
+        assert_eq!(claim.preferred(&[tag("fr-x-private")]), Some(&"Français"));
+        // Less specific locales don't match more specific tags.
+        assert_eq!(claim.preferred(&[tag("en")]), Some(&"Default"));
+        assert_eq!(LocalizedClaim::<&str>::new().preferred(&[tag("en")]), None);
+    }
+
+    #[test]
+    fn test_phone_number_e164() {
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+1 (604) 555-1234;ext=5678").unwrap(),
+            EndUserPhoneNumber::new("+16045551234;ext=5678".to_string())
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164(" +44 20.7946.0000 ").unwrap(),
+            EndUserPhoneNumber::new("+442079460000".to_string())
+        );
+
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("(604) 555-1234"),
+            Err(PhoneNumberError::MissingPlusSign)
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+1 604 555 CALL"),
+            Err(PhoneNumberError::InvalidCharacter('C'))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+0 604 555 1234"),
+            Err(PhoneNumberError::InvalidCountryCode)
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+1234567890123456"),
+            Err(PhoneNumberError::InvalidLength(16))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+"),
+            Err(PhoneNumberError::InvalidLength(0))
+        );
+        assert_eq!(
+            EndUserPhoneNumber::new_e164("+16045551234;ext=x1"),
+            Err(PhoneNumberError::InvalidExtension)
+        );
+
+        assert!(EndUserPhoneNumber::new("+16045551234".to_string()).is_e164());
+        assert!(!EndUserPhoneNumber::new("+1 604 555 1234".to_string()).is_e164());
+        assert!(!EndUserPhoneNumber::new("555-1234".to_string()).is_e164());
+    }
+
+    #[test]
+    fn test_redacted_secrets() {
+        let client_secret = ClientSecret::new("client_secret".to_string());
+        assert_eq!(format!("{:?}", client_secret), "ClientSecret([redacted])");
+        assert_eq!(client_secret.expose_secret(), "client_secret");
+
+        let login_hint = LoginHint::new("alice@example.com".to_string());
+        assert_eq!(format!("{:?}", login_hint), "LoginHint([redacted])");
+        assert_eq!(login_hint.expose_secret(), "alice@example.com");
+
+        let nonce = Nonce::new("the_nonce".to_string());
+        assert_eq!(format!("{:?}", nonce), "Nonce([redacted])");
+        assert_eq!(nonce.expose_secret(), "the_nonce");
+    }
+
+    #[test]
+    fn test_nonce_from_pkce_verifier() {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let nonce = Nonce::from_pkce_verifier(&pkce_verifier);
+        assert_eq!(
+            nonce,
+            Nonce::from_pkce_verifier(&PkceCodeVerifier::new(pkce_verifier.secret().clone()))
+        );
+        assert_eq!(nonce.secret().len(), 43);
+        assert_ne!(nonce.secret(), pkce_challenge.as_str());
+        assert_ne!(nonce.secret(), pkce_verifier.secret());
+
+        let (_, other_verifier) = PkceCodeChallenge::new_random_sha256();
+        assert_ne!(nonce, Nonce::from_pkce_verifier(&other_verifier));
+    }
+
     #[cfg(feature = "accept-string-booleans")]
     #[test]
     fn test_string_bool_parse() {
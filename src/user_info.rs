@@ -3,27 +3,30 @@ use std::ops::Deref;
 use std::str;
 
 use chrono::{DateTime, Utc};
-use http::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+use http::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
 use http::method::Method;
 use http::status::StatusCode;
-use oauth2::AccessToken;
+use oauth2::{AccessToken, ClientId};
 use thiserror::Error;
-use url::Url;
+use url::{form_urlencoded, Url};
 
 use crate::helpers::FilteredFlatten;
-use crate::http_utils::{auth_bearer, content_type_has_essence, MIME_TYPE_JSON, MIME_TYPE_JWT};
-use crate::jwt::{JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
+use crate::http_utils::{
+    auth_bearer, content_type_has_essence, MIME_TYPE_FORM_URLENCODED, MIME_TYPE_JSON, MIME_TYPE_JWT,
+};
+use crate::jwt::{JsonWebTokenAccess, JsonWebTokenError, JsonWebTokenJsonPayloadSerde};
 use crate::types::helpers::deserialize_string_or_vec_opt;
 use crate::types::LocalizedClaim;
 use crate::verification::UserInfoVerifier;
 use crate::{
     AdditionalClaims, AddressClaim, Audience, AudiencesClaim, ClaimsVerificationError,
-    EndUserBirthday, EndUserEmail, EndUserFamilyName, EndUserGivenName, EndUserMiddleName,
-    EndUserName, EndUserNickname, EndUserPhoneNumber, EndUserPictureUrl, EndUserProfileUrl,
-    EndUserTimezone, EndUserUsername, EndUserWebsiteUrl, GenderClaim, HttpRequest, HttpResponse,
-    IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType, JsonWebKeyUse, JsonWebToken,
-    JweContentEncryptionAlgorithm, JwsSigningAlgorithm, LanguageTag, PrivateSigningKey,
-    StandardClaims, SubjectIdentifier,
+    DecryptionKey, EncryptionKey, EndUserBirthday, EndUserEmail, EndUserFamilyName,
+    EndUserGivenName, EndUserMiddleName, EndUserName, EndUserNickname, EndUserPhoneNumber,
+    EndUserPictureUrl, EndUserProfileUrl, EndUserTimezone, EndUserUsername, EndUserWebsiteUrl,
+    GenderClaim, HttpRequest, HttpResponse, IssuerClaim, IssuerUrl, JsonWebKey, JsonWebKeyType,
+    JsonWebKeyUse, JsonWebToken, JsonWebTokenHeader, JweContentEncryptionAlgorithm,
+    JweKeyManagementAlgorithm, JwsSigningAlgorithm, LanguageTag, PrivateSigningKey, StandardClaims,
+    SubjectIdentifier,
 };
 
 ///
@@ -40,6 +43,9 @@ where
     pub(super) url: &'a UserInfoUrl,
     pub(super) access_token: AccessToken,
     pub(super) require_signed_response: bool,
+    pub(super) use_post: bool,
+    pub(super) access_token_in_body: bool,
+    pub(super) extra_headers: HeaderMap,
     pub(super) signed_response_verifier: UserInfoVerifier<'static, JE, JS, JT, JU, K>,
 }
 impl<'a, JE, JS, JT, JU, K> UserInfoRequest<'a, JE, JS, JT, JU, K>
@@ -92,18 +98,83 @@ where
         self.user_info_response(http_response)
     }
 
+    ///
+    /// Submits this request to the associated user info endpoint using the specified synchronous
+    /// HTTP client, returning both the verified claims and the raw JSON claims.
+    ///
+    /// The raw JSON claims preserve all fields returned by the provider (including those not
+    /// modeled by `AC`), which is useful for forwarding provider-specific claims. For signed JWT
+    /// responses, the raw JSON claims are the JWT payload, which is only returned after the JWT
+    /// has been verified.
+    ///
+    pub fn request_with_json<AC, GC, HC, RE>(
+        self,
+        http_client: HC,
+    ) -> Result<(UserInfoClaims<AC, GC>, serde_json::Value), UserInfoError<RE>>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        HC: FnOnce(HttpRequest) -> Result<HttpResponse, RE>,
+        RE: std::error::Error + 'static,
+    {
+        http_client(self.prepare_request())
+            .map_err(UserInfoError::Request)
+            .and_then(|http_response| self.user_info_response_with_json(http_response))
+    }
+
+    ///
+    /// Submits this request to the associated user info endpoint using the specified asynchronous
+    /// HTTP client, returning both the verified claims and the raw JSON claims (see
+    /// [`request_with_json`](Self::request_with_json)).
+    ///
+    pub async fn request_with_json_async<AC, C, F, GC, RE>(
+        self,
+        http_client: C,
+    ) -> Result<(UserInfoClaims<AC, GC>, serde_json::Value), UserInfoError<RE>>
+    where
+        AC: AdditionalClaims,
+        C: FnOnce(HttpRequest) -> F,
+        F: Future<Output = Result<HttpResponse, RE>>,
+        GC: GenderClaim,
+        RE: std::error::Error + 'static,
+    {
+        let http_request = self.prepare_request();
+        let http_response = http_client(http_request)
+            .await
+            .map_err(UserInfoError::Request)?;
+
+        self.user_info_response_with_json(http_response)
+    }
+
     fn prepare_request(&self) -> HttpRequest {
-        let (auth_header, auth_value) = auth_bearer(&self.access_token);
+        let mut headers = vec![(ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON))];
+        let body = if self.access_token_in_body {
+            headers.push((
+                CONTENT_TYPE,
+                HeaderValue::from_static(MIME_TYPE_FORM_URLENCODED),
+            ));
+            form_urlencoded::Serializer::new(String::new())
+                .append_pair("access_token", self.access_token.secret())
+                .finish()
+                .into_bytes()
+        } else {
+            headers.push(auth_bearer(&self.access_token));
+            Vec::new()
+        };
+
         HttpRequest {
             url: self.url.url().clone(),
-            method: Method::GET,
-            headers: vec![
-                (ACCEPT, HeaderValue::from_static(MIME_TYPE_JSON)),
-                (auth_header, auth_value),
-            ]
-            .into_iter()
-            .collect(),
-            body: Vec::new(),
+            method: if self.use_post || self.access_token_in_body {
+                Method::POST
+            } else {
+                Method::GET
+            },
+            headers: {
+                let mut headers = headers.into_iter().collect::<HeaderMap>();
+                headers.extend(self.extra_headers.clone());
+                headers
+            },
+            body,
         }
     }
 
@@ -111,6 +182,19 @@ where
         self,
         http_response: HttpResponse,
     ) -> Result<UserInfoClaims<AC, GC>, UserInfoError<RE>>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        RE: std::error::Error + 'static,
+    {
+        self.user_info_response_with_json(http_response)
+            .map(|(claims, _)| claims)
+    }
+
+    fn user_info_response_with_json<AC, GC, RE>(
+        self,
+        http_response: HttpResponse,
+    ) -> Result<(UserInfoClaims<AC, GC>, serde_json::Value), UserInfoError<RE>>
     where
         AC: AdditionalClaims,
         GC: GenderClaim,
@@ -136,21 +220,40 @@ where
                         ClaimsVerificationError::NoSignature,
                     ));
                 }
-                UserInfoClaims::from_json(
+                let claims = UserInfoClaims::from_json(
                     &http_response.body,
                     self.signed_response_verifier.expected_subject(),
+                )?;
+                let json = serde_path_to_error::deserialize(
+                    &mut serde_json::Deserializer::from_slice(&http_response.body),
                 )
+                .map_err(UserInfoError::Parse)?;
+                Ok((claims, json))
             }
             ref content_type if content_type_has_essence(content_type, MIME_TYPE_JWT) => {
                 let jwt_str = String::from_utf8(http_response.body).map_err(|_| {
                     UserInfoError::Other("response body has invalid UTF-8 encoding".to_string())
                 })?;
-                serde_path_to_error::deserialize::<_, UserInfoJsonWebToken<AC, GC, JE, JS, JT>>(
-                    serde_json::Value::String(jwt_str),
-                )
+                let claims = serde_path_to_error::deserialize::<
+                    _,
+                    UserInfoJsonWebToken<AC, GC, JE, JS, JT>,
+                >(serde_json::Value::String(jwt_str.clone()))
                 .map_err(UserInfoError::Parse)?
                 .claims(&self.signed_response_verifier)
-                .map_err(UserInfoError::ClaimsVerification)
+                .map_err(UserInfoError::ClaimsVerification)?;
+                // The JWT has been verified, so its payload can be trusted.
+                let payload = jwt_str
+                    .split('.')
+                    .nth(1)
+                    .and_then(|payload| {
+                        base64::decode_config(payload, base64::URL_SAFE_NO_PAD).ok()
+                    })
+                    .ok_or_else(|| UserInfoError::Other("invalid JWT payload".to_string()))?;
+                let json = serde_path_to_error::deserialize(
+                    &mut serde_json::Deserializer::from_slice(&payload),
+                )
+                .map_err(UserInfoError::Parse)?;
+                Ok((claims, json))
             }
             ref content_type => Err(UserInfoError::Response(
                 http_response.status_code,
@@ -168,6 +271,43 @@ where
         self
     }
 
+    ///
+    /// Submits the user info request using the HTTP `POST` method instead of the default `GET`.
+    ///
+    /// Both methods are permitted by
+    /// [Section 5.3.1](https://openid.net/specs/openid-connect-core-1_0.html#UserInfoRequest) of
+    /// the OpenID Connect Core spec, but some providers only accept one of them.
+    ///
+    pub fn use_post(mut self) -> Self {
+        self.use_post = true;
+        self
+    }
+
+    ///
+    /// Adds an additional header to the user info request (e.g., a correlation ID or an API
+    /// gateway key).
+    ///
+    /// Any header of the same name set by this crate is replaced.
+    ///
+    pub fn add_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.extra_headers.append(name, value);
+        self
+    }
+
+    ///
+    /// Sends the access token as the `access_token` form parameter in the request body instead
+    /// of in the `Authorization` header, as described in
+    /// [Section 2.2 of RFC 6750](https://tools.ietf.org/html/rfc6750#section-2.2).
+    ///
+    /// This is useful for providers located behind gateways that strip bearer `Authorization`
+    /// headers. Since the form body requires the HTTP `POST` method, this option implies
+    /// [`use_post`](Self::use_post).
+    ///
+    pub fn send_access_token_in_body(mut self) -> Self {
+        self.access_token_in_body = true;
+        self
+    }
+
     ///
     /// Specifies whether to require the issuer of the signed JWT response to match the expected
     /// issuer URL for this provider.
@@ -181,6 +321,22 @@ where
         self
     }
 
+    ///
+    /// Overrides the expected issuer of the signed JWT response for this request.
+    ///
+    /// By default, the issuer must match the client's configured issuer URL. This is useful in
+    /// aggregator scenarios, in which user info is fetched from an endpoint belonging to a
+    /// different issuer than the one that issued the ID token. Use
+    /// [`require_issuer_match`](Self::require_issuer_match) to skip the check entirely.
+    ///
+    /// This option has no effect on unsigned JSON responses, whose `iss` claim (if any) is not
+    /// verified.
+    ///
+    pub fn set_expected_issuer(mut self, issuer: IssuerUrl) -> Self {
+        self.signed_response_verifier = self.signed_response_verifier.set_expected_issuer(issuer);
+        self
+    }
+
     ///
     /// Specifies whether to require the audience of the signed JWT response to match the expected
     /// audience (client ID).
@@ -193,6 +349,29 @@ where
             .require_audience_match(aud_required);
         self
     }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are allowed for signed JWT responses.
+    ///
+    /// This option has no effect on unsigned JSON responses.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.signed_response_verifier = self.signed_response_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is allowed for signed JWT responses.
+    ///
+    /// This option has no effect on unsigned JSON responses.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.signed_response_verifier = self.signed_response_verifier.allow_any_alg();
+        self
+    }
 }
 
 ///
@@ -421,6 +600,76 @@ where
         Ok(Self(JsonWebToken::new(claims.0, signing_key, &alg)?))
     }
 
+    ///
+    /// Initializes a new signed JWT in the same manner as [`new`](Self::new), including the given
+    /// additional parameters in the JOSE header (see
+    /// [`JsonWebToken::new_with_header_params`]).
+    ///
+    pub fn new_with_header_params<JU, K, S>(
+        claims: UserInfoClaims<AC, GC>,
+        signing_key: &S,
+        alg: JS,
+        header_params: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        Ok(Self(JsonWebToken::new_with_header_params(
+            claims.0,
+            signing_key,
+            &alg,
+            header_params,
+        )?))
+    }
+
+    ///
+    /// Returns the JOSE header of this JWT **without verifying its signature**.
+    ///
+    /// # Security Warning
+    ///
+    /// The header may have been forged, so it must only be used to decide how to verify the
+    /// token or for debugging. Use [`claims`](Self::claims) to verify the token.
+    ///
+    pub fn unverified_header(&self) -> &JsonWebTokenHeader<JE, JS, JT> {
+        self.0.unverified_header()
+    }
+
+    ///
+    /// Initializes a new signed user info response for the specified client, as returned by a
+    /// provider's user info endpoint when the client registered a
+    /// `userinfo_signed_response_alg`.
+    ///
+    /// This sets the `iss` claim to `issuer` and the `aud` claim to `client_id` (as required for
+    /// signed user info responses by
+    /// [Section 5.3.2](https://openid.net/specs/openid-connect-core-1_0.html#UserInfoResponse) of
+    /// the OpenID Connect Core spec), overriding any values present in `claims`, and then signs
+    /// the claims with the specified key and signing algorithm. The serialized token (see the
+    /// [`Display`](std::fmt::Display) implementation) should be returned with a `Content-Type` of
+    /// `application/jwt`.
+    ///
+    /// If the client registered a `userinfo_encrypted_response_alg`, the response should be
+    /// encrypted using [`encrypt`](Self::encrypt) and returned with the same content type.
+    ///
+    pub fn new_response<JU, K, S>(
+        claims: UserInfoClaims<AC, GC>,
+        issuer: &IssuerUrl,
+        client_id: &ClientId,
+        signing_key: &S,
+        alg: JS,
+    ) -> Result<Self, JsonWebTokenError>
+    where
+        JU: JsonWebKeyUse,
+        K: JsonWebKey<JS, JT, JU>,
+        S: PrivateSigningKey<JS, JT, JU, K>,
+    {
+        let claims = claims
+            .set_issuer(Some(issuer.clone()))
+            .set_audiences(Some(vec![Audience::new((**client_id).clone())]));
+        Self::new(claims, signing_key, alg)
+    }
+
     ///
     /// Verifies and returns the user info claims.
     ///
@@ -434,6 +683,77 @@ where
     {
         Ok(UserInfoClaims(verifier.verified_claims(self.0)?))
     }
+
+    ///
+    /// Encrypts this signed user info response using the given key and algorithms, returning the
+    /// resulting nested JWT in JWE Compact Serialization.
+    ///
+    pub fn encrypt<JK, EK>(
+        &self,
+        encryption_key: &EK,
+        alg: &JK,
+        enc: &JE,
+    ) -> Result<String, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        EK: EncryptionKey<JE, JK, JT>,
+    {
+        self.0.encrypt(encryption_key, alg, enc)
+    }
+
+    ///
+    /// Encrypts this signed user info response in the same manner as
+    /// [`encrypt`](Self::encrypt), compressing it using DEFLATE (`zip=DEF`) before encryption.
+    ///
+    pub fn encrypt_compressed<JK, EK>(
+        &self,
+        encryption_key: &EK,
+        alg: &JK,
+        enc: &JE,
+    ) -> Result<String, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        EK: EncryptionKey<JE, JK, JT>,
+    {
+        self.0.encrypt_compressed(encryption_key, alg, enc)
+    }
+
+    ///
+    /// Decrypts an encrypted user info response (i.e., a nested JWT) using the client's private
+    /// decryption key, returning the inner signed user info response.
+    ///
+    /// The returned user info response must still be verified using [`claims`](Self::claims).
+    ///
+    pub fn decrypt<JK, DK>(nested_jwt: &str, decryption_key: &DK) -> Result<Self, JsonWebTokenError>
+    where
+        JK: JweKeyManagementAlgorithm,
+        DK: DecryptionKey<JE, JK, JT>,
+    {
+        JsonWebToken::decrypt(nested_jwt, decryption_key).map(Self)
+    }
+}
+
+impl<AC, GC, JE, JS, JT> std::fmt::Display for UserInfoJsonWebToken<AC, GC, JE, JS, JT>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let serialized = serde_json::to_value(self)
+            // This should never arise, since we're just asking serde_json to serialize the
+            // signing input concatenated with the signature, both of which are precomputed.
+            .expect("user info JWT serialization failed");
+        f.write_str(
+            serialized
+                .as_str()
+                // This should also never arise, since the JWT serializer always calls
+                // serialize_str.
+                .expect("user info JWT serializer did not produce a str"),
+        )
+    }
 }
 
 new_url_type![
@@ -482,10 +802,16 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::core::CoreGenderClaim;
-    use crate::{AdditionalClaims, UserInfoClaims};
+    use chrono::{TimeZone, Utc};
 
-    use std::collections::HashMap;
+    use crate::core::{CoreClient, CoreGenderClaim, CoreUserInfoClaims};
+    use crate::http::header::HeaderName;
+    use crate::http::{HeaderMap, HeaderValue, Method, StatusCode};
+    use crate::http_utils::MIME_TYPE_JSON;
+    use crate::{
+        AccessToken, AdditionalClaims, AllOtherClaims, AuthUrl, ClientId, HttpRequest,
+        HttpResponse, IssuerUrl, JsonWebKeySet, UserInfoClaims, UserInfoUrl,
+    };
 
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
     struct TestClaims {
@@ -533,10 +859,6 @@ mod tests {
         .expect_err("missing claim should fail to deserialize");
     }
 
-    #[derive(Debug, Deserialize, Serialize)]
-    struct AllOtherClaims(HashMap<String, serde_json::Value>);
-    impl AdditionalClaims for AllOtherClaims {}
-
     #[test]
     fn test_catch_all_additional_claims() {
         let claims = UserInfoClaims::<AllOtherClaims, CoreGenderClaim>::from_json::<
@@ -554,7 +876,218 @@ mod tests {
         )
         .expect("failed to deserialize");
 
-        assert_eq!(claims.additional_claims().0.len(), 1);
-        assert_eq!(claims.additional_claims().0["tfa_method"], "u2f");
+        assert_eq!(claims.additional_claims().len(), 1);
+        assert_eq!(claims.additional_claims()["tfa_method"], "u2f");
+
+        // `updated_at` is a standard claim parsed from seconds since the epoch.
+        assert_eq!(
+            claims.standard_claims().updated_at(),
+            Some(Utc.timestamp_opt(1000, 0).unwrap())
+        );
+        assert_eq!(
+            serde_json::to_value(&claims).unwrap()["updated_at"],
+            serde_json::json!(1000)
+        );
+    }
+
+    fn new_user_info_client() -> CoreClient {
+        CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            None,
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            JsonWebKeySet::default(),
+        )
+    }
+
+    fn user_info_response(
+        expected_method: Method,
+        expected_authorization: Option<&'static str>,
+        expected_body: &'static str,
+    ) -> impl FnOnce(HttpRequest) -> Result<HttpResponse, crate::reqwest::HttpClientError> {
+        move |request| {
+            assert_eq!(request.method, expected_method);
+            assert_eq!(request.url.as_str(), "https://example/userinfo");
+            assert_eq!(
+                request.headers.get(crate::http::header::AUTHORIZATION),
+                expected_authorization
+                    .map(HeaderValue::from_static)
+                    .as_ref()
+            );
+            assert_eq!(request.body, expected_body.as_bytes());
+
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                crate::http::header::CONTENT_TYPE,
+                HeaderValue::from_static(MIME_TYPE_JSON),
+            );
+            Ok(HttpResponse {
+                status_code: StatusCode::OK,
+                headers,
+                body: b"{\"sub\":\"24400320\"}".to_vec(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_user_info_expected_issuer() {
+        use crate::core::{
+            CoreJsonWebKey, CoreJsonWebKeySet, CoreJwsSigningAlgorithm, CoreRsaPrivateSigningKey,
+            CoreUserInfoJsonWebToken,
+        };
+        use crate::http_utils::MIME_TYPE_JWT;
+        use crate::jwt::tests::{TEST_RSA_PRIV_KEY, TEST_RSA_PUB_KEY};
+        use crate::{
+            Audience, ClaimsVerificationError, EmptyAdditionalClaims, StandardClaims,
+            SubjectIdentifier, UserInfoError,
+        };
+
+        let client = CoreClient::new(
+            ClientId::new("aaa".to_string()),
+            None,
+            IssuerUrl::new("https://example".to_string()).unwrap(),
+            AuthUrl::new("https://example/authorize".to_string()).unwrap(),
+            None,
+            Some(UserInfoUrl::new("https://example/userinfo".to_string()).unwrap()),
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .unwrap()]),
+        );
+        let user_info_jwt = CoreUserInfoJsonWebToken::new(
+            UserInfoClaims::new(
+                StandardClaims::new(SubjectIdentifier::new("24400320".to_string())),
+                EmptyAdditionalClaims {},
+            )
+            .set_issuer(Some(
+                IssuerUrl::new("https://aggregator.example".to_string()).unwrap(),
+            ))
+            .set_audiences(Some(vec![Audience::new("aaa".to_string())])),
+            &CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+        let response = |_: HttpRequest| -> Result<HttpResponse, crate::reqwest::HttpClientError> {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                crate::http::header::CONTENT_TYPE,
+                HeaderValue::from_static(MIME_TYPE_JWT),
+            );
+            Ok(HttpResponse {
+                status_code: StatusCode::OK,
+                headers,
+                body: serde_json::to_value(&user_info_jwt)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .as_bytes()
+                    .to_vec(),
+            })
+        };
+
+        match client
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .request::<EmptyAdditionalClaims, CoreGenderClaim, _, _>(response)
+        {
+            Err(UserInfoError::ClaimsVerification(ClaimsVerificationError::InvalidIssuer(_))) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let claims: CoreUserInfoClaims = client
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .set_expected_issuer(IssuerUrl::new("https://aggregator.example".to_string()).unwrap())
+            .request(response)
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
+
+        let claims: CoreUserInfoClaims = client
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .require_issuer_match(false)
+            .request(response)
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
+
+        let (claims, json): (CoreUserInfoClaims, _) = client
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .require_issuer_match(false)
+            .request_with_json(response)
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
+        assert_eq!(json["iss"], "https://aggregator.example");
+    }
+
+    #[test]
+    fn test_user_info_request_with_json() {
+        let (claims, json): (CoreUserInfoClaims, _) = new_user_info_client()
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .request_with_json(
+                |_| -> Result<HttpResponse, crate::reqwest::HttpClientError> {
+                    Ok(HttpResponse {
+                        status_code: StatusCode::OK,
+                        headers: HeaderMap::new(),
+                        body: br#"{"sub":"24400320","vendor":{"tier":"gold"}}"#.to_vec(),
+                    })
+                },
+            )
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
+        assert_eq!(
+            json,
+            serde_json::json!({"sub": "24400320", "vendor": {"tier": "gold"}})
+        );
+    }
+
+    #[test]
+    fn test_user_info_request_method() {
+        let client = new_user_info_client();
+
+        let claims: CoreUserInfoClaims = client
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .request(user_info_response(Method::GET, Some("Bearer token"), ""))
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
+
+        let claims: CoreUserInfoClaims = client
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .use_post()
+            .request(user_info_response(Method::POST, Some("Bearer token"), ""))
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
+
+        // Additional headers are sent alongside those set by this crate.
+        let claims: CoreUserInfoClaims = client
+            .user_info(AccessToken::new("token".to_string()), None)
+            .unwrap()
+            .add_header(
+                HeaderName::from_static("x-correlation-id"),
+                HeaderValue::from_static("abc123"),
+            )
+            .request(|request: HttpRequest| {
+                assert_eq!(request.headers["x-correlation-id"], "abc123");
+                user_info_response(Method::GET, Some("Bearer token"), "")(request)
+            })
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
+
+        let claims: CoreUserInfoClaims = client
+            .user_info(AccessToken::new("to ken&".to_string()), None)
+            .unwrap()
+            .send_access_token_in_body()
+            .request(user_info_response(
+                Method::POST,
+                None,
+                "access_token=to+ken%26",
+            ))
+            .unwrap();
+        assert_eq!(claims.subject().as_str(), "24400320");
     }
 }
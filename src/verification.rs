@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -10,13 +11,16 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::access_token::ACCESS_TOKEN_TYPE;
+use crate::discovery::SignedMetadataClaims;
 use crate::jwt::{JsonWebToken, JsonWebTokenJsonPayloadSerde};
+use crate::logout::LOGOUT_TOKEN_TYPE;
 use crate::user_info::UserInfoClaimsImpl;
 use crate::{
     AdditionalClaims, Audience, AuthenticationContextClass, GenderClaim, IdTokenClaims, IssuerUrl,
-    JsonWebKey, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse, JsonWebTokenAccess,
+    JsonWebKey, JsonWebKeyId, JsonWebKeySet, JsonWebKeyType, JsonWebKeyUse, JsonWebTokenAccess,
     JsonWebTokenAlgorithm, JsonWebTokenHeader, JweContentEncryptionAlgorithm, JwsSigningAlgorithm,
-    Nonce, SubjectIdentifier,
+    JwtAccessTokenClaims, LogoutTokenClaims, Nonce, SubjectIdentifier,
 };
 
 pub(crate) trait AudiencesClaim {
@@ -36,9 +40,15 @@ pub enum ClaimsVerificationError {
     /// Claims have expired.
     #[error("Expired: {0}")]
     Expired(String),
+    /// Access token hash (`at_hash`) claim does not match the access token.
+    #[error("Invalid access token hash: {0}")]
+    InvalidAccessTokenHash(String),
     /// Audience claim is invalid.
     #[error("Invalid audiences: {0}")]
     InvalidAudience(String),
+    /// Authorization code hash (`c_hash`) claim does not match the authorization code.
+    #[error("Invalid authorization code hash: {0}")]
+    InvalidAuthorizationCodeHash(String),
     /// Authorization context class reference (`acr`) claim is invalid.
     #[error("Invalid authorization context class reference: {0}")]
     InvalidAuthContext(String),
@@ -60,6 +70,9 @@ pub enum ClaimsVerificationError {
     /// An unexpected error occurred.
     #[error("{0}")]
     Other(String),
+    /// Claims are malformed (e.g., missing required claims or not valid JSON).
+    #[error("Malformed claims: {0}")]
+    MalformedClaims(String),
     /// Failed to verify the claims signature.
     #[error("Signature verification failed")]
     SignatureVerification(#[source] SignatureVerificationError),
@@ -113,6 +126,14 @@ pub enum SignatureVerificationError {
     Other(String),
 }
 
+// JOSE header parameters registered by RFC 7515 and RFC 7516, which must not be listed in `crit`.
+const REGISTERED_HEADER_PARAMS: &[&str] = &[
+    "alg", "enc", "zip", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit",
+];
+
+type CriticalHeaderHandler<'a> =
+    Arc<dyn Fn(&serde_json::Value) -> Result<(), String> + 'a + Send + Sync>;
+
 // This struct is intentionally private.
 #[derive(Clone)]
 struct JwtClaimsVerifier<'a, JS, JT, JU, K>
@@ -123,14 +144,19 @@ where
     K: JsonWebKey<JS, JT, JU>,
 {
     allowed_algs: Option<HashSet<JS>>,
+    allow_unsigned: bool,
     aud_match_required: bool,
     client_id: ClientId,
     client_secret: Option<ClientSecret>,
     iss_required: bool,
     issuer: IssuerUrl,
     is_signature_check_enabled: bool,
+    normalize_issuer_scheme: bool,
     other_aud_verifier_fn: Arc<dyn Fn(&Audience) -> bool + 'a + Send + Sync>,
-    signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    other_iss_verifier_fn: Arc<dyn Fn(&IssuerUrl) -> bool + 'a + Send + Sync>,
+    critical_header_handlers: HashMap<String, CriticalHeaderHandler<'a>>,
+    explicit_type_required: bool,
+    signature_keys: Cow<'a, JsonWebKeySet<JS, JT, JU, K>>,
 }
 impl<'a, JS, JT, JU, K> JwtClaimsVerifier<'a, JS, JT, JU, K>
 where
@@ -146,18 +172,41 @@ where
     ) -> Self {
         JwtClaimsVerifier {
             allowed_algs: Some([JS::rsa_sha_256()].iter().cloned().collect()),
+            allow_unsigned: false,
             aud_match_required: true,
             client_id,
             client_secret: None,
             iss_required: true,
             issuer,
             is_signature_check_enabled: true,
+            normalize_issuer_scheme: false,
             // Secure default: reject all other audiences as untrusted, since any other audience
             // can potentially impersonate the user when by sending its copy of these claims
             // to this relying party.
             other_aud_verifier_fn: Arc::new(|_| false),
-            signature_keys,
+            other_iss_verifier_fn: Arc::new(|_| false),
+            critical_header_handlers: HashMap::new(),
+            explicit_type_required: false,
+            signature_keys: Cow::Owned(signature_keys),
+        }
+    }
+
+    // Initializes a verifier that only verifies signatures (along with the JOSE header), and not
+    // the issuer or audiences.
+    fn new_signature_only(signature_keys: &'a JsonWebKeySet<JS, JT, JU, K>) -> Self {
+        let empty_issuer = IssuerUrl::new("https://0.0.0.0".to_owned())
+            .expect("Creating empty issuer url mustn't fail");
+        Self {
+            signature_keys: Cow::Borrowed(signature_keys),
+            ..Self::new(
+                ClientId::new(String::new()),
+                empty_issuer,
+                JsonWebKeySet::default(),
+            )
         }
+        .require_audience_match(false)
+        .require_issuer_match(false)
+        .allow_any_alg()
     }
 
     pub fn require_audience_match(mut self, aud_required: bool) -> Self {
@@ -170,11 +219,27 @@ where
         self
     }
 
+    pub fn set_issuer(mut self, issuer: IssuerUrl) -> Self {
+        self.issuer = issuer;
+        self
+    }
+
     pub fn require_signature_check(mut self, sig_required: bool) -> Self {
         self.is_signature_check_enabled = sig_required;
         self
     }
 
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn insecure_allow_unsigned(mut self, allow_unsigned: bool) -> Self {
+        self.allow_unsigned = allow_unsigned;
+        self
+    }
+
+    pub fn normalize_issuer_scheme(mut self, normalize: bool) -> Self {
+        self.normalize_issuer_scheme = normalize;
+        self
+    }
+
     pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
     where
         I: IntoIterator<Item = JS>,
@@ -200,21 +265,80 @@ where
         self
     }
 
-    fn validate_jose_header<JE>(
+    pub fn set_other_issuer_verifier_fn<T>(mut self, other_iss_verifier_fn: T) -> Self
+    where
+        T: Fn(&IssuerUrl) -> bool + 'a + Send + Sync,
+    {
+        self.other_iss_verifier_fn = Arc::new(other_iss_verifier_fn);
+        self
+    }
+
+    pub fn set_critical_header_handler<T>(mut self, name: String, handler: T) -> Self
+    where
+        T: Fn(&serde_json::Value) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.critical_header_handlers
+            .insert(name, Arc::new(handler));
+        self
+    }
+
+    pub fn require_explicit_type(mut self, explicit_type_required: bool) -> Self {
+        self.explicit_type_required = explicit_type_required;
+        self
+    }
+
+    // Validates the 'typ' header field against the explicit type of the expected kind of token
+    // (e.g., `logout+jwt`), if any, to prevent one kind of token from being substituted for
+    // another (see https://tools.ietf.org/html/rfc8725#section-3.11).
+    fn validate_jwt_type<JE>(
+        &self,
         jose_header: &JsonWebTokenHeader<JE, JS, JT>,
+        explicit_type: Option<&str>,
     ) -> Result<(), ClaimsVerificationError>
     where
         JE: JweContentEncryptionAlgorithm<JT>,
     {
-        // The 'typ' header field must either be omitted or have the canonicalized value JWT.
-        if let Some(ref jwt_type) = jose_header.typ {
-            if jwt_type.to_uppercase() != "JWT" {
+        match (jose_header.typ.as_ref(), explicit_type) {
+            (Some(jwt_type), _) => {
+                // Per https://tools.ietf.org/html/rfc7515#section-4.1.9, the `application/` prefix
+                // may be omitted, and media types are compared case-insensitively.
+                let normalized = jwt_type
+                    .get(..12)
+                    .filter(|prefix| prefix.eq_ignore_ascii_case("application/"))
+                    .map(|_| &jwt_type[12..])
+                    .unwrap_or(jwt_type.as_str());
+                let is_expected = match explicit_type {
+                    Some(explicit_type) if normalized.eq_ignore_ascii_case(explicit_type) => true,
+                    // Tokens that predate explicit typing may use the generic JWT type unless
+                    // explicit types are required.
+                    Some(_) if self.explicit_type_required => false,
+                    _ => normalized.eq_ignore_ascii_case("JWT"),
+                };
+                if !is_expected {
+                    return Err(ClaimsVerificationError::Unsupported(format!(
+                        "unexpected or unsupported JWT type `{}`",
+                        **jwt_type
+                    )));
+                }
+            }
+            (None, Some(explicit_type)) if self.explicit_type_required => {
                 return Err(ClaimsVerificationError::Unsupported(format!(
-                    "unexpected or unsupported JWT type `{}`",
-                    **jwt_type
+                    "missing JWT type (expected `{}`)",
+                    explicit_type
                 )));
             }
+            (None, _) => {}
         }
+        Ok(())
+    }
+
+    fn validate_jose_header<JE>(
+        &self,
+        jose_header: &JsonWebTokenHeader<JE, JS, JT>,
+    ) -> Result<(), ClaimsVerificationError>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
         // The 'cty' header field must be omitted, since it's only used for JWTs that contain
         // content types other than JSON-encoded claims. This may include nested JWTs, such as if
         // JWE encryption is used. This is currently unsupported.
@@ -231,19 +355,95 @@ where
             }
         }
 
-        // If 'crit' fields are specified, we must reject any we do not understand. Since this
-        // implementation doesn't understand any of them, unconditionally reject the JWT. Note that
-        // the spec prohibits this field from containing any of the standard headers or being empty.
-        if jose_header.crit.is_some() {
-            // https://tools.ietf.org/html/rfc7515#appendix-E
-            return Err(ClaimsVerificationError::Unsupported(
-                "critical JWT header fields are unsupported".to_string(),
-            ));
+        // If 'crit' fields are specified, we must reject any we do not understand (see
+        // https://tools.ietf.org/html/rfc7515#section-4.1.11). This implementation doesn't
+        // understand any of them, so each one must have a handler registered by the application.
+        // Note that the spec prohibits this field from containing any of the standard headers or
+        // being empty.
+        if let Some(ref crit) = jose_header.crit {
+            if crit.is_empty() {
+                return Err(ClaimsVerificationError::Unsupported(
+                    "critical JWT header fields must not be empty".to_string(),
+                ));
+            }
+            for name in crit {
+                if REGISTERED_HEADER_PARAMS.contains(&name.as_str()) {
+                    return Err(ClaimsVerificationError::Unsupported(format!(
+                        "critical JWT header fields must not include registered header `{}`",
+                        name
+                    )));
+                }
+                // https://tools.ietf.org/html/rfc7515#appendix-E
+                let handler = self.critical_header_handlers.get(name).ok_or_else(|| {
+                    ClaimsVerificationError::Unsupported(format!(
+                        "critical JWT header fields are unsupported: `{}`",
+                        name
+                    ))
+                })?;
+                let value = jose_header.other.get(name).ok_or_else(|| {
+                    ClaimsVerificationError::Unsupported(format!(
+                        "critical JWT header field `{}` is missing",
+                        name
+                    ))
+                })?;
+                handler(value).map_err(|err| {
+                    ClaimsVerificationError::Unsupported(format!(
+                        "invalid critical JWT header field `{}`: {}",
+                        name, err
+                    ))
+                })?;
+            }
         }
         Ok(())
     }
 
     pub fn verified_claims<A, C, JE, T>(&self, jwt: A) -> Result<T, ClaimsVerificationError>
+    where
+        A: JsonWebTokenAccess<JE, JS, JT, C, ReturnType = T>,
+        C: AudiencesClaim + Debug + DeserializeOwned + IssuerClaim + Serialize,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        T: AudiencesClaim + IssuerClaim,
+    {
+        self.verified_claims_of_type(jwt, None)
+    }
+
+    // Like `verified_claims`, but also returns information about the key that verified the JWT's
+    // signature, or `None` if signature verification is disabled or an unsigned JWT was allowed.
+    pub fn verified_claims_and_signature<A, C, JE, T>(
+        &self,
+        jwt: A,
+    ) -> Result<(T, Option<VerifiedSignature<JS, JT>>), ClaimsVerificationError>
+    where
+        A: JsonWebTokenAccess<JE, JS, JT, C, ReturnType = T>,
+        C: AudiencesClaim + Debug + DeserializeOwned + IssuerClaim + Serialize,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        T: AudiencesClaim + IssuerClaim,
+    {
+        self.verified_claims_and_signature_of_type(jwt, None)
+    }
+
+    // Verifies the claims of a JWT whose `typ` header field must either match the given explicit
+    // type or (unless explicit types are required) be omitted or have the generic value JWT.
+    pub fn verified_claims_of_type<A, C, JE, T>(
+        &self,
+        jwt: A,
+        explicit_type: Option<&str>,
+    ) -> Result<T, ClaimsVerificationError>
+    where
+        A: JsonWebTokenAccess<JE, JS, JT, C, ReturnType = T>,
+        C: AudiencesClaim + Debug + DeserializeOwned + IssuerClaim + Serialize,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        T: AudiencesClaim + IssuerClaim,
+    {
+        self.verified_claims_and_signature_of_type(jwt, explicit_type)
+            .map(|(claims, _)| claims)
+    }
+
+    fn verified_claims_and_signature_of_type<A, C, JE, T>(
+        &self,
+        jwt: A,
+        explicit_type: Option<&str>,
+    ) -> Result<(T, Option<VerifiedSignature<JS, JT>>), ClaimsVerificationError>
     where
         A: JsonWebTokenAccess<JE, JS, JT, C, ReturnType = T>,
         C: AudiencesClaim + Debug + DeserializeOwned + IssuerClaim + Serialize,
@@ -252,7 +452,8 @@ where
     {
         {
             let jose_header = jwt.unverified_header();
-            Self::validate_jose_header(jose_header)?;
+            self.validate_jwt_type(jose_header, explicit_type)?;
+            self.validate_jose_header(jose_header)?;
 
             // The code below roughly follows the validation steps described in
             // https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
@@ -277,20 +478,17 @@ where
         {
             // 2. The Issuer Identifier for the OpenID Provider (which is typically obtained during
             //    Discovery) MUST exactly match the value of the iss (issuer) Claim.
-            let unverified_claims = jwt.unverified_payload_ref();
+            let unverified_claims = if self.normalize_issuer_scheme {
+                jwt.unverified_payload_ref_normalizing_issuer()?
+            } else {
+                jwt.unverified_payload_ref()?
+            };
             if self.iss_required {
-                if let Some(issuer) = unverified_claims.issuer() {
-                    if *issuer != self.issuer {
-                        return Err(ClaimsVerificationError::InvalidIssuer(format!(
-                            "expected `{}` (found `{}`)",
-                            *self.issuer, **issuer
-                        )));
-                    }
-                } else {
-                    return Err(ClaimsVerificationError::InvalidIssuer(
-                        "missing issuer claim".to_string(),
-                    ));
-                }
+                verify_issuer(
+                    unverified_claims,
+                    &self.issuer,
+                    &*self.other_iss_verifier_fn,
+                )?;
             }
 
             // 3. The Client MUST validate that the aud (audience) Claim contains its client_id value
@@ -299,38 +497,11 @@ where
             //    rejected if the ID Token does not list the Client as a valid audience, or if it
             //    contains additional audiences not trusted by the Client.
             if self.aud_match_required {
-                if let Some(audiences) = unverified_claims.audiences() {
-                    if !audiences
-                        .iter()
-                        .any(|aud| (**aud).deref() == self.client_id.deref())
-                    {
-                        return Err(ClaimsVerificationError::InvalidAudience(format!(
-                            "must contain `{}` (found audiences: {})",
-                            *self.client_id,
-                            audiences
-                                .iter()
-                                .map(|aud| format!("`{}`", Deref::deref(aud)))
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        )));
-                    } else if audiences.len() > 1 {
-                        audiences
-                            .iter()
-                            .filter(|aud| (**aud).deref() != self.client_id.deref())
-                            .find(|aud| !(self.other_aud_verifier_fn)(aud))
-                            .map(|aud| {
-                                Err(ClaimsVerificationError::InvalidAudience(format!(
-                                    "`{}` is not a trusted audience",
-                                    **aud,
-                                )))
-                            })
-                            .unwrap_or(Ok(()))?;
-                    }
-                } else {
-                    return Err(ClaimsVerificationError::InvalidAudience(
-                        "missing audiences claim".to_string(),
-                    ));
-                }
+                verify_audiences(
+                    unverified_claims,
+                    &self.client_id,
+                    &*self.other_aud_verifier_fn,
+                )?;
             }
         }
         // Steps 4--5 (azp claim validation) are specific to the ID token.
@@ -341,7 +512,7 @@ where
         //    signature of all other ID Tokens according to JWS [JWS] using the algorithm specified
         //    in the JWT alg Header Parameter. The Client MUST use the keys provided by the Issuer.
         if !self.is_signature_check_enabled {
-            return Ok(jwt.unverified_payload());
+            return Ok((jwt.unverified_payload()?, None));
         }
 
         // Borrow the header again. We had to drop the reference above to allow for the
@@ -357,7 +528,11 @@ where
             //
             // While there's technically a use case where this is ok, we choose not to
             // support it for now to protect against accidental misuse. If demand arises,
-            // we can figure out a API that mitigates the risk.
+            // we can figure out a API that mitigates the risk. Unsigned JWTs are only accepted
+            // when explicitly allowed for testing.
+            JsonWebTokenAlgorithm::None if self.allow_unsigned => {
+                return Ok((jwt.unverified_payload()?, None))
+            }
             JsonWebTokenAlgorithm::None => return Err(ClaimsVerificationError::NoSignature),
         }
         .clone();
@@ -392,6 +567,39 @@ where
         // JOSE header, as an attacker could manipulate these while forging the JWT. The code
         // below must be secure regardless of how these fields are manipulated.
 
+        let (claims, key_id, key_type) =
+            match self.signature_key(jwt.unverified_header(), &signature_alg)? {
+                SignatureKey::Shared(key) => {
+                    let key_type = key.key_type().clone();
+                    (jwt.payload(&signature_alg, &key)?, None, key_type)
+                }
+                SignatureKey::Public(key) => (
+                    jwt.payload(&signature_alg, key)?,
+                    key.key_id().cloned(),
+                    key.key_type().clone(),
+                ),
+            };
+        Ok((
+            claims,
+            Some(VerifiedSignature {
+                alg: signature_alg,
+                key_id,
+                key_type,
+            }),
+        ))
+
+        // Steps 9--13 are specific to the ID token.
+    }
+
+    // Selects the key used to verify a JWT signed with the given algorithm.
+    fn signature_key<JE>(
+        &self,
+        jose_header: &JsonWebTokenHeader<JE, JS, JT>,
+        signature_alg: &JS,
+    ) -> Result<SignatureKey<'_, K>, ClaimsVerificationError>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
         if signature_alg.uses_shared_secret() {
             // 8. If the JWT alg Header Parameter uses a MAC based algorithm such as HS256,
             //    HS384, or HS512, the octets of the UTF-8 representation of the client_secret
@@ -400,10 +608,9 @@ where
             //    is unspecified if the aud is multi-valued or if an azp value is present that
             //    is different than the aud value.
             if let Some(ref client_secret) = self.client_secret {
-                let key = K::new_symmetric(client_secret.secret().clone().into_bytes());
-                return jwt
-                    .payload(&signature_alg, &key)
-                    .map_err(ClaimsVerificationError::SignatureVerification);
+                return Ok(SignatureKey::Shared(K::new_symmetric(
+                    client_secret.secret().clone().into_bytes(),
+                )));
             } else {
                 // The client secret isn't confidential for public clients, so anyone can forge a
                 // JWT with a valid signature.
@@ -419,26 +626,12 @@ where
         // if the JWK set contains more than one public key.
 
         // See if any key has a matching key ID (if supplied) and compatible type.
-        let public_keys = {
-            let jose_header = jwt.unverified_header();
-            self.signature_keys
-                .keys()
-                .iter()
-                .filter(|key|
-                    // The key must be of the type expected for this signature algorithm.
-                    Some(key.key_type()) == signature_alg.key_type().as_ref() &&
-                        // Either the key hasn't specified it's allowed usage (in which case
-                        // any usage is acceptable), or the key supports signing.
-                        (key.key_use().is_none() ||
-                            key.key_use().iter().any(
-                                |key_use| key_use.allows_signature()
-                            )) &&
-                        // Either the JWT doesn't include a 'kid' (in which case any 'kid'
-                        // is acceptable), or the 'kid' matches the key's ID.
-                        (jose_header.kid.is_none() ||
-                            jose_header.kid.as_ref() == key.key_id()))
-                .collect::<Vec<&K>>()
-        };
+        // The key must be of the type expected for this signature algorithm, and must either
+        // support signing or not specify its allowed usage (in which case any usage is
+        // acceptable). If the JWT includes a 'kid', only keys with a matching key ID are eligible.
+        let public_keys = self
+            .signature_keys
+            .signing_keys_for_alg(signature_alg, jose_header.kid.as_ref());
         if public_keys.is_empty() {
             return Err(ClaimsVerificationError::SignatureVerification(
                 SignatureVerificationError::NoMatchingKey,
@@ -468,13 +661,139 @@ where
             ));
         }
 
-        jwt.payload(
-            &signature_alg.clone(),
+        Ok(SignatureKey::Public(
             *public_keys.first().expect("unreachable"),
-        )
-        .map_err(ClaimsVerificationError::SignatureVerification)
+        ))
+    }
+}
 
-        // Steps 9--13 are specific to the ID token.
+// Verifies that the `iss` claim matches the expected issuer (or is otherwise trusted).
+fn verify_issuer<C>(
+    claims: &C,
+    expected_issuer: &IssuerUrl,
+    other_iss_verifier_fn: &dyn Fn(&IssuerUrl) -> bool,
+) -> Result<(), ClaimsVerificationError>
+where
+    C: IssuerClaim,
+{
+    let issuer = claims.issuer().ok_or_else(|| {
+        ClaimsVerificationError::InvalidIssuer("missing issuer claim".to_string())
+    })?;
+    if *issuer != *expected_issuer && !other_iss_verifier_fn(issuer) {
+        return Err(ClaimsVerificationError::InvalidIssuer(format!(
+            "expected `{}` (found `{}`)",
+            **expected_issuer, **issuer
+        )));
+    }
+    Ok(())
+}
+
+// Verifies that the `aud` claim contains the client ID and that any other audiences are trusted.
+fn verify_audiences<C>(
+    claims: &C,
+    client_id: &ClientId,
+    other_aud_verifier_fn: &dyn Fn(&Audience) -> bool,
+) -> Result<(), ClaimsVerificationError>
+where
+    C: AudiencesClaim,
+{
+    let audiences = claims.audiences().ok_or_else(|| {
+        ClaimsVerificationError::InvalidAudience("missing audiences claim".to_string())
+    })?;
+    if !audiences.iter().any(|aud| *aud == *client_id) {
+        return Err(ClaimsVerificationError::InvalidAudience(format!(
+            "must contain `{}` (found audiences: {})",
+            **client_id,
+            audiences
+                .iter()
+                .map(|aud| format!("`{}`", Deref::deref(aud)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    audiences
+        .iter()
+        .filter(|aud| **aud != *client_id)
+        .find(|aud| !other_aud_verifier_fn(aud))
+        .map(|aud| {
+            Err(ClaimsVerificationError::InvalidAudience(format!(
+                "`{}` is not a trusted audience",
+                **aud,
+            )))
+        })
+        .unwrap_or(Ok(()))
+}
+
+// Key used to verify a JWT signature.
+enum SignatureKey<'k, K> {
+    // Symmetric key derived from the client secret.
+    Shared(K),
+    // Public key from the provider's JSON Web Key Set.
+    Public(&'k K),
+}
+
+// Verifies the signature of the given JWT using the given keys, without validating its claims.
+#[allow(clippy::type_complexity)]
+pub(crate) fn verify_signature<'b, AC, GC, JE, JS, JT, JU, K>(
+    jwt: &'b JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
+    signature_keys: &JsonWebKeySet<JS, JT, JU, K>,
+) -> Result<(&'b IdTokenClaims<AC, GC>, VerifiedSignature<JS, JT>), ClaimsVerificationError>
+where
+    AC: AdditionalClaims,
+    GC: GenderClaim,
+    JE: JweContentEncryptionAlgorithm<JT>,
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    let (claims, signature) =
+        JwtClaimsVerifier::new_signature_only(signature_keys).verified_claims_and_signature(jwt)?;
+    Ok((claims, signature.expect("signature check is enabled")))
+}
+
+///
+/// Information about the key used to verify a JSON Web Token's signature.
+///
+/// This is useful for audit logging and for detecting when a provider begins signing tokens with
+/// an unexpected key (see [`IdToken::claims_with_signature`](crate::IdToken::claims_with_signature)).
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedSignature<JS, JT>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    alg: JS,
+    key_id: Option<JsonWebKeyId>,
+    key_type: JT,
+}
+impl<JS, JT> VerifiedSignature<JS, JT>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+{
+    ///
+    /// Returns the signature algorithm used to sign the token.
+    ///
+    pub fn alg(&self) -> &JS {
+        &self.alg
+    }
+
+    ///
+    /// Returns the ID (`kid`) of the key that verified the signature, if the key has one.
+    ///
+    /// Keys derived from the client secret (i.e., for HMAC signatures) have no ID.
+    ///
+    pub fn key_id(&self) -> Option<&JsonWebKeyId> {
+        self.key_id.as_ref()
+    }
+
+    ///
+    /// Returns the type of the key that verified the signature.
+    ///
+    pub fn key_type(&self) -> &JT {
+        &self.key_type
     }
 }
 
@@ -524,14 +843,8 @@ where
     JU: JsonWebKeyUse,
     K: JsonWebKey<JS, JT, JU>,
 {
-    acr_verifier_fn:
-        Arc<dyn Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + 'a + Send + Sync>,
-    #[allow(clippy::type_complexity)]
-    auth_time_verifier_fn:
-        Arc<dyn Fn(Option<DateTime<Utc>>) -> Result<(), String> + 'a + Send + Sync>,
-    iat_verifier_fn: Arc<dyn Fn(DateTime<Utc>) -> Result<(), String> + 'a + Send + Sync>,
+    claims_policy: IdTokenClaimsPolicy<'a>,
     jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
-    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
 }
 impl<'a, JS, JT, JU, K> IdTokenVerifier<'a, JS, JT, JU, K>
 where
@@ -542,14 +855,8 @@ where
 {
     fn new(jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>) -> Self {
         IdTokenVerifier {
-            // By default, accept authorization context reference (acr claim).
-            acr_verifier_fn: Arc::new(|_| Ok(())),
-            auth_time_verifier_fn: Arc::new(|_| Ok(())),
-            // By default, accept any issued time (iat claim).
-            iat_verifier_fn: Arc::new(|_| Ok(())),
+            claims_policy: IdTokenClaimsPolicy::new(),
             jwt_verifier,
-            // By default, use the current system time.
-            time_fn: Arc::new(Utc::now),
         }
     }
 
@@ -629,7 +936,7 @@ where
     where
         T: Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + 'a + Send + Sync,
     {
-        self.acr_verifier_fn = Arc::new(acr_verifier_fn);
+        self.claims_policy.acr_verifier_fn = Arc::new(acr_verifier_fn);
         self
     }
 
@@ -643,7 +950,24 @@ where
     where
         T: Fn(Option<DateTime<Utc>>) -> Result<(), String> + 'a + Send + Sync,
     {
-        self.auth_time_verifier_fn = Arc::new(auth_time_verifier_fn);
+        self.claims_policy.auth_time_verifier_fn = Arc::new(auth_time_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies the subject (`sub` claim) that the ID token must contain.
+    ///
+    /// The subject identifier for an End-User is stable for a given client, including when
+    /// pairwise subject identifiers are used. This option is useful for verifying that ID tokens
+    /// returned by subsequent requests (e.g., refreshed ID tokens, which are required to have the
+    /// same subject as the original ID token by
+    /// [Section 12.2](https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse)
+    /// of the OpenID Connect Core spec) identify the same End-User. To perform the same check on
+    /// user info responses, pass the expected subject to
+    /// [`Client::user_info`](crate::Client::user_info).
+    ///
+    pub fn set_expected_subject(mut self, expected_subject: SubjectIdentifier) -> Self {
+        self.claims_policy.expected_subject = Some(expected_subject);
         self
     }
 
@@ -672,6 +996,23 @@ where
         self
     }
 
+    ///
+    /// Accepts unsigned ID tokens (i.e., those using the `none` algorithm).
+    ///
+    /// This is intended for tests and local mock providers, so that test fixtures don't require
+    /// real signatures. Unlike
+    /// [`insecure_disable_signature_check`](Self::insecure_disable_signature_check), the
+    /// signatures of signed ID tokens are still verified.
+    ///
+    /// **DO NOT USE IN PRODUCTION.** Anyone can forge an unsigned ID token. This function is only
+    /// available with the `test-util` feature enabled.
+    ///
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn insecure_allow_unsigned_for_tests(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.insecure_allow_unsigned(true);
+        self
+    }
+
     ///
     /// Specifies whether the issuer claim must match the expected issuer URL for the provider.
     ///
@@ -680,6 +1021,22 @@ where
         self
     }
 
+    ///
+    /// Specifies whether an issuer claim without a URL scheme should be treated as if it used the
+    /// `https` scheme when comparing it to the expected issuer URL for the provider.
+    ///
+    /// Some providers (notably Google) have historically issued ID tokens whose `iss` claim is
+    /// `accounts.google.com`, while their discovery document reports
+    /// `https://accounts.google.com`. Such issuers aren't valid URLs, so these ID tokens fail to
+    /// parse (and are rejected) unless this option is enabled. When enabled, the issuer is parsed
+    /// as if it used the `https` scheme, which is also reflected in the returned claims. This
+    /// option is disabled by default.
+    ///
+    pub fn normalize_issuer_scheme(mut self, normalize: bool) -> Self {
+        self.jwt_verifier = self.jwt_verifier.normalize_issuer_scheme(normalize);
+        self
+    }
+
     ///
     /// Specifies whether the audience claim must match this client's client ID.
     ///
@@ -697,7 +1054,7 @@ where
     where
         T: Fn() -> DateTime<Utc> + 'a + Send + Sync,
     {
-        self.time_fn = Arc::new(time_fn);
+        self.claims_policy.time_fn = Arc::new(time_fn);
         self
     }
 
@@ -711,7 +1068,7 @@ where
     where
         T: Fn(DateTime<Utc>) -> Result<(), String> + 'a + Send + Sync,
     {
-        self.iat_verifier_fn = Arc::new(iat_verifier_fn);
+        self.claims_policy.iat_verifier_fn = Arc::new(iat_verifier_fn);
         self
     }
 
@@ -735,6 +1092,66 @@ where
         self
     }
 
+    ///
+    /// Specifies a function for verifying `iss` claims that differ from the expected issuer URL
+    /// for the provider.
+    ///
+    /// The function should return `true` if the issuer is trusted, or `false` otherwise. This is
+    /// useful for multi-tenant providers whose issuer varies with the tenant (see
+    /// [`IssuerUrl::matches_template`]). By default, all other issuers are rejected.
+    ///
+    pub fn set_other_issuer_verifier_fn<T>(mut self, other_iss_verifier_fn: T) -> Self
+    where
+        T: Fn(&IssuerUrl) -> bool + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_other_issuer_verifier_fn(other_iss_verifier_fn);
+        self
+    }
+
+    ///
+    /// Registers a handler for the given critical JOSE header parameter.
+    ///
+    /// As required by [Section 4.1.11 of RFC 7515](https://tools.ietf.org/html/rfc7515#section-4.1.11),
+    /// tokens listing header parameters in their `crit` header are rejected unless the recipient
+    /// understands each of those parameters. This library doesn't understand any extension
+    /// parameters, so tokens with a `crit` header are rejected unless a handler has been
+    /// registered for each of the listed parameters.
+    ///
+    /// The handler is passed the parameter's value and should return an error describing why the
+    /// value is unacceptable, if applicable. For example, a handler for the `b64` parameter
+    /// (see [RFC 7797](https://tools.ietf.org/html/rfc7797)) should only accept `true`, since
+    /// unencoded payloads are not supported.
+    ///
+    pub fn set_critical_header_handler<T>(mut self, name: &str, handler: T) -> Self
+    where
+        T: Fn(&serde_json::Value) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_critical_header_handler(name.to_string(), handler);
+        self
+    }
+
+    ///
+    /// Specifies whether tokens with an explicit type must include it in the JOSE header `typ`
+    /// field.
+    ///
+    /// Logout tokens are explicitly typed as `logout+jwt`. By default, logout tokens that omit
+    /// the `typ` field or use the generic `JWT` type are also accepted for compatibility with
+    /// providers that predate explicit typing. Enabling this option rejects such tokens, which
+    /// defends against token-type confusion attacks. ID tokens have no explicit type, so this
+    /// option does not affect them; ID tokens with any `typ` value other than `JWT` are always
+    /// rejected.
+    ///
+    pub fn require_explicit_type(mut self, explicit_type_required: bool) -> Self {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .require_explicit_type(explicit_type_required);
+        self
+    }
+
     pub(super) fn verified_claims<'b, AC, GC, JE, N>(
         &self,
         jwt: &'b JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
@@ -749,11 +1166,35 @@ where
         // The code below roughly follows the validation steps described in
         // https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
 
+        self.verified_claims_with_signature(jwt, nonce_verifier)
+            .map(|(claims, _)| claims)
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub(super) fn verified_claims_with_signature<'b, AC, GC, JE, N>(
+        &self,
+        jwt: &'b JsonWebToken<JE, JS, JT, IdTokenClaims<AC, GC>, JsonWebTokenJsonPayloadSerde>,
+        nonce_verifier: N,
+    ) -> Result<
+        (&'b IdTokenClaims<AC, GC>, Option<VerifiedSignature<JS, JT>>),
+        ClaimsVerificationError,
+    >
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        JE: JweContentEncryptionAlgorithm<JT>,
+        N: NonceVerifier,
+    {
+        // The code below roughly follows the validation steps described in
+        // https://openid.net/specs/openid-connect-core-1_0.html#IDTokenValidation
+
         // Steps 1--3 are handled by the generic JwtClaimsVerifier.
-        let partially_verified_claims = self.jwt_verifier.verified_claims(jwt)?;
+        let (partially_verified_claims, signature) =
+            self.jwt_verifier.verified_claims_and_signature(jwt)?;
 
-        self.verify_claims(partially_verified_claims, nonce_verifier)?;
-        Ok(partially_verified_claims)
+        self.claims_policy
+            .verify(partially_verified_claims, nonce_verifier)?;
+        Ok((partially_verified_claims, signature))
     }
 
     pub(super) fn verified_claims_owned<AC, GC, JE, N>(
@@ -773,29 +1214,271 @@ where
         // Steps 1--3 are handled by the generic JwtClaimsVerifier.
         let partially_verified_claims = self.jwt_verifier.verified_claims(jwt)?;
 
-        self.verify_claims(&partially_verified_claims, nonce_verifier)?;
+        self.claims_policy
+            .verify(&partially_verified_claims, nonce_verifier)?;
         Ok(partially_verified_claims)
     }
 
-    fn verify_claims<AC, GC, N>(
+    pub(super) fn verified_logout_token_claims<'b, JE>(
         &self,
-        partially_verified_claims: &'_ IdTokenClaims<AC, GC>,
-        nonce_verifier: N,
-    ) -> Result<(), ClaimsVerificationError>
+        jwt: &'b JsonWebToken<JE, JS, JT, LogoutTokenClaims, JsonWebTokenJsonPayloadSerde>,
+    ) -> Result<&'b LogoutTokenClaims, ClaimsVerificationError>
     where
-        AC: AdditionalClaims,
-        GC: GenderClaim,
-        N: NonceVerifier,
+        JE: JweContentEncryptionAlgorithm<JT>,
     {
-        // 4. If the ID Token contains multiple audiences, the Client SHOULD verify that an azp
-        //    Claim is present.
+        // The code below follows the validation steps described in
+        // https://openid.net/specs/openid-connect-backchannel-1_0.html#Validation
 
-        // There is significant confusion and contradiction in the OpenID Connect Core spec around
-        // the azp claim. See https://bitbucket.org/openid/connect/issues/973/ for a detailed
-        // discussion. Given the lack of clarity around how this claim should be used, we defer
-        // any verification of it here until a use case becomes apparent. If such a use case does
-        // arise, we most likely want to allow clients to pass in a function for validating the
-        // azp claim rather than introducing logic that affects all clients of this library.
+        // Steps 1--2 (decryption and signature verification) and the iss and aud checks of step 3
+        // are handled by the generic JwtClaimsVerifier, which also verifies the explicit type.
+        let partially_verified_claims = self
+            .jwt_verifier
+            .verified_claims_of_type(jwt, Some(LOGOUT_TOKEN_TYPE))?;
+
+        // 3. Validate the iss, aud, iat, and exp Claims in the same way they are validated in ID
+        //    Tokens.
+        let cur_time = (*self.claims_policy.time_fn)();
+        if cur_time >= partially_verified_claims.expiration() {
+            return Err(ClaimsVerificationError::Expired(format!(
+                "logout token expired at {} (current time is {})",
+                partially_verified_claims.expiration(),
+                cur_time
+            )));
+        }
+        (*self.claims_policy.iat_verifier_fn)(partially_verified_claims.issue_time())
+            .map_err(ClaimsVerificationError::Expired)?;
+
+        // 4--6. Verify that the Logout Token contains a sub Claim, a sid Claim, or both, that it
+        //       contains an events Claim whose value is a JSON object containing the member name
+        //       http://schemas.openid.net/event/backchannel-logout, and that it does not contain a
+        //       nonce Claim.
+        partially_verified_claims
+            .validate()
+            .map_err(ClaimsVerificationError::Other)?;
+
+        Ok(partially_verified_claims)
+    }
+}
+
+///
+/// Validator for the claims of an ID token whose signature has already been verified.
+///
+/// Unlike [`IdTokenVerifier`], this validator does not verify signatures. It is intended to be
+/// used along with [`IdToken::verify_signature`](crate::IdToken::verify_signature) by
+/// applications that need to verify signatures and validate claims separately (e.g., to apply
+/// custom claim policies to ID tokens signed by a trusted key). By default, the validator applies
+/// the same `iss`, `aud`, `exp`, and nonce checks as [`IdTokenVerifier`].
+///
+#[derive(Clone)]
+pub struct ClaimsValidator<'a> {
+    aud_match_required: bool,
+    claims_policy: IdTokenClaimsPolicy<'a>,
+    client_id: ClientId,
+    iss_required: bool,
+    issuer: IssuerUrl,
+    other_aud_verifier_fn: Arc<dyn Fn(&Audience) -> bool + 'a + Send + Sync>,
+    other_iss_verifier_fn: Arc<dyn Fn(&IssuerUrl) -> bool + 'a + Send + Sync>,
+}
+impl<'a> ClaimsValidator<'a> {
+    ///
+    /// Initializes a new validator for ID tokens issued by `issuer` to the client `client_id`.
+    ///
+    pub fn new(client_id: ClientId, issuer: IssuerUrl) -> Self {
+        ClaimsValidator {
+            aud_match_required: true,
+            claims_policy: IdTokenClaimsPolicy::new(),
+            client_id,
+            iss_required: true,
+            issuer,
+            // Secure default: reject all other audiences and issuers as untrusted.
+            other_aud_verifier_fn: Arc::new(|_| false),
+            other_iss_verifier_fn: Arc::new(|_| false),
+        }
+    }
+
+    ///
+    /// Specifies whether the issuer claim must match the expected issuer URL for the provider.
+    ///
+    pub fn require_issuer_match(mut self, iss_required: bool) -> Self {
+        self.iss_required = iss_required;
+        self
+    }
+
+    ///
+    /// Specifies whether the audience claim must match this client's client ID.
+    ///
+    pub fn require_audience_match(mut self, aud_required: bool) -> Self {
+        self.aud_match_required = aud_required;
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying audiences included in the `aud` claim that differ from
+    /// this client's client ID (see [`IdTokenVerifier::set_other_audience_verifier_fn`]).
+    ///
+    pub fn set_other_audience_verifier_fn<T>(mut self, other_aud_verifier_fn: T) -> Self
+    where
+        T: Fn(&Audience) -> bool + 'a + Send + Sync,
+    {
+        self.other_aud_verifier_fn = Arc::new(other_aud_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying `iss` claims that differ from the expected issuer URL
+    /// for the provider (see [`IdTokenVerifier::set_other_issuer_verifier_fn`]).
+    ///
+    pub fn set_other_issuer_verifier_fn<T>(mut self, other_iss_verifier_fn: T) -> Self
+    where
+        T: Fn(&IssuerUrl) -> bool + 'a + Send + Sync,
+    {
+        self.other_iss_verifier_fn = Arc::new(other_iss_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for verifying the ID token expiration time.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + 'a + Send + Sync,
+    {
+        self.claims_policy.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying the ID token issue time.
+    ///
+    /// The function should return `Ok(())` if the claim is valid, or a string describing the error
+    /// otherwise.
+    ///
+    pub fn set_issue_time_verifier_fn<T>(mut self, iat_verifier_fn: T) -> Self
+    where
+        T: Fn(DateTime<Utc>) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.claims_policy.iat_verifier_fn = Arc::new(iat_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying the `acr` claim.
+    ///
+    /// The function should return `Ok(())` if the claim is valid, or a string describing the error
+    /// otherwise.
+    ///
+    pub fn set_auth_context_verifier_fn<T>(mut self, acr_verifier_fn: T) -> Self
+    where
+        T: Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.claims_policy.acr_verifier_fn = Arc::new(acr_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying the `auth_time` claim.
+    ///
+    /// The function should return `Ok(())` if the claim is valid, or a string describing the error
+    /// otherwise.
+    ///
+    pub fn set_auth_time_verifier_fn<T>(mut self, auth_time_verifier_fn: T) -> Self
+    where
+        T: Fn(Option<DateTime<Utc>>) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.claims_policy.auth_time_verifier_fn = Arc::new(auth_time_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies the subject (`sub` claim) that the ID token must contain.
+    ///
+    /// The subject identifier for an End-User is stable for a given client, including when
+    /// pairwise subject identifiers are used. This option is useful for verifying that ID tokens
+    /// returned by subsequent requests (e.g., refreshed ID tokens, which are required to have the
+    /// same subject as the original ID token by
+    /// [Section 12.2](https://openid.net/specs/openid-connect-core-1_0.html#RefreshTokenResponse)
+    /// of the OpenID Connect Core spec) identify the same End-User. To perform the same check on
+    /// user info responses, pass the expected subject to
+    /// [`Client::user_info`](crate::Client::user_info).
+    ///
+    pub fn set_expected_subject(mut self, expected_subject: SubjectIdentifier) -> Self {
+        self.claims_policy.expected_subject = Some(expected_subject);
+        self
+    }
+
+    ///
+    /// Validates the given ID token claims.
+    ///
+    /// The claims are assumed to belong to an ID token whose signature has already been verified.
+    ///
+    pub fn validate<AC, GC, N>(
+        &self,
+        claims: &IdTokenClaims<AC, GC>,
+        nonce_verifier: N,
+    ) -> Result<(), ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        N: NonceVerifier,
+    {
+        if self.iss_required {
+            verify_issuer(claims, &self.issuer, &*self.other_iss_verifier_fn)?;
+        }
+        if self.aud_match_required {
+            verify_audiences(claims, &self.client_id, &*self.other_aud_verifier_fn)?;
+        }
+        self.claims_policy.verify(claims, nonce_verifier)
+    }
+}
+
+// Policies for the ID token claims that are verified after the signature, issuer, and audiences
+// (i.e., steps 9--13 of the ID token validation).
+#[derive(Clone)]
+struct IdTokenClaimsPolicy<'a> {
+    acr_verifier_fn:
+        Arc<dyn Fn(Option<&AuthenticationContextClass>) -> Result<(), String> + 'a + Send + Sync>,
+    #[allow(clippy::type_complexity)]
+    auth_time_verifier_fn:
+        Arc<dyn Fn(Option<DateTime<Utc>>) -> Result<(), String> + 'a + Send + Sync>,
+    expected_subject: Option<SubjectIdentifier>,
+    iat_verifier_fn: Arc<dyn Fn(DateTime<Utc>) -> Result<(), String> + 'a + Send + Sync>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
+}
+impl<'a> IdTokenClaimsPolicy<'a> {
+    fn new() -> Self {
+        IdTokenClaimsPolicy {
+            // By default, accept authorization context reference (acr claim).
+            acr_verifier_fn: Arc::new(|_| Ok(())),
+            auth_time_verifier_fn: Arc::new(|_| Ok(())),
+            expected_subject: None,
+            // By default, accept any issued time (iat claim).
+            iat_verifier_fn: Arc::new(|_| Ok(())),
+            // By default, use the current system time.
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
+    fn verify<AC, GC, N>(
+        &self,
+        partially_verified_claims: &'_ IdTokenClaims<AC, GC>,
+        nonce_verifier: N,
+    ) -> Result<(), ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        GC: GenderClaim,
+        N: NonceVerifier,
+    {
+        // 4. If the ID Token contains multiple audiences, the Client SHOULD verify that an azp
+        //    Claim is present.
+
+        // There is significant confusion and contradiction in the OpenID Connect Core spec around
+        // the azp claim. See https://bitbucket.org/openid/connect/issues/973/ for a detailed
+        // discussion. Given the lack of clarity around how this claim should be used, we defer
+        // any verification of it here until a use case becomes apparent. If such a use case does
+        // arise, we most likely want to allow clients to pass in a function for validating the
+        // azp claim rather than introducing logic that affects all clients of this library.
 
         // This naive implementation of the spec would almost certainly not be useful in practice:
         /*
@@ -819,6 +1502,19 @@ where
 
         // Steps 6--8 are handled by the generic JwtClaimsVerifier.
 
+        // Verify that the subject matches a previously-verified subject for the same End-User
+        // (e.g., from the original ID token when verifying a refreshed one), which detects
+        // identity drift such as inconsistent pairwise subject identifiers.
+        if let Some(ref expected_subject) = self.expected_subject {
+            if partially_verified_claims.subject() != expected_subject {
+                return Err(ClaimsVerificationError::InvalidSubject(format!(
+                    "expected `{}` (found `{}`)",
+                    expected_subject.as_str(),
+                    partially_verified_claims.subject().as_str()
+                )));
+            }
+        }
+
         // 9. The current time MUST be before the time represented by the exp Claim.
         let cur_time = (*self.time_fn)();
         if cur_time >= partially_verified_claims.expiration() {
@@ -904,6 +1600,32 @@ where
         self.expected_subject.as_ref()
     }
 
+    pub(crate) fn set_expected_issuer(mut self, issuer: IssuerUrl) -> Self {
+        self.jwt_verifier = self.jwt_verifier.set_issuer(issuer);
+        self
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    /// By default, only `RS256` is allowed.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
     ///
     /// Specifies whether the issuer claim must match the expected issuer URL for the provider.
     ///
@@ -912,6 +1634,36 @@ where
         self
     }
 
+    ///
+    /// Specifies a function for verifying `iss` claims that differ from the expected issuer URL
+    /// for the provider.
+    ///
+    /// The function should return `true` if the issuer is trusted, or `false` otherwise.
+    ///
+    pub fn set_other_issuer_verifier_fn<T>(mut self, other_iss_verifier_fn: T) -> Self
+    where
+        T: Fn(&IssuerUrl) -> bool + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_other_issuer_verifier_fn(other_iss_verifier_fn);
+        self
+    }
+
+    ///
+    /// Registers a handler for the given critical JOSE header parameter (see
+    /// [`IdTokenVerifier::set_critical_header_handler`]).
+    ///
+    pub fn set_critical_header_handler<T>(mut self, name: &str, handler: T) -> Self
+    where
+        T: Fn(&serde_json::Value) -> Result<(), String> + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_critical_header_handler(name.to_string(), handler);
+        self
+    }
+
     ///
     /// Specifies whether the audience claim must match this client's client ID.
     ///
@@ -952,15 +1704,231 @@ where
     }
 }
 
+///
+/// Verifier for the `signed_metadata` JWT included in provider metadata (see
+/// [Section 2.1 of RFC 8414](https://tools.ietf.org/html/rfc8414#section-2.1)).
+///
+/// The signing keys must be obtained from a source trusted independently of the provider
+/// metadata itself, since a JWT signed using keys published in the same document provides no
+/// additional assurance.
+///
+#[derive(Clone)]
+pub struct SignedMetadataVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+}
+impl<'a, JS, JT, JU, K> SignedMetadataVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates a signed metadata verifier that trusts metadata signed by `signer` (the
+    /// expected `iss` claim) using any of the given keys.
+    ///
+    pub fn new(signer: IssuerUrl, signature_keys: JsonWebKeySet<JS, JT, JU, K>) -> Self {
+        SignedMetadataVerifier {
+            // Signed metadata has no audience.
+            jwt_verifier: JwtClaimsVerifier::new(
+                ClientId::new(String::new()),
+                signer,
+                signature_keys,
+            )
+            .require_audience_match(false),
+        }
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    /// By default, only `RS256` is allowed.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying `iss` claims that differ from the expected signer.
+    ///
+    /// The function should return `true` if the signer is trusted, or `false` otherwise.
+    ///
+    pub fn set_other_issuer_verifier_fn<T>(mut self, other_iss_verifier_fn: T) -> Self
+    where
+        T: Fn(&IssuerUrl) -> bool + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_other_issuer_verifier_fn(other_iss_verifier_fn);
+        self
+    }
+
+    pub(crate) fn verified_claims<JE>(
+        &self,
+        signed_metadata: JsonWebToken<
+            JE,
+            JS,
+            JT,
+            SignedMetadataClaims,
+            JsonWebTokenJsonPayloadSerde,
+        >,
+    ) -> Result<SignedMetadataClaims, ClaimsVerificationError>
+    where
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
+        self.jwt_verifier.verified_claims(signed_metadata)
+    }
+}
+
+///
+/// Access token verifier for resource servers, as described in
+/// [Section 4](https://tools.ietf.org/html/rfc9068#section-4) of RFC 9068.
+///
+#[derive(Clone)]
+pub struct JwtAccessTokenVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    jwt_verifier: JwtClaimsVerifier<'a, JS, JT, JU, K>,
+    time_fn: Arc<dyn Fn() -> DateTime<Utc> + 'a + Send + Sync>,
+}
+impl<'a, JS, JT, JU, K> JwtAccessTokenVerifier<'a, JS, JT, JU, K>
+where
+    JS: JwsSigningAlgorithm<JT>,
+    JT: JsonWebKeyType,
+    JU: JsonWebKeyUse,
+    K: JsonWebKey<JS, JT, JU>,
+{
+    ///
+    /// Instantiates an access token verifier for the resource server identified by `audience`
+    /// (the expected `aud` claim), trusting access tokens issued by `issuer` and signed using any
+    /// of the given keys (typically the authorization server's JSON Web Key Set).
+    ///
+    pub fn new(
+        audience: Audience,
+        issuer: IssuerUrl,
+        signature_keys: JsonWebKeySet<JS, JT, JU, K>,
+    ) -> Self {
+        JwtAccessTokenVerifier {
+            // RFC 9068 requires access tokens to be explicitly typed.
+            jwt_verifier: JwtClaimsVerifier::new(
+                ClientId::new(audience.to_string()),
+                issuer,
+                signature_keys,
+            )
+            .require_explicit_type(true),
+            // By default, use the current system time.
+            time_fn: Arc::new(Utc::now),
+        }
+    }
+
+    ///
+    /// Specifies which JSON Web Signature algorithms are supported.
+    ///
+    /// By default, only `RS256` is allowed.
+    ///
+    pub fn set_allowed_algs<I>(mut self, algs: I) -> Self
+    where
+        I: IntoIterator<Item = JS>,
+    {
+        self.jwt_verifier = self.jwt_verifier.set_allowed_algs(algs);
+        self
+    }
+
+    ///
+    /// Specifies that any signature algorithm is supported.
+    ///
+    pub fn allow_any_alg(mut self) -> Self {
+        self.jwt_verifier = self.jwt_verifier.allow_any_alg();
+        self
+    }
+
+    ///
+    /// Specifies a function for verifying audiences included in the `aud` claim that differ from
+    /// this resource server's audience.
+    ///
+    /// The function should return `true` if the audience is trusted, or `false` otherwise.
+    ///
+    pub fn set_other_audience_verifier_fn<T>(mut self, other_aud_verifier_fn: T) -> Self
+    where
+        T: Fn(&Audience) -> bool + 'a + Send + Sync,
+    {
+        self.jwt_verifier = self
+            .jwt_verifier
+            .set_other_audience_verifier_fn(other_aud_verifier_fn);
+        self
+    }
+
+    ///
+    /// Specifies a function for returning the current time.
+    ///
+    /// This function is used for verifying the access token expiration time.
+    ///
+    pub fn set_time_fn<T>(mut self, time_fn: T) -> Self
+    where
+        T: Fn() -> DateTime<Utc> + 'a + Send + Sync,
+    {
+        self.time_fn = Arc::new(time_fn);
+        self
+    }
+
+    pub(crate) fn verified_claims<'b, AC, JE>(
+        &self,
+        jwt: &'b JsonWebToken<JE, JS, JT, JwtAccessTokenClaims<AC>, JsonWebTokenJsonPayloadSerde>,
+    ) -> Result<&'b JwtAccessTokenClaims<AC>, ClaimsVerificationError>
+    where
+        AC: AdditionalClaims,
+        JE: JweContentEncryptionAlgorithm<JT>,
+    {
+        // The signature, issuer, audience, and explicit type are verified by the generic
+        // JwtClaimsVerifier.
+        let partially_verified_claims = self
+            .jwt_verifier
+            .verified_claims_of_type(jwt, Some(ACCESS_TOKEN_TYPE))?;
+
+        let cur_time = (*self.time_fn)();
+        if cur_time >= partially_verified_claims.expiration() {
+            return Err(ClaimsVerificationError::Expired(format!(
+                "access token expired at {} (current time is {})",
+                partially_verified_claims.expiration(),
+                cur_time
+            )));
+        }
+        Ok(partially_verified_claims)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
     use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
     use chrono::{TimeZone, Utc};
     use oauth2::{ClientId, ClientSecret};
 
     use super::{
-        AudiencesClaim, ClaimsVerificationError, IssuerClaim, JsonWebTokenHeader,
+        AudiencesClaim, ClaimsValidator, ClaimsVerificationError, IssuerClaim, JsonWebTokenHeader,
         JwtClaimsVerifier, SignatureVerificationError, SubjectIdentifier,
     };
     use crate::core::{
@@ -1005,20 +1973,24 @@ mod tests {
 
     #[test]
     fn test_jose_header() {
+        let signature_keys = CoreJsonWebKeySet::new(vec![]);
+        let verifier = CoreJwtClaimsVerifier::new_signature_only(&signature_keys);
+
         // Unexpected JWT type.
         assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
+            verifier.validate_jwt_type(
                 &serde_json::from_str::<CoreJsonWebTokenHeader>(
                     "{\"alg\":\"RS256\",\"typ\":\"NOT_A_JWT\"}",
                 )
                 .expect("failed to deserialize"),
+                None,
             ),
             "unsupported JWT type",
         );
 
         // Nested JWTs.
         assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
+            verifier.validate_jose_header(
                 &serde_json::from_str::<CoreJsonWebTokenHeader>(
                     "{\"alg\":\"RS256\",\"cty\":\"JWT\"}",
                 )
@@ -1027,7 +1999,7 @@ mod tests {
             "nested JWT",
         );
         assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
+            verifier.validate_jose_header(
                 &serde_json::from_str::<CoreJsonWebTokenHeader>(
                     "{\"alg\":\"RS256\",\"cty\":\"NOT_A_JWT\"}",
                 )
@@ -1038,7 +2010,7 @@ mod tests {
 
         // Critical fields. Adapted from https://tools.ietf.org/html/rfc7515#appendix-E
         assert_unsupported(
-            CoreJwtClaimsVerifier::validate_jose_header(
+            verifier.validate_jose_header(
                 &serde_json::from_str::<CoreJsonWebTokenHeader>(
                     "{\
                      \"alg\":\"RS256\",\
@@ -1052,6 +2024,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_critical_header_handlers() {
+        let signature_keys = CoreJsonWebKeySet::new(vec![]);
+        let verifier = CoreJwtClaimsVerifier::new_signature_only(&signature_keys)
+            .set_critical_header_handler("b64".to_string(), |value| {
+                if *value == serde_json::Value::Bool(true) {
+                    Ok(())
+                } else {
+                    Err("unencoded payloads are unsupported".to_string())
+                }
+            });
+        let header = |json: &str| {
+            serde_json::from_str::<CoreJsonWebTokenHeader>(json).expect("failed to deserialize")
+        };
+
+        verifier
+            .validate_jose_header(&header(r#"{"alg":"RS256","crit":["b64"],"b64":true}"#))
+            .unwrap();
+        assert_eq!(
+            header(r#"{"alg":"RS256","crit":["b64"],"b64":true}"#).other["b64"],
+            serde_json::Value::Bool(true)
+        );
+        assert_unsupported(
+            verifier.validate_jose_header(&header(r#"{"alg":"RS256","crit":["b64"],"b64":false}"#)),
+            "invalid critical JWT header field `b64`: unencoded payloads are unsupported",
+        );
+        assert_unsupported(
+            verifier.validate_jose_header(&header(r#"{"alg":"RS256","crit":["b64"]}"#)),
+            "critical JWT header field `b64` is missing",
+        );
+        assert_unsupported(
+            verifier.validate_jose_header(&header(
+                r#"{"alg":"RS256","crit":["b64","other"],"b64":true,"other":1}"#,
+            )),
+            "critical JWT header fields are unsupported: `other`",
+        );
+        assert_unsupported(
+            verifier.validate_jose_header(&header(r#"{"alg":"RS256","crit":["kid"],"kid":"a"}"#)),
+            "must not include registered header `kid`",
+        );
+        assert_unsupported(
+            verifier.validate_jose_header(&header(r#"{"alg":"RS256","crit":[]}"#)),
+            "must not be empty",
+        );
+    }
+
     #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
     struct TestClaims {
         aud: Option<Vec<Audience>>,
@@ -1255,13 +2273,44 @@ mod tests {
         // "none" algorithm (unsigned JWT).
         match verifier.verified_claims(
             serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
-                "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
-                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ."
+                "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ."
+                    .to_string(),
+            ))
+            .expect("failed to deserialize"),
+        ) {
+            Err(ClaimsVerificationError::NoSignature) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // "none" algorithm explicitly allowed for testing.
+        verifier
+            .clone()
+            .insecure_allow_unsigned(true)
+            .verified_claims(
+                serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                    "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ."
+                        .to_string(),
+                ))
+                .expect("failed to deserialize"),
+            )
+            .expect("verification should succeed");
+        // Signatures of signed JWTs are still verified.
+        match verifier
+            .clone()
+            .insecure_allow_unsigned(true)
+            .verified_claims(
+            serde_json::from_value::<TestClaimsJsonWebToken>(serde_json::Value::String(
+                "eyJhbGciOiJSUzI1NiJ9.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                 S5jb20iLCJwYXlsb2FkIjoiaGVsbG8gd29ybGQifQ.YmFkX2hhc2g"
                     .to_string(),
             ))
             .expect("failed to deserialize"),
         ) {
-            Err(ClaimsVerificationError::NoSignature) => {}
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::CryptoError(_),
+            )) => {}
             other => panic!("unexpected result: {:?}", other),
         }
 
@@ -1634,6 +2683,26 @@ mod tests {
             }
             mock_is_valid_issue_time.store(true, Ordering::Relaxed);
 
+            // Unsigned JWT explicitly allowed for testing.
+            let unsigned_jwt = serde_json::from_value::<CoreIdTokenJwt>(serde_json::Value::String(
+                "eyJhbGciOiJub25lIn0.eyJhdWQiOlsibXlfY2xpZW50Il0sImlzcyI6Imh0dHBzOi8vZXhhbXBsZ\
+                     S5jb20iLCJzdWIiOiJzdWJqZWN0IiwiZXhwIjoxNTQ0OTMyMTQ5LCJpYXQiOjE1NDQ5Mjg1NDl9."
+                    .to_string(),
+            ))
+            .expect("failed to deserialize");
+            match public_client_verifier.verified_claims(&unsigned_jwt, |_: Option<&Nonce>| Ok(()))
+            {
+                Err(ClaimsVerificationError::NoSignature) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            let unsigned_verifier = public_client_verifier
+                .clone()
+                .insecure_allow_unsigned_for_tests();
+            let (_, signature) = unsigned_verifier
+                .verified_claims_with_signature(&unsigned_jwt, |_: Option<&Nonce>| Ok(()))
+                .expect("verification should succeed");
+            assert_eq!(signature, None);
+
             let valid_nonce = Nonce::new("the_nonce".to_string());
 
             // Successful verification w/o checking nonce
@@ -1928,6 +2997,332 @@ mod tests {
             )
             .unwrap();
         assert_eq!(claims, unverified);
+
+        let (verified, signature) = id_token.claims_with_signature(&verifier, &nonce).unwrap();
+        assert_eq!(verified, claims);
+        let signature = signature.unwrap();
+        assert_eq!(
+            *signature.alg(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256
+        );
+        assert_eq!(
+            signature.key_id().map(|kid| kid.as_str()),
+            Some("bilbo.baggins@hobbiton.example")
+        );
+        assert_eq!(*signature.key_type(), CoreJsonWebKeyType::RSA);
+        let (_, signature) = id_token
+            .claims_with_signature(
+                &CoreIdTokenVerifier::new_insecure_without_verification().set_time_fn(time_fn),
+                &nonce,
+            )
+            .unwrap();
+        assert_eq!(signature, None);
+    }
+
+    #[test]
+    fn test_verify_signature_and_validate_claims() {
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let id_token = CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                issuer.clone(),
+                vec![Audience::new("other_client".to_string())],
+                Utc.timestamp_opt(1544932149, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                Utc.timestamp_opt(1544928549, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+                Default::default(),
+            )
+            .set_nonce(Some(nonce.clone())),
+            &rsa_priv_key,
+            CoreJwsSigningAlgorithm::RsaSsaPssSha256,
+            None,
+            None,
+        )
+        .unwrap();
+        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+        let jwks = CoreJsonWebKeySet::new(vec![rsa_pub_key]);
+
+        // The signature is verified regardless of the claims (e.g., the audience).
+        let (claims, signature) = id_token.verify_signature(&jwks).unwrap();
+        assert_eq!(
+            *claims.subject(),
+            SubjectIdentifier::new("subject".to_string())
+        );
+        assert_eq!(*signature.alg(), CoreJwsSigningAlgorithm::RsaSsaPssSha256);
+        assert_eq!(
+            signature.key_id().map(|kid| kid.as_str()),
+            Some("bilbo.baggins@hobbiton.example")
+        );
+        match CoreIdToken::from_str(&format!("{}AA", id_token.to_string()))
+            .unwrap()
+            .verify_signature(&jwks)
+        {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::CryptoError(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match id_token.verify_signature(&CoreJsonWebKeySet::new(vec![])) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let time_fn = || {
+            Utc.timestamp_opt(1544932148, 0)
+                .single()
+                .expect("valid timestamp")
+        };
+        let validator = ClaimsValidator::new(client_id, issuer).set_time_fn(time_fn);
+        match validator.validate(claims, &nonce) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let validator = validator.set_other_audience_verifier_fn(|_| true);
+        match validator.validate(claims, &nonce) {
+            Err(ClaimsVerificationError::InvalidAudience(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let validator = validator.require_audience_match(false);
+        validator.validate(claims, &nonce).unwrap();
+        match validator.validate(claims, &Nonce::new("other_nonce".to_string())) {
+            Err(ClaimsVerificationError::InvalidNonce(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match validator
+            .clone()
+            .set_time_fn(|| {
+                Utc.timestamp_opt(1544932149, 0)
+                    .single()
+                    .expect("valid timestamp")
+            })
+            .validate(claims, &nonce)
+        {
+            Err(ClaimsVerificationError::Expired(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match ClaimsValidator::new(
+            ClientId::new("other_client".to_string()),
+            IssuerUrl::new("https://attacker.com".to_string()).unwrap(),
+        )
+        .set_time_fn(time_fn)
+        .validate(claims, &nonce)
+        {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_id_token_expected_subject() {
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let id_token = CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                issuer.clone(),
+                vec![Audience::new("my_client".to_string())],
+                Utc.timestamp_opt(1544932149, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                Utc.timestamp_opt(1544928549, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                StandardClaims::new(SubjectIdentifier::new("pairwise_subject".to_string())),
+                Default::default(),
+            ),
+            &CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap(),
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap();
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            ClientId::new("my_client".to_string()),
+            issuer,
+            CoreJsonWebKeySet::new(vec![serde_json::from_str::<CoreJsonWebKey>(
+                TEST_RSA_PUB_KEY,
+            )
+            .expect("deserialization failed")]),
+        )
+        .set_time_fn(|| {
+            Utc.timestamp_opt(1544928550, 0)
+                .single()
+                .expect("valid timestamp")
+        });
+
+        id_token
+            .claims(
+                &verifier
+                    .clone()
+                    .set_expected_subject(SubjectIdentifier::new("pairwise_subject".to_string())),
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .unwrap();
+        match id_token.claims(
+            &verifier.set_expected_subject(SubjectIdentifier::new("other_subject".to_string())),
+            |_: Option<&Nonce>| Ok(()),
+        ) {
+            Err(ClaimsVerificationError::InvalidSubject(msg)) => {
+                assert_eq!(msg, "expected `other_subject` (found `pairwise_subject`)")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_id_token_other_issuer() {
+        let client_id = ClientId::new("my_client".to_string());
+        let tenant_issuer =
+            IssuerUrl::new("https://login.microsoftonline.com/the_tenant/v2.0".to_string())
+                .unwrap();
+        let template_issuer =
+            IssuerUrl::new("https://login.microsoftonline.com/{tenantid}/v2.0".to_string())
+                .unwrap();
+        let nonce = Nonce::new("the_nonce".to_string());
+        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+
+        let id_token = CoreIdToken::new(
+            CoreIdTokenClaims::new(
+                tenant_issuer,
+                vec![Audience::new((*client_id).clone())],
+                Utc.timestamp_opt(1544932149, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                Utc.timestamp_opt(1544928549, 0)
+                    .single()
+                    .expect("valid timestamp"),
+                StandardClaims::new(SubjectIdentifier::new("subject".to_string())),
+                Default::default(),
+            )
+            .set_nonce(Some(nonce.clone())),
+            &rsa_priv_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            client_id,
+            template_issuer.clone(),
+            CoreJsonWebKeySet::new(vec![rsa_pub_key]),
+        )
+        .set_time_fn(|| {
+            Utc.timestamp_opt(1544932148, 0)
+                .single()
+                .expect("valid timestamp")
+        });
+
+        // Strict issuer matching (error)
+        match id_token.claims(&verifier, &nonce) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Templated issuer (allowed)
+        id_token
+            .claims(
+                &verifier
+                    .clone()
+                    .set_other_issuer_verifier_fn(|iss| template_issuer.matches_template(iss)),
+                &nonce,
+            )
+            .expect("verification should succeed");
+
+        // Untrusted issuer (error)
+        match id_token.claims(
+            &verifier.set_other_issuer_verifier_fn(|iss| {
+                **iss == "https://login.microsoftonline.com/other_tenant/v2.0"
+            }),
+            &nonce,
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_id_token_issuer_without_scheme() {
+        let client_id = ClientId::new("my_client".to_string());
+        let nonce = Nonce::new("the_nonce".to_string());
+        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let rsa_pub_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+
+        let claims_json = serde_json::json!({
+            "iss": "accounts.google.com",
+            "aud": "my_client",
+            "exp": 1544932149,
+            "iat": 1544928549,
+            "nonce": "the_nonce",
+            "sub": "subject"
+        });
+        // Issuers without a URL scheme aren't valid issuer URLs.
+        assert!(serde_json::from_value::<CoreIdTokenClaims>(claims_json.clone()).is_err());
+
+        let id_token = JsonWebToken::<
+            crate::core::CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            crate::core::CoreJsonWebKeyType,
+            serde_json::Value,
+            JsonWebTokenJsonPayloadSerde,
+        >::new(
+            claims_json,
+            &rsa_priv_key,
+            &CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+        let id_token =
+            serde_json::from_value::<CoreIdToken>(serde_json::to_value(&id_token).unwrap())
+                .expect("failed to parse");
+
+        let time_fn = || {
+            Utc.timestamp_opt(1544932148, 0)
+                .single()
+                .expect("valid timestamp")
+        };
+        let verifier = CoreIdTokenVerifier::new_public_client(
+            client_id,
+            IssuerUrl::new("https://accounts.google.com".to_string()).unwrap(),
+            CoreJsonWebKeySet::new(vec![rsa_pub_key.clone()]),
+        )
+        .set_time_fn(time_fn);
+
+        // Strict issuer parsing (error)
+        match id_token.claims(&verifier, &nonce) {
+            Err(ClaimsVerificationError::MalformedClaims(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // Issuer scheme normalization (allowed)
+        let claims = id_token
+            .claims(&verifier.clone().normalize_issuer_scheme(true), &nonce)
+            .expect("verification should succeed");
+        assert_eq!(claims.issuer().as_str(), "https://accounts.google.com");
+
+        // Issuer scheme normalization with a different expected issuer (error)
+        match id_token.claims(
+            &CoreIdTokenVerifier::new_public_client(
+                ClientId::new("my_client".to_string()),
+                IssuerUrl::new("https://accounts.example.com".to_string()).unwrap(),
+                CoreJsonWebKeySet::new(vec![rsa_pub_key]),
+            )
+            .set_time_fn(time_fn)
+            .normalize_issuer_scheme(true),
+            &nonce,
+        ) {
+            Err(ClaimsVerificationError::InvalidIssuer(_)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 
     #[test]
@@ -2029,7 +3424,7 @@ mod tests {
             .clone()
             .claims(
                 &CoreUserInfoVerifier::new(
-                    client_id,
+                    client_id.clone(),
                     IssuerUrl::new("https://attacker.com".to_string()).unwrap(),
                     CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
                     Some(sub.clone()),
@@ -2049,6 +3444,37 @@ mod tests {
             other => panic!("unexpected result: {:?}", other),
         }
 
+        // JWT response with disallowed signing algorithm (error)
+        match jwt_claims.clone().claims(
+            &CoreUserInfoVerifier::new(
+                client_id.clone(),
+                issuer.clone(),
+                CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
+                Some(sub.clone()),
+            )
+            .set_allowed_algs(vec![CoreJwsSigningAlgorithm::EcdsaP256Sha256]),
+        ) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(_),
+            )) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // JWT response with any signing algorithm allowed
+        jwt_claims
+            .clone()
+            .claims(
+                &CoreUserInfoVerifier::new(
+                    client_id,
+                    issuer.clone(),
+                    CoreJsonWebKeySet::new(vec![rsa_key.clone()]),
+                    Some(sub.clone()),
+                )
+                .set_allowed_algs(vec![CoreJwsSigningAlgorithm::EcdsaP256Sha256])
+                .allow_any_alg(),
+            )
+            .expect("verification should succeed");
+
         // JWT response with invalid audience claim (allowed)
         jwt_claims
             .claims(
@@ -2117,4 +3543,54 @@ mod tests {
             serde_json::to_value(claims_jwt).unwrap().as_str().unwrap()
         );
     }
+
+    #[test]
+    fn test_new_user_info_response() {
+        let client_id = ClientId::new("my_client".to_string());
+        let issuer = IssuerUrl::new("https://example.com".to_string()).unwrap();
+        let sub = SubjectIdentifier::new("the_subject".to_string());
+
+        let claims = CoreUserInfoClaims::new(
+            StandardClaims::new(sub.clone())
+                .set_name(Some(EndUserName::new("John Doe".to_string()).into())),
+            Default::default(),
+        )
+        .set_issuer(Some(
+            IssuerUrl::new("https://other.example.com".to_string()).unwrap(),
+        ));
+
+        let rsa_priv_key = CoreRsaPrivateSigningKey::from_pem(TEST_RSA_PRIV_KEY, None).unwrap();
+        let claims_jwt = CoreUserInfoJsonWebToken::new_response(
+            claims,
+            &issuer,
+            &client_id,
+            &rsa_priv_key,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+        )
+        .unwrap();
+
+        let serialized = claims_jwt.to_string();
+        let claims_jwt = serde_json::from_value::<CoreUserInfoJsonWebToken>(
+            serde_json::Value::String(serialized),
+        )
+        .expect("failed to deserialize");
+
+        let rsa_key = serde_json::from_str::<CoreJsonWebKey>(TEST_RSA_PUB_KEY)
+            .expect("deserialization failed");
+        let verifier = CoreUserInfoVerifier::new(
+            client_id,
+            issuer.clone(),
+            CoreJsonWebKeySet::new(vec![rsa_key]),
+            Some(sub.clone()),
+        );
+        let verified = claims_jwt
+            .claims(&verifier)
+            .expect("verification should succeed");
+        assert_eq!(verified.issuer(), Some(&issuer));
+        assert_eq!(
+            verified.audiences(),
+            Some(&vec![Audience::new("my_client".to_string())])
+        );
+        assert_eq!(verified.subject(), &sub);
+    }
 }
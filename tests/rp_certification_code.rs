@@ -25,8 +25,7 @@ use openidconnect::core::{
 use openidconnect::Nonce;
 use openidconnect::{
     AccessToken, AuthType, AuthenticationFlow, AuthorizationCode, ClaimsVerificationError,
-    CsrfToken, OAuth2TokenResponse, RequestTokenError, Scope, SignatureVerificationError,
-    UserInfoError,
+    CsrfToken, OAuth2TokenResponse, Scope, SignatureVerificationError, UserInfoError,
 };
 
 #[macro_use]
@@ -93,7 +92,7 @@ impl TestState {
                         authorization_request = authorization_request.add_scope(scope.clone());
                         authorization_request
                     });
-            let (url, state, nonce) = authorization_request.url();
+            let (url, state, nonce) = authorization_request.url().unwrap();
             log_debug!("Authorize URL: {:?}", url);
 
             let http_client = Client::builder().redirect(Policy::none()).build().unwrap();
@@ -364,20 +363,13 @@ fn rp_id_token_kid_absent_single_jwks() {
 #[test]
 #[ignore]
 fn rp_id_token_iat() {
-    let mut test_state = TestState::init("rp-id_token-iat", |reg| reg).authorize(&[]);
-
-    let token_response = test_state
-        .client
-        .exchange_code(
-            test_state
-                .authorization_code
-                .take()
-                .expect("no authorization_code"),
-        )
-        .request(http_client);
+    let test_state = TestState::init("rp-id_token-iat", |reg| reg)
+        .authorize(&[])
+        .exchange_code();
 
-    match token_response {
-        Err(RequestTokenError::Parse(_, _)) => {
+    // ID token claims are decoded lazily, so the missing claim is detected during verification.
+    match test_state.id_token_claims_failure() {
+        ClaimsVerificationError::MalformedClaims(_) => {
             log_error!("ID token failed to parse without `iat` claim (expected result)")
         }
         other => panic!("Unexpected result verifying ID token claims: {:?}", other),
@@ -474,20 +466,13 @@ fn rp_id_token_sig_hs256() {
 #[test]
 #[ignore]
 fn rp_id_token_sub() {
-    let mut test_state = TestState::init("rp-id_token-sub", |reg| reg).authorize(&[]);
-
-    let token_response = test_state
-        .client
-        .exchange_code(
-            test_state
-                .authorization_code
-                .take()
-                .expect("no authorization_code"),
-        )
-        .request(http_client);
+    let test_state = TestState::init("rp-id_token-sub", |reg| reg)
+        .authorize(&[])
+        .exchange_code();
 
-    match token_response {
-        Err(RequestTokenError::Parse(_, _)) => {
+    // ID token claims are decoded lazily, so the missing claim is detected during verification.
+    match test_state.id_token_claims_failure() {
+        ClaimsVerificationError::MalformedClaims(_) => {
             log_error!("ID token failed to parse without `sub` claim (expected result)")
         }
         other => panic!("Unexpected result verifying ID token claims: {:?}", other),